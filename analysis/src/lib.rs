@@ -0,0 +1,12 @@
+//! The text analysis of `stemmer_uk` that needs no stemmer: the tokenizer, the sentence
+//! splitter and the stop-word list, which `stemmer_uk` re-exports. The analyzers, which
+//! stem the tokens, stay in `stemmer_uk` with the `Stemmer` they are built on.
+
+pub mod sentences;
+pub mod stopwords;
+pub mod tokenizer;
+
+/// Builds the default tokenizer now instead of on first use.
+pub fn warm_up() {
+    tokenizer::warm_up();
+}
@@ -0,0 +1,209 @@
+//! Splits Ukrainian text into sentences, e.g. to stem or extract the keywords of each
+//! sentence of a document on its own.
+//!
+//! A sentence ends at ".", "!", "?" or "…", and any closing quotes and brackets after
+//! them, when whitespace and a sentence start follow: an uppercase letter, a digit, or a
+//! quote, bracket or dash before one. The dot of an abbreviation usually followed by a
+//! name or a number, such as "вул." or "ім.", and of an initial, such as "Т.", ends no
+//! sentence; nor does the "!" of direct speech followed by the words of the author, as in
+//! "«Іди!» — сказав він". A blank line ends a sentence without a full stop, e.g. a
+//! heading.
+
+use std::iter::FusedIterator;
+
+// abbreviations followed by a name or a number rather than ending a sentence, lowercase
+// without their dot
+const ABBREVIATIONS: [&str; 30] = [
+    "акад",
+    "бульв",
+    "буд",
+    "вул",
+    "гл",
+    "див",
+    "доц",
+    "д-р",
+    "ім",
+    "кв",
+    "м",
+    "напр",
+    "обл",
+    "п",
+    "пл",
+    "пор",
+    "порівн",
+    "пп",
+    "пров",
+    "проф",
+    "просп",
+    "рис",
+    "р-н",
+    "с",
+    "св",
+    "смт",
+    "ст",
+    "т",
+    "табл",
+    "тел",
+];
+
+const TERMINATORS: [char; 4] = ['.', '!', '?', '…'];
+const CLOSING: [char; 7] = ['»', '”', '“', '"', ')', ']', '\''];
+const OPENING: [char; 6] = ['«', '„', '“', '"', '(', '['];
+const DASHES: [char; 3] = ['—', '–', '-'];
+
+/// A sentence borrowed from the text, without the whitespace around it; `start..end` is
+/// its byte range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sentence<'a> {
+    pub text: &'a str,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The sentences of `text`, in order.
+pub fn sentences(text: &str) -> Sentences<'_> {
+    Sentences { text, pos: 0 }
+}
+
+/// Iterator returned by `sentences`.
+#[derive(Clone, Debug)]
+pub struct Sentences<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Sentences<'a> {
+    fn sentence(&mut self, start: usize, end: usize) -> Sentence<'a> {
+        self.pos = end;
+        let text = self.text[start..end].trim_end();
+        Sentence {
+            text,
+            start,
+            end: start + text.len(),
+        }
+    }
+
+    // whether the terminators at `at`, with the closing quotes up to `end`, end the
+    // sentence starting at `start`
+    fn ends_at(&self, start: usize, at: usize, end: usize) -> bool {
+        let rest = &self.text[end..];
+        if rest.is_empty() {
+            return true;
+        }
+        if !rest.starts_with(char::is_whitespace) {
+            return false;
+        }
+        if self.text[at..end].trim_end_matches(CLOSING) == "." {
+            let word = last_word(&self.text[start..at]);
+            let mut chars = word.chars();
+            let initial =
+                matches!((chars.next(), chars.next()), (Some(c), None) if c.is_uppercase());
+            if initial || ABBREVIATIONS.contains(&word.to_lowercase().as_str()) {
+                return false;
+            }
+        }
+        starts_sentence(rest)
+    }
+}
+
+impl<'a> Iterator for Sentences<'a> {
+    type Item = Sentence<'a>;
+
+    fn next(&mut self) -> Option<Sentence<'a>> {
+        let rest = &self.text[self.pos..];
+        let start = self.pos + (rest.len() - rest.trim_start().len());
+        if start == self.text.len() {
+            self.pos = start;
+            return None;
+        }
+        let mut chars = self.text[start..].char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let at = start + i;
+            if TERMINATORS.contains(&c) {
+                let mut end = at + c.len_utf8();
+                while let Some(&(j, next)) = chars.peek() {
+                    if !TERMINATORS.contains(&next) && !CLOSING.contains(&next) {
+                        break;
+                    }
+                    end = start + j + next.len_utf8();
+                    chars.next();
+                }
+                if self.ends_at(start, at, end) {
+                    return Some(self.sentence(start, end));
+                }
+            } else if c == '\n'
+                && self.text[at + 1..]
+                    .trim_start_matches([' ', '\t', '\r'])
+                    .starts_with('\n')
+            {
+                return Some(self.sentence(start, at));
+            }
+        }
+        Some(self.sentence(start, self.text.len()))
+    }
+}
+
+impl FusedIterator for Sentences<'_> {}
+
+// the letters and hyphens at the end of `text`
+fn last_word(text: &str) -> &str {
+    let start = text
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphabetic() || *c == '-')
+        .last()
+        .map_or(text.len(), |(i, _)| i);
+    &text[start..]
+}
+
+// whether `rest`, the text after the whitespace following a terminator, starts a
+// sentence rather than continuing one, as the words of the author after direct speech
+fn starts_sentence(rest: &str) -> bool {
+    let next = rest.trim_start();
+    let next = match next.strip_prefix(DASHES) {
+        Some(after) => after.trim_start(),
+        None => next.trim_start_matches(OPENING),
+    };
+    match next.chars().next() {
+        Some(c) => c.is_uppercase() || c.is_numeric() || !c.is_alphabetic(),
+        None => true,
+    }
+}
+
+#[test]
+fn sentences_test() {
+    let split = |text| -> Vec<&str> { sentences(text).map(|s| s.text).collect() };
+    assert_eq!(
+        split("Він живе на вул. Хрещатик, 5. Тут затишно."),
+        ["Він живе на вул. Хрещатик, 5.", "Тут затишно."]
+    );
+    assert_eq!(
+        split("Університет ім. Т. Г. Шевченка заснували у 1834 р. Він найстаріший."),
+        [
+            "Університет ім. Т. Г. Шевченка заснували у 1834 р.",
+            "Він найстаріший."
+        ]
+    );
+    assert_eq!(
+        split("«Іди додому!» — сказав він. — Добре… — Вона пішла."),
+        ["«Іди додому!» — сказав він.", "— Добре…", "— Вона пішла."]
+    );
+    assert_eq!(
+        split("Є багато мов, напр. українська... Ціна 3.50 грн. Дорого?! «Так.»"),
+        [
+            "Є багато мов, напр. українська...",
+            "Ціна 3.50 грн.",
+            "Дорого?!",
+            "«Так.»"
+        ]
+    );
+    assert_eq!(
+        split("  Заголовок\n \nТекст\nбез крапки "),
+        ["Заголовок", "Текст\nбез крапки"]
+    );
+    assert!(split(" \n ").is_empty());
+    let text = "Ой у гаю. При Дунаї!";
+    let ranges: Vec<(usize, usize)> = sentences(text).map(|s| (s.start, s.end)).collect();
+    assert_eq!(ranges.len(), 2);
+    assert_eq!(&text[ranges[1].0..ranges[1].1], "При Дунаї!");
+}
@@ -0,0 +1,121 @@
+//! The default Ukrainian stop-word list.
+
+// Pronouns, prepositions, conjunctions, particles and forms of "бути", sorted by code
+// point so lookups can binary search.
+const DEFAULT: [&str; 96] = [
+    "а",
+    "або",
+    "адже",
+    "але",
+    "б",
+    "без",
+    "би",
+    "був",
+    "була",
+    "були",
+    "було",
+    "бути",
+    "в",
+    "вам",
+    "вас",
+    "ваш",
+    "вже",
+    "ви",
+    "вона",
+    "вони",
+    "воно",
+    "все",
+    "всі",
+    "від",
+    "він",
+    "де",
+    "для",
+    "до",
+    "ж",
+    "же",
+    "з",
+    "за",
+    "зі",
+    "й",
+    "коли",
+    "лише",
+    "мене",
+    "мені",
+    "ми",
+    "мною",
+    "між",
+    "мій",
+    "на",
+    "навіть",
+    "над",
+    "нам",
+    "нас",
+    "наш",
+    "не",
+    "нею",
+    "неї",
+    "ним",
+    "них",
+    "ну",
+    "ні",
+    "о",
+    "от",
+    "по",
+    "при",
+    "про",
+    "під",
+    "себе",
+    "собі",
+    "та",
+    "так",
+    "також",
+    "там",
+    "твій",
+    "те",
+    "тебе",
+    "теж",
+    "ти",
+    "то",
+    "тобі",
+    "той",
+    "тому",
+    "тут",
+    "тільки",
+    "у",
+    "хто",
+    "це",
+    "цей",
+    "ця",
+    "ці",
+    "через",
+    "чи",
+    "що",
+    "щоб",
+    "як",
+    "є",
+    "і",
+    "із",
+    "їй",
+    "їм",
+    "їх",
+    "її",
+];
+
+/// Whether `word` is in the default Ukrainian stop-word list; case insensitive.
+pub fn is_stopword(word: &str) -> bool {
+    let word = word.to_lowercase();
+    DEFAULT.binary_search(&word.as_str()).is_ok()
+}
+
+/// The default stop-word list.
+pub fn default_list() -> &'static [&'static str] {
+    &DEFAULT
+}
+
+#[test]
+fn stopwords_test() {
+    assert!(DEFAULT.windows(2).all(|w| w[0] < w[1]));
+    assert!(is_stopword("Що"));
+    assert!(is_stopword("та"));
+    assert!(!is_stopword("слово"));
+}
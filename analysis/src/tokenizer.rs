@@ -0,0 +1,827 @@
+use lazy_static::lazy_static;
+use std::borrow::Cow;
+
+pub use stemmer_uk_core::normalize::is_invisible;
+use stemmer_uk_core::normalize::APOSTROPHES;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Word,
+    Number,
+    Whitespace,
+    Punctuation,
+    /// A single pictographic emoji, including modifiers, ZWJ sequences and flags.
+    Emoji,
+    /// An ASCII emoticon such as `:)` or a run of brackets like `)))`.
+    Emoticon,
+    /// A run of bidirectional formatting characters (marks, embeddings, isolates) outside
+    /// a word. Inside words they are kept in the word token, see `strip_bidi_controls`.
+    Control,
+    /// A `@mention` or `#hashtag`, only produced when `Tokenizer::tags` is on.
+    Tag,
+    Other,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Script {
+    Cyrillic,
+    Latin,
+    Greek,
+    Arabic,
+    Hebrew,
+    /// Han, Kana and Hangul.
+    Cjk,
+    /// Letters from more than one script.
+    Mixed,
+    /// No letters at all, or a script not listed above.
+    Other,
+}
+
+fn char_script(c: char) -> Option<Script> {
+    if !c.is_alphabetic() {
+        return None;
+    }
+    let script = match c as u32 {
+        0x0400..=0x052F | 0x1C80..=0x1C8F | 0x2DE0..=0x2DFF | 0xA640..=0xA69F => Script::Cyrillic,
+        0x0041..=0x024F | 0x1E00..=0x1EFF => Script::Latin,
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => Script::Greek,
+        0x0600..=0x06FF | 0x0750..=0x077F | 0x08A0..=0x08FF | 0xFB50..=0xFDFF | 0xFE70..=0xFEFF => {
+            Script::Arabic
+        }
+        0x0590..=0x05FF => Script::Hebrew,
+        0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF => {
+            Script::Cjk
+        }
+        _ => Script::Other,
+    };
+    Some(script)
+}
+
+/// The script of the letters in `text`.
+pub fn script(text: &str) -> Script {
+    let mut res = None;
+    for script in text.chars().filter_map(char_script) {
+        match res {
+            None => res = Some(script),
+            Some(prev) if prev != script => return Script::Mixed,
+            _ => {}
+        }
+    }
+    res.unwrap_or(Script::Other)
+}
+
+/// Whether `word` is a token the rules have nothing to do with: a word without Cyrillic
+/// letters, such as "Kyiv" or "2024", a URL or an e-mail address. Tokens without letters
+/// or digits are not.
+pub fn is_foreign(word: &str) -> bool {
+    if !word.chars().any(char::is_alphanumeric) {
+        return false;
+    }
+    let email = word
+        .split_once('@')
+        .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'));
+    email
+        || word.contains("://")
+        || word.starts_with("www.")
+        || !word
+            .chars()
+            .any(|c| char_script(c) == Some(Script::Cyrillic))
+}
+
+// the longest words `is_acronym` takes for acronyms rather than shouted words
+const MAX_ACRONYM_CHARS: usize = 5;
+
+/// Whether `word` looks like an acronym such as "НАТО", "ЗСУ" or "ЄС": two to five
+/// Cyrillic letters, all uppercase. All-caps words of headlines that short are taken for
+/// acronyms too.
+pub fn is_acronym(word: &str) -> bool {
+    let chars = word.chars().count();
+    (2..=MAX_ACRONYM_CHARS).contains(&chars)
+        && word
+            .chars()
+            .all(|c| c.is_uppercase() && char_script(c) == Some(Script::Cyrillic))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sentiment {
+    Positive,
+    Negative,
+    Neutral,
+}
+
+impl Sentiment {
+    pub fn tag(self) -> &'static str {
+        match self {
+            Sentiment::Positive => "<positive>",
+            Sentiment::Negative => "<negative>",
+            Sentiment::Neutral => "<neutral>",
+        }
+    }
+}
+
+/// What the tokenizer does with emoji and emoticon tokens.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EmojiPolicy {
+    #[default]
+    Keep,
+    Drop,
+    /// Keep the token and attach its sentiment.
+    Sentiment,
+}
+
+/// A token borrowed from the input; `start..end` is its byte range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    pub start: usize,
+    pub end: usize,
+    /// Only set for emoji and emoticons under `EmojiPolicy::Sentiment`.
+    pub sentiment: Option<Sentiment>,
+}
+
+/// The characters the tokenizer treats specially, on top of the Unicode letter, digit and
+/// whitespace properties.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CharClasses {
+    word_joiners: Vec<char>,
+    number_separators: Vec<char>,
+    whitespace: Vec<char>,
+    punctuation: Vec<char>,
+}
+
+impl Default for CharClasses {
+    fn default() -> Self {
+        let mut word_joiners = APOSTROPHES.to_vec();
+        word_joiners.push('-');
+        CharClasses {
+            word_joiners,
+            number_separators: vec!['.', ','],
+            whitespace: Vec::new(),
+            punctuation: UNICODE_PUNCTUATION.to_vec(),
+        }
+    }
+}
+
+impl CharClasses {
+    /// Adds a character kept inside a word when it sits between two letters, like the
+    /// apostrophe in "п'ять" or the non-breaking hyphen U+2011.
+    pub fn word_joiner(mut self, c: char) -> Self {
+        self.word_joiners.push(c);
+        self
+    }
+
+    /// Replaces the word joiners; the default is the apostrophes and '-'.
+    pub fn word_joiners(mut self, chars: &[char]) -> Self {
+        self.word_joiners = chars.to_vec();
+        self
+    }
+
+    /// Adds a character kept inside a number when it sits between two digits.
+    pub fn number_separator(mut self, c: char) -> Self {
+        self.number_separators.push(c);
+        self
+    }
+
+    /// Replaces the number separators; the default is '.' and ','.
+    pub fn number_separators(mut self, chars: &[char]) -> Self {
+        self.number_separators = chars.to_vec();
+        self
+    }
+
+    /// Treats a character without the Unicode whitespace property, such as the zero-width
+    /// space, as whitespace.
+    pub fn whitespace(mut self, c: char) -> Self {
+        self.whitespace.push(c);
+        self
+    }
+
+    /// Adds a non-ASCII punctuation mark; ASCII punctuation always is one.
+    pub fn punctuation(mut self, c: char) -> Self {
+        self.punctuation.push(c);
+        self
+    }
+
+    fn is_word_joiner(&self, c: char) -> bool {
+        self.word_joiners.contains(&c)
+    }
+
+    fn is_number_separator(&self, c: char) -> bool {
+        self.number_separators.contains(&c)
+    }
+
+    fn is_whitespace(&self, c: char) -> bool {
+        c.is_whitespace() || self.whitespace.contains(&c)
+    }
+
+    fn is_punctuation(&self, c: char) -> bool {
+        c.is_ascii_punctuation() || self.punctuation.contains(&c)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tokenizer {
+    emoji: EmojiPolicy,
+    classes: CharClasses,
+    tags: bool,
+    ordinals: bool,
+    segmentation: Segmentation,
+}
+
+/// How text is split into words.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Segmentation {
+    /// The built-in rules, tuned for Ukrainian: `CharClasses` decides which characters join
+    /// words and numbers.
+    #[default]
+    Custom,
+    /// UAX #29 word boundaries from the `unicode-segmentation` crate. `CharClasses` is
+    /// not used for splitting; emoticons and tags are still recognized.
+    #[cfg(feature = "uax29")]
+    Uax29,
+}
+
+lazy_static! {
+    static ref DEFAULT_TOKENIZER: Tokenizer = Tokenizer::new();
+}
+
+pub(crate) fn warm_up() {
+    lazy_static::initialize(&DEFAULT_TOKENIZER);
+}
+
+// longest first, so ":-)" wins over ":-"
+const EMOTICONS: [(&str, Sentiment); 22] = [
+    (":'-(", Sentiment::Negative),
+    (":-)", Sentiment::Positive),
+    (":-(", Sentiment::Negative),
+    (":-D", Sentiment::Positive),
+    (":-P", Sentiment::Positive),
+    (";-)", Sentiment::Positive),
+    (":'(", Sentiment::Negative),
+    ("^_^", Sentiment::Positive),
+    ("-_-", Sentiment::Negative),
+    ("o_O", Sentiment::Neutral),
+    ("O_o", Sentiment::Neutral),
+    (":)", Sentiment::Positive),
+    (":(", Sentiment::Negative),
+    (":D", Sentiment::Positive),
+    (":P", Sentiment::Positive),
+    (":p", Sentiment::Positive),
+    (";)", Sentiment::Positive),
+    (":/", Sentiment::Negative),
+    (":|", Sentiment::Neutral),
+    (":*", Sentiment::Positive),
+    ("xD", Sentiment::Positive),
+    ("<3", Sentiment::Positive),
+];
+
+const UNICODE_PUNCTUATION: [char; 14] = [
+    '«', '»', '„', '“', '”', '‘', '’', '—', '–', '…', '‐', '‑', '§', '№',
+];
+
+/// Invisible characters that change the display direction of text: LRM, RLM, ALM and the
+/// embedding, override and isolate controls. Scraped RTL content is full of them.
+pub fn is_bidi_control(c: char) -> bool {
+    matches!(c, '\u{200E}' | '\u{200F}' | '\u{061C}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+/// Removes the characters of `is_invisible`, borrowing when there are none.
+pub fn strip_invisible(text: &str) -> Cow<'_, str> {
+    if text.chars().any(is_invisible) {
+        Cow::Owned(text.chars().filter(|c| !is_invisible(*c)).collect())
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Removes bidirectional formatting characters, borrowing when there are none.
+pub fn strip_bidi_controls(text: &str) -> Cow<'_, str> {
+    if text.chars().any(is_bidi_control) {
+        Cow::Owned(text.chars().filter(|c| !is_bidi_control(*c)).collect())
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+pub fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0x2300..=0x23FF | 0x3030 | 0x303D)
+}
+
+fn is_emoji_continuation(c: char) -> bool {
+    matches!(c as u32, 0xFE0F | 0x1F3FB..=0x1F3FF | 0x20E3 | 0xE0020..=0xE007F)
+}
+
+fn emoji_sentiment(text: &str) -> Sentiment {
+    let c = text.chars().next().map_or(0, |c| c as u32);
+    match c {
+        0x1F600..=0x1F60D
+        | 0x1F617..=0x1F61D
+        | 0x1F642
+        | 0x1F923
+        | 0x1F970
+        | 0x1F973
+        | 0x2764
+        | 0x1F44D
+        | 0x1F44F
+        | 0x1F389
+        | 0x1F525
+        | 0x1F496..=0x1F49F => Sentiment::Positive,
+        0x1F61E..=0x1F62D
+        | 0x1F630..=0x1F631
+        | 0x1F641
+        | 0x1F614
+        | 0x1F615
+        | 0x1F44E
+        | 0x1F494
+        | 0x1F92C
+        | 0x1F92E => Sentiment::Negative,
+        _ => Sentiment::Neutral,
+    }
+}
+
+impl Tokenizer {
+    pub fn new() -> Self {
+        Tokenizer::default()
+    }
+
+    pub fn emoji_policy(mut self, policy: EmojiPolicy) -> Self {
+        self.emoji = policy;
+        self
+    }
+
+    pub fn char_classes(mut self, classes: CharClasses) -> Self {
+        self.classes = classes;
+        self
+    }
+
+    /// Keeps `@mention` and `#hashtag` as single `TokenKind::Tag` tokens. A sigil right
+    /// after a letter or digit, as in an e-mail address, starts no tag.
+    pub fn tags(mut self, enabled: bool) -> Self {
+        self.tags = enabled;
+        self
+    }
+
+    /// Keeps the inflected tail of numerals written with digits, "25-го", "2004-му",
+    /// "90-х", in their `TokenKind::Number` token instead of splitting off a word; see
+    /// `strip_numeral_tail`. Only the custom segmentation recognizes them.
+    pub fn ordinals(mut self, enabled: bool) -> Self {
+        self.ordinals = enabled;
+        self
+    }
+
+    pub fn segmentation(mut self, segmentation: Segmentation) -> Self {
+        self.segmentation = segmentation;
+        self
+    }
+
+    pub fn tokenize<'t, 'a>(&'t self, text: &'a str) -> Tokens<'t, 'a> {
+        self.tokenize_from(text, 0)
+    }
+
+    /// `tokenize` from byte offset `pos` of `text`, which must be a token start, e.g. to
+    /// tokenize again the end of a text edited there.
+    pub fn tokenize_from<'t, 'a>(&'t self, text: &'a str, pos: usize) -> Tokens<'t, 'a> {
+        Tokens {
+            tokenizer: self,
+            text,
+            pos,
+        }
+    }
+
+    // returns the byte length and kind of the token starting at `rest`, `prev` being the
+    // character before it
+    fn scan(&self, rest: &str, prev: Option<char>) -> (usize, TokenKind, Option<Sentiment>) {
+        let mut chars = rest.char_indices().peekable();
+        let (_, first) = chars.next().expect("scan on empty input");
+
+        if self.tags && (first == '@' || first == '#') && !prev.is_some_and(char::is_alphanumeric) {
+            let name = rest[1..]
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len() - 1);
+            if name > 0 {
+                return (1 + name, TokenKind::Tag, None);
+            }
+        }
+
+        if let Some((emoticon, sentiment)) = EMOTICONS.iter().find(|(e, _)| {
+            rest.starts_with(*e) && !rest[e.len()..].starts_with(char::is_alphanumeric)
+        }) {
+            return (emoticon.len(), TokenKind::Emoticon, Some(*sentiment));
+        }
+        if first == ')' || first == '(' {
+            let len = rest.len() - rest.trim_start_matches(first).len();
+            if len > 1 {
+                let sentiment = if first == ')' {
+                    Sentiment::Positive
+                } else {
+                    Sentiment::Negative
+                };
+                return (len, TokenKind::Emoticon, Some(sentiment));
+            }
+        }
+
+        #[cfg(feature = "uax29")]
+        if self.segmentation == Segmentation::Uax29 {
+            return self.scan_uax29(rest, first);
+        }
+
+        if first.is_alphabetic() {
+            let mut end = first.len_utf8();
+            let mut prev_letter = true;
+            while let Some((i, c)) = chars.next() {
+                let joiner = self.classes.is_word_joiner(c);
+                let next_letter = chars.peek().is_some_and(|(_, n)| n.is_alphabetic());
+                if c.is_alphabetic() || c == '\u{0301}' {
+                    prev_letter = true;
+                } else if (is_bidi_control(c) || is_invisible(c)) && next_letter {
+                    // markers and soft hyphens inserted inside a word must neither split
+                    // it nor count as a letter for the joiners around them
+                } else if joiner && prev_letter && next_letter {
+                    prev_letter = false;
+                } else {
+                    break;
+                }
+                end = i + c.len_utf8();
+            }
+            return (end, TokenKind::Word, None);
+        }
+
+        if first.is_numeric() {
+            let mut end = first.len_utf8();
+            while let Some((i, c)) = chars.next() {
+                let separator = self.classes.is_number_separator(c);
+                let next_digit = chars.peek().is_some_and(|(_, n)| n.is_numeric());
+                if !(c.is_numeric() || separator && next_digit) {
+                    break;
+                }
+                end = i + c.len_utf8();
+            }
+            if self.ordinals {
+                end += numeral_tail(&rest[end..]);
+            }
+            return (end, TokenKind::Number, None);
+        }
+
+        if is_bidi_control(first) {
+            let len = rest.len() - rest.trim_start_matches(is_bidi_control).len();
+            return (len, TokenKind::Control, None);
+        }
+
+        if self.classes.is_whitespace(first) {
+            let len = rest.len()
+                - rest
+                    .trim_start_matches(|c| self.classes.is_whitespace(c))
+                    .len();
+            return (len, TokenKind::Whitespace, None);
+        }
+
+        if is_emoji(first) {
+            let mut end = first.len_utf8();
+            let regional = (0x1F1E6..=0x1F1FF).contains(&(first as u32));
+            while let Some((i, c)) = chars.next() {
+                if is_emoji_continuation(c) {
+                    end = i + c.len_utf8();
+                } else if c == '\u{200D}' && chars.peek().is_some_and(|(_, n)| is_emoji(*n)) {
+                    let (j, n) = chars.next().unwrap();
+                    end = j + n.len_utf8();
+                } else if regional
+                    && end == first.len_utf8()
+                    && (0x1F1E6..=0x1F1FF).contains(&(c as u32))
+                {
+                    end = i + c.len_utf8();
+                } else {
+                    break;
+                }
+            }
+            let sentiment = emoji_sentiment(&rest[..end]);
+            return (end, TokenKind::Emoji, Some(sentiment));
+        }
+
+        if self.classes.is_punctuation(first) {
+            return (first.len_utf8(), TokenKind::Punctuation, None);
+        }
+
+        (first.len_utf8(), TokenKind::Other, None)
+    }
+}
+
+#[cfg(feature = "uax29")]
+impl Tokenizer {
+    fn scan_uax29(&self, rest: &str, first: char) -> (usize, TokenKind, Option<Sentiment>) {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        let segment = rest.split_word_bounds().next().unwrap_or(rest);
+        let kind = if segment.chars().any(char::is_alphabetic) {
+            TokenKind::Word
+        } else if first.is_numeric() {
+            TokenKind::Number
+        } else if is_bidi_control(first) {
+            TokenKind::Control
+        } else if first.is_whitespace() {
+            TokenKind::Whitespace
+        } else if is_emoji(first) {
+            return (
+                segment.len(),
+                TokenKind::Emoji,
+                Some(emoji_sentiment(segment)),
+            );
+        } else if self.classes.is_punctuation(first) {
+            TokenKind::Punctuation
+        } else {
+            TokenKind::Other
+        };
+        (segment.len(), kind, None)
+    }
+}
+
+impl<'a> Token<'a> {
+    pub fn script(&self) -> Script {
+        script(self.text)
+    }
+}
+
+pub struct Tokens<'t, 'a> {
+    tokenizer: &'t Tokenizer,
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'t, 'a> Iterator for Tokens<'t, 'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        while self.pos < self.text.len() {
+            let start = self.pos;
+            let prev = self.text[..start].chars().next_back();
+            let (len, kind, sentiment) = self.tokenizer.scan(&self.text[start..], prev);
+            self.pos += len;
+
+            let emoji = kind == TokenKind::Emoji || kind == TokenKind::Emoticon;
+            let sentiment = match self.tokenizer.emoji {
+                EmojiPolicy::Drop if emoji => continue,
+                EmojiPolicy::Sentiment => sentiment,
+                _ => None,
+            };
+
+            return Some(Token {
+                kind,
+                text: &self.text[start..self.pos],
+                start,
+                end: self.pos,
+                sentiment,
+            });
+        }
+        None
+    }
+}
+
+// the most letters an inflected tail of a numeral has, as in "25-ого"
+const MAX_NUMERAL_TAIL_CHARS: usize = 3;
+
+// the byte length of the "-го" starting `rest` if it is the tail of a numeral: a hyphen
+// and up to three Cyrillic letters ending the word; 0 otherwise
+fn numeral_tail(rest: &str) -> usize {
+    let letters = match rest.strip_prefix('-') {
+        Some(letters) => letters,
+        None => return 0,
+    };
+    let len = letters
+        .find(|c: char| !c.is_alphabetic())
+        .unwrap_or(letters.len());
+    let tail = &letters[..len];
+    let chars = tail.chars().count();
+    if chars == 0 || chars > MAX_NUMERAL_TAIL_CHARS || script(tail) != Script::Cyrillic {
+        return 0;
+    }
+    1 + len
+}
+
+/// A number token without the inflected tail `Tokenizer::ordinals` keeps in it: "25" for
+/// "25-го" and "1990" for "1990-х".
+pub fn strip_numeral_tail(number: &str) -> &str {
+    match number.rfind('-') {
+        Some(hyphen) if numeral_tail(&number[hyphen..]) == number.len() - hyphen => {
+            &number[..hyphen]
+        }
+        _ => number,
+    }
+}
+
+/// Tokenizes with the default configuration.
+pub fn tokenize(text: &str) -> Tokens<'static, '_> {
+    DEFAULT_TOKENIZER.tokenize(text)
+}
+
+#[test]
+fn tokenize_test() {
+    let kinds: Vec<(TokenKind, &str)> = tokenize("Інтернет-магазин п’ять 3,5 грн!")
+        .map(|t| (t.kind, t.text))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (TokenKind::Word, "Інтернет-магазин"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Word, "п’ять"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Number, "3,5"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Word, "грн"),
+            (TokenKind::Punctuation, "!"),
+        ]
+    );
+}
+
+#[test]
+fn ordinals_test() {
+    let text = "25-го, 2004-му і 90-х; 5-річний 3-";
+    let numbers = |tokenizer: &Tokenizer| -> Vec<&str> {
+        tokenizer
+            .tokenize(text)
+            .filter(|t| t.kind == TokenKind::Number)
+            .map(|t| t.text)
+            .collect()
+    };
+    assert_eq!(
+        numbers(&Tokenizer::new().ordinals(true)),
+        ["25-го", "2004-му", "90-х", "5", "3"]
+    );
+    assert_eq!(numbers(&Tokenizer::new()), ["25", "2004", "90", "5", "3"]);
+    assert_eq!(strip_numeral_tail("25-го"), "25");
+    assert_eq!(strip_numeral_tail("3,5"), "3,5");
+}
+
+#[test]
+fn script_test() {
+    assert_eq!(script("слово"), Script::Cyrillic);
+    assert_eq!(script("word"), Script::Latin);
+    assert_eq!(script("كتاب"), Script::Arabic);
+    assert_eq!(script("東京"), Script::Cjk);
+    assert_eq!(script("cлово"), Script::Mixed);
+    assert_eq!(script("2024"), Script::Other);
+}
+
+#[test]
+fn is_foreign_test() {
+    for word in [
+        "Kyiv",
+        "2024",
+        "COVID-19",
+        "https://uk.wikipedia.org/wiki/Слово",
+    ] {
+        assert!(is_foreign(word), "{}", word);
+    }
+    assert!(is_foreign("олег@приклад.укр"));
+    assert!(is_foreign("www.новини.укр"));
+    for word in ["слово", "iPhone-ом", "cлово", "...", "", "@мітка"] {
+        assert!(!is_foreign(word), "{}", word);
+    }
+}
+
+#[test]
+fn emoji_policy_test() {
+    let text = "супер 👍🏽 :) дякую))) 👨‍👩‍👧 🇺🇦";
+    let emoji: Vec<&str> = tokenize(text)
+        .filter(|t| t.kind == TokenKind::Emoji || t.kind == TokenKind::Emoticon)
+        .map(|t| t.text)
+        .collect();
+    assert_eq!(emoji, vec!["👍🏽", ":)", ")))", "👨‍👩‍👧", "🇺🇦"]);
+
+    let dropped = Tokenizer::new().emoji_policy(EmojiPolicy::Drop);
+    assert!(dropped
+        .tokenize(text)
+        .all(|t| t.kind != TokenKind::Emoji && t.kind != TokenKind::Emoticon));
+
+    let tagged = Tokenizer::new().emoji_policy(EmojiPolicy::Sentiment);
+    let sentiments: Vec<Sentiment> = tagged.tokenize(text).filter_map(|t| t.sentiment).collect();
+    assert_eq!(sentiments[..3], [Sentiment::Positive; 3]);
+}
+
+#[test]
+fn char_classes_test() {
+    let text = "північно\u{2011}східний 10\u{a0}000 грн\u{200b}¶";
+    let words = |tokenizer: &Tokenizer| -> Vec<(TokenKind, String)> {
+        tokenizer
+            .tokenize(text)
+            .map(|t| (t.kind, t.text.to_string()))
+            .collect()
+    };
+    assert_eq!(
+        words(&Tokenizer::new())[1],
+        (TokenKind::Punctuation, "\u{2011}".into())
+    );
+
+    let legal = Tokenizer::new().char_classes(
+        CharClasses::default()
+            .word_joiner('\u{2011}')
+            .number_separator('\u{a0}')
+            .whitespace('\u{200b}')
+            .punctuation('¶'),
+    );
+    assert_eq!(
+        words(&legal),
+        [
+            (TokenKind::Word, "північно\u{2011}східний".into()),
+            (TokenKind::Whitespace, " ".into()),
+            (TokenKind::Number, "10\u{a0}000".into()),
+            (TokenKind::Whitespace, " ".into()),
+            (TokenKind::Word, "грн".into()),
+            (TokenKind::Whitespace, "\u{200b}".into()),
+            (TokenKind::Punctuation, "¶".into()),
+        ]
+    );
+
+    let no_hyphens =
+        Tokenizer::new().char_classes(CharClasses::default().word_joiners(&APOSTROPHES));
+    assert_eq!(no_hyphens.tokenize("інтернет-магазин").count(), 3);
+}
+
+#[test]
+fn bidi_test() {
+    let text = "\u{202B}שלום\u{202C} і при\u{200E}віт, مرحبا\u{200F} 42\u{2066}!\u{2069}";
+    let tokens: Vec<Token> = tokenize(text).collect();
+
+    let mut pos = 0;
+    for token in &tokens {
+        assert_eq!(token.start, pos);
+        assert_eq!(&text[token.start..token.end], token.text);
+        pos = token.end;
+    }
+    assert_eq!(pos, text.len());
+
+    let words: Vec<&str> = tokens
+        .iter()
+        .filter(|t| t.kind == TokenKind::Word)
+        .map(|t| t.text)
+        .collect();
+    assert_eq!(words, ["שלום", "і", "при\u{200E}віт", "مرحبا"]);
+    assert_eq!(strip_bidi_controls(words[2]), "привіт");
+    assert!(matches!(strip_bidi_controls("слово"), Cow::Borrowed(_)));
+    assert_eq!(
+        tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::Control)
+            .count(),
+        5
+    );
+}
+
+#[test]
+fn tags_test() {
+    let text = "@oleh_b глянь #новини_дня, пиши на a@b.ua # 5";
+    let tags: Vec<&str> = Tokenizer::new()
+        .tags(true)
+        .tokenize(text)
+        .filter(|t| t.kind == TokenKind::Tag)
+        .map(|t| t.text)
+        .collect();
+    assert_eq!(tags, ["@oleh_b", "#новини_дня"]);
+    assert!(tokenize(text).all(|t| t.kind != TokenKind::Tag));
+}
+
+#[cfg(feature = "uax29")]
+#[test]
+fn uax29_test() {
+    let words = |tokenizer: &Tokenizer, text| -> Vec<String> {
+        tokenizer
+            .tokenize(text)
+            .filter(|t| t.kind == TokenKind::Word || t.kind == TokenKind::Number)
+            .map(|t| t.text.to_string())
+            .collect()
+    };
+    let custom = Tokenizer::new();
+    let uax29 = Tokenizer::new().segmentation(Segmentation::Uax29);
+
+    // both agree on apostrophes inside words and decimal numbers
+    let text = "п'ять м’яч 3.14 півʼяблука";
+    assert_eq!(words(&custom, text), words(&uax29, text));
+
+    // hyphenated compounds are one word only for the custom rules, abbreviations with
+    // dots only for UAX #29
+    assert_eq!(words(&custom, "як-небудь"), ["як-небудь"]);
+    assert_eq!(words(&uax29, "як-небудь"), ["як", "небудь"]);
+    assert_eq!(words(&custom, "т.зв"), ["т", "зв"]);
+    assert_eq!(words(&uax29, "т.зв"), ["т.зв"]);
+    assert_eq!(words(&uax29, "COVID19 :)"), ["COVID19"]);
+}
+
+#[test]
+fn invisible_test() {
+    // soft hyphens from a PDF, a zero-width space from a web page
+    let text = "ін\u{AD}фор\u{AD}ма\u{AD}ці\u{AD}єю Украї\u{200B}ни\u{200B} \u{FEFF}Київ";
+    let words: Vec<&str> = tokenize(text)
+        .filter(|t| t.kind == TokenKind::Word)
+        .map(|t| t.text)
+        .collect();
+    assert_eq!(
+        words,
+        [
+            "ін\u{AD}фор\u{AD}ма\u{AD}ці\u{AD}єю",
+            "Украї\u{200B}ни",
+            "Київ"
+        ]
+    );
+    assert_eq!(strip_invisible(words[1]), "України");
+    assert!(matches!(strip_invisible("Київ"), Cow::Borrowed(_)));
+}
@@ -0,0 +1,93 @@
+//! Stemming throughput over a corpus of about a million tokens, as separate words and as
+//! one running text.
+//!
+//! Set `STEMMER_UK_BENCH_CORPUS` to a UTF-8 text file to measure on real text; by default
+//! a built-in passage is repeated.
+
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+const TOKENS: usize = 1_000_000;
+
+const PASSAGE: &str = "Верховна Рада України ухвалила в першому читанні законопроєкт про \
+    місцеве самоврядування. Депутати обговорювали зміни до бюджетного кодексу, які \
+    стосуються розподілу податків між громадами та областями. Міністерство фінансів \
+    наполягало на поступовому впровадженні нових правил, а представники громад \
+    вимагали швидших рішень. Слідство триває, свідки дають показання, експерти \
+    досліджують документи. У Києві та Львові пройшли зустрічі з мешканцями, на яких \
+    говорили про ремонт доріг, шкіл і лікарень. Найбільші міста отримають додаткові \
+    кошти на громадський транспорт, а менші громади зможуть залучати інвестиції. \
+    Молодість не вічна, але чесність і відповідальність залишаються найважливішими \
+    якостями керівника, вважають опитані громадяни.";
+
+fn corpus() -> Vec<String> {
+    let text = match std::env::var("STEMMER_UK_BENCH_CORPUS") {
+        Ok(path) => std::fs::read_to_string(path).expect("cannot read the bench corpus"),
+        Err(_) => PASSAGE.to_string(),
+    };
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect();
+    assert!(!words.is_empty(), "the bench corpus has no words");
+    words.iter().cycle().take(TOKENS).cloned().collect()
+}
+
+fn stem_benchmark(c: &mut Criterion) {
+    let words = corpus();
+    let lowercase: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+    let stemmer = stemmer_uk::Stemmer::default();
+    stemmer_uk::warm_up();
+
+    let mut group = c.benchmark_group("stem");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(15));
+    group.throughput(Throughput::Elements(words.len() as u64));
+    group.bench_function("stem_word", |b| {
+        b.iter(|| {
+            words
+                .iter()
+                .map(|w| stemmer_uk::stem_word(w.clone()).len())
+                .sum::<usize>()
+        })
+    });
+    group.bench_function("stem", |b| {
+        b.iter(|| {
+            lowercase
+                .iter()
+                .map(|w| stemmer_uk::stem(w).len())
+                .sum::<usize>()
+        })
+    });
+    group.bench_function("Stemmer::stem", |b| {
+        b.iter(|| words.iter().map(|w| stemmer.stem(w).len()).sum::<usize>())
+    });
+    group.finish();
+}
+
+fn text_benchmark(c: &mut Criterion) {
+    let words = corpus();
+    let text = words.join(" ");
+    let analyzer = stemmer_uk::analyzer::Analyzer::new();
+    stemmer_uk::warm_up();
+
+    let mut group = c.benchmark_group("text");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(15));
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.bench_function("Analyzer::stem_text", |b| {
+        b.iter(|| analyzer.stem_text(&text).len())
+    });
+    group.bench_function("Analyzer::analyze", |b| {
+        b.iter(|| analyzer.analyze(&text).len())
+    });
+    group.bench_function("fold_text", |b| {
+        b.iter(|| stemmer_uk::preprocess::fold_text(&text).len())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, stem_benchmark, text_benchmark);
+criterion_main!(benches);
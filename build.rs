@@ -0,0 +1,65 @@
+// Generates the table of the `precomputed` feature: the stems of the forms of a frequency
+// list under the built-in rules, as a perfect hash table in $OUT_DIR. The list is
+// rules/frequent.txt unless UKSTEM_FREQUENT_WORDS names another one; either way a line
+// holds a form, optionally followed by its count, most frequent first.
+
+#[cfg(feature = "precomputed")]
+fn precomputed() {
+    use std::collections::HashSet;
+    use std::convert::TryFrom;
+    use std::path::Path;
+    use std::{env, fs};
+
+    use stemmer_uk_core::normalize::{normalize, Normalization};
+    use stemmer_uk_core::{stem_cut, RuleGroups};
+
+    // the forms of the list built in at most, in order
+    const MAX_PRECOMPUTED: usize = 50_000;
+
+    println!("cargo:rerun-if-env-changed=UKSTEM_FREQUENT_WORDS");
+    let path = env::var("UKSTEM_FREQUENT_WORDS").unwrap_or_else(|_| "rules/frequent.txt".into());
+    println!("cargo:rerun-if-changed={}", path);
+    let source = fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("precomputed stems: {}: {}", path, err));
+
+    let groups = RuleGroups::builtin();
+    let mut seen = HashSet::new();
+    let mut stems = Vec::new();
+    for line in source.lines().map(str::trim) {
+        if line.starts_with('#') {
+            continue;
+        }
+        let form = match line.split_whitespace().next() {
+            Some(form) => normalize(form, Normalization::default()).into_owned(),
+            None => continue,
+        };
+        if !seen.insert(form.clone()) {
+            continue;
+        }
+        let cut = stem_cut(&form, &groups, &mut None, None, 0, 0);
+        if let Ok(Ok(end)) = cut.map(|cut| u8::try_from(cut.end)) {
+            stems.push((form, end));
+        }
+        if stems.len() == MAX_PRECOMPUTED {
+            break;
+        }
+    }
+    let mut map = phf_codegen::Map::new();
+    for (form, end) in &stems {
+        map.entry(form.as_str(), end.to_string());
+    }
+    let table = format!(
+        "// @generated by build.rs from {}, do not edit.\n\n\
+         pub(crate) static STEMS: phf::Map<&'static str, u8> = {};\n",
+        path,
+        map.build()
+    );
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("precomputed_table.rs");
+    fs::write(&out, table).unwrap_or_else(|err| panic!("{}: {}", out.display(), err));
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "precomputed")]
+    precomputed();
+}
@@ -0,0 +1,42 @@
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+
+use stemmer_uk::dict::DictionaryBuilder;
+use stemmer_uk::Stemmer;
+
+use crate::config::parse_stemmer;
+use crate::{open_input, open_output};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// `form<TAB>stem` per line, such as the output of `mine-exceptions` (`-` for stdin)
+    input: PathBuf,
+    /// Output file, stdout by default
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Configuration whose normalization the forms get, as the stemmer looks them up;
+    /// use the configuration the dictionary will be used with
+    #[arg(long, default_value = "default", value_parser = parse_stemmer)]
+    config: Stemmer,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let mut builder = DictionaryBuilder::new();
+    for (i, line) in open_input(&args.input)?.lines().enumerate() {
+        let line = line?;
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (form, stem) = line.split_once('\t').ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: expected `form<TAB>stem`", i + 1),
+            )
+        })?;
+        builder.insert(&args.config.normalize(form), stem.trim());
+    }
+    builder.write(open_output(args.output.as_ref())?)?;
+    eprintln!("{} forms", builder.len());
+    Ok(())
+}
@@ -0,0 +1,222 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use stemmer_uk::analyzer::Analyzer;
+use stemmer_uk::Stemmer;
+
+use crate::config::parse_stemmer;
+use crate::open_output;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Input files, stemmed one after another
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+    /// Output file, stdout when not given
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Number of worker threads (all cores by default, always one on WASI)
+    #[arg(short, long)]
+    jobs: Option<usize>,
+    /// Size of the chunks the threads take, in MiB; a chunk ends at the next line break
+    #[arg(long, default_value_t = 16)]
+    chunk_mib: usize,
+    /// Read one word per line and write one stem per line, instead of replacing the words
+    /// of running text
+    #[arg(short, long)]
+    lines: bool,
+    /// Configuration, e.g. `default` or `surzhyk,ocr`
+    #[arg(long, default_value = "default", value_parser = parse_stemmer)]
+    config: Stemmer,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let analyzer = crate::analyzer().stemmer(args.config);
+    let chunks = Chunks {
+        bytes: args.chunk_mib.max(1) << 20,
+        jobs: crate::jobs(args.jobs),
+        lines: args.lines,
+    };
+    let mut out = open_output(args.output.as_ref())?;
+    for (i, path) in args.inputs.iter().enumerate() {
+        let contents = map(path)
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))?;
+        chunks.stem(&analyzer, &contents, path, &mut out)?;
+        eprintln!("[{}/{}] {}", i + 1, args.inputs.len(), path.display());
+    }
+    out.flush()
+}
+
+// How `Chunks::stem` splits the work
+struct Chunks {
+    bytes: usize,
+    jobs: usize,
+    lines: bool,
+}
+
+impl Chunks {
+    // Writes the stems of `contents`, read from `path`, to `out` in input order.
+    fn stem<W: Write + ?Sized>(
+        &self,
+        analyzer: &Analyzer,
+        contents: &[u8],
+        path: &Path,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let with_path =
+            |err: io::Error| io::Error::new(err.kind(), format!("{}: {}", path.display(), err));
+        let (jobs, lines) = (self.jobs, self.lines);
+        let ranges = chunks(contents, self.bytes);
+        // a batch of chunks at a time, so that at most `jobs` stemmed chunks wait to be
+        // written
+        for batch in ranges.chunks(jobs) {
+            let stemmed: Vec<io::Result<String>> = if jobs == 1 {
+                batch
+                    .iter()
+                    .map(|range| stem_chunk(analyzer, contents, range.clone(), lines))
+                    .collect()
+            } else {
+                thread::scope(|scope| {
+                    let handles: Vec<_> = batch
+                        .iter()
+                        .map(|range| {
+                            scope
+                                .spawn(move || stem_chunk(analyzer, contents, range.clone(), lines))
+                        })
+                        .collect();
+                    handles
+                        .into_iter()
+                        .map(|handle| handle.join().expect("a stemming thread panicked"))
+                        .collect()
+                })
+            };
+            for chunk in stemmed {
+                out.write_all(chunk.map_err(with_path)?.as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// The contents of `path`, mapped into memory rather than read where the platform allows.
+#[cfg(not(target_os = "wasi"))]
+fn map(path: &Path) -> io::Result<memmap2::Mmap> {
+    let file = File::open(path)?;
+    // Safety: the map is only read, and like any tool reading a file, `bulk` expects the
+    // file not to be truncated while it runs; the map would fault on the lost pages.
+    unsafe { memmap2::Mmap::map(&file) }
+}
+
+#[cfg(target_os = "wasi")]
+fn map(path: &Path) -> io::Result<Vec<u8>> {
+    std::fs::read(path)
+}
+
+// `bytes` split into ranges of about `size` bytes, each ending after a line break except
+// the last
+fn chunks(bytes: &[u8], size: usize) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while start < bytes.len() {
+        let from = (start + size).min(bytes.len());
+        let end = bytes[from..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(bytes.len(), |i| from + i + 1);
+        ranges.push(start..end);
+        start = end;
+    }
+    ranges
+}
+
+// Stems the lines of `contents[range]` as `ukstem stem` does, every one ending in a line
+// break.
+fn stem_chunk(
+    analyzer: &Analyzer,
+    contents: &[u8],
+    range: Range<usize>,
+    lines: bool,
+) -> io::Result<String> {
+    let start = range.start;
+    let text = std::str::from_utf8(&contents[range]).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid UTF-8 at byte {}", start + e.valid_up_to()),
+        )
+    })?;
+    let stemmer = analyzer.get_stemmer();
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let line = line.trim_end_matches(['\n', '\r']);
+        if lines {
+            out.push_str(&stemmer.stem(line.trim()));
+        } else {
+            out.push_str(&analyzer.stem_text(line));
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[test]
+fn chunks_test() {
+    // a chunk ends after the line break following its size, even inside a multi-byte char
+    let text = "ааа\nбб\nв".as_bytes();
+    assert_eq!(chunks(text, 1), [0..7, 7..12, 12..14]);
+    assert_eq!(chunks(text, 7), [0..12, 12..14]);
+    for range in chunks(text, 3) {
+        assert!(std::str::from_utf8(&text[range]).is_ok());
+    }
+    // smaller than one chunk, and empty
+    let whole = chunks(text, 1 << 20);
+    assert_eq!((whole.len(), whole[0].end), (1, text.len()));
+    assert!(chunks(b"", 1).is_empty());
+}
+
+#[test]
+fn stem_chunks_test() {
+    let analyzer = crate::analyzer();
+    let words: Vec<String> = (0..200)
+        .map(|i| ["книгами", "народи", "слова", "автора"][i % 4].to_string())
+        .collect();
+    // no line break at the end
+    let text = words.join("\n");
+    let expected: String = words
+        .iter()
+        .map(|word| analyzer.get_stemmer().stem(word) + "\n")
+        .collect();
+
+    let path = Path::new("words.txt");
+    for (bytes, jobs) in [(1, 1), (1, 8), (10, 3), (1 << 20, 4)].iter() {
+        let chunks = Chunks {
+            bytes: *bytes,
+            jobs: *jobs,
+            lines: true,
+        };
+        let mut out = Vec::new();
+        chunks
+            .stem(&analyzer, text.as_bytes(), path, &mut out)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            expected,
+            "{} {}",
+            bytes,
+            jobs
+        );
+    }
+
+    let chunks = Chunks {
+        bytes: 1,
+        jobs: 2,
+        lines: false,
+    };
+    let mut out = Vec::new();
+    let err = chunks
+        .stem(&analyzer, b"\xff\n", path, &mut out)
+        .unwrap_err();
+    assert_eq!(err.to_string(), "words.txt: invalid UTF-8 at byte 0");
+}
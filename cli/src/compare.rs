@@ -0,0 +1,212 @@
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+
+use crate::{analyzer, open_input, open_output};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Word list, one word per line (`-` for stdin)
+    input: PathBuf,
+    /// Stemmers to compare with: `snowball-ru` (Snowball `stemwords`), `hunspell`, or
+    /// `cmd:<program> [args]` for any program printing one stem per input line
+    #[arg(long, value_delimiter = ',', required = true)]
+    against: Vec<String>,
+    /// Dictionary passed to hunspell
+    #[arg(long, default_value = "uk_UA")]
+    hunspell_dict: String,
+    /// Side-by-side table of the stems (stdout by default)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+enum Output {
+    /// One stem per input line.
+    Lines,
+    /// `hunspell -s`: lines of `word stem` per input word, each group ended by a blank line.
+    Hunspell,
+}
+
+fn command(name: &str, args: &Args) -> io::Result<(Command, Output)> {
+    let (program, params, output): (&str, Vec<&str>, Output) = match name {
+        "snowball-ru" => ("stemwords", vec!["-l", "russian"], Output::Lines),
+        "hunspell" => (
+            "hunspell",
+            vec!["-s", "-d", &args.hunspell_dict],
+            Output::Hunspell,
+        ),
+        _ => match name.strip_prefix("cmd:") {
+            Some(cmd) => {
+                let mut parts = cmd.split_whitespace();
+                let program = parts.next().unwrap_or_default();
+                (program, parts.collect(), Output::Lines)
+            }
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "unknown stemmer `{}`, expected snowball-ru, hunspell or cmd:<program>",
+                        name
+                    ),
+                ))
+            }
+        },
+    };
+    let mut command = Command::new(program);
+    command.args(params);
+    Ok((command, output))
+}
+
+// feeds the words to an external stemmer and collects one stem per word
+fn external_stems(name: &str, args: &Args, words: &[String]) -> io::Result<Vec<String>> {
+    let (mut command, format) = command(name, args)?;
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", name, err)))?;
+    let mut stdin = child.stdin.take().unwrap();
+    let input = words.join("\n");
+    let writer = thread::spawn(move || writeln!(stdin, "{}", input));
+
+    let stems = parse_stems(BufReader::new(child.stdout.take().unwrap()), &format)?;
+    writer.join().unwrap()?;
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("{} failed: {}", name, status)));
+    }
+    if stems.len() != words.len() {
+        return Err(io::Error::other(format!(
+            "{} returned {} stems for {} words",
+            name,
+            stems.len(),
+            words.len()
+        )));
+    }
+    Ok(stems)
+}
+
+// the stems of the output of an external stemmer, in input order
+fn parse_stems<R: BufRead>(output: R, format: &Output) -> io::Result<Vec<String>> {
+    let mut stems = Vec::new();
+    let mut group: Option<String> = None;
+    for line in output.lines() {
+        let line = line?;
+        match format {
+            Output::Lines => stems.push(line.trim().to_lowercase()),
+            Output::Hunspell if line.trim().is_empty() => {
+                if let Some(stem) = group.take() {
+                    stems.push(stem);
+                }
+            }
+            Output::Hunspell => {
+                // the first analysis wins, unknown words come back alone
+                if group.is_none() {
+                    let stem = line.split_whitespace().last().unwrap_or_default();
+                    group = Some(stem.to_lowercase());
+                }
+            }
+        }
+    }
+    stems.extend(group);
+    Ok(stems)
+}
+
+// for every word, the set of words sharing its stem
+fn classes(words: &[String], stems: &[String]) -> Vec<BTreeSet<usize>> {
+    let mut by_stem: HashMap<&str, BTreeSet<usize>> = HashMap::new();
+    for (i, stem) in stems.iter().enumerate() {
+        by_stem.entry(stem).or_default().insert(i);
+    }
+    (0..words.len())
+        .map(|i| by_stem[stems[i].as_str()].clone())
+        .collect()
+}
+
+// the number of distinct stems of `theirs`, and of the words whose conflation group it
+// shares with `ours`: stems of different stemmers are not comparable as strings, the groups
+// of words they conflate are
+fn agreement(words: &[String], ours: &[String], theirs: &[String]) -> (usize, usize) {
+    let agree = classes(words, theirs)
+        .iter()
+        .zip(classes(words, ours))
+        .filter(|(a, b)| **a == *b)
+        .count();
+    (theirs.iter().collect::<BTreeSet<_>>().len(), agree)
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let mut words: Vec<String> = Vec::new();
+    let mut seen = BTreeSet::new();
+    for line in open_input(&args.input)?.lines() {
+        let word = line?.trim().to_lowercase();
+        if !word.is_empty() && seen.insert(word.clone()) {
+            words.push(word);
+        }
+    }
+
+    let ours: Vec<String> = analyzer().get_stemmer().stem_all(&words).collect();
+    let theirs = args
+        .against
+        .iter()
+        .map(|name| external_stems(name, &args, &words))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let mut out = open_output(args.output.as_ref())?;
+    writeln!(out, "word\tukstem\t{}", args.against.join("\t"))?;
+    for (i, word) in words.iter().enumerate() {
+        write!(out, "{}\t{}", word, ours[i])?;
+        for stems in &theirs {
+            write!(out, "\t{}", stems[i])?;
+        }
+        writeln!(out)?;
+    }
+    out.flush()?;
+
+    let our_count = ours.iter().collect::<BTreeSet<_>>().len();
+    eprintln!("{} words, ukstem: {} stems", words.len(), our_count);
+    for (name, stems) in args.against.iter().zip(&theirs) {
+        let (count, agree) = agreement(&words, &ours, stems);
+        eprintln!(
+            "{}: {} stems, same conflation group for {} words ({:.1}%)",
+            name,
+            count,
+            agree,
+            100.0 * agree as f64 / words.len().max(1) as f64
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn parse_stems_test() {
+    let lines = "книг\nНарод\n";
+    let stems = parse_stems(lines.as_bytes(), &Output::Lines).unwrap();
+    assert_eq!(stems, ["книг", "народ"]);
+
+    // the first analysis of each word, and unknown words echoed alone
+    let hunspell = "книгами книга\nкнигами книг\n\nqwerty\n\nнароди народ\n";
+    let stems = parse_stems(hunspell.as_bytes(), &Output::Hunspell).unwrap();
+    assert_eq!(stems, ["книга", "qwerty", "народ"]);
+}
+
+#[test]
+fn agreement_test() {
+    let words: Vec<String> = ["книга", "книгами", "народ", "народи", "слово"]
+        .iter()
+        .map(|w| w.to_string())
+        .collect();
+    let stems = |stems: [&str; 5]| -> Vec<String> { stems.iter().map(|s| s.to_string()).collect() };
+    let ours = stems(["книг", "книг", "народ", "народ", "слов"]);
+    // other strings, the same groups
+    let renamed = stems(["a", "a", "b", "b", "c"]);
+    assert_eq!(agreement(&words, &ours, &renamed), (3, 5));
+    // "народи" apart: its group and that of "народ" differ
+    let split = stems(["книга", "книга", "народ", "народи", "слово"]);
+    assert_eq!(agreement(&words, &ours, &split), (4, 3));
+    // everything merged: only groups of one word could agree, and there are none
+    let merged = stems(["x"; 5]);
+    assert_eq!(agreement(&words, &ours, &merged), (1, 0));
+}
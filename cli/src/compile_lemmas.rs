@@ -0,0 +1,22 @@
+use std::io;
+use std::path::PathBuf;
+
+use stemmer_uk::lemmas::LemmaDictionary;
+
+use crate::{open_input, open_output};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// VESUM's `dict_corp_lt.txt`, `form lemma tags` per line (`-` for stdin)
+    input: PathBuf,
+    /// Output file, stdout by default
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let lemmas = LemmaDictionary::from_vesum(open_input(&args.input)?)?;
+    lemmas.write(open_output(args.output.as_ref())?)?;
+    eprintln!("{} forms", lemmas.len());
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use stemmer_uk::homoglyph::HomoglyphRepair;
+use stemmer_uk::ocr::Confusions;
+use stemmer_uk::{
+    packs, Acronyms, Apostrophes, Compounds, Foreign, Language, Mode, Stemmer, SUPERLATIVE_PREFIXES,
+};
+
+/// Names accepted in a configuration spec such as `surzhyk,ocr`.
+pub const NAMES: [&str; 20] = [
+    "default",
+    "surzhyk",
+    "historical",
+    "kharkiv",
+    "vocative",
+    "ocr",
+    "confusions",
+    "apostrophes",
+    "unicode",
+    "homoglyphs",
+    "ge",
+    "acronyms",
+    "idempotent",
+    "compounds",
+    "light",
+    "ukrainian",
+    "superlatives",
+    "feminitives",
+    "diminutives",
+    "translit",
+];
+
+/// Parses a comma-separated list of configuration names into a stemmer.
+pub fn parse_stemmer(spec: &str) -> Result<Stemmer, String> {
+    let mut builder = Stemmer::builder();
+    for name in spec.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        builder = match name {
+            "default" => builder,
+            "surzhyk" => builder.pack(packs::surzhyk()),
+            "historical" => builder.pack(packs::historical()),
+            "kharkiv" => builder.pack(packs::kharkiv()),
+            "vocative" => builder.pack(packs::vocative()),
+            "ocr" => builder.ocr_tolerant(true),
+            "confusions" => builder.ocr_confusions(Confusions::default()),
+            "apostrophes" => builder.apostrophes(Apostrophes::Keep),
+            "unicode" => builder.fold_unicode(true),
+            "homoglyphs" => builder.homoglyph_repair(HomoglyphRepair::Letters),
+            "ge" => builder.fold_ge(true),
+            "acronyms" => builder.acronyms(Acronyms::Keep),
+            "idempotent" => builder.idempotent(true),
+            "compounds" => builder.compounds(Compounds::Parts),
+            "light" => builder.mode(Mode::Light),
+            "ukrainian" => builder.language(Language::Ukrainian),
+            "superlatives" => builder.strip_prefixes(SUPERLATIVE_PREFIXES.iter().copied()),
+            "feminitives" => builder.feminitives(true),
+            "diminutives" => builder.diminutives(true),
+            "translit" => builder.foreign(Foreign::Transliterate),
+            _ => {
+                return Err(format!(
+                    "unknown configuration `{}`, expected one of: {}",
+                    name,
+                    NAMES.join(", ")
+                ))
+            }
+        };
+    }
+    Ok(builder.build())
+}
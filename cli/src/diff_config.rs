@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+use stemmer_uk::tokenizer::{tokenize, TokenKind};
+use stemmer_uk::Stemmer;
+
+use crate::config::parse_stemmer;
+use crate::{open_input, open_output};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Baseline configuration, e.g. `default` or `surzhyk,ocr`
+    #[arg(long, default_value = "default", value_parser = parse_stemmer)]
+    a: Stemmer,
+    /// Configuration to compare against the baseline
+    #[arg(long, value_parser = parse_stemmer)]
+    b: Stemmer,
+    /// Corpus text (`-` for stdin)
+    input: PathBuf,
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+struct Diff {
+    word: String,
+    count: usize,
+    a: String,
+    b: String,
+}
+
+// Rules fired by only one side, or "normalization" when both fired the same rules and the
+// difference comes from preprocessing (packs, OCR repair).
+fn reason(a: &[&str], b: &[&str]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    parts.extend(
+        a.iter()
+            .filter(|r| !b.contains(r))
+            .map(|r| format!("-{}", r)),
+    );
+    parts.extend(
+        b.iter()
+            .filter(|r| !a.contains(r))
+            .map(|r| format!("+{}", r)),
+    );
+    if parts.is_empty() {
+        "normalization".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let mut text = String::new();
+    open_input(&args.input)?.read_to_string(&mut text)?;
+
+    let mut words: BTreeMap<String, usize> = BTreeMap::new();
+    for token in tokenize(&text).filter(|t| t.kind == TokenKind::Word) {
+        *words.entry(token.text.to_lowercase()).or_insert(0) += 1;
+    }
+
+    let mut groups: BTreeMap<String, Vec<Diff>> = BTreeMap::new();
+    for (word, count) in words.iter() {
+        let (a, fired_a) = args.a.stem_with_rules(word);
+        let (b, fired_b) = args.b.stem_with_rules(word);
+        if a != b {
+            groups
+                .entry(reason(&fired_a, &fired_b))
+                .or_default()
+                .push(Diff {
+                    word: word.clone(),
+                    count: *count,
+                    a,
+                    b,
+                });
+        }
+    }
+
+    let mut out = open_output(args.output.as_ref())?;
+    let changed: usize = groups.values().map(Vec::len).sum();
+    writeln!(
+        out,
+        "{} of {} distinct words stem differently",
+        changed,
+        words.len()
+    )?;
+    let mut groups: Vec<(String, Vec<Diff>)> = groups.into_iter().collect();
+    groups
+        .sort_by_key(|(_, diffs)| std::cmp::Reverse(diffs.iter().map(|d| d.count).sum::<usize>()));
+    for (reason, mut diffs) in groups {
+        let occurrences: usize = diffs.iter().map(|d| d.count).sum();
+        writeln!(
+            out,
+            "\n== {} ({} words, {} occurrences)",
+            reason,
+            diffs.len(),
+            occurrences
+        )?;
+        diffs.sort_by_key(|d| std::cmp::Reverse(d.count));
+        for d in diffs {
+            writeln!(out, "  {}\t{} -> {}\t({})", d.word, d.a, d.b, d.count)?;
+        }
+    }
+    out.flush()
+}
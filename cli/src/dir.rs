@@ -0,0 +1,239 @@
+use globset::Glob;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use walkdir::WalkDir;
+
+use stemmer_uk::analyzer::Analyzer;
+
+use crate::manifest::Manifest;
+
+// input bytes processed between two manifest checkpoints of a file
+const CHECKPOINT_BYTES: u64 = 8 << 20;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Root of the corpus
+    input: PathBuf,
+    /// Files to process, matched against the path relative to the input root
+    #[arg(long, default_value = "**/*.txt")]
+    glob: String,
+    /// Output root; the input directory tree is mirrored below it
+    #[arg(long)]
+    out: PathBuf,
+    /// Number of worker threads (all cores by default, always one on WASI)
+    #[arg(short, long)]
+    jobs: Option<usize>,
+    /// Ignore the job manifest and reprocess files whose output already exists
+    #[arg(long)]
+    force: bool,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let matcher = Glob::new(&args.glob)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?
+        .compile_matcher();
+
+    let mut files = Vec::new();
+    for entry in WalkDir::new(&args.input).sort_by_file_name() {
+        let entry = entry?;
+        let rel = entry
+            .path()
+            .strip_prefix(&args.input)
+            .unwrap_or(entry.path());
+        if entry.file_type().is_file() && matcher.is_match(rel) {
+            files.push(rel.to_path_buf());
+        }
+    }
+
+    let jobs = crate::jobs(args.jobs);
+    let analyzer = crate::analyzer();
+    let manifest = Mutex::new(Manifest::open(
+        &args.out,
+        &analyzer.fingerprint().to_string(),
+        args.force,
+    )?);
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let skipped = AtomicUsize::new(0);
+    let errors = Mutex::new(Vec::new());
+
+    let worker = || loop {
+        let i = next.fetch_add(1, Ordering::Relaxed);
+        let Some(rel) = files.get(i) else { break };
+        let dest = args.out.join(rel);
+        let (finished, resume) = {
+            let manifest = manifest.lock().unwrap();
+            let untracked = manifest.partial(rel).is_none() && dest.exists();
+            (
+                manifest.is_done(rel) || untracked && !args.force,
+                manifest.partial(rel),
+            )
+        };
+        let res = if finished {
+            skipped.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        } else {
+            let src = args.input.join(rel);
+            process_file(&analyzer, &src, &dest, resume, |input, output| {
+                manifest.lock().unwrap().checkpoint(rel, input, output)
+            })
+            .and_then(|_| manifest.lock().unwrap().mark_done(rel))
+        };
+        let n = done.fetch_add(1, Ordering::Relaxed) + 1;
+        match res {
+            Ok(()) => eprintln!("[{}/{}] {}", n, files.len(), rel.display()),
+            Err(err) => {
+                eprintln!("[{}/{}] {}: {}", n, files.len(), rel.display(), err);
+                errors.lock().unwrap().push(rel.clone());
+            }
+        }
+    };
+    if jobs == 1 {
+        worker();
+    } else {
+        thread::scope(|scope| {
+            for _ in 0..jobs {
+                scope.spawn(worker);
+            }
+        });
+    }
+
+    let errors = errors.into_inner().unwrap();
+    eprintln!(
+        "{} files, {} skipped as already done, {} failed",
+        files.len(),
+        skipped.into_inner(),
+        errors.len()
+    );
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{} files failed", errors.len())))
+    }
+}
+
+// Streams the file line by line into a `.part` file, renamed into place once complete, so an
+// interrupted run never leaves an output that would be skipped as finished. `resume` holds
+// the input and output offsets of the last checkpoint.
+fn process_file<C>(
+    analyzer: &Analyzer,
+    src: &Path,
+    dest: &Path,
+    resume: Option<(u64, u64)>,
+    mut checkpoint: C,
+) -> io::Result<()>
+where
+    C: FnMut(u64, u64) -> io::Result<()>,
+{
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut part = dest.as_os_str().to_owned();
+    part.push(".part");
+    let part = PathBuf::from(part);
+
+    let (mut input_pos, mut output_pos) = resume.unwrap_or((0, 0));
+    let mut input = BufReader::new(File::open(src)?);
+    input.seek(SeekFrom::Start(input_pos))?;
+    let mut output = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(resume.is_none())
+        .open(&part)?;
+    output.set_len(output_pos)?;
+    output.seek(SeekFrom::Start(output_pos))?;
+    let mut output = io::BufWriter::new(output);
+
+    let mut line = String::new();
+    let mut last_checkpoint = input_pos;
+    loop {
+        line.clear();
+        let read = input.read_line(&mut line)?;
+        if read == 0 {
+            break;
+        }
+        let stemmed = analyzer.stem_text(&line);
+        output.write_all(stemmed.as_bytes())?;
+        input_pos += read as u64;
+        output_pos += stemmed.len() as u64;
+
+        if input_pos - last_checkpoint >= CHECKPOINT_BYTES {
+            output.flush()?;
+            output.get_ref().sync_data()?;
+            checkpoint(input_pos, output_pos)?;
+            last_checkpoint = input_pos;
+        }
+    }
+    output.flush()?;
+    drop(output);
+    fs::rename(&part, dest)
+}
+
+#[cfg(test)]
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ukstem-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn resume_test() {
+    let root = temp_dir("dir-resume");
+    let (input, out) = (root.join("corpus"), root.join("stemmed"));
+    fs::create_dir_all(input.join("news")).unwrap();
+    fs::write(input.join("a.txt"), "Народи книгами\n").unwrap();
+    fs::write(input.join("news/b.txt"), "слова автора\n").unwrap();
+    let args = |force| Args {
+        input: input.clone(),
+        glob: "**/*.txt".to_string(),
+        out: out.clone(),
+        jobs: Some(2),
+        force,
+    };
+    run(args(false)).unwrap();
+    let analyzer = crate::analyzer();
+    let stemmed = fs::read_to_string(out.join("news/b.txt")).unwrap();
+    assert_eq!(stemmed, analyzer.stem_text("слова автора\n"));
+
+    // finished files are skipped, so the changed output stays
+    fs::write(out.join("a.txt"), "kept").unwrap();
+    run(args(false)).unwrap();
+    assert_eq!(fs::read_to_string(out.join("a.txt")).unwrap(), "kept");
+    run(args(true)).unwrap();
+    assert_ne!(fs::read_to_string(out.join("a.txt")).unwrap(), "kept");
+
+    // another configuration refuses to resume, unless forced
+    let manifest = out.join(crate::manifest::MANIFEST_NAME);
+    let text = fs::read_to_string(&manifest).unwrap();
+    let other = text.replacen("config ", "config 0000", 1);
+    fs::write(&manifest, other).unwrap();
+    let err = run(args(false)).err().unwrap();
+    assert!(err.to_string().starts_with("refusing to resume"), "{}", err);
+    run(args(true)).unwrap();
+    assert_eq!(fs::read_to_string(&manifest).unwrap(), text);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn process_file_resume_test() {
+    let root = temp_dir("dir-partial");
+    let (src, dest) = (root.join("in.txt"), root.join("out.txt"));
+    fs::write(&src, "народи\nкнигами\n").unwrap();
+    let analyzer = crate::analyzer();
+    let first = analyzer.stem_text("народи\n");
+    // the output of an interrupted run, past its last checkpoint
+    fs::write(root.join("out.txt.part"), format!("{}half a li", first)).unwrap();
+    let resume = ("народи\n".len() as u64, first.len() as u64);
+    process_file(&analyzer, &src, &dest, Some(resume), |_, _| Ok(())).unwrap();
+    assert_eq!(
+        fs::read_to_string(&dest).unwrap(),
+        analyzer.stem_text("народи\nкнигами\n")
+    );
+    assert!(!root.join("out.txt.part").exists());
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,38 @@
+use std::io;
+use std::path::PathBuf;
+
+use stemmer_uk::fingerprint;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Verify that these artifacts were stamped with the current configuration
+    #[arg(long, num_args = 1..)]
+    check: Vec<PathBuf>,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let current = crate::analyzer().fingerprint();
+    if args.check.is_empty() {
+        println!("{}", current);
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for artifact in &args.check {
+        match fingerprint::verify(artifact, current) {
+            Ok(()) => println!("{}: ok", artifact.display()),
+            Err(err) => {
+                println!("{}", err);
+                failed += 1;
+            }
+        }
+    }
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "{} artifacts do not match configuration {}",
+            failed, current
+        )))
+    }
+}
@@ -0,0 +1,124 @@
+use clap::{Parser, Subcommand};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+
+use stemmer_uk::analyzer::Analyzer;
+
+mod build_dict;
+mod bulk;
+mod compare;
+mod compile_lemmas;
+mod config;
+mod diff_config;
+mod dir;
+mod fingerprint;
+mod manifest;
+mod mine_exceptions;
+mod sample_bugreport;
+#[cfg(feature = "server")]
+mod serve;
+mod site_index;
+mod stem;
+mod uniq_stems;
+
+#[derive(Parser)]
+#[command(name = "ukstem", version, about = "Stemmer for the ukrainian language")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Stem words or running text from files or stdin to stdout
+    Stem(stem::Args),
+    /// Write the sorted unique stems of a word list, one word per line, using external sorting
+    UniqStems(uniq_stems::Args),
+    /// Stem every matching file of a directory tree in parallel into a mirrored output tree
+    Dir(dir::Args),
+    /// Stem large files in parallel chunks of memory-mapped input, writing the stems in order
+    Bulk(bulk::Args),
+    /// Print the configuration fingerprint or check the stamps of artifacts against it
+    Fingerprint(fingerprint::Args),
+    /// Build a lunr-style JSON search index of a static site's content
+    SiteIndex(site_index::Args),
+    /// Report the words of a corpus that two configurations stem differently, by rule
+    DiffConfig(Box<diff_config::Args>),
+    /// Compare stems and conflation groups with other stemmers on a word list
+    Compare(compare::Args),
+    /// Propose an exception dictionary from a lemma-annotated corpus
+    MineExceptions(mine_exceptions::Args),
+    /// Compile VESUM's `dict_corp_lt.txt` into a lemma dictionary for `stem --lemmas`
+    CompileLemmas(compile_lemmas::Args),
+    /// Compile a `form<TAB>stem` dictionary into a memory-mapped file for `stem --dict`
+    BuildDict(build_dict::Args),
+    /// Sample words of a corpus the stemmer handles unusually, without context, for bug
+    /// reports
+    SampleBugreport(sample_bugreport::Args),
+    /// Serve `POST /stem` over HTTP, taking a JSON array of words or raw text
+    #[cfg(feature = "server")]
+    Serve(serve::Args),
+}
+
+/// The analysis configuration shared by all subcommands; every artifact is stamped with
+/// its fingerprint.
+pub fn analyzer() -> Analyzer {
+    Analyzer::new()
+}
+
+/// The number of worker threads for `--jobs`: all cores by default, always one on WASI,
+/// which has no threads.
+pub fn jobs(requested: Option<usize>) -> usize {
+    if cfg!(target_os = "wasi") {
+        1
+    } else {
+        requested
+            .or_else(|| thread::available_parallelism().ok().map(|n| n.get()))
+            .unwrap_or(1)
+            .max(1)
+    }
+}
+
+/// Opens `path` for reading, `-` meaning stdin.
+pub fn open_input(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    if path == Path::new("-") {
+        Ok(Box::new(BufReader::new(io::stdin())))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+/// Opens `path` for writing, stdout when `None`.
+pub fn open_output(path: Option<&PathBuf>) -> io::Result<Box<dyn Write>> {
+    match path {
+        Some(path) => Ok(Box::new(BufWriter::new(File::create(path)?))),
+        None => Ok(Box::new(BufWriter::new(io::stdout()))),
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let res = match cli.command {
+        Command::Stem(args) => stem::run(args),
+        Command::UniqStems(args) => uniq_stems::run(args),
+        Command::Dir(args) => dir::run(args),
+        Command::Bulk(args) => bulk::run(args),
+        Command::Fingerprint(args) => fingerprint::run(args),
+        Command::SiteIndex(args) => site_index::run(args),
+        Command::DiffConfig(args) => diff_config::run(*args),
+        Command::Compare(args) => compare::run(args),
+        Command::MineExceptions(args) => mine_exceptions::run(args),
+        Command::CompileLemmas(args) => compile_lemmas::run(args),
+        Command::BuildDict(args) => build_dict::run(args),
+        Command::SampleBugreport(args) => sample_bugreport::run(args),
+        #[cfg(feature = "server")]
+        Command::Serve(args) => serve::run(args),
+    };
+    if let Err(err) = res {
+        eprintln!("ukstem: {}", err);
+        process::exit(1);
+    }
+}
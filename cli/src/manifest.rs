@@ -0,0 +1,135 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const MANIFEST_NAME: &str = ".ukstem-manifest";
+
+/// Progress of a `dir` job, stored next to its outputs so an interrupted run can resume.
+///
+/// The text format is one record per line:
+/// `config <hash>`, `done <path>` and `partial <input offset> <output offset> <path>`.
+pub struct Manifest {
+    path: PathBuf,
+    config: String,
+    done: BTreeSet<PathBuf>,
+    partial: BTreeMap<PathBuf, (u64, u64)>,
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl Manifest {
+    /// Loads the manifest in `out_dir`, or starts a new one when there is none or `reset` is set.
+    /// Fails if an existing manifest was written with a different configuration.
+    pub fn open(out_dir: &Path, config: &str, reset: bool) -> io::Result<Self> {
+        let mut manifest = Manifest {
+            path: out_dir.join(MANIFEST_NAME),
+            config: config.to_string(),
+            done: BTreeSet::new(),
+            partial: BTreeMap::new(),
+        };
+        if reset || !manifest.path.exists() {
+            return Ok(manifest);
+        }
+
+        for line in fs::read_to_string(&manifest.path)?.lines() {
+            let mut parts = line.splitn(2, ' ');
+            match (parts.next(), parts.next()) {
+                (Some("config"), Some(hash)) if hash != config => {
+                    return Err(invalid(format!(
+                        "refusing to resume: {} was written with configuration {}, current is {} (use --force to start over)",
+                        manifest.path.display(),
+                        hash,
+                        config
+                    )));
+                }
+                (Some("config"), Some(_)) => {}
+                (Some("done"), Some(path)) => {
+                    manifest.done.insert(PathBuf::from(path));
+                }
+                (Some("partial"), Some(rest)) => {
+                    let mut fields = rest.splitn(3, ' ');
+                    let offsets = (fields.next(), fields.next(), fields.next());
+                    match offsets {
+                        (Some(input), Some(output), Some(path)) => {
+                            let input = input.parse().map_err(|_| invalid(line.to_string()))?;
+                            let output = output.parse().map_err(|_| invalid(line.to_string()))?;
+                            manifest
+                                .partial
+                                .insert(PathBuf::from(path), (input, output));
+                        }
+                        _ => return Err(invalid(format!("malformed manifest line: {}", line))),
+                    }
+                }
+                _ => return Err(invalid(format!("malformed manifest line: {}", line))),
+            }
+        }
+        Ok(manifest)
+    }
+
+    pub fn is_done(&self, rel: &Path) -> bool {
+        self.done.contains(rel)
+    }
+
+    /// Input and output byte offsets of the last checkpoint of an unfinished file.
+    pub fn partial(&self, rel: &Path) -> Option<(u64, u64)> {
+        self.partial.get(rel).copied()
+    }
+
+    pub fn checkpoint(&mut self, rel: &Path, input: u64, output: u64) -> io::Result<()> {
+        self.partial.insert(rel.to_path_buf(), (input, output));
+        self.save()
+    }
+
+    pub fn mark_done(&mut self, rel: &Path) -> io::Result<()> {
+        self.partial.remove(rel);
+        self.done.insert(rel.to_path_buf());
+        self.save()
+    }
+
+    // written to a temporary file and renamed, so a crash never leaves half a manifest
+    fn save(&self) -> io::Result<()> {
+        let mut text = format!("config {}\n", self.config);
+        for path in &self.done {
+            text.push_str(&format!("done {}\n", path.display()));
+        }
+        for (path, (input, output)) in &self.partial {
+            text.push_str(&format!(
+                "partial {} {} {}\n",
+                input,
+                output,
+                path.display()
+            ));
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, text)?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+#[test]
+fn manifest_test() {
+    let dir = std::env::temp_dir().join(format!("ukstem-manifest-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let mut manifest = Manifest::open(&dir, "abc", false).unwrap();
+    manifest
+        .checkpoint(Path::new("news/a b.txt"), 10, 8)
+        .unwrap();
+    manifest.mark_done(Path::new("b.txt")).unwrap();
+
+    let reopened = Manifest::open(&dir, "abc", false).unwrap();
+    assert_eq!(reopened.partial(Path::new("news/a b.txt")), Some((10, 8)));
+    assert!(reopened.is_done(Path::new("b.txt")));
+    assert!(!reopened.is_done(Path::new("news/a b.txt")));
+
+    let err = Manifest::open(&dir, "abd", false).err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let reset = Manifest::open(&dir, "abd", true).unwrap();
+    assert_eq!(reset.partial(Path::new("news/a b.txt")), None);
+    fs::remove_dir_all(&dir).unwrap();
+}
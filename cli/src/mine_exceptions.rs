@@ -0,0 +1,30 @@
+use std::io;
+use std::path::PathBuf;
+
+use stemmer_uk::corpus::read_conllu;
+use stemmer_uk::mining::{mine_exceptions, write_exceptions};
+use stemmer_uk::Stemmer;
+
+use crate::config::parse_stemmer;
+use crate::{open_input, open_output};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Configuration to tune, e.g. `default` or `surzhyk,ocr`
+    #[arg(long, default_value = "default", value_parser = parse_stemmer)]
+    config: Stemmer,
+    /// Keep at most this many exceptions, the most frequent forms
+    #[arg(long, default_value_t = 1000)]
+    max_entries: usize,
+    /// Lemma-annotated corpus in CoNLL-U (`-` for stdin)
+    input: PathBuf,
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let pairs = read_conllu(open_input(&args.input)?)?;
+    let mined = mine_exceptions(&args.config, &pairs, args.max_entries);
+    eprintln!("{} exceptions from {} tokens", mined.len(), pairs.len());
+    write_exceptions(open_output(args.output.as_ref())?, &mined)
+}
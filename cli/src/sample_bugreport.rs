@@ -0,0 +1,78 @@
+use globset::Glob;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use walkdir::WalkDir;
+
+use stemmer_uk::audit::{sample_unusual, write_unusual};
+use stemmer_uk::tokenizer::{tokenize, TokenKind};
+
+use crate::{analyzer, open_output};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Corpus directory, or a single text file
+    input: PathBuf,
+    /// Files of a directory to read, matched against the path relative to it
+    #[arg(long, default_value = "**/*.txt")]
+    glob: String,
+    /// Number of words in the sample
+    #[arg(long, default_value_t = 500)]
+    words: usize,
+    /// Seed of the shuffle; the same corpus and seed give the same sample
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Output file (stdout by default)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let matcher = Glob::new(&args.glob)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?
+        .compile_matcher();
+    let mut words = HashSet::new();
+    for entry in WalkDir::new(&args.input) {
+        let entry = entry?;
+        let rel = entry
+            .path()
+            .strip_prefix(&args.input)
+            .unwrap_or(entry.path());
+        let single = entry.depth() == 0;
+        if entry.file_type().is_file() && (single || matcher.is_match(rel)) {
+            let text = fs::read_to_string(entry.path())?;
+            for token in tokenize(&text).filter(|t| t.kind == TokenKind::Word) {
+                if !words.contains(token.text) {
+                    words.insert(token.text.to_string());
+                }
+            }
+        }
+    }
+
+    let analyzer = analyzer();
+    let stemmer = analyzer.get_stemmer();
+    let sample = sample_unusual(
+        stemmer,
+        words.iter().map(String::as_str),
+        args.words,
+        args.seed,
+    );
+    // only words, without the text around them, so the sample can be attached to an issue
+    let mut out = open_output(args.output.as_ref())?;
+    writeln!(
+        out,
+        "# ukstem sample-bugreport, stemmer_uk {}, configuration {}, seed {}",
+        env!("CARGO_PKG_VERSION"),
+        analyzer.fingerprint(),
+        args.seed
+    )?;
+    write_unusual(&mut out, &sample)?;
+    out.flush()?;
+    eprintln!(
+        "{} unusual words sampled from {} distinct words",
+        sample.len(),
+        words.len()
+    );
+    Ok(())
+}
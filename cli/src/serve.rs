@@ -0,0 +1,164 @@
+use std::io;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::Router;
+use stemmer_uk::analyzer::Analyzer;
+use stemmer_uk::cache::ShardedCache;
+use stemmer_uk::Stemmer;
+
+use crate::analyzer;
+use crate::config::parse_stemmer;
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[arg(long, default_value_t = 8080)]
+    port: u16,
+    /// Address to listen on; `0.0.0.0` accepts connections from other hosts
+    #[arg(long, default_value = "127.0.0.1")]
+    host: IpAddr,
+    /// Configuration, e.g. `default` or `surzhyk,ocr`
+    #[arg(long, default_value = "default", value_parser = parse_stemmer)]
+    config: Stemmer,
+    /// Words whose stems the workers share in a cache, none by default
+    #[arg(long, default_value_t = 0)]
+    cache: usize,
+    /// Seconds a cached stem is kept, forever when not given
+    #[arg(long)]
+    cache_ttl: Option<u64>,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let mut stemmer = args.config;
+    if args.cache > 0 {
+        let mut cache = ShardedCache::with_max_entries(args.cache);
+        if let Some(seconds) = args.cache_ttl {
+            cache = cache.ttl(Duration::from_secs(seconds));
+        }
+        stemmer = stemmer.to_builder().sharded_cache(Arc::new(cache)).build();
+    }
+    let app = app(Arc::new(analyzer().stemmer(stemmer)));
+    let addr = (args.host, args.port);
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        eprintln!("listening on http://{}", listener.local_addr()?);
+        axum::serve(listener, app).await
+    })
+}
+
+fn app(analyzer: Arc<Analyzer>) -> Router {
+    Router::new()
+        .route("/stem", post(stem))
+        .route("/stats", get(stats))
+        .with_state(analyzer)
+}
+
+// `POST /stem`: a JSON array of stems, off the async workers since long texts take a while
+async fn stem(State(analyzer): State<Arc<Analyzer>>, body: Bytes) -> Response {
+    match tokio::task::spawn_blocking(move || stems(&analyzer, &body)).await {
+        Ok(Ok(stems)) => (
+            [(header::CONTENT_TYPE, "application/json")],
+            serde_json::Value::from(stems).to_string(),
+        )
+            .into_response(),
+        Ok(Err(message)) => (StatusCode::BAD_REQUEST, message).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+// `GET /stats`: the counters of the cache as a JSON object, `null` without `--cache`
+async fn stats(State(analyzer): State<Arc<Analyzer>>) -> Response {
+    let stats = analyzer.get_stemmer().cache_stats().map(|stats| {
+        serde_json::json!({
+            "entries": stats.entries,
+            "bytes": stats.bytes,
+            "hits": stats.hits,
+            "misses": stats.misses,
+            "evictions": stats.evictions,
+            "expired": stats.expired,
+        })
+    });
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        serde_json::Value::from(stats).to_string(),
+    )
+        .into_response()
+}
+
+// The stems of a JSON array of words, in order, or of the words of a JSON string or of any
+// other body, taken as raw text.
+fn stems(analyzer: &Analyzer, body: &[u8]) -> Result<Vec<String>, String> {
+    let body =
+        std::str::from_utf8(body).map_err(|err| format!("the body is not UTF-8: {}", err))?;
+    let json = |err: serde_json::Error| format!("invalid JSON: {}", err);
+    let text = match body.trim_start().chars().next() {
+        Some('[') => {
+            let words: Vec<String> = serde_json::from_str(body).map_err(json)?;
+            return Ok(analyzer.get_stemmer().stem_words(&words));
+        }
+        Some('"') => serde_json::from_str(body).map_err(json)?,
+        _ => body.to_string(),
+    };
+    Ok(analyzer
+        .stem_tokens(&text)
+        .map(|token| token.stem.into_owned())
+        .collect())
+}
+
+#[cfg(test)]
+async fn call(app: Router, request: axum::http::Request<axum::body::Body>) -> (StatusCode, String) {
+    use tower::ServiceExt;
+
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), 1 << 20)
+        .await
+        .unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn handlers_test() {
+    use axum::body::Body;
+    use axum::http::Request;
+
+    let post = |body: &'static str| Request::post("/stem").body(Body::from(body)).unwrap();
+    let cache = ShardedCache::with_max_entries(100);
+    let stemmer = Stemmer::builder().sharded_cache(Arc::new(cache)).build();
+    let cached = app(Arc::new(analyzer().stemmer(stemmer)));
+
+    let words = call(cached.clone(), post(r#"["книгами", "Народи"]"#)).await;
+    assert_eq!(words, (StatusCode::OK, r#"["книг","народ"]"#.to_string()));
+    let text = call(cached.clone(), post("Слова автора")).await;
+    assert_eq!(
+        text.1,
+        call(cached.clone(), post(r#""Слова автора""#)).await.1
+    );
+    let invalid = call(cached.clone(), post("[1, 2")).await;
+    assert_eq!(invalid.0, StatusCode::BAD_REQUEST);
+    assert!(invalid.1.starts_with("invalid JSON"), "{}", invalid.1);
+
+    let stats = call(
+        cached.clone(),
+        Request::get("/stats").body(Body::empty()).unwrap(),
+    )
+    .await;
+    assert_eq!(stats.0, StatusCode::OK);
+    let stats: serde_json::Value = serde_json::from_str(&stats.1).unwrap();
+    assert_eq!(stats["entries"], 4);
+    assert!(stats["hits"].as_u64().unwrap() > 0);
+
+    let uncached = app(Arc::new(analyzer()));
+    let stats = call(
+        uncached,
+        Request::get("/stats").body(Body::empty()).unwrap(),
+    )
+    .await;
+    assert_eq!(stats, (StatusCode::OK, "null".to_string()));
+}
@@ -0,0 +1,198 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use stemmer_uk::export::{Field, SiteIndex};
+use stemmer_uk::fingerprint;
+
+use crate::open_output;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Content directory (Hugo/Zola `content/`, or any tree of .md/.html/.txt files)
+    input: PathBuf,
+    /// Output JSON file (stdout by default)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    #[arg(long, default_value_t = 10.0)]
+    title_boost: f64,
+    #[arg(long, default_value_t = 1.0)]
+    body_boost: f64,
+}
+
+const EXTENSIONS: [&str; 5] = ["md", "markdown", "html", "htm", "txt"];
+
+pub fn run(args: Args) -> io::Result<()> {
+    let fields = vec![
+        Field {
+            name: "title".into(),
+            boost: args.title_boost,
+        },
+        Field {
+            name: "body".into(),
+            boost: args.body_boost,
+        },
+    ];
+    let analyzer = crate::analyzer();
+    let fingerprint = analyzer.fingerprint();
+    let mut index = SiteIndex::new(analyzer, fields);
+
+    for entry in WalkDir::new(&args.input).sort_by_file_name() {
+        let entry = entry?;
+        let path = entry.path();
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        if !entry.file_type().is_file() || !EXTENSIONS.contains(&ext.as_str()) {
+            continue;
+        }
+        let text = fs::read_to_string(path)?;
+        let (title, body) = split_document(path, &text, ext.starts_with("htm"));
+        let rel = path.strip_prefix(&args.input).unwrap_or(path);
+        let doc_ref = rel.to_string_lossy().replace('\\', "/");
+        index.add(&doc_ref, &title, &[&title, &body]);
+    }
+
+    let mut out = open_output(args.output.as_ref())?;
+    out.write_all(index.to_json().as_bytes())?;
+    out.flush()?;
+    if let Some(output) = &args.output {
+        fingerprint::stamp(output, fingerprint)?;
+    }
+    eprintln!("{} documents indexed", index.len());
+    Ok(())
+}
+
+// Title from front matter, `<title>` or the first heading, falling back to the file name;
+// the body with front matter and markup removed.
+fn split_document(path: &Path, text: &str, html: bool) -> (String, String) {
+    let mut title = None;
+    let mut body = text;
+    for delimiter in ["---", "+++"] {
+        if let Some(rest) = text.strip_prefix(delimiter) {
+            if let Some(end) = rest.find(&format!("\n{}", delimiter)) {
+                title = front_matter_title(&rest[..end]);
+                body = &rest[end + 1 + delimiter.len()..];
+            }
+        }
+    }
+
+    let body = if html {
+        strip_tags(body)
+    } else {
+        body.to_string()
+    };
+    if html && title.is_none() {
+        title = text
+            .find("<title>")
+            .and_then(|start| {
+                let rest = &text[start + 7..];
+                rest.find("</title>")
+                    .map(|end| rest[..end].trim().to_string())
+            })
+            .filter(|t| !t.is_empty());
+    }
+    let title = title
+        .or_else(|| {
+            body.lines()
+                .find_map(|line| line.strip_prefix('#'))
+                .map(|line| line.trim_start_matches('#').trim().to_string())
+        })
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+    (title, body)
+}
+
+fn front_matter_title(front: &str) -> Option<String> {
+    front.lines().find_map(|line| {
+        let value = line
+            .strip_prefix("title:")
+            .or_else(|| line.strip_prefix("title ="))
+            .or_else(|| line.strip_prefix("title="))?;
+        Some(value.trim().trim_matches(['"', '\'']).to_string())
+    })
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut res = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                res.push(' ');
+            }
+            c if !in_tag => res.push(c),
+            _ => {}
+        }
+    }
+    res
+}
+
+#[test]
+fn site_index_test() {
+    let root = std::env::temp_dir().join(format!("ukstem-site-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let content = root.join("content");
+    fs::create_dir_all(content.join("posts")).unwrap();
+    let files = [
+        (
+            "posts/народи.md",
+            "---\ntitle: \"Народи країни\"\n---\nПро народи і автора\n",
+        ),
+        (
+            "about.html",
+            "<html><head><title>Автор</title></head><body><p>Книги автора</p></body></html>",
+        ),
+        ("notes.txt", "# Слова\nСлова автора\n"),
+        ("logo.png", "not text"),
+    ];
+    for (name, text) in files.iter() {
+        fs::write(content.join(name), text).unwrap();
+    }
+    let output = root.join("search_index.json");
+    run(Args {
+        input: content,
+        output: Some(output.clone()),
+        title_boost: 10.0,
+        body_boost: 1.0,
+    })
+    .unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+    let fingerprint = crate::analyzer().fingerprint().to_string();
+    assert_eq!(json["fingerprint"], fingerprint.as_str());
+    assert_eq!(
+        json["fields"],
+        serde_json::json!([{"name": "title", "boost": 10}, {"name": "body", "boost": 1}])
+    );
+    // in file name order, with titles from `<title>`, the first heading and front matter
+    assert_eq!(
+        json["docs"],
+        serde_json::json!([
+            {"ref": "about.html", "title": "Автор"},
+            {"ref": "notes.txt", "title": "Слова"},
+            {"ref": "posts/народи.md", "title": "Народи країни"},
+        ])
+    );
+    let docs = |term: &str| -> Vec<u64> {
+        let postings = json["index"][term].as_array().unwrap();
+        postings.iter().map(|p| p[0].as_u64().unwrap()).collect()
+    };
+    assert_eq!(docs("автор"), [0, 1, 2]);
+    assert_eq!(docs("книг"), [0]);
+    assert_eq!(docs("народ"), [2]);
+    // title words weigh more than body words
+    let score = |term: &str, doc: usize| json["index"][term][doc][1].as_f64().unwrap();
+    assert!(score("слов", 0) > score("автор", 1));
+    assert!(json["index"].get("title").is_none());
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,161 @@
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use stemmer_uk::dict::CompiledDictionary;
+use stemmer_uk::export::json_string;
+use stemmer_uk::lemmas::LemmaDictionary;
+use stemmer_uk::tokenizer::{tokenize, TokenKind};
+use stemmer_uk::Stemmer;
+
+use crate::config::parse_stemmer;
+use crate::{analyzer, open_input};
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Format {
+    /// The input with its words replaced, or one stem per line with `--lines`
+    Plain,
+    /// One JSON object per word
+    Jsonl,
+    Csv,
+    Tsv,
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Input files, stdin when none are given or for `-`
+    inputs: Vec<PathBuf>,
+    /// Read one word per line and write one stem per line, instead of replacing the words
+    /// of running text
+    #[arg(short, long)]
+    lines: bool,
+    /// Write how every word was stemmed: its RV region, the rules and the outcome
+    #[arg(short, long, conflicts_with = "format")]
+    explain: bool,
+    /// Write a record of `original`, `stem` and `offset`, the byte offset of the word in
+    /// its input, per word
+    #[arg(long, value_enum, default_value_t = Format::Plain)]
+    format: Format,
+    /// Configuration, e.g. `default` or `surzhyk,ocr`
+    #[arg(long, default_value = "default", value_parser = parse_stemmer)]
+    config: Stemmer,
+    /// Lemmatize the words of a dictionary written by `compile-lemmas`, stemming the others
+    #[arg(long)]
+    lemmas: Option<PathBuf>,
+    /// Look the stems of words up in a dictionary written by `build-dict` first
+    #[arg(long)]
+    dict: Option<PathBuf>,
+}
+
+// Writes the header of `format` if it has one.
+fn write_header(out: &mut impl Write, format: Format) -> io::Result<()> {
+    match format {
+        Format::Csv => writeln!(out, "original,stem,offset"),
+        Format::Tsv => writeln!(out, "original\tstem\toffset"),
+        Format::Plain | Format::Jsonl => Ok(()),
+    }
+}
+
+fn write_record(
+    out: &mut impl Write,
+    format: Format,
+    original: &str,
+    stem: &str,
+    offset: usize,
+) -> io::Result<()> {
+    match format {
+        Format::Jsonl => writeln!(
+            out,
+            "{{\"original\":{},\"stem\":{},\"offset\":{}}}",
+            json_string(original),
+            json_string(stem),
+            offset
+        ),
+        Format::Csv => writeln!(
+            out,
+            "{},{},{}",
+            csv_field(original),
+            csv_field(stem),
+            offset
+        ),
+        Format::Tsv => writeln!(
+            out,
+            "{}\t{}\t{}",
+            tsv_field(original),
+            tsv_field(stem),
+            offset
+        ),
+        Format::Plain => writeln!(out, "{}", stem),
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// tabs and line breaks can't be escaped in TSV, so they become spaces
+fn tsv_field(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let inputs = if args.inputs.is_empty() {
+        vec![PathBuf::from("-")]
+    } else {
+        args.inputs
+    };
+    let stemmer = match &args.lemmas {
+        Some(path) => {
+            let lemmas = LemmaDictionary::load(open_input(path)?)?;
+            args.config.to_builder().lemmatize(lemmas).build()
+        }
+        None => args.config,
+    };
+    let stemmer = match &args.dict {
+        Some(path) => {
+            let dictionary = CompiledDictionary::open(path)
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+            stemmer.to_builder().dictionary(dictionary).build()
+        }
+        None => stemmer,
+    };
+    let analyzer = analyzer().stemmer(stemmer);
+    let stemmer = analyzer.get_stemmer();
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    write_header(&mut out, args.format)?;
+    for input in &inputs {
+        let mut input = open_input(input)?;
+        let mut line = String::new();
+        let mut offset = 0;
+        while input.read_line(&mut line)? > 0 {
+            let text = line.trim_end_matches(['\n', '\r']);
+            if args.lines {
+                let word = text.trim();
+                let start = offset + (text.len() - text.trim_start().len());
+                if args.explain {
+                    writeln!(out, "{}", stemmer.stem_explained(word))?;
+                } else {
+                    write_record(&mut out, args.format, word, &stemmer.stem(word), start)?;
+                }
+            } else if args.explain {
+                for token in tokenize(text).filter(|t| t.kind == TokenKind::Word) {
+                    writeln!(out, "{}", stemmer.stem_explained(token.text))?;
+                }
+            } else if args.format == Format::Plain {
+                writeln!(out, "{}", analyzer.stem_text(text))?;
+            } else {
+                for token in analyzer.stem_tokens(text) {
+                    let start = offset + token.start;
+                    write_record(&mut out, args.format, token.original, &token.stem, start)?;
+                }
+            }
+            offset += line.len();
+            line.clear();
+        }
+    }
+    out.flush()
+}
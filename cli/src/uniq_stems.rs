@@ -0,0 +1,36 @@
+use std::io;
+use std::path::PathBuf;
+
+use stemmer_uk::extsort::ExternalDedup;
+use stemmer_uk::fingerprint;
+
+use crate::{analyzer, open_input, open_output};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Word list, one word per line (`-` for stdin)
+    input: PathBuf,
+    /// Output file (stdout by default), stamped with the configuration fingerprint
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Maximum number of stems held in memory before spilling a sorted run to disk
+    #[arg(long, default_value_t = 1_000_000)]
+    chunk_stems: usize,
+    /// Directory for temporary run files
+    #[arg(long)]
+    tmp_dir: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let analyzer = analyzer();
+    let mut dedup = ExternalDedup::new(analyzer.get_stemmer()).chunk_stems(args.chunk_stems);
+    if let Some(tmp_dir) = &args.tmp_dir {
+        dedup = dedup.tmp_dir(tmp_dir);
+    }
+    let written = dedup.run(open_input(&args.input)?, open_output(args.output.as_ref())?)?;
+    if let Some(output) = &args.output {
+        fingerprint::stamp(output, analyzer.fingerprint())?;
+    }
+    eprintln!("{} unique stems", written);
+    Ok(())
+}
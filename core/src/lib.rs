@@ -0,0 +1,757 @@
+//! The suffix rules of `stemmer_uk` on their own: the normalization of words, the rule
+//! groups, the RV region and the four-step pipeline.
+//!
+//! Without its default `std` feature the crate needs only `alloc` and
+//! `unicode-normalization` (plus `tracing` with the `tracing` feature), so it builds for
+//! embedded and `wasm32-unknown-unknown` targets. Tokenizing and every configurable part
+//! of the stemmer live in `stemmer_uk`, which re-exports this crate as `stemmer_uk::core`.
+
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+
+#[doc(hidden)]
+#[rustfmt::skip]
+pub mod rule_tables;
+pub mod normalize;
+pub mod suffix;
+
+use normalize::Normalization;
+use suffix::Suffixes;
+
+// Names of the built-in rule groups, in pipeline order, as reported by
+// `Stemmer::stem_with_rules`.
+//
+// A group removes at most one suffix: the longest of its alternatives the word ends with.
+
+pub const PERFECTIVE_GROUND: &str = "perfective_ground";
+pub const REFLEXIVE: &str = "reflexive";
+pub const ADJECTIVE: &str = "adjective";
+pub const PARTICIPLE: &str = "participle";
+pub const VERB: &str = "verb";
+pub const NOUN: &str = "noun";
+/// Step 2, a remaining final "и".
+pub const I_ENDING: &str = "i_ending";
+/// Step 3, "ость" in derivational words.
+pub const DERIVATIONAL: &str = "derivational";
+/// Step 4, the final soft sign.
+pub const SOFT_SIGN: &str = "soft_sign";
+pub const SUPERLATIVE: &str = "superlative";
+pub const DOUBLE_N: &str = "double_n";
+
+pub const ALL: [&str; 11] = [
+    PERFECTIVE_GROUND,
+    REFLEXIVE,
+    ADJECTIVE,
+    PARTICIPLE,
+    VERB,
+    NOUN,
+    I_ENDING,
+    DERIVATIONAL,
+    SOFT_SIGN,
+    SUPERLATIVE,
+    DOUBLE_N,
+];
+
+/// A rule group that changed the word, as reported by `Stemmer::stem_with_trace`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleHit {
+    pub rule: &'static str,
+    /// The suffix the group matched.
+    pub suffix: String,
+    /// What the suffix was replaced with, usually nothing.
+    pub replacement: &'static str,
+    /// 0, or how many times the inflectional groups had already run when they removed the
+    /// suffix again, see `StemmerBuilder::restrip`.
+    pub pass: usize,
+}
+
+/// A suffix rule of its own, e.g. for the terms of a domain, run at a `RulePosition` of
+/// the pipeline.
+pub trait Rule: Send + Sync {
+    /// Names the rule in traces and errors.
+    fn name(&self) -> &'static str;
+
+    /// Where the suffix to remove starts in `rv`, the chars of RV as the rules before left
+    /// it, or `None` when the rule doesn't apply. Rules see and cut whole chars, so none can
+    /// split one, whatever its width in UTF-8.
+    fn find(&self, rv: &[char]) -> Option<usize>;
+
+    /// What the suffix is replaced with, a prefix of it since rules only cut words.
+    fn replacement(&self) -> &'static str {
+        ""
+    }
+}
+
+/// Where an extra rule runs: right before or after a rule group, one of `ALL`, whether
+/// or not the group removes a suffix. Rules at the same position run in the order they
+/// were added, and don't change which groups the pipeline runs; the groups of step 1 are
+/// alternatives, so a word losing an adjective ending never reaches `After(NOUN)`, while
+/// `Before(I_ENDING)` sees every word once step 1 is done.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RulePosition {
+    Before(&'static str),
+    After(&'static str),
+}
+
+/// A `Rule` and where it runs.
+#[derive(Clone)]
+pub struct ExtraRule {
+    pub position: RulePosition,
+    pub rule: Arc<dyn Rule>,
+}
+
+impl fmt::Debug for ExtraRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} {}", self.position, self.rule.name())
+    }
+}
+
+// rules are told apart by name, as traces do
+impl PartialEq for ExtraRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.position == other.position && self.rule.name() == other.rule.name()
+    }
+}
+
+impl Eq for ExtraRule {}
+
+/// The suffix alternatives of every rule group, and the extra rules run among them.
+/// `stemmer_uk::rules::RuleSet` wraps them with validation, parsing and serialization.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleGroups {
+    pub perfective_ground: Suffixes,
+    pub reflexive: Suffixes,
+    pub adjective: Suffixes,
+    pub participle: Suffixes,
+    pub verb: Suffixes,
+    pub noun: Suffixes,
+    pub i_ending: Suffixes,
+    pub derivational: Suffixes,
+    pub soft_sign: Suffixes,
+    pub superlative: Suffixes,
+    pub double_n: Suffixes,
+    pub extra: Vec<ExtraRule>,
+}
+
+impl Default for RuleGroups {
+    fn default() -> Self {
+        RuleGroups::builtin()
+    }
+}
+
+impl RuleGroups {
+    /// The rules `stem_word` uses, generated from rules/suffixes.txt.
+    pub fn builtin() -> Self {
+        let suffixes = |alternatives: &[&'static str]| Suffixes::new(alternatives.iter().copied());
+        RuleGroups {
+            // The PCRE original also had `ывшись((?<=[ая])(в|вши|вшись))`, which cannot
+            // match.
+            perfective_ground: suffixes(&["ив", "ивши", "ившись", "ыв", "ывши"]),
+            reflexive: suffixes(rule_tables::REFLEXIVE),
+            adjective: suffixes(rule_tables::ADJECTIVE),
+            participle: suffixes(rule_tables::PARTICIPLE),
+            verb: suffixes(rule_tables::VERB),
+            noun: suffixes(rule_tables::NOUN),
+            i_ending: suffixes(&["и"]),
+            derivational: suffixes(&["ость"]),
+            soft_sign: suffixes(&["ь"]),
+            superlative: suffixes(&["ейш", "ейше"]),
+            double_n: suffixes(&["нн"]),
+            extra: Vec::new(),
+        }
+    }
+
+    /// The suffixes of `group`, one of `ALL`.
+    pub fn group(&self, group: &str) -> Option<&Suffixes> {
+        Some(match group {
+            PERFECTIVE_GROUND => &self.perfective_ground,
+            REFLEXIVE => &self.reflexive,
+            ADJECTIVE => &self.adjective,
+            PARTICIPLE => &self.participle,
+            VERB => &self.verb,
+            NOUN => &self.noun,
+            I_ENDING => &self.i_ending,
+            DERIVATIONAL => &self.derivational,
+            SOFT_SIGN => &self.soft_sign,
+            SUPERLATIVE => &self.superlative,
+            DOUBLE_N => &self.double_n,
+            _ => return None,
+        })
+    }
+
+    pub fn group_mut(&mut self, group: &str) -> Option<&mut Suffixes> {
+        Some(match group {
+            PERFECTIVE_GROUND => &mut self.perfective_ground,
+            REFLEXIVE => &mut self.reflexive,
+            ADJECTIVE => &mut self.adjective,
+            PARTICIPLE => &mut self.participle,
+            VERB => &mut self.verb,
+            NOUN => &mut self.noun,
+            I_ENDING => &mut self.i_ending,
+            DERIVATIONAL => &mut self.derivational,
+            SOFT_SIGN => &mut self.soft_sign,
+            SUPERLATIVE => &mut self.superlative,
+            DOUBLE_N => &mut self.double_n,
+            _ => return None,
+        })
+    }
+}
+
+/// A rule group matched in a way the pipeline can't apply, which only rule groups with
+/// invalid suffixes cause; `stemmer_uk` reports it as `StemError::InternalMatch`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MatchError {
+    pub rule: &'static str,
+    pub message: &'static str,
+}
+
+impl fmt::Display for MatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "internal error in rule {}: {}", self.rule, self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MatchError {}
+
+/// Where the rules cut a word. They only ever shorten it, every group removes a suffix
+/// and "нн" becomes "н", so the stem is `word[..end]`, followed by a newline when
+/// `newline`: PCRE's `$` matches before a final newline, which the original rules then
+/// kept after the suffix they removed. Stemming thus allocates nothing but the trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cut {
+    pub end: usize,
+    pub newline: bool,
+}
+
+impl Cut {
+    /// The stem of `word`, borrowed unless a newline has to be put back.
+    pub fn of(self, word: &str) -> Cow<'_, str> {
+        match (self.newline, &word[self.end..]) {
+            (false, _) => Cow::Borrowed(&word[..self.end]),
+            (true, "\n") => Cow::Borrowed(word),
+            (true, _) => Cow::Owned(format!("{}\n", &word[..self.end])),
+        }
+    }
+
+    /// Turns `word` into its stem in place.
+    pub fn truncate(self, word: &mut String) {
+        if self.newline && &word[self.end..] == "\n" {
+            return;
+        }
+        word.truncate(self.end);
+        if self.newline {
+            word.push('\n');
+        }
+    }
+}
+
+// the word being stemmed; RV is `word[rv..cut.end]`
+struct Region<'w> {
+    word: &'w str,
+    rv: usize,
+    cut: Cut,
+    // the fewest chars a rule may leave of the word
+    min_chars: usize,
+    extra: &'w [ExtraRule],
+}
+
+impl<'w> Region<'w> {
+    // RV as the rules match it, without a final newline
+    fn rv(&mut self, rule: &'static str) -> Result<&'w str, MatchError> {
+        let rv = self
+            .word
+            .get(self.rv..self.cut.end)
+            .ok_or_else(|| internal(rule, "RV is not on a char boundary"))?;
+        match rv.strip_suffix('\n') {
+            Some(rv) if !self.cut.newline => {
+                self.cut = Cut {
+                    end: self.cut.end - 1,
+                    newline: true,
+                };
+                Ok(rv)
+            }
+            _ => Ok(rv),
+        }
+    }
+}
+
+// the shortest stem a re-run of the inflectional groups may leave, so that it strips
+// repeated endings but not the end of the root
+const MIN_RESTRIPPED_CHARS: usize = 3;
+
+/// The alternatives of step 1, which the pipeline tries in this order, keeping the first
+/// that removes a suffix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Inflection {
+    /// `PERFECTIVE_GROUND`.
+    PerfectiveGround,
+    /// `REFLEXIVE`, then `ADJECTIVE` and `PARTICIPLE`.
+    Adjective,
+    /// `REFLEXIVE`, then `VERB`.
+    Verb,
+    /// `REFLEXIVE`, then `NOUN`.
+    Noun,
+}
+
+impl Inflection {
+    pub const ALL: [Inflection; 4] = [
+        Inflection::PerfectiveGround,
+        Inflection::Adjective,
+        Inflection::Verb,
+        Inflection::Noun,
+    ];
+}
+
+// Step 1 of the rules: the inflectional groups, only those of `only` when set
+fn inflectional(
+    groups: &RuleGroups,
+    region: &mut Region,
+    fired: &mut Option<Vec<RuleHit>>,
+    only: Option<Inflection>,
+) -> Result<(), MatchError> {
+    let perfective_ground = |region: &mut Region, fired: &mut Option<Vec<RuleHit>>| {
+        rule(
+            PERFECTIVE_GROUND,
+            &groups.perfective_ground,
+            "",
+            region,
+            fired,
+        )
+    };
+    match only {
+        Some(Inflection::PerfectiveGround) => {
+            perfective_ground(region, fired)?;
+            return Ok(());
+        }
+        None if perfective_ground(region, fired)? => return Ok(()),
+        _ => {}
+    }
+    rule(REFLEXIVE, &groups.reflexive, "", region, fired)?;
+
+    match only {
+        None => {
+            if rule(ADJECTIVE, &groups.adjective, "", region, fired)? {
+                rule(PARTICIPLE, &groups.participle, "", region, fired)?;
+            } else if !rule(VERB, &groups.verb, "", region, fired)? {
+                rule(NOUN, &groups.noun, "", region, fired)?;
+            }
+        }
+        Some(Inflection::Adjective) => {
+            if rule(ADJECTIVE, &groups.adjective, "", region, fired)? {
+                rule(PARTICIPLE, &groups.participle, "", region, fired)?;
+            }
+        }
+        Some(Inflection::Verb) => {
+            rule(VERB, &groups.verb, "", region, fired)?;
+        }
+        Some(Inflection::Noun) => {
+            rule(NOUN, &groups.noun, "", region, fired)?;
+        }
+        Some(Inflection::PerfectiveGround) => {}
+    }
+    Ok(())
+}
+
+// the ending the rules removed from `stem` to leave `stripped`, ignoring the final newline
+// they keep
+fn stripped<'s>(stem: &'s str, stripped: &str) -> &'s str {
+    let stem = stem.strip_suffix('\n').unwrap_or(stem);
+    let stripped = stripped.strip_suffix('\n').unwrap_or(stripped);
+    stem.get(stripped.len()..).unwrap_or("")
+}
+
+// applies `suffixes`, with the extra rules around them
+fn rule(
+    name: &'static str,
+    suffixes: &Suffixes,
+    to: &'static str,
+    region: &mut Region,
+    trace: &mut Option<Vec<RuleHit>>,
+) -> Result<bool, MatchError> {
+    extra_rules(RulePosition::Before(name), region, trace)?;
+    let rv = region.rv(name)?;
+    let applied = match suffixes.find_chars(rv) {
+        Some(chars) => cut(name, rv, chars, to, region, trace)?,
+        None => false,
+    };
+    extra_rules(RulePosition::After(name), region, trace)?;
+    Ok(applied)
+}
+
+// runs the extra rules at `position`
+fn extra_rules(
+    position: RulePosition,
+    region: &mut Region,
+    trace: &mut Option<Vec<RuleHit>>,
+) -> Result<(), MatchError> {
+    let extra = region.extra;
+    for extra in extra.iter().filter(|extra| extra.position == position) {
+        let name = extra.rule.name();
+        let rv = region.rv(name)?;
+        let chars: Vec<char> = rv.chars().collect();
+        if let Some(start) = extra.rule.find(&chars) {
+            if start > chars.len() {
+                return Err(internal(name, "the suffix starts past the end of RV"));
+            }
+            let suffix_chars = chars.len() - start;
+            cut(
+                name,
+                rv,
+                suffix_chars,
+                extra.rule.replacement(),
+                region,
+                trace,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// replaces the last `suffix_chars` chars of `rv` with `to` and, when tracing, records the
+// removed suffix if it changed `rv`; the one place a rule changes the word, counting
+// whole chars back from its end
+fn cut(
+    name: &'static str,
+    rv: &str,
+    suffix_chars: usize,
+    to: &'static str,
+    region: &mut Region,
+    trace: &mut Option<Vec<RuleHit>>,
+) -> Result<bool, MatchError> {
+    let start = match suffix_chars.checked_sub(1) {
+        None => rv.len(),
+        Some(last) => match rv.char_indices().rev().nth(last) {
+            Some((start, _)) => start,
+            None => return Err(internal(name, "the suffix is longer than RV")),
+        },
+    };
+    let suffix = &rv[start..];
+    if suffix == to {
+        return Ok(false);
+    }
+    if !suffix.starts_with(to) {
+        return Err(internal(name, "the replacement does not shorten the word"));
+    }
+    let end = region.rv + start + to.len();
+    if region.min_chars > 0 && region.word[..end].chars().count() < region.min_chars {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(rule = name, suffix, "kept: the stem would be too short");
+        return Ok(false);
+    }
+    #[cfg(feature = "tracing")]
+    tracing::debug!(rule = name, suffix, replacement = to, "rule fired");
+    if let Some(trace) = trace {
+        trace.push(RuleHit {
+            rule: name,
+            suffix: suffix.to_string(),
+            replacement: to,
+            pass: 0,
+        });
+    }
+    region.cut.end = end;
+    Ok(true)
+}
+
+fn internal(rule: &'static str, message: &'static str) -> MatchError {
+    MatchError { rule, message }
+}
+
+// Applies a derivational group unless `guard` rejects the stem it leaves.
+fn guarded_rule(
+    name: &'static str,
+    suffixes: &Suffixes,
+    region: &mut Region,
+    trace: &mut Option<Vec<RuleHit>>,
+    guard: Option<&dyn Fn(&str) -> bool>,
+) -> Result<bool, MatchError> {
+    extra_rules(RulePosition::Before(name), region, trace)?;
+    let before = region.cut;
+    let rv = region.rv(name)?;
+    let mut applied = match suffixes.find_chars(rv) {
+        Some(chars) => cut(name, rv, chars, "", region, trace)?,
+        None => false,
+    };
+    if applied && guard.is_some_and(|allows| !allows(&region.cut.of(region.word))) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(rule = name, "kept: the guard rejected the stem");
+        region.cut = before;
+        if let Some(trace) = trace {
+            trace.pop();
+        }
+        applied = false;
+    }
+    extra_rules(RulePosition::After(name), region, trace)?;
+    Ok(applied)
+}
+
+/// The stem of `word`, a normalized word, with `groups`; the word itself should the rules
+/// fail, which only invalid rule groups cause.
+pub fn stem<'w>(word: &'w str, groups: &RuleGroups) -> Cow<'w, str> {
+    match stem_cut(word, groups, &mut None, None, 0, 0) {
+        Ok(cut) => cut.of(word),
+        Err(_) => Cow::Borrowed(word),
+    }
+}
+
+/// Stems any word with `groups`, as `stemmer_uk::stem_word` does with the built-in rules:
+/// the word is normalized first, see `normalize::normalize`.
+pub fn stem_word(word: &str, groups: &RuleGroups) -> String {
+    let word = normalize::normalize(word, Normalization::default());
+    stem(&word, groups).into_owned()
+}
+
+/// Runs `groups` over `word`, a normalized word, recording the groups that removed a
+/// suffix in `fired` when it is set. `guard`, when set, is asked about the stem every
+/// derivational group would leave and keeps the suffix when it returns false.
+///
+/// When the inflectional groups of step 1 removed a suffix, they run again on what they
+/// left, up to `restrip` more times, as long as every run removes the same ending again and
+/// leaves `MIN_RESTRIPPED_CHARS` chars.
+///
+/// A rule whose suffix would leave fewer than `min_chars` chars of the word removes
+/// nothing; 0 lets the rules shorten words down to RV.
+pub fn stem_cut(
+    word: &str,
+    groups: &RuleGroups,
+    fired: &mut Option<Vec<RuleHit>>,
+    guard: Option<&dyn Fn(&str) -> bool>,
+    restrip: usize,
+    min_chars: usize,
+) -> Result<Cut, MatchError> {
+    stem_cut_as(word, groups, fired, guard, restrip, min_chars, None)
+}
+
+/// `stem_cut` with step 1 limited to the groups of `inflection`, so that the stems the
+/// other alternatives would leave can be compared; the pipeline order when `None`.
+pub fn stem_cut_as(
+    word: &str,
+    groups: &RuleGroups,
+    fired: &mut Option<Vec<RuleHit>>,
+    guard: Option<&dyn Fn(&str) -> bool>,
+    restrip: usize,
+    min_chars: usize,
+    inflection: Option<Inflection>,
+) -> Result<Cut, MatchError> {
+    let cut = Cut {
+        end: word.len(),
+        newline: false,
+    };
+    #[cfg(feature = "tracing")]
+    let _span = tracing::trace_span!("rules", word = word.trim_end_matches('\n')).entered();
+    let rv = match suffix::rv_start(word) {
+        Some(rv) => rv,
+        None => {
+            #[cfg(feature = "tracing")]
+            tracing::trace!("no RV: the word has no vowel");
+            return Ok(cut);
+        }
+    };
+    #[cfg(feature = "tracing")]
+    tracing::trace!(rv = &word[rv..], "RV");
+    let region = &mut Region {
+        word,
+        rv,
+        cut,
+        min_chars,
+        extra: &groups.extra,
+    };
+
+    // Step 1
+    let mut stem = region.cut.of(word);
+    inflectional(groups, region, fired, inflection)?;
+    for pass in 1..=restrip {
+        let (before, hits) = (region.cut, fired.as_ref().map_or(0, Vec::len));
+        let removed = stripped(&stem, &region.cut.of(word)).to_string();
+        if removed.is_empty() {
+            break;
+        }
+        stem = region.cut.of(word);
+        inflectional(groups, region, fired, inflection)?;
+        let restripped = region.cut.of(word);
+        if stripped(&stem, &restripped) != removed
+            || restripped.trim_end_matches('\n').chars().count() < MIN_RESTRIPPED_CHARS
+        {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(pass, "restrip undone");
+            region.cut = before;
+            if let Some(fired) = fired {
+                fired.truncate(hits);
+            }
+            break;
+        }
+        if let Some(fired) = fired {
+            fired[hits..].iter_mut().for_each(|hit| hit.pass = pass);
+        }
+    }
+    // Step 2
+    rule(I_ENDING, &groups.i_ending, "", region, fired)?;
+
+    // Step 3
+    if suffix::is_derivational(region.rv(DERIVATIONAL)?) {
+        guarded_rule(DERIVATIONAL, &groups.derivational, region, fired, guard)?;
+    }
+
+    // Step 4
+    if rule(SOFT_SIGN, &groups.soft_sign, "", region, fired)? {
+        guarded_rule(SUPERLATIVE, &groups.superlative, region, fired, guard)?;
+        rule(DOUBLE_N, &groups.double_n, "н", region, fired)?;
+    }
+    Ok(region.cut)
+}
+
+#[test]
+fn stem_test() {
+    let groups = RuleGroups::builtin();
+    assert!(matches!(stem("ручкаєт", &groups), Cow::Borrowed("ручкаєт")));
+    assert_eq!(stem("книгами", &groups), "книг");
+    assert_eq!(stem("найдовша", &groups), "найдовш");
+    assert_eq!(stem("пгт", &groups), "пгт");
+    let cut = stem_cut("осі", &groups, &mut None, None, 0, 3).unwrap();
+    assert_eq!(cut.of("осі"), "осі");
+
+    // "double_n" replaces its suffix with "н", which "ж" doesn't start with
+    let mut broken = RuleGroups::builtin();
+    broken.soft_sign = Suffixes::new(vec!["к"]);
+    broken.double_n = Suffixes::new(vec!["ж"]);
+    assert_eq!(
+        stem_cut("книжка", &broken, &mut None, None, 0, 0),
+        Err(MatchError {
+            rule: DOUBLE_N,
+            message: "the replacement does not shorten the word"
+        })
+    );
+    assert_eq!(stem("книжка", &broken), "книжка");
+}
+
+#[test]
+fn stem_cut_as_test() {
+    let groups = RuleGroups::builtin();
+    let stem_as = |word: &'static str, inflection| {
+        stem_cut_as(word, &groups, &mut None, None, 0, 0, inflection)
+            .unwrap()
+            .of(word)
+    };
+    assert_eq!(stem_as("ліси", None), "лі");
+    assert_eq!(stem_as("ліси", Some(Inflection::PerfectiveGround)), "ліс");
+    assert_eq!(stem_as("сміятися", Some(Inflection::Noun)), "сміят");
+    assert_eq!(stem_as("сміятися", Some(Inflection::Verb)), "смі");
+    assert_eq!(stem_as("рукою", Some(Inflection::Verb)), "руко");
+}
+
+#[test]
+fn stem_word_test() {
+    let groups = RuleGroups::builtin();
+    assert_eq!(stem_word("Ручкається", &groups), "ручкаєт");
+    assert_eq!(stem_word("здоров'я", &groups), "здоров");
+}
+
+#[test]
+fn suffix_priority_test() {
+    let groups = RuleGroups::builtin();
+    let tables: [(&Suffixes, &[&str]); 5] = [
+        (&groups.reflexive, rule_tables::REFLEXIVE),
+        (&groups.adjective, rule_tables::ADJECTIVE),
+        (&groups.participle, rule_tables::PARTICIPLE),
+        (&groups.verb, rule_tables::VERB),
+        (&groups.noun, rule_tables::NOUN),
+    ];
+    for (suffixes, alternatives) in tables.iter() {
+        for alternative in alternatives.iter() {
+            let word = format!("ж{}", alternative);
+            let longest = alternatives
+                .iter()
+                .filter(|a| word.ends_with(**a))
+                .max_by_key(|a| a.len())
+                .unwrap();
+            let start = suffixes.find(&word).unwrap();
+            assert_eq!(&word[start..], *longest, "{}", word);
+        }
+    }
+}
+
+#[test]
+fn guarded_rule_test() {
+    let groups = RuleGroups::builtin();
+    let allows = |stem: &str| stem == "молод";
+    for (word, stem) in [("молодость", "молод"), ("медость", "медость")].iter()
+    {
+        let mut region = Region {
+            word,
+            rv: 4,
+            cut: Cut {
+                end: word.len(),
+                newline: false,
+            },
+            min_chars: 0,
+            extra: &[],
+        };
+        let mut trace = Some(Vec::new());
+        let applied = guarded_rule(
+            DERIVATIONAL,
+            &groups.derivational,
+            &mut region,
+            &mut trace,
+            Some(&allows),
+        )
+        .unwrap();
+        assert_eq!(region.cut.of(word), *stem);
+        assert_eq!(trace.unwrap().len(), applied as usize);
+    }
+}
+
+#[test]
+fn extra_rules_test() {
+    // "-ит" of the names of inflammations, in medical texts
+    struct Inflammation;
+    impl Rule for Inflammation {
+        fn name(&self) -> &'static str {
+            "inflammation"
+        }
+        fn find(&self, rv: &[char]) -> Option<usize> {
+            rv.ends_with(&['и', 'т']).then_some(rv.len() - 2)
+        }
+    }
+    let mut groups = RuleGroups::builtin();
+    groups.extra.push(ExtraRule {
+        position: RulePosition::After(NOUN),
+        rule: Arc::new(Inflammation),
+    });
+    assert_eq!(stem("гастритом", &groups), "гастр");
+    assert_eq!(stem("гастрит", &groups), "гастр");
+    assert_eq!(stem("гастрит", &RuleGroups::builtin()), "гастрит");
+    let mut fired = Some(Vec::new());
+    stem_cut("гастритом", &groups, &mut fired, None, 0, 0).unwrap();
+    let rules: Vec<&str> = fired.unwrap().iter().map(|hit| hit.rule).collect();
+    assert_eq!(rules, [NOUN, "inflammation"]);
+    assert_ne!(groups, RuleGroups::builtin());
+
+    struct Broken;
+    impl Rule for Broken {
+        fn name(&self) -> &'static str {
+            "broken"
+        }
+        fn find(&self, rv: &[char]) -> Option<usize> {
+            Some(rv.len() + 1)
+        }
+    }
+    groups.extra.push(ExtraRule {
+        position: RulePosition::Before(PERFECTIVE_GROUND),
+        rule: Arc::new(Broken),
+    });
+    assert_eq!(
+        stem_cut("гастрит", &groups, &mut None, None, 0, 0),
+        Err(MatchError {
+            rule: "broken",
+            message: "the suffix starts past the end of RV"
+        })
+    );
+}
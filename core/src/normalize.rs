@@ -0,0 +1,126 @@
+//! The normalization words get before the rules, which only match lowercase words.
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+/// The apostrophes Ukrainian text is typed with: ASCII, the typographic "’", the modifier
+/// letter "ʼ" of the orthography and the backtick of some keyboard layouts.
+pub const APOSTROPHES: [char; 4] = ['\'', '’', 'ʼ', '`'];
+
+/// What `normalize` does besides lowercasing; the default is what `stem_word` does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Normalization {
+    /// Removes the apostrophes, so that "п'ять" and "пять" get the same stem.
+    pub strip_apostrophes: bool,
+    /// Composes words to NFC, so that "и" with a combining breve is "й", and takes all the
+    /// `APOSTROPHES` for apostrophes, writing the ones it keeps as the ASCII one; otherwise
+    /// only the ASCII one is, as in the original stemmer.
+    pub fold_unicode: bool,
+    /// Removes the characters of `is_invisible`.
+    pub strip_invisible: bool,
+    /// Replaces the Russian "ё" with "е" and "ъ", typed for an apostrophe on Russian
+    /// layouts, with "ї".
+    pub fold_russian: bool,
+}
+
+impl Default for Normalization {
+    fn default() -> Self {
+        Normalization {
+            strip_apostrophes: true,
+            fold_unicode: false,
+            strip_invisible: false,
+            fold_russian: true,
+        }
+    }
+}
+
+/// Whether `c` is an invisible character that PDF extraction and web pages leave inside
+/// words: soft hyphens, zero-width spaces and joiners, word joiners and stray BOMs.
+pub fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}' | '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}'
+    )
+}
+
+/// Whether `normalize` would return `word` unchanged.
+pub fn is_normalized(word: &str, normalization: Normalization) -> bool {
+    if normalization.fold_unicode && is_nfc_quick(word.chars()) != IsNormalized::Yes {
+        return false;
+    }
+    word.chars().all(|c| match c {
+        '\'' => !normalization.strip_apostrophes,
+        c if APOSTROPHES.contains(&c) => !normalization.fold_unicode,
+        'ё' | 'ъ' => !normalization.fold_russian,
+        c if normalization.strip_invisible && is_invisible(c) => false,
+        c => c.to_lowercase().eq(core::iter::once(c)),
+    })
+}
+
+/// `word` lowercased, with the Russian "ё" as "е" and "ъ" as "ї" unless `fold_russian` is
+/// off; borrowed when nothing changes.
+pub fn normalize(word: &str, normalization: Normalization) -> Cow<'_, str> {
+    if is_normalized(word, normalization) {
+        return Cow::Borrowed(word);
+    }
+    let lowercase = if normalization.fold_unicode {
+        word.nfc().collect::<String>().to_lowercase()
+    } else {
+        word.to_lowercase()
+    };
+    let is_apostrophe =
+        |c: char| c == '\'' || (normalization.fold_unicode && APOSTROPHES.contains(&c));
+    let normalized: String = lowercase
+        .chars()
+        .filter_map(|c| match c {
+            c if is_apostrophe(c) && normalization.strip_apostrophes => None,
+            c if is_apostrophe(c) => Some('\''),
+            c if normalization.strip_invisible && is_invisible(c) => None,
+            'ё' if normalization.fold_russian => Some('е'),
+            'ъ' if normalization.fold_russian => Some('ї'),
+            c => Some(c),
+        })
+        .collect();
+    Cow::Owned(normalized)
+}
+
+#[test]
+fn normalize_test() {
+    let default = Normalization::default();
+    assert!(matches!(
+        normalize("книга", default),
+        Cow::Borrowed("книга")
+    ));
+    assert_eq!(normalize("П'ять\u{AD}ох", default), "пять\u{AD}ох");
+    assert_eq!(normalize("ёлка", default), "елка");
+    let keep = Normalization {
+        strip_apostrophes: false,
+        fold_unicode: false,
+        strip_invisible: false,
+        fold_russian: false,
+    };
+    assert_eq!(normalize("П'ять\u{AD}", keep), "п'ять\u{AD}");
+    assert_eq!(normalize("Ёлка", keep), "ёлка");
+    assert!(is_normalized("объём", keep));
+    assert!(is_normalized("п'ять", keep));
+    // only the ASCII apostrophe is one unless `fold_unicode`
+    assert_eq!(normalize("П’ять", default), "п’ять");
+    assert!(is_normalized("чаи\u{306}ка", default));
+    let folded = Normalization {
+        fold_unicode: true,
+        strip_invisible: true,
+        ..default
+    };
+    assert_eq!(normalize("П'ять\u{AD}ох", folded), "пятьох");
+    let folded_keep = Normalization {
+        fold_unicode: true,
+        ..keep
+    };
+    for word in ["п’ять", "пʼять", "п`ять", "П'ять"].iter() {
+        assert_eq!(normalize(word, folded), "пять");
+        assert_eq!(normalize(word, folded_keep), "п'ять");
+    }
+    assert_eq!(normalize("Чаи\u{306}ка", folded), "чайка");
+    assert!(!is_normalized("чаи\u{306}ка", folded_keep));
+}
@@ -0,0 +1,11 @@
+// @generated by `cargo run --example gen_rules` from rules/suffixes.txt, do not edit.
+
+pub const REFLEXIVE: &[&str] = &["ся", "сь", "си"];
+
+pub const ADJECTIVE: &[&str] = &["ими", "ій", "ий", "а", "е", "ова", "ове", "ів", "є", "їй", "єє", "еє", "я", "ім", "ем", "им", "их", "іх", "ою", "йми", "іми", "у", "ю", "ого", "ому", "ої"];
+
+pub const PARTICIPLE: &[&str] = &["ий", "ого", "ому", "им", "ім", "а", "ій", "у", "ою", "і", "их", "йми"];
+
+pub const VERB: &[&str] = &["сь", "ся", "ив", "ать", "ять", "у", "ю", "ав", "али", "учи", "ячи", "вши", "ши", "е", "ме", "ати", "яти", "є"];
+
+pub const NOUN: &[&str] = &["а", "ев", "ов", "е", "ями", "ами", "еи", "и", "ей", "ой", "ий", "й", "иям", "ям", "ием", "ем", "ам", "ом", "о", "у", "ах", "иях", "ях", "ы", "ь", "ию", "ью", "ю", "ия", "ья", "я", "і", "ові", "ї", "ею", "єю", "ою", "є", "еві", "єм", "ів", "їв"];
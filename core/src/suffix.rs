@@ -0,0 +1,154 @@
+//! The matching primitives of the rule pipeline.
+//!
+//! These replaced the PCRE patterns the rules were first written with and keep their
+//! semantics, so stems did not change. They match at the very end of the text; the PCRE
+//! quirk that `$` also matches before a final newline is emulated once by the pipeline.
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+// http://uk.wikipedia.org/wiki/Голосний_звук
+/// The vowels RV and the derivational group look for.
+pub const VOWELS: &str = "аеиоуюяіїє";
+
+/// Whether `c` is one of `VOWELS`.
+pub fn is_vowel(c: char) -> bool {
+    VOWELS.contains(c)
+}
+
+/// Alternative suffixes of one rule group. Like the anchored alternation `(a|b|…)$`, `find`
+/// matches the longest alternative the text ends with, whatever the order of the table.
+#[derive(Clone, Debug)]
+pub struct Suffixes {
+    // as listed, and longest first
+    listed: Vec<Cow<'static, str>>,
+    alternatives: Vec<Cow<'static, str>>,
+}
+
+impl Suffixes {
+    /// A group matching any of `alternatives`.
+    pub fn new<I, S>(alternatives: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Cow<'static, str>>,
+    {
+        let listed: Vec<Cow<'static, str>> = alternatives.into_iter().map(Into::into).collect();
+        let mut alternatives = listed.clone();
+        alternatives.sort_by_key(|s| core::cmp::Reverse(s.len()));
+        Suffixes {
+            listed,
+            alternatives,
+        }
+    }
+
+    /// The alternatives in the order they were given.
+    pub fn listed(&self) -> impl Iterator<Item = &str> + '_ {
+        self.listed.iter().map(|s| &**s)
+    }
+
+    /// The byte offset where the matched suffix starts. Alternatives are matched as whole
+    /// strs, so it is always a char boundary of `text`, whatever the width of the chars.
+    pub fn find(&self, text: &str) -> Option<usize> {
+        self.matched(text).map(|s| text.len() - s.len())
+    }
+
+    /// The number of chars of the matched suffix, which the rules cut.
+    pub fn find_chars(&self, text: &str) -> Option<usize> {
+        self.matched(text).map(|s| s.chars().count())
+    }
+
+    fn matched(&self, text: &str) -> Option<&str> {
+        self.alternatives
+            .iter()
+            .map(|s| &**s)
+            .find(|s| text.ends_with(s))
+    }
+}
+
+impl PartialEq for Suffixes {
+    fn eq(&self, other: &Suffixes) -> bool {
+        self.listed == other.listed
+    }
+}
+
+impl Eq for Suffixes {}
+
+/// The byte offset right after the first vowel of `word`, where the RV region starts.
+pub fn rv_start(word: &str) -> Option<usize> {
+    word.char_indices()
+        .find(|(_, c)| is_vowel(*c))
+        .map(|(i, c)| i + c.len_utf8())
+}
+
+/// Whether the derivational group may apply to `rv`: it ends in "ость" or "ост" and the
+/// part before "ст" ends in two consonant-vowel transitions, which is the original
+/// pattern `[^V][V]+[^V]+[V].*(?<=о)сть?$`.
+pub fn is_derivational(rv: &str) -> bool {
+    let head = match rv.strip_suffix("сть").or_else(|| rv.strip_suffix("ст")) {
+        Some(head) if head.ends_with('о') => head,
+        _ => return false,
+    };
+    // `.*` between the last vowel of the pattern and "ст" does not cross a newline
+    let line_start = head.rfind('\n').map_or(0, |i| i + 1);
+    head[line_start..]
+        .char_indices()
+        .filter(|(_, c)| is_vowel(*c))
+        .any(|(i, _)| ends_in_transition(&head[..line_start + i]))
+}
+
+// whether `text` ends in `[^V][V]+[^V]+`
+fn ends_in_transition(text: &str) -> bool {
+    let mut chars = text.chars().rev().peekable();
+    let mut consonants = 0;
+    while chars.next_if(|c| !is_vowel(*c)).is_some() {
+        consonants += 1;
+    }
+    let mut vowels = 0;
+    while chars.next_if(|c| is_vowel(*c)).is_some() {
+        vowels += 1;
+    }
+    consonants > 0 && vowels > 0 && chars.next().is_some()
+}
+
+#[test]
+fn suffixes_test() {
+    let suffixes = Suffixes::new(vec!["а", "ами", "ми"]);
+    assert_eq!(suffixes.find("книгами"), Some(8));
+    assert_eq!(suffixes.find("книга"), Some(8));
+    assert_eq!(suffixes.find("книга\n"), None);
+    assert_eq!(suffixes.find("книг"), None);
+    assert_eq!(suffixes.find(""), None);
+    // "а" is D0 B0 and "ї" D1 97: byte tails of other letters never match
+    let wide = Suffixes::new(vec!["ї", "ꙗ", "𐐨"]);
+    assert_eq!(wide.find("краї"), Some("кра".len()));
+    assert_eq!(wide.find("сꙗ"), Some("с".len()));
+    assert_eq!(wide.find("ж𐐨"), Some("ж".len()));
+    assert_eq!(wide.find("краі"), None);
+    assert_eq!(wide.find_chars("ж𐐨"), Some(1));
+    assert_eq!(suffixes.find_chars("книгами"), Some(3));
+}
+
+#[test]
+fn rv_start_test() {
+    assert_eq!(rv_start("книга"), Some(6));
+    assert_eq!(rv_start("пгт"), None);
+    // after the first vowel as in the reference `^(.*?[аеиоуюяіїє])(.*)$`, on char
+    // boundaries whatever the width of the chars before it
+    assert_eq!(rv_start("їжак"), Some("ї".len()));
+    assert_eq!(rv_start("ёлка"), Some("ёлка".len()));
+    assert_eq!(rv_start("🙂ся"), Some("🙂ся".len()));
+    assert_eq!(rv_start("d🙂жук"), Some("d🙂жу".len()));
+    for word in ["лють", "m'ясо", "ґанок", "ь"] {
+        assert!(rv_start(word).is_none_or(|rv| word.is_char_boundary(rv)));
+    }
+}
+
+#[test]
+fn is_derivational_test() {
+    assert!(is_derivational("лодость"));
+    assert!(is_derivational("лодост"));
+    assert!(!is_derivational("дость"));
+    assert!(!is_derivational("лодасть"));
+    assert!(is_derivational("лод\nость"));
+    assert!(!is_derivational("лодость\n"));
+}
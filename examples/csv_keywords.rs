@@ -0,0 +1,87 @@
+// Adds a `keywords` column to a CSV file: the most frequent stems of a text column, each as
+// the form it occurs in most. Run from the crate root as
+// `cargo run --example csv_keywords -- reviews.csv text > keywords.csv`.
+use std::env;
+use std::fs;
+use std::process;
+
+use stemmer_uk::analyzer::Analyzer;
+use stemmer_uk::keywords::keywords;
+
+const KEYWORDS: usize = 5;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() != 2 {
+        eprintln!("usage: csv_keywords <file.csv> <column>");
+        process::exit(2);
+    }
+    let input = fs::read_to_string(&args[0]).unwrap_or_else(|err| {
+        eprintln!("csv_keywords: {}: {}", args[0], err);
+        process::exit(1);
+    });
+    let mut records = parse_csv(&input).into_iter();
+    let header = records.next().unwrap_or_default();
+    let column = header
+        .iter()
+        .position(|c| *c == args[1])
+        .unwrap_or_else(|| {
+            eprintln!("csv_keywords: no column {:?} in {}", args[1], args[0]);
+            process::exit(1);
+        });
+
+    let analyzer = Analyzer::new();
+    println!("{},keywords", join(&header));
+    for record in records {
+        let text = record.get(column).map_or("", String::as_str);
+        let words: Vec<String> = keywords(&analyzer, text, KEYWORDS)
+            .into_iter()
+            .map(|k| k.word)
+            .collect();
+        println!("{},{}", join(&record), field(&words.join("; ")));
+    }
+}
+
+// RFC 4180 records: fields separated by commas, quoted when they contain commas, quotes
+// or line breaks, with quotes doubled inside
+fn parse_csv(input: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+fn field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn join(record: &[String]) -> String {
+    let fields: Vec<String> = record.iter().map(|f| field(f)).collect();
+    fields.join(",")
+}
@@ -0,0 +1,25 @@
+// Regenerates core/src/rule_tables.rs from rules/suffixes.txt; run from the crate root.
+use std::fs;
+use std::process;
+
+use stemmer_uk::codegen::{generate, OUTPUT_PATH, SOURCE_PATH};
+
+fn main() {
+    let source = fs::read_to_string(SOURCE_PATH).unwrap_or_else(|err| {
+        eprintln!("gen_rules: {}: {}", SOURCE_PATH, err);
+        process::exit(1);
+    });
+    match generate(&source) {
+        Ok(tables) => {
+            fs::write(OUTPUT_PATH, tables).unwrap_or_else(|err| {
+                eprintln!("gen_rules: {}: {}", OUTPUT_PATH, err);
+                process::exit(1);
+            });
+            println!("wrote {}", OUTPUT_PATH);
+        }
+        Err(err) => {
+            eprintln!("gen_rules: {}", err);
+            process::exit(1);
+        }
+    }
+}
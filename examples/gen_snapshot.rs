@@ -0,0 +1,20 @@
+// Rewrites the stems of rules/snapshot_v2.txt with those of `Algorithm::V2`; run from the
+// crate root.
+use std::fs;
+use std::process;
+
+use stemmer_uk::codegen::{restem_snapshot, SNAPSHOT_PATH};
+use stemmer_uk::{Algorithm, Stemmer};
+
+fn main() {
+    let source = fs::read_to_string(SNAPSHOT_PATH).unwrap_or_else(|err| {
+        eprintln!("gen_snapshot: {}: {}", SNAPSHOT_PATH, err);
+        process::exit(1);
+    });
+    let stemmer = Stemmer::builder().algorithm(Algorithm::V2).build();
+    fs::write(SNAPSHOT_PATH, restem_snapshot(&source, &stemmer)).unwrap_or_else(|err| {
+        eprintln!("gen_snapshot: {}: {}", SNAPSHOT_PATH, err);
+        process::exit(1);
+    });
+    println!("wrote {}", SNAPSHOT_PATH);
+}
@@ -0,0 +1,56 @@
+// The matching half of a Telegram bot that alerts on keywords: reads messages as
+// `chat<TAB>text` lines and prints an alert for those mentioning a keyword in any form.
+// Fed from the Bot API and run from the crate root:
+//
+//     curl -s "https://api.telegram.org/bot$TOKEN/getUpdates" \
+//         | jq -r '.result[].message | [.chat.title, .text] | @tsv' \
+//         | cargo run --example keyword_alerts -- keywords.txt
+//
+// `keywords.txt` has a keyword per line. A bot would send every alert with `sendMessage`
+// instead of printing it.
+use std::env;
+use std::fs;
+use std::io::{self, BufRead};
+use std::process;
+
+use stemmer_uk::analyzer::Analyzer;
+use stemmer_uk::keywords::Watchlist;
+
+fn main() {
+    let path = env::args().nth(1).unwrap_or_else(|| {
+        eprintln!("usage: keyword_alerts <keywords.txt> < messages.tsv");
+        process::exit(2);
+    });
+    let keywords = fs::read_to_string(&path).unwrap_or_else(|err| {
+        eprintln!("keyword_alerts: {}: {}", path, err);
+        process::exit(1);
+    });
+    let keywords = keywords.lines().map(str::trim).filter(|k| !k.is_empty());
+    let watchlist = Watchlist::new(Analyzer::new(), keywords);
+
+    for line in io::stdin().lock().lines() {
+        let line = line.unwrap_or_else(|err| {
+            eprintln!("keyword_alerts: {}", err);
+            process::exit(1);
+        });
+        let (chat, text) = line.split_once('\t').unwrap_or(("", &line));
+        let matches = watchlist.matches(text);
+        if matches.is_empty() {
+            continue;
+        }
+        // the message with the matched words marked, e.g. "після *виборів*"
+        let mut marked = String::new();
+        let mut end = 0;
+        for m in &matches {
+            if m.range.start >= end {
+                marked.push_str(&text[end..m.range.start]);
+                marked.push_str(&format!("*{}*", m.word));
+                end = m.range.end;
+            }
+        }
+        marked.push_str(&text[end..]);
+        let mut keywords: Vec<&str> = matches.iter().map(|m| m.keyword).collect();
+        keywords.dedup();
+        println!("[{}] {}: {}", chat, keywords.join(", "), marked);
+    }
+}
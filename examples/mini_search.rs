@@ -0,0 +1,71 @@
+// A search over a folder of .txt files: every word is indexed by its stem, so a query finds
+// a file whatever the forms its words take there. Run from the crate root as
+// `cargo run --example mini_search -- docs/ "результати виборів"`.
+use std::collections::HashMap;
+use std::env;
+use std::path::Path;
+use std::process;
+
+use stemmer_uk::analyzer::Analyzer;
+use stemmer_uk::ellipsize_uk;
+use stemmer_uk::ingest::read_documents;
+use stemmer_uk::stopwords;
+
+const RESULTS: usize = 10;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() != 2 {
+        eprintln!("usage: mini_search <dir> <query>");
+        process::exit(2);
+    }
+    let documents = read_documents(Path::new(&args[0]), "txt").unwrap_or_else(|err| {
+        eprintln!("mini_search: {}: {}", args[0], err);
+        process::exit(1);
+    });
+    // stop words would match nearly every file
+    let analyzer = Analyzer::new().stopwords(stopwords::default_words());
+
+    // stem → the files it occurs in and how often
+    let mut index: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+    for (doc, document) in documents.iter().enumerate() {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for term in analyzer.terms(&document.text) {
+            *counts.entry(term).or_insert(0) += 1;
+        }
+        for (term, count) in counts {
+            index.entry(term).or_default().push((doc, count));
+        }
+    }
+
+    // tf-idf: stems found in few files weigh more
+    let query = analyzer.terms(&args[1]);
+    let mut scores: HashMap<usize, f64> = HashMap::new();
+    for term in &query {
+        let postings = index.get(term).map_or(&[][..], Vec::as_slice);
+        let idf = (documents.len() as f64 / postings.len() as f64).ln() + 1.0;
+        for &(doc, count) in postings {
+            *scores.entry(doc).or_insert(0.0) += count as f64 * idf;
+        }
+    }
+    let mut ranked: Vec<(usize, f64)> = scores.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    println!(
+        "{} of {} files match {:?}",
+        ranked.len(),
+        documents.len(),
+        args[1]
+    );
+    for &(doc, score) in ranked.iter().take(RESULTS) {
+        let document = &documents[doc];
+        // the first line with a word of the query, to show why the file matched
+        let line = document
+            .text
+            .lines()
+            .find(|line| analyzer.terms(line).iter().any(|t| query.contains(t)))
+            .unwrap_or("");
+        println!("{:8.2}  {}", score, document.path.display());
+        println!("          {}", ellipsize_uk(line.trim(), 100));
+    }
+}
@@ -0,0 +1,25 @@
+// Prints the trigram table of `ngram` trained on word lists, one word per line; run from
+// the crate root as `cargo run --example train_ngrams -- words.txt > rules/trigrams.txt`.
+use std::env;
+use std::fs;
+use std::process;
+
+use stemmer_uk::trigram_model::NgramModel;
+
+fn main() {
+    let mut text = String::new();
+    for path in env::args().skip(1) {
+        match fs::read_to_string(&path) {
+            Ok(words) => text.push_str(&words),
+            Err(err) => {
+                eprintln!("train_ngrams: {}: {}", path, err);
+                process::exit(1);
+            }
+        }
+        text.push('\n');
+    }
+    let model = NgramModel::train(text.split_whitespace());
+    println!("# character trigrams of Ukrainian words and their counts, see src/ngram.rs");
+    println!("# @generated by `cargo run --example train_ngrams`");
+    print!("{}", model.to_text());
+}
@@ -0,0 +1,185 @@
+/* C ABI of stemmer_uk, built with `cargo build --release --features ffi`.
+ *
+ * Inputs are borrowed for the duration of a call. Every ukstem_string and ukstem_tokens
+ * returned is owned by the caller and released exactly once with the matching free
+ * function. Outputs are UTF-8 and not NUL-terminated, except the C strings of uk_stem
+ * and uk_stem_batch. A stemmer handle is immutable and may be used from several threads
+ * at once.
+ *
+ * No function unwinds into the caller: a bug of the library is reported as UKSTEM_PANIC
+ * and leaves the handle usable. This needs the library built with the default
+ * panic = "unwind".
+ *
+ * Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#ifndef UKSTEM_H
+#define UKSTEM_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define UKSTEM_OK 0
+
+#define UKSTEM_NULL_POINTER 1
+
+#define UKSTEM_INVALID_UTF8 2
+
+#define UKSTEM_PANIC 3
+
+/*
+ A C string can't hold the stem: the word, which is valid UTF-8, contains a NUL.
+ */
+#define UKSTEM_INTERIOR_NUL 4
+
+#define UKSTEM_WORD 0
+
+#define UKSTEM_NUMBER 1
+
+#define UKSTEM_WHITESPACE 2
+
+#define UKSTEM_PUNCTUATION 3
+
+#define UKSTEM_EMOJI 4
+
+#define UKSTEM_EMOTICON 5
+
+#define UKSTEM_CONTROL 6
+
+#define UKSTEM_TAG 7
+
+#define UKSTEM_OTHER 8
+
+/*
+ A configured stemmer. `Stemmer::default()` behaves exactly like `stem_word`.
+ */
+typedef struct ukstem_stemmer ukstem_stemmer;
+
+/*
+ Borrowed UTF-8 input.
+ */
+typedef struct {
+  const uint8_t *ptr;
+  size_t len;
+} ukstem_str;
+
+/*
+ Owned UTF-8 output, free with `ukstem_string_free`.
+ */
+typedef struct {
+  uint8_t *ptr;
+  size_t len;
+} ukstem_string;
+
+/*
+ A token as a byte range of the input text; `kind` follows the `TokenKind` order.
+ */
+typedef struct {
+  uint32_t kind;
+  size_t start;
+  size_t end;
+} ukstem_token;
+
+/*
+ Owned token array, free with `ukstem_tokens_free`.
+ */
+typedef struct {
+  ukstem_token *ptr;
+  size_t len;
+} ukstem_tokens;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ A stemmer with the default configuration, null only if creating it failed.
+ */
+ukstem_stemmer *ukstem_stemmer_new(void);
+
+/*
+ # Safety
+ `stemmer` must be null or come from `ukstem_stemmer_new` and not be freed yet.
+ */
+void ukstem_stemmer_free(ukstem_stemmer *stemmer);
+
+/*
+ Stems `word` into `*out`, which is left untouched on failure.
+
+ # Safety
+ `stemmer` must be a live handle, `word` a valid view and `out` writable.
+ */
+int32_t ukstem_stem(const ukstem_stemmer *stemmer, ukstem_str word, ukstem_string *out);
+
+/*
+ Stems `count` words into `out[0..count]`. Either all outputs are written or, on
+ failure, none are.
+
+ # Safety
+ `words` must point to `count` valid views and `out` to `count` writable slots.
+ */
+int32_t ukstem_stem_batch(const ukstem_stemmer *stemmer,
+                          const ukstem_str *words,
+                          size_t count,
+                          ukstem_string *out);
+
+/*
+ # Safety
+ `s` must come from this library and not be freed yet.
+ */
+void ukstem_string_free(ukstem_string s);
+
+/*
+ Tokenizes `text` with the default tokenizer into `*out`.
+
+ # Safety
+ `text` must be a valid view and `out` writable.
+ */
+int32_t ukstem_tokenize(ukstem_str text, ukstem_tokens *out);
+
+/*
+ # Safety
+ `tokens` must come from `ukstem_tokenize` and not be freed yet.
+ */
+void ukstem_tokens_free(ukstem_tokens tokens);
+
+/*
+ Stems the `len` UTF-8 bytes at `word` with the default configuration. Returns a
+ NUL-terminated string to free with `uk_stem_free`, or null if `word` is null with a
+ nonzero `len`, is not UTF-8 or contains a NUL.
+
+ # Safety
+ `word` must be null or valid for reads of `len` bytes.
+ */
+char *uk_stem(const char *word, size_t len);
+
+/*
+ # Safety
+ `stem` must be null or come from `uk_stem` or `uk_stem_batch` and not be freed yet.
+ */
+void uk_stem_free(char *stem);
+
+/*
+ Stems `count` words, `words[i]` of `lens[i]` bytes, into `out[0..count]` like
+ `uk_stem`. Either all outputs are written or, on failure, none are; a word with a NUL
+ fails with `UKSTEM_INTERIOR_NUL`.
+
+ # Safety
+ `words` and `lens` must point to `count` valid entries and `out` to `count`
+ writable slots.
+ */
+int32_t uk_stem_batch(const char *const *words, const size_t *lens, size_t count, char **out);
+
+/*
+ Frees the `count` stems `uk_stem_batch` wrote to `stems`.
+
+ # Safety
+ `stems` must be null or hold `count` stems of a successful `uk_stem_batch`, not freed
+ yet.
+ */
+void uk_stem_batch_free(char **stems, size_t count);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* UKSTEM_H */
@@ -0,0 +1,1125 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::{Deref, Range};
+use std::sync::Arc;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::fingerprint::Fingerprint;
+use crate::homoglyph;
+use crate::offsets::{MappedStem, Tracked};
+use crate::pos::PosGuess;
+use crate::preprocess::fold_text;
+use crate::stemmer::is_latin_word;
+use crate::tokenizer::{
+    script, strip_bidi_controls, strip_numeral_tail, Script, Token, TokenKind, Tokenizer, Tokens,
+};
+use crate::trigram_model;
+use crate::wordlist::WordList;
+use crate::{translit, Foreign, Stemmer};
+
+/// Normalizes word tokens in scripts the stemmer has no rules for.
+pub trait FallbackNormalizer: Send + Sync {
+    fn normalize(&self, token: &str) -> String;
+
+    /// Names the normalization in the analyzer fingerprint; custom normalizers that
+    /// change behavior between runs should return distinct ids.
+    fn id(&self) -> &str {
+        "custom"
+    }
+}
+
+/// Returns the token unchanged.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PassThrough;
+
+/// Lowercases the token, like `stem_word` does for words it can't stem.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Lowercase;
+
+/// Applies Unicode NFC normalization only.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Nfc;
+
+/// NFC normalization followed by lowercasing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LowercaseNfc;
+
+impl FallbackNormalizer for PassThrough {
+    fn normalize(&self, token: &str) -> String {
+        token.to_string()
+    }
+
+    fn id(&self) -> &str {
+        "pass-through"
+    }
+}
+
+impl FallbackNormalizer for Lowercase {
+    fn normalize(&self, token: &str) -> String {
+        token.to_lowercase()
+    }
+
+    fn id(&self) -> &str {
+        "lowercase"
+    }
+}
+
+impl FallbackNormalizer for Nfc {
+    fn normalize(&self, token: &str) -> String {
+        token.nfc().collect()
+    }
+
+    fn id(&self) -> &str {
+        "nfc"
+    }
+}
+
+impl FallbackNormalizer for LowercaseNfc {
+    fn normalize(&self, token: &str) -> String {
+        token.nfc().collect::<String>().to_lowercase()
+    }
+
+    fn id(&self) -> &str {
+        "lowercase-nfc"
+    }
+}
+
+impl<F> FallbackNormalizer for F
+where
+    F: Fn(&str) -> String + Send + Sync,
+{
+    fn normalize(&self, token: &str) -> String {
+        self(token)
+    }
+}
+
+/// An indexable term produced from a token.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Term<'a> {
+    pub token: Token<'a>,
+    pub term: String,
+}
+
+/// When Latin-script words are converted to Cyrillic before stemming.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TranslitRepair {
+    #[default]
+    Off,
+    /// Every Latin word the detector classifies as transliterated Ukrainian.
+    Token,
+    /// Every Latin word, if most Latin words of the text look transliterated.
+    Document,
+}
+
+/// How words of code-switched text, Ukrainian mixed with English terms as in technical
+/// documentation, are analyzed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CodeSwitching {
+    /// Latin words go through translit repair and the fallback normalizer, and compounds
+    /// like "API-запиті" through the fallback as a whole.
+    #[default]
+    Off,
+    /// Latin words are only lowercased, never transliterated, and the parts of hyphenated
+    /// compounds are handled by script: "API-запиті" becomes "api-запит".
+    Lowercase,
+    /// Like `Lowercase`, keeping Latin words exactly as written: "API-запит".
+    Exact,
+}
+
+/// Tokenizes text and turns every word, number, emoji and emoticon into a term.
+/// Cyrillic words are stemmed, words in other scripts go through the fallback normalizer.
+#[derive(Clone)]
+pub struct Analyzer {
+    tokenizer: Tokenizer,
+    stemmer: Stemmer,
+    fallback: Arc<dyn FallbackNormalizer>,
+    translit: TranslitRepair,
+    code_switching: CodeSwitching,
+    min_word_score: Option<f32>,
+    stopwords: Option<Arc<WordList<'static>>>,
+    phrases: Vec<Vec<String>>,
+    proper_nouns: bool,
+    abbreviations: BTreeMap<String, Vec<String>>,
+    synonyms: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Analyzer {
+            tokenizer: Tokenizer::default(),
+            stemmer: Stemmer::default(),
+            fallback: Arc::new(Lowercase),
+            translit: TranslitRepair::Off,
+            code_switching: CodeSwitching::Off,
+            min_word_score: None,
+            stopwords: None,
+            phrases: Vec::new(),
+            proper_nouns: false,
+            abbreviations: BTreeMap::new(),
+            synonyms: BTreeMap::new(),
+        }
+    }
+}
+
+impl fmt::Debug for Analyzer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Analyzer")
+            .field("tokenizer", &self.tokenizer)
+            .field("stemmer", &self.stemmer)
+            .field("translit", &self.translit)
+            .field("code_switching", &self.code_switching)
+            .field("min_word_score", &self.min_word_score)
+            .field("stopwords", &self.stopwords)
+            .field("phrases", &self.phrases)
+            .field("proper_nouns", &self.proper_nouns)
+            .field("abbreviations", &self.abbreviations)
+            .field("synonyms", &self.synonyms)
+            .finish()
+    }
+}
+
+impl Analyzer {
+    pub fn new() -> Self {
+        Analyzer::default()
+    }
+
+    pub fn tokenizer(mut self, tokenizer: Tokenizer) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    pub fn stemmer(mut self, stemmer: Stemmer) -> Self {
+        self.stemmer = stemmer;
+        self
+    }
+
+    pub fn fallback<N: FallbackNormalizer + 'static>(mut self, fallback: N) -> Self {
+        self.fallback = Arc::new(fallback);
+        self
+    }
+
+    pub fn translit_repair(mut self, mode: TranslitRepair) -> Self {
+        self.translit = mode;
+        self
+    }
+
+    pub fn code_switching(mut self, mode: CodeSwitching) -> Self {
+        self.code_switching = mode;
+        self
+    }
+
+    /// Leaves Cyrillic words scoring below `min_score` in `trigram_model::looks_like_ukrainian`
+    /// unstemmed, only lowercased, so random strings in logs don't add made-up stems to
+    /// the vocabulary; 0.2 keeps nearly all real words.
+    pub fn min_word_score(mut self, min_score: f32) -> Self {
+        self.min_word_score = Some(min_score);
+        self
+    }
+
+    /// Drops words on the list from `analyze` and `terms`, unless they are part of a
+    /// phrase. `stem_text` keeps them.
+    pub fn stopwords(mut self, words: WordList<'static>) -> Self {
+        self.stopwords = Some(Arc::new(words));
+        self
+    }
+
+    /// Adds a multi-word expression, like "Верховна Рада", analyzed into a single term
+    /// joining the stems of its words with `_`. Longer phrases win over shorter ones.
+    pub fn phrase(mut self, phrase: &str) -> Self {
+        let words: Vec<String> = phrase.split_whitespace().map(str::to_lowercase).collect();
+        if !words.is_empty() {
+            self.phrases.push(words);
+            self.phrases
+                .sort_by_key(|words| std::cmp::Reverse(words.len()));
+        }
+        self
+    }
+
+    pub fn phrases<'p, I: IntoIterator<Item = &'p str>>(self, phrases: I) -> Self {
+        phrases.into_iter().fold(self, Analyzer::phrase)
+    }
+
+    /// Keeps capitalized Cyrillic words that do not start a sentence unstemmed, only
+    /// lowercased, so names are not conflated with common words.
+    pub fn proper_nouns(mut self, enabled: bool) -> Self {
+        self.proper_nouns = enabled;
+        self
+    }
+
+    /// Analyzes `abbreviation` as the stems of the words of `expansion`, all pointing at
+    /// the abbreviation token. A trailing dot, as in "ст.", must follow the word in the
+    /// text and is then part of the token instead of ending the sentence.
+    pub fn abbreviation(mut self, abbreviation: &str, expansion: &str) -> Self {
+        let words = expansion
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+        self.abbreviations
+            .insert(abbreviation.to_lowercase(), words);
+        self
+    }
+
+    /// Also analyzes every word whose stem is the stem of `word` into the stems of the
+    /// words of `synonym`, at the position of the word. Meant for the query side of a
+    /// `PairedConfig`, so the index stays free of duplicate terms.
+    pub fn synonym(mut self, word: &str, synonym: &str) -> Self {
+        let words = synonym.split_whitespace().map(str::to_lowercase);
+        self.synonyms
+            .entry(word.to_lowercase())
+            .or_default()
+            .extend(words);
+        self
+    }
+
+    /// Identifies the whole analysis chain, see `fingerprint::Fingerprint`.
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::of_version(
+            self.rules_version(),
+            &format!(
+                "{} {:?} {:?} {:?}",
+                self.core_config(),
+                self.translit,
+                self.stopwords,
+                self.synonyms
+            ),
+        )
+    }
+
+    /// Identifies the part of the chain that decides which term a word becomes: the
+    /// tokenizer, stemmer, fallback, phrases, proper nouns, abbreviations, code switching
+    /// and the minimum word score, but not the query-side options translit repair,
+    /// stopwords and synonyms.
+    pub fn core_fingerprint(&self) -> Fingerprint {
+        Fingerprint::of_version(self.rules_version(), &self.core_config())
+    }
+
+    // the version of the built-in rules the stemmer runs, see `Algorithm`
+    fn rules_version(&self) -> u32 {
+        self.stemmer.options().algorithm.rules_version()
+    }
+
+    fn core_config(&self) -> String {
+        format!(
+            "{:?} {:?} {} {:?} {} {:?} {:?} {:?}",
+            self.tokenizer,
+            self.stemmer.options(),
+            self.fallback.id(),
+            self.phrases,
+            self.proper_nouns,
+            self.abbreviations,
+            self.code_switching,
+            self.min_word_score
+        )
+    }
+
+    pub fn get_tokenizer(&self) -> &Tokenizer {
+        &self.tokenizer
+    }
+
+    pub fn get_stemmer(&self) -> &Stemmer {
+        &self.stemmer
+    }
+
+    /// The term for a single token; `TranslitRepair::Document` falls back to per-token
+    /// detection here since there is no surrounding text.
+    pub fn term(&self, token: &Token) -> Option<String> {
+        let translit = self.translit != TranslitRepair::Off && translit::is_translit(token.text)
+            || self.stemmer.transliterates(token.text);
+        self.term_with(token, translit, None)
+    }
+
+    /// The term for a single token like `term`, with the document offset every character
+    /// of it comes from.
+    pub fn term_mapped(&self, token: &Token) -> Option<MappedStem> {
+        let term = self.term(token)?;
+        let mut tracked = Tracked::new(token.text, token.start);
+        let word = strip_bidi_controls(token.text);
+        tracked.rewrite(&word);
+        let translit = self.translit != TranslitRepair::Off && translit::is_translit(token.text)
+            || self.stemmer.transliterates(token.text);
+        match token.kind {
+            TokenKind::Word if token.script() == Script::Latin && translit => {
+                tracked.rewrite(&word.to_lowercase());
+                let (cyrillic, sources) = translit::to_cyrillic_mapped(&word);
+                tracked.rewrite_from(cyrillic, &sources);
+                self.stemmer.stem_tracked(&mut tracked);
+            }
+            TokenKind::Word => {
+                if token.script() == Script::Cyrillic || token.script() == Script::Mixed {
+                    self.stemmer.stem_tracked(&mut tracked);
+                }
+                tracked.rewrite(&term);
+            }
+            _ => tracked.rewrite(&term),
+        }
+        Some(tracked.finish())
+    }
+
+    /// The word at a cursor at byte offset `pos` of `text`, with its term, e.g. to
+    /// highlight the other forms of the word in an editor. A cursor right after a word
+    /// belongs to it unless another word starts there.
+    pub fn word_at<'a>(&self, text: &'a str, pos: usize) -> Option<StemmedToken<'a>> {
+        let mut found = None;
+        for token in self.tokenizer.tokenize(text) {
+            if token.start > pos {
+                break;
+            }
+            if token.kind != TokenKind::Word {
+                continue;
+            }
+            if pos < token.end {
+                found = Some(token);
+                break;
+            }
+            if pos == token.end {
+                found = Some(token);
+            }
+        }
+        let token = found?;
+        let translit = self.translit_pass(text);
+        let term = self.term_with(&token, translit(&token), None)?;
+        Some(self.stemmed_token(&token, term, false))
+    }
+
+    // `token` with its term, guessing its part of speech if `pos`
+    fn stemmed_token<'a>(&self, token: &Token<'a>, term: String, pos: bool) -> StemmedToken<'a> {
+        let word = strip_bidi_controls(token.text);
+        let stemmed = term != token.text && term != self.stemmer.normalize(&word);
+        let stemmable = matches!(token.script(), Script::Cyrillic | Script::Mixed);
+        StemmedToken {
+            original: token.text,
+            stem: Cow::Owned(term),
+            start: token.start,
+            end: token.end,
+            stemmed,
+            pos: (pos && token.kind == TokenKind::Word && stemmable)
+                .then(|| self.stemmer.stem_with_pos(&word).1),
+        }
+    }
+
+    /// The byte ranges of the words in `text` with the same term as `word`, its other
+    /// forms included, e.g. for highlighting them all.
+    pub fn find_inflections(&self, text: &str, word: &str) -> Vec<Range<usize>> {
+        let target = self
+            .tokenizer
+            .tokenize(word)
+            .find(|t| t.kind == TokenKind::Word)
+            .and_then(|t| self.term(&t));
+        let target = match target {
+            Some(target) => target,
+            None => return Vec::new(),
+        };
+        let translit = self.translit_pass(text);
+        self.tokenizer
+            .tokenize(text)
+            .filter(|t| t.kind == TokenKind::Word)
+            .filter(|t| self.term_with(t, translit(t), None).as_ref() == Some(&target))
+            .map(|t| t.start..t.end)
+            .collect()
+    }
+
+    // `folded` is the token's range of the `fold_text` of the text, if there is one
+    fn term_with(&self, token: &Token, translit: bool, folded: Option<&str>) -> Option<String> {
+        match token.kind {
+            TokenKind::Word => {
+                let word = strip_bidi_controls(token.text);
+                if let Some(term) = self.code_switched_term(&word) {
+                    return Some(term);
+                }
+                match token.script() {
+                    Script::Cyrillic if self.is_garbage(&word) => Some(word.to_lowercase()),
+                    Script::Cyrillic => Some(match (&word, folded) {
+                        (Cow::Borrowed(word), Some(folded)) => {
+                            self.stemmer.stem_prefolded(word, folded)
+                        }
+                        _ => self.stemmer.stem(&word),
+                    }),
+                    Script::Mixed
+                        if homoglyph::is_repairable(&word, self.stemmer.options().homoglyphs) =>
+                    {
+                        Some(self.stemmer.stem(&word))
+                    }
+                    Script::Latin if translit => {
+                        Some(self.stemmer.stem(&translit::to_cyrillic(&word)))
+                    }
+                    _ => Some(self.fallback.normalize(&word)),
+                }
+            }
+            TokenKind::Emoji | TokenKind::Emoticon => Some(match token.sentiment {
+                Some(sentiment) => sentiment.tag().to_string(),
+                None => token.text.to_string(),
+            }),
+            TokenKind::Number => Some(strip_numeral_tail(token.text).to_string()),
+            TokenKind::Tag => Some(token.text.to_string()),
+            _ => None,
+        }
+    }
+
+    fn is_garbage(&self, word: &str) -> bool {
+        self.min_word_score
+            .is_some_and(|min_score| trigram_model::looks_like_ukrainian(word) < min_score)
+    }
+
+    // the term of a Latin word or a compound of Latin and Cyrillic parts under
+    // `CodeSwitching`, `None` for the other words
+    fn code_switched_term(&self, word: &str) -> Option<String> {
+        let latin = |part: &str| match self.code_switching {
+            CodeSwitching::Off => None,
+            CodeSwitching::Lowercase => Some(part.to_lowercase()),
+            CodeSwitching::Exact => Some(part.to_string()),
+        };
+        match script(word) {
+            Script::Latin => latin(word),
+            Script::Mixed if word.contains('-') => {
+                let parts = word.split('-').map(|part| match script(part) {
+                    Script::Latin => latin(part),
+                    Script::Cyrillic => Some(self.stemmer.stem(part)),
+                    Script::Mixed => None,
+                    _ => Some(part.to_string()),
+                });
+                parts
+                    .collect::<Option<Vec<_>>>()
+                    .map(|parts| parts.join("-"))
+            }
+            _ => None,
+        }
+    }
+
+    // decides per token whether it goes through the transliteration repair pass
+    fn translit_pass(&self, text: &str) -> impl Fn(&Token) -> bool {
+        let mode = self.translit;
+        let document = mode == TranslitRepair::Document && translit::is_translit_document(text);
+        let stemmer = self.stemmer.options().foreign == Foreign::Transliterate;
+        move |token: &Token| {
+            stemmer && is_latin_word(token.text)
+                || match mode {
+                    TranslitRepair::Off => false,
+                    TranslitRepair::Token => translit::is_translit(token.text),
+                    TranslitRepair::Document => document,
+                }
+        }
+    }
+
+    pub fn analyze<'a>(&self, text: &'a str) -> Vec<Term<'a>> {
+        self.analyze_from(text, 0, true)
+    }
+
+    // Analyzes `text` from the token starting at byte offset `from`, given whether a
+    // sentence starts there; `TranslitRepair::Document` only looks at the analyzed part.
+    pub(crate) fn analyze_from<'a>(
+        &self,
+        text: &'a str,
+        from: usize,
+        mut sentence_start: bool,
+    ) -> Vec<Term<'a>> {
+        let translit = self.translit_pass(&text[from..]);
+        let folded = fold_text(&text[from..]);
+        let folded = |token: &Token| &folded[token.start - from..token.end - from];
+        let mut terms = Vec::new();
+        let mut tokens = self.tokenizer.tokenize_from(text, from).peekable();
+        while let Some(token) = tokens.next() {
+            if let Some((token, expansion)) = self.expand(text, token, &mut tokens) {
+                sentence_start = false;
+                terms.extend(expansion.iter().map(|word| Term {
+                    token,
+                    term: self.stemmer.stem(word),
+                }));
+                continue;
+            }
+            let starts_sentence = sentence_start;
+            match token.kind {
+                TokenKind::Whitespace | TokenKind::Control => {}
+                TokenKind::Punctuation => sentence_start = Analyzer::ends_sentence(&token),
+                _ => sentence_start = false,
+            }
+            let term = if self.proper_nouns && !starts_sentence && is_proper_noun(&token) {
+                Some(strip_bidi_controls(token.text).to_lowercase())
+            } else {
+                self.term_with(&token, translit(&token), Some(folded(&token)))
+            };
+            if let Some(term) = term {
+                terms.push(Term { token, term });
+            }
+        }
+        let terms = self.add_synonyms(self.join_phrases(text, terms));
+        match &self.stopwords {
+            Some(stopwords) => terms
+                .into_iter()
+                .filter(|t| {
+                    t.token.kind != TokenKind::Word || !stopwords.contains_lowercase(t.token.text)
+                })
+                .collect(),
+            None => terms,
+        }
+    }
+
+    // the expansion of an abbreviation starting at `token`, with the token extended over
+    // the abbreviation's dot, which is then consumed
+    fn expand<'a>(
+        &self,
+        text: &'a str,
+        token: Token<'a>,
+        tokens: &mut std::iter::Peekable<Tokens<'_, 'a>>,
+    ) -> Option<(Token<'a>, &[String])> {
+        if token.kind != TokenKind::Word || self.abbreviations.is_empty() {
+            return None;
+        }
+        let word = token.text.to_lowercase();
+        let dot = tokens.peek().is_some_and(|t| t.text == ".");
+        if dot {
+            if let Some(expansion) = self.abbreviations.get(&format!("{}.", word)) {
+                let end = tokens.next().unwrap().end;
+                let token = Token {
+                    text: &text[token.start..end],
+                    end,
+                    ..token
+                };
+                return Some((token, expansion));
+            }
+        }
+        self.abbreviations.get(&word).map(|e| (token, e.as_slice()))
+    }
+
+    // replaces runs of word terms matching a phrase, separated only by whitespace, with
+    // one term spanning the whole run
+    fn join_phrases<'a>(&self, text: &'a str, mut terms: Vec<Term<'a>>) -> Vec<Term<'a>> {
+        if self.phrases.is_empty() {
+            return terms;
+        }
+        let phrases: Vec<Vec<String>> = self
+            .phrases
+            .iter()
+            .map(|words| words.iter().map(|w| self.stemmer.stem(w)).collect())
+            .collect();
+        let keys: Vec<Option<String>> = terms
+            .iter()
+            .map(|t| (t.token.kind == TokenKind::Word).then(|| self.stemmer.stem(t.token.text)))
+            .collect();
+        // whether only whitespace separates a term from the previous one
+        let adjacent: Vec<bool> = (0..terms.len())
+            .map(|i| {
+                i > 0
+                    && text[terms[i - 1].token.end..terms[i].token.start]
+                        .chars()
+                        .all(char::is_whitespace)
+            })
+            .collect();
+
+        let mut res = Vec::with_capacity(terms.len());
+        let mut i = 0;
+        while i < terms.len() {
+            let phrase = phrases.iter().find(|phrase| {
+                i + phrase.len() <= terms.len()
+                    && phrase.iter().enumerate().all(|(j, stem)| {
+                        keys[i + j].as_ref() == Some(stem) && (j == 0 || adjacent[i + j])
+                    })
+            });
+            match phrase {
+                Some(phrase) if phrase.len() > 1 => {
+                    let (first, last) = (terms[i].token, terms[i + phrase.len() - 1].token);
+                    let token = Token {
+                        kind: TokenKind::Word,
+                        text: &text[first.start..last.end],
+                        start: first.start,
+                        end: last.end,
+                        sentiment: None,
+                    };
+                    res.push(Term {
+                        token,
+                        term: phrase.join("_"),
+                    });
+                    i += phrase.len();
+                }
+                _ => {
+                    res.push(Term {
+                        token: terms[i].token,
+                        term: std::mem::take(&mut terms[i].term),
+                    });
+                    i += 1;
+                }
+            }
+        }
+        res
+    }
+
+    // how many trailing terms a word appended to the text can change: a phrase ending in
+    // the new word replaces as many, an abbreviation's dot one more
+    pub(crate) fn lookbehind(&self) -> usize {
+        self.phrases.first().map_or(1, Vec::len) + 1
+    }
+
+    // whether `token` ends a sentence, as `analyze` decides it
+    pub(crate) fn ends_sentence(token: &Token) -> bool {
+        token.kind == TokenKind::Punctuation && matches!(token.text, "." | "!" | "?" | "…")
+    }
+
+    // follows every term with the terms of its synonyms
+    fn add_synonyms<'a>(&self, terms: Vec<Term<'a>>) -> Vec<Term<'a>> {
+        if self.synonyms.is_empty() {
+            return terms;
+        }
+        let synonyms: HashMap<String, Vec<String>> = self
+            .synonyms
+            .iter()
+            .map(|(word, synonyms)| {
+                let stems = synonyms.iter().map(|w| self.stemmer.stem(w)).collect();
+                (self.stemmer.stem(word), stems)
+            })
+            .collect();
+        let mut res = Vec::with_capacity(terms.len());
+        for term in terms {
+            let token = term.token;
+            let expansion = match token.kind {
+                TokenKind::Word => synonyms.get(&term.term),
+                _ => None,
+            };
+            res.push(term);
+            for stem in expansion.into_iter().flatten() {
+                res.push(Term {
+                    token,
+                    term: stem.clone(),
+                });
+            }
+        }
+        res
+    }
+
+    pub fn terms(&self, text: &str) -> Vec<String> {
+        self.analyze(text).into_iter().map(|t| t.term).collect()
+    }
+
+    /// Replaces every word in `text` with its term, leaving everything else untouched.
+    pub fn stem_text(&self, text: &str) -> String {
+        self.stem_text_iter(text).collect()
+    }
+
+    /// The words of `text` with their stems and byte ranges, e.g. to highlight matches of
+    /// a stemmed query in the original text.
+    pub fn stem_tokens<'s, 'a>(&'s self, text: &'a str) -> StemTokens<'a, &'s Analyzer> {
+        StemTokens::new(self, text)
+    }
+
+    /// `stem_text` piece by piece: the term of every word token and the text of every
+    /// other token, in order.
+    pub fn stem_text_iter<'s, 'a>(&'s self, text: &'a str) -> StemText<'a, &'s Analyzer> {
+        StemText::new(self, text)
+    }
+}
+
+/// Iterator returned by `Analyzer::stem_text_iter` and `stem_text_iter`.
+pub struct StemText<'a, A> {
+    analyzer: A,
+    text: &'a str,
+    folded: Cow<'a, str>,
+    pos: usize,
+    translit: Box<dyn Fn(&Token) -> bool>,
+}
+
+impl<'a, A: Deref<Target = Analyzer>> StemText<'a, A> {
+    pub(crate) fn new(analyzer: A, text: &'a str) -> Self {
+        let translit = Box::new(analyzer.translit_pass(text));
+        StemText {
+            analyzer,
+            text,
+            folded: fold_text(text),
+            pos: 0,
+            translit,
+        }
+    }
+}
+
+impl<'a, A: Deref<Target = Analyzer>> StemText<'a, A> {
+    // the next token, with its term if it is a word
+    fn next_token(&mut self) -> Option<(Token<'a>, Option<String>)> {
+        let token = self
+            .analyzer
+            .tokenizer
+            .tokenize_from(self.text, self.pos)
+            .next()?;
+        self.pos = token.end;
+        let term = match token.kind {
+            TokenKind::Word => Some(
+                self.analyzer
+                    .term_with(
+                        &token,
+                        (self.translit)(&token),
+                        Some(&self.folded[token.start..token.end]),
+                    )
+                    .unwrap_or_default(),
+            ),
+            // ordinals, see `Tokenizer::ordinals`
+            TokenKind::Number => {
+                let number = strip_numeral_tail(token.text);
+                (number.len() < token.text.len()).then(|| number.to_string())
+            }
+            _ => None,
+        };
+        Some((token, term))
+    }
+}
+
+impl<'a, A: Deref<Target = Analyzer>> Iterator for StemText<'a, A> {
+    type Item = Cow<'a, str>;
+
+    fn next(&mut self) -> Option<Cow<'a, str>> {
+        let (token, term) = self.next_token()?;
+        Some(match term {
+            Some(term) => Cow::Owned(term),
+            None => Cow::Borrowed(token.text),
+        })
+    }
+}
+
+impl<'a, A: Deref<Target = Analyzer>> FusedIterator for StemText<'a, A> {}
+
+/// A word of the text with its stem; `start..end` is the byte range of `original`.
+/// Displayed as "Книгами 0..14 → книг", with its `pos` in brackets when guessed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StemmedToken<'a> {
+    pub original: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub stem: Cow<'a, str>,
+    pub start: usize,
+    pub end: usize,
+    /// Whether the stem is not just the normalized word: a rule removed an ending, or an
+    /// ordinal lost its tail.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub stemmed: bool,
+    /// The part of speech the stemmer guessed, for words of `StemTokens::with_pos`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pos: Option<PosGuess>,
+}
+
+impl fmt::Display for StemmedToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {}..{} → {}",
+            self.original, self.start, self.end, self.stem
+        )?;
+        match self.pos {
+            Some(pos) => write!(f, " ({})", pos),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Iterator returned by `Analyzer::stem_tokens` and `stem_tokens`.
+pub struct StemTokens<'a, A> {
+    text: StemText<'a, A>,
+    pos: bool,
+}
+
+impl<'a, A: Deref<Target = Analyzer>> StemTokens<'a, A> {
+    pub(crate) fn new(analyzer: A, text: &'a str) -> Self {
+        StemTokens {
+            text: StemText::new(analyzer, text),
+            pos: false,
+        }
+    }
+
+    /// Also guesses the part of speech of every Cyrillic word, see `Stemmer::stem_with_pos`;
+    /// it stems the words a second time, with a trace of the rules.
+    pub fn with_pos(mut self) -> Self {
+        self.pos = true;
+        self
+    }
+}
+
+impl<'a, A: Deref<Target = Analyzer>> Iterator for StemTokens<'a, A> {
+    type Item = StemmedToken<'a>;
+
+    fn next(&mut self) -> Option<StemmedToken<'a>> {
+        loop {
+            if let (token, Some(stem)) = self.text.next_token()? {
+                return Some(self.text.analyzer.stemmed_token(&token, stem, self.pos));
+            }
+        }
+    }
+}
+
+impl<'a, A: Deref<Target = Analyzer>> FusedIterator for StemTokens<'a, A> {}
+
+fn is_proper_noun(token: &Token) -> bool {
+    token.kind == TokenKind::Word
+        && token.script() == Script::Cyrillic
+        && token.text.chars().next().is_some_and(char::is_uppercase)
+}
+
+#[test]
+fn ordinals_test() {
+    let analyzer = Analyzer::new().tokenizer(Tokenizer::new().ordinals(true));
+    let text = "З 1-го по 25-те, у 2004-му";
+    assert_eq!(analyzer.terms(text), ["з", "1", "по", "25", "у", "2004"]);
+    assert_eq!(analyzer.stem_text(text), "з 1 по 25, у 2004");
+    assert_eq!(Analyzer::new().stem_text("25-го"), "25-го");
+}
+
+#[test]
+fn min_word_score_test() {
+    let text = "Помилка ключа ґщзкхиа у запиті";
+    let analyzer = Analyzer::new().min_word_score(0.2);
+    assert_eq!(
+        analyzer.terms(text),
+        ["помилк", "ключ", "ґщзкхиа", "у", "запит"]
+    );
+    assert_eq!(Analyzer::new().terms(text)[2], "ґщзкхи");
+    assert_ne!(
+        analyzer.core_fingerprint(),
+        Analyzer::new().core_fingerprint()
+    );
+}
+
+#[test]
+fn code_switching_test() {
+    let text = "Відправляємо HTTP-запити до REST API через curl";
+    let terms = |analyzer: Analyzer| -> Vec<String> {
+        analyzer.analyze(text).into_iter().map(|t| t.term).collect()
+    };
+    assert_eq!(terms(Analyzer::new())[1], "http-запити");
+    assert_eq!(
+        terms(Analyzer::new().code_switching(CodeSwitching::Lowercase)),
+        [
+            "відправляєм",
+            "http-запит",
+            "до",
+            "rest",
+            "api",
+            "через",
+            "curl"
+        ]
+    );
+    let exact = Analyzer::new()
+        .code_switching(CodeSwitching::Exact)
+        .translit_repair(TranslitRepair::Document);
+    assert_eq!(
+        terms(exact.clone())[1..5],
+        ["HTTP-запит", "до", "REST", "API"]
+    );
+    assert_eq!(exact.terms("pryvit"), ["pryvit"]);
+    assert_ne!(exact.core_fingerprint(), Analyzer::new().core_fingerprint());
+}
+
+#[test]
+fn analyzer_fallback_test() {
+    let text = "Привіт СВІТ Ｔｏｋｙｏ 東京";
+    assert_eq!(
+        Analyzer::new().terms(text),
+        ["привіт", "світ", "ｔｏｋｙｏ", "東京"]
+    );
+
+    let passthrough = Analyzer::new().fallback(PassThrough);
+    assert_eq!(passthrough.terms(text)[2], "Ｔｏｋｙｏ");
+
+    let custom = Analyzer::new().fallback(|t: &str| format!("<{}>", t));
+    assert_eq!(custom.terms("слово كتاب"), ["слов", "<كتاب>"]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_stemmed_token_test() {
+    let token = Analyzer::new().stem_tokens("Народами").next().unwrap();
+    let json = serde_json::to_string(&token).unwrap();
+    assert_eq!(
+        json,
+        r#"{"original":"Народами","stem":"народ","start":0,"end":16,"stemmed":true,"pos":null}"#
+    );
+    assert_eq!(serde_json::from_str::<StemmedToken>(&json).unwrap(), token);
+    let token = Analyzer::new()
+        .stem_tokens("Народами")
+        .with_pos()
+        .next()
+        .unwrap();
+    assert!(serde_json::to_string(&token)
+        .unwrap()
+        .ends_with(r#""pos":"noun"}"#));
+    let old = r#"{"original":"хліб","stem":"хліб","start":0,"end":8}"#;
+    let old: StemmedToken = serde_json::from_str(old).unwrap();
+    assert!(!old.stemmed && old.pos.is_none());
+}
+
+#[test]
+fn stem_text_test() {
+    assert_eq!(
+        Analyzer::new().stem_text("Слідство триває, а народи  чекають :)"),
+        "слідств трив, а народ  чекают :)"
+    );
+}
+
+#[test]
+fn translit_repair_test() {
+    let text = "Pryvit z Kyieva, dyakuyu za novyny";
+    assert_eq!(Analyzer::new().terms(text)[0], "pryvit");
+
+    let repaired = Analyzer::new().translit_repair(TranslitRepair::Document);
+    assert_eq!(
+        repaired.terms(text),
+        Analyzer::new().terms("Привіт з Києва, дякую за новини")
+    );
+
+    let tokens = Analyzer::new().translit_repair(TranslitRepair::Token);
+    assert_eq!(tokens.terms("download pryvit")[0], "download");
+}
+
+#[test]
+fn bidi_terms_test() {
+    let text = "\u{202B}שלום\u{202C} при\u{200E}віт";
+    let terms = Analyzer::new().analyze(text);
+    assert_eq!(terms[1].term, "привіт");
+    assert_eq!(
+        &text[terms[1].token.start..terms[1].token.end],
+        "при\u{200E}віт"
+    );
+    assert_eq!(
+        Analyzer::new().stem_text(text),
+        "\u{202B}שלום\u{202C} привіт"
+    );
+}
+
+#[test]
+fn nfc_fallback_test() {
+    // "e" + combining acute
+    assert_eq!(Nfc.normalize("cafe\u{301}"), "café");
+    assert_eq!(LowercaseNfc.normalize("CAFE\u{301}"), "café");
+}
+
+#[test]
+fn phrases_test() {
+    let analyzer = Analyzer::new()
+        .phrase("Верховна Рада")
+        .phrase("Рада національної безпеки і оборони")
+        .stopwords(crate::stopwords::default_words());
+    let text = "Рішення Верховної Ради і Ради національної безпеки і оборони, рада";
+    let terms = analyzer.analyze(text);
+    let terms: Vec<&str> = terms.iter().map(|t| t.term.as_str()).collect();
+    assert_eq!(terms[1], "верховн_рад");
+    assert_eq!(terms[2], "рад_національн_безпек_і_оборон");
+    assert_eq!(terms.len(), 4);
+
+    let joined = &analyzer.analyze(text)[1].token;
+    assert_eq!(&text[joined.start..joined.end], "Верховної Ради");
+}
+
+#[test]
+fn proper_nouns_test() {
+    let analyzer = Analyzer::new().proper_nouns(true);
+    assert_eq!(
+        analyzer.terms("Книги Шевченка. Книги шевченка"),
+        ["книг", "шевченка", "книг", "шевченк"]
+    );
+}
+
+#[test]
+fn abbreviations_test() {
+    let analyzer = Analyzer::new()
+        .abbreviation("ст.", "стаття")
+        .abbreviation("ЦКУ", "Цивільний кодекс України")
+        .proper_nouns(true);
+    let text = "ст. 5 ЦКУ. Стаття Ст";
+    let terms = analyzer.analyze(text);
+    let words: Vec<&str> = terms.iter().map(|t| t.term.as_str()).collect();
+    assert_eq!(
+        words,
+        ["статт", "5", "цивільн", "кодекс", "україн", "статт", "ст"]
+    );
+    assert_eq!(terms[0].token.text, "ст.");
+    assert_eq!(terms[3].token.text, "ЦКУ");
+}
+
+#[test]
+fn term_mapped_test() {
+    let analyzer = Analyzer::new().translit_repair(TranslitRepair::Token);
+    let text = "Дякую, shchastia!";
+    let tokens: Vec<Token> = analyzer.get_tokenizer().tokenize(text).collect();
+    let mapped = analyzer.term_mapped(&tokens[3]).unwrap();
+    assert_eq!(mapped.stem, analyzer.term(&tokens[3]).unwrap());
+    assert_eq!(
+        &text[mapped.map_offset(0)..mapped.map_offset(0) + 4],
+        "shch"
+    );
+    assert_eq!(mapped.origins()[0], tokens[3].start);
+}
+
+#[test]
+fn word_at_test() {
+    let analyzer = Analyzer::new();
+    let text = "Нові книги, книгами.";
+    let word = analyzer.word_at(text, 10).unwrap();
+    assert_eq!((word.start, word.end), (9, 19));
+    assert_eq!((word.original, word.stem.as_ref()), ("книги", "книг"));
+    assert!(word.stemmed);
+    // right after the word, and inside its last character
+    assert_eq!(analyzer.word_at(text, 19).unwrap().original, "книги");
+    assert_eq!(analyzer.word_at(text, 18).unwrap().original, "книги");
+    assert_eq!(analyzer.word_at(text, 21).unwrap().stem, "книг");
+    assert_eq!(analyzer.word_at(text, 0).unwrap().original, "Нові");
+    assert_eq!(analyzer.word_at(text, 20), None);
+    assert_eq!(analyzer.word_at(text, text.len()), None);
+    assert_eq!(analyzer.word_at(text, 1000), None);
+    assert_eq!(analyzer.word_at("3,5 грн", 1), None);
+}
+
+#[test]
+fn find_inflections_test() {
+    let analyzer = Analyzer::new();
+    let text = "Книга лежить. Немає книги, а з книгами — книжка.";
+    let found: Vec<&str> = analyzer
+        .find_inflections(text, "книгою")
+        .into_iter()
+        .map(|range| &text[range])
+        .collect();
+    assert_eq!(found, ["Книга", "книги", "книгами"]);
+    assert!(analyzer.find_inflections(text, "123").is_empty());
+    assert!(analyzer.find_inflections(text, "стіл").is_empty());
+}
+
+#[test]
+fn stem_text_iter_test() {
+    let analyzer = Analyzer::new();
+    let text = "Слідство триває, 3,5 грн :)";
+    let pieces: Vec<Cow<str>> = analyzer.stem_text_iter(text).collect();
+    assert_eq!(
+        pieces,
+        [
+            "слідств",
+            " ",
+            "трив",
+            ",",
+            " ",
+            "3,5",
+            " ",
+            "грн",
+            " ",
+            ":)"
+        ]
+    );
+    assert!(matches!(pieces[1], Cow::Borrowed(" ")));
+    assert_eq!(pieces.concat(), analyzer.stem_text(text));
+}
+
+#[test]
+fn stem_tokens_test() {
+    let analyzer = Analyzer::new();
+    let text = "Нові книги — 3 грн.";
+    let tokens: Vec<StemmedToken> = analyzer.stem_tokens(text).collect();
+    let words: Vec<(&str, &str)> = tokens
+        .iter()
+        .map(|t| (t.original, t.stem.as_ref()))
+        .collect();
+    assert_eq!(words, [("Нові", "нов"), ("книги", "книг"), ("грн", "грн")]);
+    for token in &tokens {
+        assert_eq!(&text[token.start..token.end], token.original);
+    }
+    assert_eq!(
+        tokens.iter().map(|t| t.stemmed).collect::<Vec<_>>(),
+        [true, true, false]
+    );
+    assert_eq!(tokens[1].to_string(), "книги 9..19 → книг");
+    let tagged: Vec<StemmedToken> = analyzer.stem_tokens(text).with_pos().collect();
+    assert_eq!(tagged[1].pos, Some(PosGuess::Noun));
+    assert_eq!(tagged[1].to_string(), "книги 9..19 → книг (noun)");
+    assert_eq!(tagged[2].stem, tokens[2].stem);
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::tokenizer::{tokenize, TokenKind};
+use crate::Stemmer;
+
+/// A masked occurrence of a name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Replacement {
+    /// Byte range of the occurrence in the input.
+    pub original: Range<usize>,
+    /// Byte range of the placeholder in the masked text.
+    pub masked: Range<usize>,
+    /// Index of the name in the list given to `anonymize`.
+    pub name: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Anonymized {
+    pub text: String,
+    pub replacements: Vec<Replacement>,
+}
+
+/// Masks every inflected occurrence of the given names, surnames and patronymics.
+///
+/// Each entry of `names` may hold several words ("Тарас Григорович Шевченко"); any of them
+/// matches on its own, so "Шевченкові" and "Тараса" are found as well. Only capitalized
+/// words match, so a name like "Віра" leaves the common noun "віра" alone. Occurrences
+/// are replaced with `[PERSON_<n>]`, where `n` counts the entries of `names` from one.
+pub fn anonymize<S: AsRef<str>>(text: &str, names: &[S]) -> Anonymized {
+    anonymize_with(text, names, |name| format!("[PERSON_{}]", name + 1))
+}
+
+/// Like `anonymize`, with the placeholder for the name at each index made by `placeholder`.
+pub fn anonymize_with<S, F>(text: &str, names: &[S], placeholder: F) -> Anonymized
+where
+    S: AsRef<str>,
+    F: Fn(usize) -> String,
+{
+    let stemmer = Stemmer::new();
+    let mut stems = HashMap::new();
+    for (i, name) in names.iter().enumerate() {
+        for word in tokenize(name.as_ref()).filter(|t| t.kind == TokenKind::Word) {
+            stems.entry(stemmer.stem(word.text)).or_insert(i);
+        }
+    }
+
+    let mut res = Anonymized {
+        text: String::with_capacity(text.len()),
+        replacements: Vec::new(),
+    };
+    let mut last = 0;
+    for token in tokenize(text) {
+        if token.kind != TokenKind::Word || !token.text.starts_with(char::is_uppercase) {
+            continue;
+        }
+        if let Some(&name) = stems.get(&stemmer.stem(token.text)) {
+            res.text.push_str(&text[last..token.start]);
+            let start = res.text.len();
+            res.text.push_str(&placeholder(name));
+            res.replacements.push(Replacement {
+                original: token.start..token.end,
+                masked: start..res.text.len(),
+                name,
+            });
+            last = token.end;
+        }
+    }
+    res.text.push_str(&text[last..]);
+    res
+}
+
+#[test]
+fn anonymize_test() {
+    let text = "Листа Шевченкові передав Тарасів друг. Шевченка чекали; віра не згасала, Віра теж.";
+    let res = anonymize(text, &["Тарас Шевченко", "Віра"]);
+    assert_eq!(
+        res.text,
+        "Листа [PERSON_1] передав [PERSON_1] друг. [PERSON_1] чекали; віра не згасала, [PERSON_2] теж."
+    );
+    assert_eq!(res.replacements.len(), 4);
+    for r in &res.replacements {
+        assert!(text[r.original.clone()].starts_with(char::is_uppercase));
+        assert!(res.text[r.masked.clone()].starts_with("[PERSON_"));
+    }
+
+    let custom = anonymize_with("Григоровича бачили", &["Григорович"], |_| "***".into());
+    assert_eq!(custom.text, "*** бачили");
+}
@@ -0,0 +1,42 @@
+//! Stemming Arrow string columns in one call, e.g. the text column of a dataframe.
+//!
+//! Polars keeps its columns in its own Arrow implementation: export them to this one
+//! through the Arrow C data interface (`arrow_array::ffi`, or `Series.to_arrow()` from
+//! Python) to stem them here.
+
+use arrow_array::builder::GenericStringBuilder;
+use arrow_array::{Array, GenericStringArray, OffsetSizeTrait};
+
+use crate::Stemmer;
+
+/// The stems of the values of `words`, a `StringArray` or `LargeStringArray`, nulls
+/// staying null. The output buffers are allocated once, sized after `words`; columns
+/// repeating words stem faster with a `StemmerBuilder::cache`.
+pub fn stem_array<O: OffsetSizeTrait>(
+    stemmer: &Stemmer,
+    words: &GenericStringArray<O>,
+) -> GenericStringArray<O> {
+    let mut stems = GenericStringBuilder::<O>::with_capacity(words.len(), words.values().len());
+    for word in words.iter() {
+        match word {
+            Some(word) => stems.append_value(stemmer.stem(word)),
+            None => stems.append_null(),
+        }
+    }
+    stems.finish()
+}
+
+#[test]
+fn stem_array_test() {
+    use arrow_array::{LargeStringArray, StringArray};
+
+    let words = StringArray::from(vec![Some("Народами"), None, Some("книгами"), Some("")]);
+    let stems = stem_array(&Stemmer::builder().cache(16).build(), &words);
+    assert_eq!(stems.len(), 4);
+    assert!(stems.is_null(1));
+    let values: Vec<Option<&str>> = stems.iter().collect();
+    assert_eq!(values, [Some("народ"), None, Some("книг"), Some("")]);
+
+    let words = LargeStringArray::from(vec!["автора"]);
+    assert_eq!(stem_array(&Stemmer::new(), &words).value(0), "автор");
+}
@@ -0,0 +1,257 @@
+//! Checks of a vocabulary before indexing, such as the names of a product catalog.
+//!
+//! `confusables` finds words typed with lookalikes from another script, "вiкно" with a
+//! Latin "i", that the stemmer keys apart from their clean spelling, so a search for
+//! "вікно" misses them.
+//!
+//! `sample_unusual` picks words the stemmer treats suspiciously, for bug reports that
+//! show the problem without sharing the documents it came from.
+
+use std::collections::{BTreeSet, HashSet};
+use std::io::{self, Write};
+
+use crate::homoglyph::{self, HomoglyphRepair};
+use crate::tokenizer::{script, Script};
+use crate::{StemOutcome, Stemmer};
+
+// stems of at most this many chars are suspicious for words with longer endings
+const SHORT_STEM_CHARS: usize = 2;
+// words at least this long usually have an ending the rules should find
+const LONG_WORD_CHARS: usize = 7;
+
+/// A word that does not stem like its clean spelling.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Confusable {
+    pub word: String,
+    /// The word with its lookalikes replaced by Cyrillic letters, `None` when some of its
+    /// letters have no Cyrillic lookalike and it must be fixed by hand.
+    pub suggestion: Option<String>,
+    pub stem: String,
+    /// The stem of the suggestion, empty without one.
+    pub clean_stem: String,
+}
+
+/// The words of `words` that mix scripts or lean on lookalike digits, each once, in the
+/// order they first occur. Mixed words that stem like their repaired spelling anyway are
+/// left out, and so are words without Cyrillic letters, which are taken to be foreign.
+pub fn confusables<'w, I>(words: I) -> Vec<Confusable>
+where
+    I: IntoIterator<Item = &'w str>,
+{
+    let stemmer = Stemmer::new();
+    let mut seen = HashSet::new();
+    let mut found = Vec::new();
+    for word in words {
+        if !seen.insert(word) {
+            continue;
+        }
+        let repaired = homoglyph::repair(word, HomoglyphRepair::Aggressive);
+        let mixed = script(word) == Script::Mixed;
+        if !mixed && repaired == word {
+            continue;
+        }
+        let stem = stemmer.stem(word);
+        let confusable = if script(&repaired) == Script::Cyrillic {
+            let clean_stem = stemmer.stem(&repaired);
+            if clean_stem == stem {
+                continue;
+            }
+            Confusable {
+                word: word.to_string(),
+                suggestion: Some(repaired.into_owned()),
+                stem,
+                clean_stem,
+            }
+        } else if repaired
+            .chars()
+            .any(|c| ('\u{0400}'..='\u{04FF}').contains(&c))
+        {
+            Confusable {
+                word: word.to_string(),
+                suggestion: None,
+                stem,
+                clean_stem: String::new(),
+            }
+        } else {
+            continue;
+        };
+        found.push(confusable);
+    }
+    found
+}
+
+/// Writes the words and their suggested spellings as tab-separated lines, words without a
+/// suggestion commented out.
+pub fn write_report<W: Write>(mut out: W, confusables: &[Confusable]) -> io::Result<()> {
+    writeln!(out, "# word\tsuggestion")?;
+    for c in confusables {
+        match &c.suggestion {
+            Some(suggestion) => writeln!(out, "{}\t{}", c.word, suggestion)?,
+            None => writeln!(out, "# {}\tno Cyrillic spelling", c.word)?,
+        }
+    }
+    Ok(())
+}
+
+/// Why `unusual` flags a word.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Oddity {
+    /// The rules removed at least three chars and left a stem of one or two.
+    ShortStem,
+    /// A word of seven or more chars that no rule changed.
+    NoRuleFired,
+}
+
+impl Oddity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Oddity::ShortStem => "short-stem",
+            Oddity::NoRuleFired => "no-rule-fired",
+        }
+    }
+}
+
+/// A word the stemmer handles suspiciously, see `unusual`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnusualWord {
+    /// The normalized word.
+    pub word: String,
+    pub stem: String,
+    pub oddity: Oddity,
+}
+
+/// Whether `word` gets a very short stem or, though long, no rule applies to it. Words
+/// without Cyrillic letters, listed words and words without a vowel are never unusual.
+pub fn unusual(stemmer: &Stemmer, word: &str) -> Option<UnusualWord> {
+    if script(word) != Script::Cyrillic {
+        return None;
+    }
+    let trace = stemmer.stem_explained(word);
+    let stem = match &trace.outcome {
+        StemOutcome::Stemmed(stem) => stem,
+        _ => return None,
+    };
+    let (word_chars, stem_chars) = (trace.normalized.chars().count(), stem.chars().count());
+    let oddity = if stem_chars <= SHORT_STEM_CHARS && word_chars >= stem_chars + 3 {
+        Oddity::ShortStem
+    } else if trace.steps.is_empty() && word_chars >= LONG_WORD_CHARS {
+        Oddity::NoRuleFired
+    } else {
+        return None;
+    };
+    Some(UnusualWord {
+        stem: stem.clone(),
+        word: trace.normalized,
+        oddity,
+    })
+}
+
+/// Up to `count` of the distinct `unusual` words of `words`, shuffled. The same words and
+/// `seed` always give the same sample, whatever the order of `words`.
+pub fn sample_unusual<'w, I>(
+    stemmer: &Stemmer,
+    words: I,
+    count: usize,
+    seed: u64,
+) -> Vec<UnusualWord>
+where
+    I: IntoIterator<Item = &'w str>,
+{
+    let normalized: BTreeSet<String> = words.into_iter().map(|w| stemmer.normalize(w)).collect();
+    let mut found: Vec<UnusualWord> = normalized
+        .iter()
+        .filter_map(|word| unusual(stemmer, word))
+        .collect();
+    // xorshift, whose state must not be 0
+    let mut state = seed ^ 0x2545f4914f6cdd1d;
+    for i in (1..found.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        found.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+    found.truncate(count);
+    found
+}
+
+/// Writes the words as tab-separated lines of the word, its stem and the oddity's name.
+pub fn write_unusual<W: Write>(mut out: W, words: &[UnusualWord]) -> io::Result<()> {
+    writeln!(out, "# word\tstem\toddity")?;
+    for w in words {
+        writeln!(out, "{}\t{}\t{}", w.word, w.stem, w.oddity.name())?;
+    }
+    Ok(())
+}
+
+#[test]
+fn confusables_test() {
+    // Latin "i", "e" and "B", a zero, and Latin "z"
+    let words = [
+        "вiкно",
+        "вікно",
+        "телефон",
+        "тeлефон",
+        "Bікна",
+        "с0бака",
+        "iPhone",
+        "вiкно",
+        "zміна",
+    ];
+    let found = confusables(words.iter().copied());
+    let suggestions: Vec<(&str, Option<&str>)> = found
+        .iter()
+        .map(|c| (c.word.as_str(), c.suggestion.as_deref()))
+        .collect();
+    assert_eq!(
+        suggestions,
+        [
+            ("вiкно", Some("вікно")),
+            ("тeлефон", Some("телефон")),
+            ("Bікна", Some("Вікна")),
+            ("с0бака", Some("собака")),
+            ("zміна", None)
+        ]
+    );
+    assert_eq!(found[0].clean_stem, "вікн");
+    assert_ne!(found[0].stem, found[0].clean_stem);
+
+    let mut report = Vec::new();
+    write_report(&mut report, &found[3..]).unwrap();
+    assert_eq!(
+        String::from_utf8(report).unwrap(),
+        "# word\tsuggestion\nс0бака\tсобака\n# zміна\tno Cyrillic spelling\n"
+    );
+}
+
+#[test]
+fn sample_unusual_test() {
+    let stemmer = Stemmer::new();
+    let oddity = |word| unusual(&stemmer, word).map(|u| u.oddity);
+    assert_eq!(oddity("книгами"), None);
+    assert_eq!(oddity("ямами"), Some(Oddity::ShortStem));
+    assert_eq!(oddity("інтерфейс"), Some(Oddity::NoRuleFired));
+    assert_eq!(oddity("грн"), None);
+    assert_eq!(oddity("computer"), None);
+
+    let words = [
+        "Ямами",
+        "ямами",
+        "книгами",
+        "інтерфейс",
+        "контингент",
+        "сад",
+    ];
+    let sample = sample_unusual(&stemmer, words.iter().copied(), 10, 7);
+    assert_eq!(sample.len(), 3);
+    assert_eq!(
+        sample,
+        sample_unusual(&stemmer, words.iter().rev().copied(), 10, 7)
+    );
+    assert_eq!(
+        sample_unusual(&stemmer, words.iter().copied(), 2, 7),
+        sample[..2]
+    );
+    let mut tsv = Vec::new();
+    write_unusual(&mut tsv, &sample[..0]).unwrap();
+    assert_eq!(tsv, b"# word\tstem\toddity\n");
+}
@@ -0,0 +1,603 @@
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::BuildHasher;
+use std::mem::size_of;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::fingerprint::Fingerprint;
+use crate::memory::HeapSize;
+
+// bookkeeping per entry besides the strings themselves: the map slot and the recency index
+const ENTRY_OVERHEAD: usize = 2 * size_of::<String>() + 2 * size_of::<u64>() + size_of::<Entry>();
+
+struct Entry {
+    stem: String,
+    used: u64,
+    // with a time to live
+    expires: Option<Instant>,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+    bytes: usize,
+    hits: u64,
+    normalized_hits: u64,
+    misses: u64,
+    evictions: u64,
+    expired: u64,
+}
+
+// A panic while the lock was held, e.g. in a caller's `stem` closure, leaves the counters
+// at worst slightly off, so the cache keeps working rather than panicking in every later
+// call.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Inner {
+    fn cost(word: &str, stem: &str) -> usize {
+        2 * word.len() + stem.len() + ENTRY_OVERHEAD
+    }
+
+    fn evict_to(&mut self, max_bytes: usize, max_entries: usize) {
+        while self.bytes > max_bytes || self.entries.len() > max_entries {
+            let (used, word) = match self.recency.iter().next() {
+                Some((used, word)) => (*used, word.clone()),
+                None => break,
+            };
+            self.recency.remove(&used);
+            if let Some(entry) = self.entries.remove(&word) {
+                self.bytes -= Inner::cost(&word, &entry.stem);
+                self.evictions += 1;
+            }
+        }
+    }
+
+    // `None` for entries expired at `now` as well, which are removed
+    fn get(&mut self, word: &str, now: Option<Instant>) -> Option<String> {
+        self.tick += 1;
+        let tick = self.tick;
+        let expired = match (self.entries.get(word), now) {
+            (None, _) => {
+                self.misses += 1;
+                return None;
+            }
+            (Some(entry), Some(now)) => entry.expires.is_some_and(|expires| expires <= now),
+            (Some(_), None) => false,
+        };
+        if expired {
+            self.remove(word);
+            self.expired += 1;
+            self.misses += 1;
+            return None;
+        }
+        let entry = self.entries.get_mut(word)?;
+        let used = std::mem::replace(&mut entry.used, tick);
+        let stem = entry.stem.clone();
+        self.hits += 1;
+        if let Some(key) = self.recency.remove(&used) {
+            self.recency.insert(tick, key);
+        }
+        Some(stem)
+    }
+
+    fn insert(&mut self, word: &str, stem: &str, expires: Option<Instant>, bounds: (usize, usize)) {
+        self.tick += 1;
+        let tick = self.tick;
+        let entry = Entry {
+            stem: stem.to_string(),
+            used: tick,
+            expires,
+        };
+        if let Some(old) = self.entries.insert(word.to_string(), entry) {
+            self.recency.remove(&old.used);
+            self.bytes -= Inner::cost(word, &old.stem);
+        }
+        self.recency.insert(tick, word.to_string());
+        self.bytes += Inner::cost(word, stem);
+        self.evict_to(bounds.0, bounds.1);
+    }
+
+    fn remove(&mut self, word: &str) {
+        if let Some(entry) = self.entries.remove(word) {
+            self.recency.remove(&entry.used);
+            self.bytes -= Inner::cost(word, &entry.stem);
+        }
+    }
+
+    fn remove_expired(&mut self, now: Instant) {
+        let expired: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.expires.is_some_and(|expires| expires <= now))
+            .map(|(word, _)| word.clone())
+            .collect();
+        for word in &expired {
+            self.remove(word);
+        }
+        self.expired += expired.len() as u64;
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+        self.bytes = 0;
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.entries.len(),
+            bytes: self.bytes,
+            hits: self.hits,
+            normalized_hits: self.normalized_hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            expired: self.expired,
+        }
+    }
+}
+
+/// Counters of a `StemCache` or a `ShardedCache`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub bytes: usize,
+    /// Lookups answered from the cache.
+    pub hits: u64,
+    /// The part of `hits` whose input only matched after normalization, e.g. "КИЄВА"
+    /// served from the entry of "києва".
+    pub normalized_hits: u64,
+    /// Lookups that ran the full stemming pipeline.
+    pub misses: u64,
+    pub evictions: u64,
+    /// Entries removed for outliving the time to live of a `ShardedCache`; their lookups
+    /// count as `misses` too.
+    pub expired: u64,
+}
+
+impl CacheStats {
+    fn add(mut self, other: CacheStats) -> CacheStats {
+        self.entries += other.entries;
+        self.bytes += other.bytes;
+        self.hits += other.hits;
+        self.normalized_hits += other.normalized_hits;
+        self.misses += other.misses;
+        self.evictions += other.evictions;
+        self.expired += other.expired;
+        self
+    }
+}
+
+/// A word → stem cache bounded by an estimate of the bytes it holds, and optionally by
+/// the number of entries; the least recently used entries are evicted first. The cache can
+/// be shared between threads.
+pub struct StemCache {
+    max_bytes: Mutex<usize>,
+    max_entries: usize,
+    inner: Mutex<Inner>,
+}
+
+impl StemCache {
+    pub fn new(max_bytes: usize) -> Self {
+        StemCache {
+            max_bytes: Mutex::new(max_bytes),
+            max_entries: usize::MAX,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    /// A cache holding at most `max_entries` words, however long they are.
+    pub fn with_max_entries(max_entries: usize) -> Self {
+        StemCache {
+            max_entries,
+            ..StemCache::new(usize::MAX)
+        }
+    }
+
+    fn inner(&self) -> MutexGuard<'_, Inner> {
+        lock(&self.inner)
+    }
+
+    pub fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        *lock(&self.max_bytes)
+    }
+
+    /// Changes the cap, evicting entries right away if the cache is now over it.
+    pub fn set_max_bytes(&self, max_bytes: usize) {
+        *lock(&self.max_bytes) = max_bytes;
+        self.inner().evict_to(max_bytes, self.max_entries);
+    }
+
+    pub fn get(&self, word: &str) -> Option<String> {
+        self.inner().get(word, None)
+    }
+
+    /// Entries larger than the whole cap are not stored.
+    pub fn insert(&self, word: &str, stem: &str) {
+        let max_bytes = self.max_bytes();
+        let cost = Inner::cost(word, stem);
+        if cost > max_bytes || self.max_entries == 0 {
+            return;
+        }
+        self.inner()
+            .insert(word, stem, None, (max_bytes, self.max_entries));
+    }
+
+    pub fn get_or_insert_with<F: FnOnce() -> String>(&self, word: &str, stem: F) -> String {
+        if let Some(cached) = self.get(word) {
+            return cached;
+        }
+        let stem = stem();
+        self.insert(word, &stem);
+        stem
+    }
+
+    /// Like `get_or_insert_with` keyed on `key`, the normalized form of `word`, counting
+    /// hits that needed the normalization in `CacheStats::normalized_hits`.
+    pub fn get_or_insert_normalized<F: FnOnce() -> String>(
+        &self,
+        word: &str,
+        key: &str,
+        stem: F,
+    ) -> String {
+        if let Some(cached) = self.get(key) {
+            if word != key {
+                self.inner().normalized_hits += 1;
+            }
+            return cached;
+        }
+        let stem = stem();
+        self.insert(key, &stem);
+        stem
+    }
+
+    pub fn clear(&self) {
+        self.inner().clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.inner().stats()
+    }
+}
+
+impl fmt::Debug for StemCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StemCache")
+            .field("max_bytes", &self.max_bytes())
+            .field("max_entries", &self.max_entries)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl HeapSize for StemCache {
+    fn heap_bytes(&self) -> usize {
+        self.inner().bytes
+    }
+}
+
+/// A word → stem cache for stemmers shared by many threads, such as the workers of a
+/// query service: the words are spread over shards with a lock each, so that lookups of
+/// different words rarely wait for each other, where a `StemCache` takes one lock for all.
+/// The bounds hold for the whole cache, each shard evicting its least recently used
+/// entries once over its share of them; with a time to live, entries older than it count
+/// as missing.
+///
+/// `StemmerBuilder::sharded_cache` lets stemmers, and the clones of each, share one cache.
+/// Without a time to live the cache never reads the clock, which panics on
+/// `wasm32-unknown-unknown`.
+pub struct ShardedCache {
+    shards: Box<[Mutex<Inner>]>,
+    hasher: RandomState,
+    max_bytes: usize,
+    max_entries: usize,
+    ttl: Option<Duration>,
+    // the stemmer configuration whose stems the cache holds, see `bind`
+    fingerprint: Mutex<Option<Fingerprint>>,
+}
+
+impl ShardedCache {
+    /// A cache of about `max_bytes`, like `StemCache::new`, with four shards per core.
+    pub fn new(max_bytes: usize) -> Self {
+        let cores = thread::available_parallelism().map_or(1, |n| n.get());
+        ShardedCache {
+            shards: Box::new([]),
+            hasher: RandomState::new(),
+            max_bytes,
+            max_entries: usize::MAX,
+            ttl: None,
+            fingerprint: Mutex::new(None),
+        }
+        .shards(4 * cores)
+    }
+
+    /// A cache holding at most `max_entries` words, however long they are.
+    pub fn with_max_entries(max_entries: usize) -> Self {
+        let cache = ShardedCache::new(usize::MAX);
+        let shards = cache.shards.len();
+        ShardedCache {
+            max_entries,
+            ..cache
+        }
+        .shards(shards)
+    }
+
+    /// Splits the cache into `shards` shards, at least one and at most one per entry the
+    /// bounds allow; a shard takes about 100 bytes while empty.
+    pub fn shards(mut self, shards: usize) -> Self {
+        let shards = shards.min(self.max_entries).max(1);
+        self.shards = (0..shards).map(|_| Mutex::default()).collect();
+        self
+    }
+
+    /// Lets entries be used for `ttl` after they were stored, so that the stems of a
+    /// stemmer whose resources are reloaded in place are eventually refreshed; expired
+    /// entries are removed when looked up or by `remove_expired`.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// An empty cache with the bounds and time to live of this one.
+    pub fn empty_like(&self) -> Self {
+        ShardedCache {
+            hasher: RandomState::new(),
+            max_bytes: self.max_bytes,
+            max_entries: self.max_entries,
+            ttl: self.ttl,
+            shards: Box::new([]),
+            fingerprint: Mutex::new(None),
+        }
+        .shards(self.shards.len())
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    pub fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    pub fn time_to_live(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    // the bounds of a shard, their shares of the bounds of the cache
+    fn shard_bounds(&self) -> (usize, usize) {
+        let shards = self.shards.len();
+        (self.max_bytes / shards, self.max_entries / shards)
+    }
+
+    fn shard(&self, word: &str) -> MutexGuard<'_, Inner> {
+        let hash = self.hasher.hash_one(word);
+        lock(&self.shards[hash as usize % self.shards.len()])
+    }
+
+    fn now(&self) -> Option<Instant> {
+        self.ttl.map(|_| Instant::now())
+    }
+
+    pub fn get(&self, word: &str) -> Option<String> {
+        self.get_at(word, self.now())
+    }
+
+    // `get` at the time `now`, which is only read with a time to live
+    fn get_at(&self, word: &str, now: Option<Instant>) -> Option<String> {
+        self.shard(word).get(word, now)
+    }
+
+    /// Entries larger than the share of the cap of a shard are not stored.
+    pub fn insert(&self, word: &str, stem: &str) {
+        self.insert_at(word, stem, self.now());
+    }
+
+    fn insert_at(&self, word: &str, stem: &str, now: Option<Instant>) {
+        let bounds = self.shard_bounds();
+        if Inner::cost(word, stem) > bounds.0 || bounds.1 == 0 {
+            return;
+        }
+        let expires = self.ttl.zip(now).map(|(ttl, now)| now + ttl);
+        self.shard(word).insert(word, stem, expires, bounds);
+    }
+
+    /// Looks `word` up and stores the result of `stem` if it is missing, without holding
+    /// the lock of its shard while `stem` runs: threads missing the same word at once
+    /// both stem it.
+    pub fn get_or_insert_with<F: FnOnce() -> String>(&self, word: &str, stem: F) -> String {
+        if let Some(cached) = self.get(word) {
+            return cached;
+        }
+        let stem = stem();
+        self.insert(word, &stem);
+        stem
+    }
+
+    /// Like `get_or_insert_with` keyed on `key`, the normalized form of `word`, as
+    /// `StemCache::get_or_insert_normalized`.
+    pub fn get_or_insert_normalized<F: FnOnce() -> String>(
+        &self,
+        word: &str,
+        key: &str,
+        stem: F,
+    ) -> String {
+        if let Some(cached) = self.get(key) {
+            if word != key {
+                self.shard(key).normalized_hits += 1;
+            }
+            return cached;
+        }
+        let stem = stem();
+        self.insert(key, &stem);
+        stem
+    }
+
+    /// Removes the entries past their time to live, e.g. from a maintenance thread, as
+    /// those never looked up again otherwise stay until evicted.
+    pub fn remove_expired(&self) {
+        if let Some(now) = self.now() {
+            self.remove_expired_at(now);
+        }
+    }
+
+    fn remove_expired_at(&self, now: Instant) {
+        for shard in self.shards.iter() {
+            lock(shard).remove_expired(now);
+        }
+    }
+
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            lock(shard).clear();
+        }
+    }
+
+    /// The counters of all the shards, added up.
+    pub fn stats(&self) -> CacheStats {
+        self.shards
+            .iter()
+            .fold(CacheStats::default(), |stats, shard| {
+                stats.add(lock(shard).stats())
+            })
+    }
+
+    // Ties the cache to the stems of one configuration: true if it is unused or already
+    // holds the stems of `fingerprint`.
+    pub(crate) fn bind(&self, fingerprint: Fingerprint) -> bool {
+        *lock(&self.fingerprint).get_or_insert(fingerprint) == fingerprint
+    }
+}
+
+impl fmt::Debug for ShardedCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ShardedCache")
+            .field("shards", &self.shards.len())
+            .field("max_bytes", &self.max_bytes)
+            .field("max_entries", &self.max_entries)
+            .field("ttl", &self.ttl)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl HeapSize for ShardedCache {
+    fn heap_bytes(&self) -> usize {
+        self.shards.iter().map(|shard| lock(shard).bytes).sum()
+    }
+}
+
+#[test]
+fn stem_cache_test() {
+    let cost = Inner::cost("народи", "народ");
+    let cache = StemCache::new(2 * cost);
+    assert_eq!(
+        cache.get_or_insert_with("народи", || "народ".into()),
+        "народ"
+    );
+    assert_eq!(
+        cache.get_or_insert_with("народи", || unreachable!()),
+        "народ"
+    );
+    cache.insert("автори", "автор");
+    // "народи" was used last, so "автори" goes
+    assert!(cache.get("народи").is_some());
+    cache.insert("слова", "слов");
+    assert!(cache.get("автори").is_none());
+
+    let stats = cache.stats();
+    assert_eq!((stats.entries, stats.hits, stats.evictions), (2, 2, 1));
+    assert!(stats.bytes <= cache.max_bytes());
+
+    cache.set_max_bytes(cost);
+    assert_eq!(cache.stats().entries, 1);
+    assert!(cache.get("слова").is_some());
+}
+
+#[test]
+fn max_entries_test() {
+    let cache = StemCache::with_max_entries(2);
+    for (word, stem) in [("народи", "народ"), ("автори", "автор"), ("слова", "слов")].iter()
+    {
+        cache.insert(word, stem);
+    }
+    assert_eq!(cache.stats().entries, 2);
+    assert!(cache.get("народи").is_none());
+    assert!(cache.get("слова").is_some());
+
+    let disabled = StemCache::with_max_entries(0);
+    disabled.insert("народи", "народ");
+    assert_eq!(disabled.stats().entries, 0);
+}
+
+#[test]
+fn sharded_cache_test() {
+    let cache = ShardedCache::with_max_entries(64).shards(4);
+    assert_eq!(cache.shard_count(), 4);
+    assert_eq!(
+        cache.get_or_insert_with("народи", || "народ".into()),
+        "народ"
+    );
+    assert_eq!(
+        cache.get_or_insert_with("народи", || unreachable!()),
+        "народ"
+    );
+    let stats = cache.stats();
+    assert_eq!((stats.entries, stats.hits, stats.misses), (1, 1, 1));
+
+    // the bound holds for the whole cache, and for many threads at once
+    thread::scope(|scope| {
+        for t in 0..4 {
+            let cache = &cache;
+            scope.spawn(move || {
+                for i in 0..100 {
+                    let word = format!("слово{}", (t * 100 + i) % 150);
+                    cache.get_or_insert_with(&word, || word.clone());
+                }
+            });
+        }
+    });
+    let stats = cache.stats();
+    assert!(stats.entries <= 64, "{} entries", stats.entries);
+    assert_eq!(stats.hits + stats.misses, 402);
+    assert!(stats.evictions > 0);
+    assert_eq!(cache.heap_bytes(), stats.bytes);
+    cache.clear();
+    assert_eq!(cache.stats().entries, 0);
+
+    // fewer entries than shards make fewer shards
+    let small = ShardedCache::with_max_entries(2).shards(16);
+    assert_eq!(small.shard_count(), 2);
+    let disabled = ShardedCache::with_max_entries(0);
+    disabled.insert("народи", "народ");
+    assert_eq!(disabled.stats().entries, 0);
+}
+
+#[test]
+fn sharded_cache_ttl_test() {
+    let cache = ShardedCache::new(1 << 20).ttl(Duration::from_secs(20));
+    let start = Instant::now();
+    let later = start + Duration::from_secs(30);
+    cache.insert_at("народи", "народ", Some(start));
+    cache.insert_at("слова", "слов", Some(start));
+    assert!(cache.get_at("народи", Some(start)).is_some());
+    assert!(cache.get_at("народи", Some(later)).is_none());
+    assert_eq!(cache.stats().entries, 1);
+    cache.remove_expired_at(later);
+    let stats = cache.stats();
+    assert_eq!((stats.entries, stats.expired, stats.bytes), (0, 2, 0));
+    assert_eq!(cache.empty_like().time_to_live(), cache.time_to_live());
+}
@@ -0,0 +1,148 @@
+//! A normalizer and a segmenter for charabia, the tokenizer of Meilisearch, which has no
+//! Ukrainian stemming of its own.
+//!
+//! charabia picks its segmenters and normalizers from fixed lists, so a deployment either
+//! adds these to the lists of its charabia build or runs `StemmerNormalizer` over the
+//! tokens charabia produces:
+//!
+//! ```ignore
+//! let normalizer = StemmerNormalizer::new();
+//! let options = NormalizerOption::default();
+//! let stems: Vec<_> = "Народами і книгами"
+//!     .tokenize()
+//!     .filter(|token| normalizer.should_normalize(token))
+//!     .map(|token| normalizer.normalize(token, &options).lemma.into_owned())
+//!     .collect();
+//! ```
+
+use std::borrow::Cow;
+
+use ::charabia::normalizer::{Normalizer, NormalizerOption};
+use ::charabia::segmenter::Segmenter;
+use ::charabia::{Script, Token};
+
+use crate::tokenizer::Tokenizer;
+use crate::Stemmer;
+
+/// Replaces the lemma of Cyrillic words with its stem. Run after charabia's own
+/// normalizers, which lowercase the lemma.
+#[derive(Clone, Debug, Default)]
+pub struct StemmerNormalizer {
+    stemmer: Stemmer,
+}
+
+impl StemmerNormalizer {
+    /// A normalizer stemming like `stem_word`.
+    pub fn new() -> Self {
+        StemmerNormalizer::default()
+    }
+
+    /// A normalizer stemming with `stemmer`.
+    pub fn with_stemmer(stemmer: Stemmer) -> Self {
+        StemmerNormalizer { stemmer }
+    }
+}
+
+impl Normalizer for StemmerNormalizer {
+    fn normalize<'o>(&self, mut token: Token<'o>, options: &NormalizerOption) -> Token<'o> {
+        let stem = self.stemmer.stem(&token.lemma);
+        if stem == token.lemma {
+            return token;
+        }
+        if options.create_char_map {
+            token.char_map = Some(shrink_char_map(&token, stem.len()));
+        }
+        token.lemma = Cow::Owned(stem);
+        token
+    }
+
+    fn should_normalize(&self, token: &Token) -> bool {
+        token.script == Script::Cyrillic && token.is_word()
+    }
+}
+
+// The char map of `token` for a lemma of `len` bytes: the stem keeps the start of the
+// lemma, so the original chars map to the bytes of the lemma up to `len` and the cut ending
+// maps to nothing. A stem longer than the lemma, e.g. with a hash from `max_stem_bytes`,
+// adds its extra bytes to the last char.
+fn shrink_char_map(token: &Token, len: usize) -> Vec<(u8, u8)> {
+    let mut map = match &token.char_map {
+        Some(map) => map.clone(),
+        None => token
+            .lemma
+            .chars()
+            .map(|c| (c.len_utf8() as u8, c.len_utf8() as u8))
+            .collect(),
+    };
+    let mut left = len;
+    for (_, normalized) in map.iter_mut() {
+        let kept = left.min(usize::from(*normalized));
+        *normalized = kept as u8;
+        left -= kept;
+    }
+    if let Some((_, last)) = map.last_mut() {
+        *last = last.saturating_add(left.min(usize::from(u8::MAX)) as u8);
+    }
+    map
+}
+
+/// Segments Cyrillic text with this crate's tokenizer, which keeps apostrophes, hyphenated
+/// words and stress marks inside words. Every piece of the text is a segment, separators
+/// included, as charabia expects.
+#[derive(Clone, Debug, Default)]
+pub struct UkrainianSegmenter {
+    tokenizer: Tokenizer,
+}
+
+impl UkrainianSegmenter {
+    pub fn new() -> Self {
+        UkrainianSegmenter::default()
+    }
+
+    /// A segmenter splitting with `tokenizer`.
+    pub fn with_tokenizer(tokenizer: Tokenizer) -> Self {
+        UkrainianSegmenter { tokenizer }
+    }
+}
+
+impl Segmenter for UkrainianSegmenter {
+    fn segment_str<'o>(&self, s: &'o str) -> Box<dyn Iterator<Item = &'o str> + 'o> {
+        let segments: Vec<&'o str> = self.tokenizer.tokenize(s).map(|t| t.text).collect();
+        Box::new(segments.into_iter())
+    }
+}
+
+#[test]
+fn stemmer_normalizer_test() {
+    use ::charabia::Tokenize;
+
+    let normalizer = StemmerNormalizer::new();
+    let options = NormalizerOption {
+        create_char_map: true,
+        ..NormalizerOption::default()
+    };
+    let tokens: Vec<_> = "Народами читали Kyiv"
+        .tokenize()
+        .map(|token| match normalizer.should_normalize(&token) {
+            true => normalizer.normalize(token, &options),
+            false => token,
+        })
+        .filter(|token| token.is_word())
+        .collect();
+    let lemmas: Vec<&str> = tokens.iter().map(|t| t.lemma()).collect();
+    assert_eq!(lemmas, ["народ", "чит", "kyiv"]);
+    // "Народами" keeps the bytes of "народ" and maps "ами" to nothing
+    let map = tokens[0].char_map.as_ref().unwrap();
+    assert_eq!(map.len(), 8);
+    assert_eq!(map.iter().map(|&(_, n)| usize::from(n)).sum::<usize>(), 10);
+    assert_eq!(map[5..], [(2, 0), (2, 0), (2, 0)]);
+}
+
+#[test]
+fn ukrainian_segmenter_test() {
+    let text = "Інтернет-магазин п’ять, 3 грн";
+    let segments: Vec<&str> = UkrainianSegmenter::new().segment_str(text).collect();
+    assert_eq!(segments.concat(), text);
+    assert!(segments.contains(&"Інтернет-магазин"));
+    assert!(segments.contains(&"п’ять"));
+}
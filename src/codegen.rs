@@ -0,0 +1,157 @@
+//! Generates `rule_tables.rs` from the human-readable rule sources in `rules/`, and the
+//! regression snapshot of the stems of `Algorithm::V2`.
+//!
+//! The output is committed, so building the crate needs neither the sources nor this
+//! generator; `cargo run --example gen_rules` and `cargo run --example gen_snapshot`
+//! rewrite it after the sources change.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::Stemmer;
+
+pub const SOURCE_PATH: &str = "rules/suffixes.txt";
+pub const OUTPUT_PATH: &str = "core/src/rule_tables.rs";
+
+pub const SNAPSHOT_PATH: &str = "rules/snapshot_v2.txt";
+
+/// Converts the suffix rule source into Rust const tables, one `&[&str]` per group.
+pub fn generate(source: &str) -> Result<String, String> {
+    let mut out = format!(
+        "// @generated by `cargo run --example gen_rules` from {}, do not edit.\n",
+        SOURCE_PATH
+    );
+    let mut groups = HashSet::new();
+    for (i, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let err = |msg: &str| Err(format!("{}:{}: {}", SOURCE_PATH, i + 1, msg));
+        let (group, suffixes) = match line.split_once(':') {
+            Some(split) => split,
+            None => return err("expected `<group>: <suffix>...`"),
+        };
+        let group = group.trim();
+        if group.is_empty() || !group.chars().all(|c| c.is_ascii_lowercase() || c == '_') {
+            return err("group names are lowercase ASCII with underscores");
+        }
+        if !groups.insert(group) {
+            return err(&format!("group {} is defined twice", group));
+        }
+        let mut seen = HashSet::new();
+        let mut items = Vec::new();
+        for suffix in suffixes.split_whitespace() {
+            if !suffix.chars().all(char::is_alphabetic) {
+                return err(&format!("suffix {:?} is not a sequence of letters", suffix));
+            }
+            if !seen.insert(suffix) {
+                return err(&format!("suffix {} is listed twice in {}", suffix, group));
+            }
+            items.push(format!("{:?}", suffix));
+        }
+        if items.is_empty() {
+            return err(&format!("group {} has no suffixes", group));
+        }
+        writeln!(
+            out,
+            "\npub const {}: &[&str] = &[{}];",
+            group.to_uppercase(),
+            items.join(", ")
+        )
+        .unwrap();
+    }
+    Ok(out)
+}
+
+/// The `form<TAB>stem` pairs of a snapshot, skipping comments and blank lines.
+pub fn snapshot_pairs(source: &str) -> impl Iterator<Item = (&str, &str)> {
+    source
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| line.split_once('\t').unwrap_or((line, "")))
+}
+
+/// The snapshot `source` with the stems `stemmer` gives its forms, keeping its
+/// comments.
+pub fn restem_snapshot(source: &str, stemmer: &Stemmer) -> String {
+    let mut out = String::new();
+    for line in source.lines() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            writeln!(out, "{}", line).unwrap();
+        } else {
+            let (form, _) = line.split_once('\t').unwrap_or((line, ""));
+            writeln!(out, "{}\t{}", form, stemmer.stem(form)).unwrap();
+        }
+    }
+    out
+}
+
+#[test]
+fn committed_tables_match_sources_test() {
+    let generated = generate(include_str!("../rules/suffixes.txt")).unwrap();
+    assert!(
+        generated == include_str!("../core/src/rule_tables.rs"),
+        "{} is out of date, run `cargo run --example gen_rules`",
+        OUTPUT_PATH
+    );
+}
+
+#[test]
+fn generate_test() {
+    assert_eq!(
+        generate("# c\nnoun: а ами\n").unwrap(),
+        format!(
+            "// @generated by `cargo run --example gen_rules` from {}, do not edit.\n\npub const NOUN: &[&str] = &[\"а\", \"ами\"];\n",
+            SOURCE_PATH
+        )
+    );
+    assert!(generate("noun: а а").unwrap_err().contains(":1: suffix а"));
+    assert!(generate("noun а").is_err());
+    assert!(generate("noun: a|b").is_err());
+}
+
+#[test]
+fn snapshot_stems_test() {
+    use crate::Algorithm;
+    use stemmer_uk_core::suffix;
+
+    let stemmer = Stemmer::builder().algorithm(Algorithm::V2).build();
+    let mut diffs = Vec::new();
+    let mut forms = 0;
+    for (form, stem) in snapshot_pairs(include_str!("../rules/snapshot_v2.txt")) {
+        forms += 1;
+        let got = stemmer.stem(form);
+        if got != stem {
+            diffs.push(format!("{}: {} → {}", form, stem, got));
+        }
+        // RV as the reference `^(.*?[аеиоуюяіїє])(.*)$` finds it, on char boundaries
+        let reference = form
+            .char_indices()
+            .find(|(_, c)| "аеиоуюяіїє".contains(*c))
+            .map(|(i, c)| i + c.len_utf8());
+        assert_eq!(suffix::rv_start(form), reference, "{}", form);
+    }
+    assert!(forms > 3000);
+    assert!(
+        diffs.is_empty(),
+        "{} of {} forms of {} no longer get their stems, which a new version should \
+         change instead:\n{}",
+        diffs.len(),
+        forms,
+        SNAPSHOT_PATH,
+        diffs.join("\n")
+    );
+}
+
+#[test]
+fn restem_snapshot_test() {
+    let stemmer = Stemmer::new();
+    let source = "# c\nкнигами\t\nнароди\tнарод\n";
+    assert_eq!(
+        restem_snapshot(source, &stemmer),
+        "# c\nкнигами\tкниг\nнароди\tнарод\n"
+    );
+    let pairs: Vec<_> = snapshot_pairs(source).collect();
+    assert_eq!(pairs, [("книгами", ""), ("народи", "народ")]);
+}
@@ -0,0 +1,75 @@
+//! Ukrainian alphabetical order, for sorted output people read.
+
+use std::cmp::Ordering;
+
+use stemmer_uk_core::normalize::APOSTROPHES;
+
+const ALPHABET: &str = "абвгґдеєжзиіїйклмнопрстуфхцчшщьюя";
+
+// Ukrainian letters sort in alphabet order after everything below the Cyrillic block and
+// before the other Cyrillic letters; all other characters by code point
+fn weight(c: char) -> (u32, u32) {
+    match ALPHABET.chars().position(|letter| letter == c) {
+        Some(rank) => (0x0400, rank as u32),
+        None => (c as u32, 0),
+    }
+}
+
+/// Compares `a` and `b` in Ukrainian alphabetical order, ignoring case and apostrophes
+/// ("ґанок" after "гуска", "м'ята" next to "мята"). Strings equal under that order are
+/// compared by code point, so the order is total.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    let key = |s: &'_ str| {
+        s.chars()
+            .flat_map(char::to_lowercase)
+            .filter(|c| !APOSTROPHES.contains(c))
+            .map(weight)
+            .collect::<Vec<_>>()
+    };
+    key(a).cmp(&key(b)).then_with(|| a.cmp(b))
+}
+
+/// Sorts `words` in the order of `compare`.
+pub fn sort<S: AsRef<str>>(words: &mut [S]) {
+    words.sort_by(|a, b| compare(a.as_ref(), b.as_ref()));
+}
+
+#[test]
+fn compare_test() {
+    let mut words = vec![
+        "яблуко",
+        "ґанок",
+        "гуска",
+        "їжак",
+        "ірис",
+        "исток",
+        "Єва",
+        "ера",
+        "жук",
+        "мята",
+        "м'ята",
+        "abc",
+        "ёж",
+    ];
+    sort(&mut words);
+    assert_eq!(
+        words,
+        [
+            "abc",
+            "гуска",
+            "ґанок",
+            "ера",
+            "Єва",
+            "жук",
+            "исток",
+            "ірис",
+            "їжак",
+            "м'ята",
+            "мята",
+            "яблуко",
+            "ёж"
+        ]
+    );
+    assert_eq!(compare("Київ", "київ"), Ordering::Less);
+    assert_eq!(compare("київ", "київ"), Ordering::Equal);
+}
@@ -0,0 +1,102 @@
+//! Migration layers for older versions of the public API.
+
+/// The original `String` in, `String` out API, kept working while the crate moves to
+/// `Stemmer`, borrowed results and `Result` return types.
+///
+/// Existing callers can `use stemmer_uk::compat::v0::*` and keep compiling; the helpers
+/// adapt the newer types to the old signatures one call site at a time.
+pub mod v0 {
+    use std::borrow::Cow;
+
+    use crate::Stem;
+
+    pub use crate::stem_word;
+
+    /// Turns a result of the borrowing API into the owned `String` v0 callers expect.
+    pub fn into_v0(stem: Cow<str>) -> String {
+        stem.into_owned()
+    }
+
+    /// Runs any `Stem` implementation with the `stem_word` calling convention.
+    pub fn stem_word_with(stemmer: &dyn Stem, word: String) -> String {
+        into_v0(stemmer.stem(&word))
+    }
+
+    /// `stem_word` over a whole list, as v0 users did with `into_iter().map(stem_word)`.
+    pub fn stem_words(words: Vec<String>) -> Vec<String> {
+        words.into_iter().map(stem_word).collect()
+    }
+}
+
+/// The shape of `rust_stemmers::Stemmer`, for code written against the Snowball stemmers,
+/// which have no Ukrainian algorithm. Switching such code over is a matter of replacing
+/// `use rust_stemmers::{Algorithm, Stemmer};` with
+/// `use stemmer_uk::compat::rust_stemmers::{Algorithm, Stemmer};`.
+#[cfg(feature = "rust-stemmers")]
+pub mod rust_stemmers {
+    use std::borrow::Cow;
+
+    use crate::Stem;
+
+    /// The languages of `Stemmer::create`; only one, unlike `rust_stemmers::Algorithm`.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum Algorithm {
+        Ukrainian,
+    }
+
+    /// `stemmer_uk::Stemmer` behind the `rust_stemmers::Stemmer` methods.
+    #[derive(Clone, Debug)]
+    pub struct Stemmer {
+        stemmer: crate::Stemmer,
+    }
+
+    impl Stemmer {
+        pub fn create(lang: Algorithm) -> Self {
+            match lang {
+                Algorithm::Ukrainian => Stemmer {
+                    stemmer: crate::Stemmer::default(),
+                },
+            }
+        }
+
+        /// The stem of `input`, borrowing it when it is its own stem. Unlike Snowball,
+        /// the input need not be lowercased first.
+        pub fn stem<'a>(&self, input: &'a str) -> Cow<'a, str> {
+            Stem::stem(&self.stemmer, input)
+        }
+    }
+
+    impl From<crate::Stemmer> for Stemmer {
+        /// An adapter for a configured stemmer.
+        fn from(stemmer: crate::Stemmer) -> Self {
+            Stemmer { stemmer }
+        }
+    }
+}
+
+#[test]
+fn v0_test() {
+    use crate::Stemmer;
+
+    let words = vec!["ручкається".to_string(), "народи".to_string()];
+    assert_eq!(v0::stem_words(words.clone()), ["ручкаєт", "народ"]);
+    for word in words {
+        assert_eq!(
+            v0::stem_word_with(&Stemmer::new(), word.clone()),
+            v0::stem_word(word)
+        );
+    }
+}
+
+#[cfg(feature = "rust-stemmers")]
+#[test]
+fn rust_stemmers_test() {
+    use rust_stemmers::{Algorithm, Stemmer};
+    use std::borrow::Cow;
+
+    let stemmer = Stemmer::create(Algorithm::Ukrainian);
+    assert_eq!(stemmer.stem("Народами"), "народ");
+    assert!(matches!(stemmer.stem("хліб"), Cow::Borrowed("хліб")));
+    let cached = Stemmer::from(crate::Stemmer::builder().cache(10).build());
+    assert_eq!(cached.stem("книгами"), "книг");
+}
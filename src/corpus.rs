@@ -0,0 +1,183 @@
+//! Validation of the rules on real corpora.
+//!
+//! The checks against the datasets are `#[ignore]`d tests, run them with
+//! `cargo test -- --ignored` after rule changes; they download the datasets on first use
+//! into `cache_dir()`. The library itself reads the files it is given: only with the
+//! `download` feature does `fetch` download a dataset, by running the `curl` program.
+//!
+//! The only dataset is the Ukrainian IU treebank of Universal Dependencies. Brown-UK is
+//! not among them: its annotated texts are not in CoNLL-U, and a reader for their format is
+//! left for later.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use stemmer_uk_core::normalize::APOSTROPHES;
+
+use crate::Stemmer;
+
+/// A downloadable file of annotated text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dataset {
+    pub name: &'static str,
+    pub url: &'static str,
+}
+
+/// Test split of the Ukrainian IU treebank of Universal Dependencies, in CoNLL-U.
+pub const UD_UKRAINIAN_IU: Dataset = Dataset {
+    name: "uk_iu-ud-test.conllu",
+    url: "https://raw.githubusercontent.com/UniversalDependencies/UD_Ukrainian-IU/master/uk_iu-ud-test.conllu",
+};
+
+/// `$UKSTEM_CORPUS_DIR`, or `ukstem-corpora` in the system temp directory.
+pub fn cache_dir() -> PathBuf {
+    std::env::var_os("UKSTEM_CORPUS_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join("ukstem-corpora"))
+}
+
+/// The cached copy of `dataset`, downloading it with `curl` into `cache_dir()` if it is not
+/// there yet. Fails if `curl` is missing or the download fails, leaving no partial file.
+#[cfg(any(test, feature = "download"))]
+pub fn fetch(dataset: &Dataset) -> io::Result<PathBuf> {
+    use std::fs;
+    use std::process::Command;
+
+    let dir = cache_dir();
+    let path = dir.join(dataset.name);
+    if path.exists() {
+        return Ok(path);
+    }
+    fs::create_dir_all(&dir)?;
+    let partial = dir.join(format!("{}.part", dataset.name));
+    let status = Command::new("curl")
+        .args(["-fsSL", "-o"])
+        .arg(&partial)
+        .arg(dataset.url)
+        .status()?;
+    if !status.success() {
+        let _ = fs::remove_file(&partial);
+        return Err(io::Error::other(format!(
+            "downloading {} failed: {}",
+            dataset.url, status
+        )));
+    }
+    fs::rename(&partial, &path)?;
+    Ok(path)
+}
+
+/// `(form, lemma)` pairs of the Cyrillic word tokens of a CoNLL-U file, lowercased and with
+/// the apostrophes of `APOSTROPHES` written as `'`, which every `Algorithm` removes.
+pub fn read_conllu<R: BufRead>(input: R) -> io::Result<Vec<(String, String)>> {
+    let mut pairs = Vec::new();
+    for line in input.lines() {
+        let line = line?;
+        if line.starts_with('#') {
+            continue;
+        }
+        let mut columns = line.split('\t');
+        let (id, form, lemma) = match (columns.next(), columns.next(), columns.next()) {
+            (Some(id), Some(form), Some(lemma)) => (id, form, lemma),
+            _ => continue,
+        };
+        // multi-word token ranges and empty nodes repeat their parts
+        if id.contains(['-', '.'])
+            || !form
+                .chars()
+                .all(|c| c.is_alphabetic() || APOSTROPHES.contains(&c))
+        {
+            continue;
+        }
+        if form.chars().any(|c| ('\u{0400}'..='\u{04FF}').contains(&c)) {
+            pairs.push((apostrophes(form), apostrophes(lemma)));
+        }
+    }
+    Ok(pairs)
+}
+
+fn apostrophes(word: &str) -> String {
+    word.chars()
+        .map(|c| if APOSTROPHES.contains(&c) { '\'' } else { c })
+        .collect::<String>()
+        .to_lowercase()
+}
+
+pub fn read_conllu_file<P: AsRef<Path>>(path: P) -> io::Result<Vec<(String, String)>> {
+    read_conllu(BufReader::new(File::open(path)?))
+}
+
+/// How well stems group the forms of a lemma and keep different lemmas apart.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConflationReport {
+    pub lemmas: usize,
+    /// Lemmas all of whose forms got the same stem.
+    pub conflated: usize,
+    /// Stems shared by forms of more than one lemma.
+    pub merged_stems: usize,
+    pub stems: usize,
+}
+
+impl ConflationReport {
+    pub fn check<'a, I>(stemmer: &Stemmer, pairs: I) -> Self
+    where
+        I: IntoIterator<Item = &'a (String, String)>,
+    {
+        let mut by_lemma: BTreeMap<&str, BTreeSet<String>> = BTreeMap::new();
+        let mut by_stem: BTreeMap<String, BTreeSet<&str>> = BTreeMap::new();
+        for (form, lemma) in pairs {
+            let stem = stemmer.stem(form);
+            by_lemma.entry(lemma).or_default().insert(stem.clone());
+            by_stem.entry(stem).or_default().insert(lemma);
+        }
+        ConflationReport {
+            lemmas: by_lemma.len(),
+            conflated: by_lemma.values().filter(|stems| stems.len() == 1).count(),
+            merged_stems: by_stem.values().filter(|lemmas| lemmas.len() > 1).count(),
+            stems: by_stem.len(),
+        }
+    }
+
+    /// Share of lemmas whose forms all conflate.
+    pub fn recall(&self) -> f64 {
+        self.conflated as f64 / self.lemmas.max(1) as f64
+    }
+
+    /// Share of stems that belong to a single lemma.
+    pub fn precision(&self) -> f64 {
+        1.0 - self.merged_stems as f64 / self.stems.max(1) as f64
+    }
+}
+
+#[test]
+fn conflation_report_test() {
+    let conllu = "# sent_id = 1\n\
+        1\tКниги\tкнига\tNOUN\n\
+        2-3\tдобрі\t_\t_\n\
+        2\tкнигою\tкнига\tNOUN\n\
+        3\t,\t,\tPUNCT\n\
+        4\tнароди\tнарод\tNOUN\n\
+        5\tнародами\tнарод\tNOUN\n\
+        6\tсім’ї\tсім’я\tNOUN\n";
+    let pairs = read_conllu(conllu.as_bytes()).unwrap();
+    assert_eq!(pairs.len(), 5);
+    assert_eq!(pairs[0], ("книги".to_string(), "книга".to_string()));
+    assert_eq!(pairs[4], ("сім'ї".to_string(), "сім'я".to_string()));
+
+    let report = ConflationReport::check(&Stemmer::new(), &pairs);
+    assert_eq!(report.lemmas, 3);
+    assert_eq!(report.recall(), 1.0);
+    assert_eq!(report.precision(), 1.0);
+}
+
+// floors below which a rule set is broken, not the figures of the current rules
+#[test]
+#[ignore]
+fn ud_conflation_test() {
+    let pairs = read_conllu_file(fetch(&UD_UKRAINIAN_IU).unwrap()).unwrap();
+    let report = ConflationReport::check(&Stemmer::new(), &pairs);
+    assert!(report.lemmas > 1000, "{:?}", report);
+    assert!(report.recall() > 0.6, "{:?}", report);
+    assert!(report.precision() > 0.8, "{:?}", report);
+}
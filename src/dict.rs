@@ -0,0 +1,313 @@
+//! Form → stem dictionaries compiled into a finite state transducer, for exception or lemma
+//! lists of millions of forms: `CompiledDictionary::open` maps the file into memory and
+//! looks forms up in place, so only the pages lookups touch are read, and `ukstem
+//! build-dict` compiles the `form<TAB>stem` files of `Dictionary::load`.
+//!
+//! A file starts with the magic `ukdict1\n`, a digest of its entries, and the length of the
+//! `fst` map that follows, all little-endian. The map gives every form a `u64`: the bytes
+//! of the form the stem starts with in the low 16 bits, and the length and offset of the
+//! rest of the stem in the table of tails after the map in the next 16 and the high 32
+//! bits. Most stems are prefixes of their forms and have no tail; the tails of lemmas,
+//! such as the "на" of "люди" → "людина", are stored once.
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::ops::{Deref, Range};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::dictionary::parse_line;
+use crate::fingerprint::Fingerprint;
+
+const MAGIC: &[u8; 8] = b"ukdict1\n";
+const HEADER_LEN: usize = 24;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Collects the entries of a `CompiledDictionary` and writes them compiled. The entries
+/// are kept in memory until then, sorted as the map needs them; a form inserted again
+/// gets the later stem, as in a `Dictionary`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DictionaryBuilder {
+    entries: BTreeMap<String, String>,
+}
+
+impl DictionaryBuilder {
+    pub fn new() -> Self {
+        DictionaryBuilder::default()
+    }
+
+    /// Reads `form<TAB>stem` lines, the format of `Dictionary::load`.
+    pub fn load<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut builder = DictionaryBuilder::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if let Some((form, stem)) = parse_line(i, &line)? {
+                builder.insert(form, stem);
+            }
+        }
+        Ok(builder)
+    }
+
+    /// Maps `form`, lowercased, to `stem`.
+    pub fn insert(&mut self, form: &str, stem: &str) {
+        self.entries.insert(form.to_lowercase(), stem.to_string());
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Writes the compiled dictionary to `out`. Fails with `InvalidInput` for forms or
+    /// stems of more than 65535 bytes.
+    pub fn write<W: Write>(&self, mut out: W) -> io::Result<()> {
+        let too_long = || io::Error::new(io::ErrorKind::InvalidInput, "entry over 65535 bytes");
+        let mut map = fst::MapBuilder::memory();
+        let mut tails = Vec::new();
+        let mut offsets: HashMap<&str, u32> = HashMap::new();
+        let mut digest = 0u64;
+        for (form, stem) in &self.entries {
+            let kept = common_prefix(form, stem);
+            let tail = &stem[kept..];
+            let offset = match offsets.get(tail) {
+                Some(&offset) => offset,
+                None => {
+                    let offset = u32::try_from(tails.len()).map_err(|_| too_long())?;
+                    tails.extend_from_slice(tail.as_bytes());
+                    offsets.insert(tail, offset);
+                    offset
+                }
+            };
+            if form.len() > 0xffff || tail.len() > 0xffff {
+                return Err(too_long());
+            }
+            let value = kept as u64 | (tail.len() as u64) << 16 | u64::from(offset) << 32;
+            map.insert(form, value).map_err(io::Error::other)?;
+            digest = digest.wrapping_add(Fingerprint::of(&format!("{}\t{}", form, stem)).value());
+        }
+        let map = map.into_inner().map_err(io::Error::other)?;
+        out.write_all(MAGIC)?;
+        out.write_all(&digest.to_le_bytes())?;
+        out.write_all(&(map.len() as u64).to_le_bytes())?;
+        out.write_all(&map)?;
+        out.write_all(&tails)?;
+        out.flush()
+    }
+
+    /// The compiled dictionary, in memory.
+    pub fn build(&self) -> CompiledDictionary {
+        let mut bytes = Vec::new();
+        self.write(&mut bytes)
+            .expect("the entries fit the format and writing to memory can't fail");
+        CompiledDictionary::from_bytes(bytes).unwrap()
+    }
+}
+
+// the bytes `form` and `stem` start with both, ending on a char boundary
+fn common_prefix(form: &str, stem: &str) -> usize {
+    form.char_indices()
+        .zip(stem.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or(form.len().min(stem.len()), |((i, _), _)| i)
+}
+
+// the bytes of a compiled dictionary
+enum Source {
+    #[cfg(not(target_family = "wasm"))]
+    Mapped(memmap2::Mmap),
+    Owned(Vec<u8>),
+}
+
+impl Deref for Source {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            #[cfg(not(target_family = "wasm"))]
+            Source::Mapped(map) => map,
+            Source::Owned(bytes) => bytes,
+        }
+    }
+}
+
+// a range of the bytes, shared by the map and the tails
+#[derive(Clone)]
+struct Region {
+    source: Arc<Source>,
+    range: Range<usize>,
+}
+
+impl AsRef<[u8]> for Region {
+    fn as_ref(&self) -> &[u8] {
+        &self.source[self.range.clone()]
+    }
+}
+
+/// A form → stem dictionary written by `DictionaryBuilder`, for
+/// `StemmerBuilder::dictionary`. Clones share the bytes.
+#[derive(Clone)]
+pub struct CompiledDictionary {
+    map: fst::Map<Region>,
+    tails: Region,
+    digest: u64,
+}
+
+impl CompiledDictionary {
+    /// Maps the file at `path` into memory; the file must not change while the dictionary
+    /// is used. On WebAssembly, which has no memory maps, the file is read.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        #[cfg(not(target_family = "wasm"))]
+        {
+            let file = std::fs::File::open(path)?;
+            // Safety: the map is only read, and the caller keeps the file from being
+            // truncated while it is in use, which would fault on the lost pages.
+            let map = unsafe { memmap2::Mmap::map(&file)? };
+            CompiledDictionary::from_source(Source::Mapped(map))
+        }
+        #[cfg(target_family = "wasm")]
+        CompiledDictionary::from_bytes(std::fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> io::Result<Self> {
+        CompiledDictionary::from_source(Source::Owned(bytes))
+    }
+
+    fn from_source(source: Source) -> io::Result<Self> {
+        if source.len() < HEADER_LEN || &source[..8] != MAGIC {
+            return Err(invalid("not a compiled dictionary"));
+        }
+        let word = |i: usize| u64::from_le_bytes(source[i..i + 8].try_into().unwrap());
+        let digest = word(8);
+        let map_end = usize::try_from(word(16))
+            .ok()
+            .and_then(|len| HEADER_LEN.checked_add(len))
+            .filter(|&end| end <= source.len())
+            .ok_or_else(|| invalid("truncated compiled dictionary"))?;
+        let end = source.len();
+        let source = Arc::new(source);
+        let region = |range| Region {
+            source: Arc::clone(&source),
+            range,
+        };
+        let map = fst::Map::new(region(HEADER_LEN..map_end))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(CompiledDictionary {
+            map,
+            tails: region(map_end..end),
+            digest,
+        })
+    }
+
+    /// The stem of `form`, or of its lowercase spelling.
+    pub fn get(&self, form: &str) -> Option<String> {
+        match self.lookup(form) {
+            Some(stem) => Some(stem),
+            None if form.chars().any(char::is_uppercase) => self.lookup(&form.to_lowercase()),
+            None => None,
+        }
+    }
+
+    // `None` as well for entries a damaged file decodes to no string
+    fn lookup(&self, form: &str) -> Option<String> {
+        let value = self.map.get(form)?;
+        let kept = (value & 0xffff) as usize;
+        let len = (value >> 16 & 0xffff) as usize;
+        let offset = (value >> 32) as usize;
+        let tail = self.tails.as_ref().get(offset..offset + len)?;
+        let mut stem = form.get(..kept)?.to_string();
+        stem.push_str(std::str::from_utf8(tail).ok()?);
+        Some(stem)
+    }
+
+    pub fn contains(&self, form: &str) -> bool {
+        self.get(form).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+// by content, as `Stemmer::fingerprint` sees dictionaries
+impl fmt::Debug for CompiledDictionary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CompiledDictionary")
+            .field("forms", &self.len())
+            .field("digest", &format_args!("{:016x}", self.digest))
+            .finish()
+    }
+}
+
+impl PartialEq for CompiledDictionary {
+    fn eq(&self, other: &Self) -> bool {
+        (self.len(), self.digest) == (other.len(), other.digest)
+    }
+}
+
+#[test]
+fn compiled_dictionary_test() {
+    let tsv = "# form\tstem\nлюди\tлюдин\nКиєва\tКиїв\nкниги\tкниг\nкнигами\tкниг\n";
+    let builder = DictionaryBuilder::load(tsv.as_bytes()).unwrap();
+    assert_eq!(builder.len(), 4);
+    let dict = builder.build();
+    assert_eq!(dict.len(), 4);
+    assert_eq!(dict.get("люди").as_deref(), Some("людин"));
+    assert_eq!(dict.get("КИЄВА").as_deref(), Some("Київ"));
+    assert_eq!(dict.get("книгами").as_deref(), Some("книг"));
+    assert_eq!(dict.get("книга"), None);
+
+    let mut bytes = Vec::new();
+    builder.write(&mut bytes).unwrap();
+    // "книг" is a prefix of both its forms: "Київ" and the "н" of "людин" are the tails
+    assert!(bytes.ends_with("Київн".as_bytes()));
+    let path = std::env::temp_dir().join(format!("ukdict-{}.fst", std::process::id()));
+    std::fs::write(&path, &bytes).unwrap();
+    let opened = CompiledDictionary::open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(opened.get("люди").as_deref(), Some("людин"));
+    assert_eq!(opened, dict);
+    assert_eq!(format!("{:?}", opened), format!("{:?}", dict));
+
+    let mut other = builder.clone();
+    other.insert("люди", "люд");
+    assert_ne!(other.build(), dict);
+    assert!(CompiledDictionary::from_bytes(b"ukdict1\n".to_vec()).is_err());
+    assert!(CompiledDictionary::from_bytes(bytes[..30].to_vec()).is_err());
+}
+
+#[test]
+fn stemmer_dictionary_test() {
+    use crate::Stemmer;
+
+    let mut builder = DictionaryBuilder::new();
+    builder.insert("люди", "людин");
+    builder.insert("пять", "п'ят");
+    let stemmer = Stemmer::builder().dictionary(builder.build()).build();
+    assert_eq!(stemmer.stem("Люди"), "людин");
+    // looked up by the normalized form, without the apostrophe
+    assert_eq!(stemmer.stem("п'ять"), "п'ят");
+    assert_eq!(stemmer.stem("книгами"), "книг");
+    let mut out = String::new();
+    stemmer.stem_into("люди", &mut out);
+    assert_eq!(out, "людин");
+    assert_ne!(stemmer.fingerprint(), Stemmer::new().fingerprint());
+    assert_eq!(
+        stemmer.fingerprint(),
+        Stemmer::builder()
+            .dictionary(builder.build())
+            .build()
+            .fingerprint()
+    );
+}
@@ -0,0 +1,194 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead};
+
+use crate::stem::Stem;
+
+// borrowed as is when the form is already lowercase
+fn lowercase(form: Cow<str>) -> Cow<str> {
+    if form.chars().any(char::is_uppercase) {
+        Cow::Owned(form.to_lowercase())
+    } else {
+        form
+    }
+}
+
+pub(crate) fn parse_line(i: usize, line: &str) -> io::Result<Option<(&str, &str)>> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    match line.split_once('\t') {
+        Some((form, lemma)) => Ok(Some((form, lemma.trim()))),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("line {}: expected `form<TAB>lemma`", i + 1),
+        )),
+    }
+}
+
+/// A form → lemma (or stem) lookup table, keyed on lowercased forms.
+///
+/// Entries may borrow from the source, so a dictionary parsed from `include_str!` data
+/// with `Dictionary::parse` keeps its strings in the binary's read-only data.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Dictionary<'a> {
+    entries: HashMap<Cow<'a, str>, Cow<'a, str>>,
+}
+
+impl<'a> Dictionary<'a> {
+    pub fn new() -> Self {
+        Dictionary::default()
+    }
+
+    pub fn from_pairs<I, K, V>(pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        let mut dict = Dictionary::new();
+        for (form, lemma) in pairs {
+            dict.insert(form, lemma);
+        }
+        dict
+    }
+
+    /// Parses tab-separated `form<TAB>lemma` lines, borrowing every lowercase entry from
+    /// `text`; blank lines and lines starting with `#` are skipped.
+    pub fn parse(text: &'a str) -> io::Result<Self> {
+        let mut dict = Dictionary::new();
+        for (i, line) in text.lines().enumerate() {
+            if let Some((form, lemma)) = parse_line(i, line)? {
+                dict.insert(form, lemma);
+            }
+        }
+        Ok(dict)
+    }
+
+    /// Reads the `parse` format from `reader` into an owned dictionary.
+    pub fn load<R: BufRead>(reader: R) -> io::Result<Dictionary<'static>> {
+        let mut dict = Dictionary::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if let Some((form, lemma)) = parse_line(i, &line)? {
+                dict.insert(form.to_string(), lemma.to_string());
+            }
+        }
+        Ok(dict)
+    }
+
+    pub fn insert<K, V>(&mut self, form: K, lemma: V)
+    where
+        K: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.entries.insert(lowercase(form.into()), lemma.into());
+    }
+
+    pub fn get(&self, form: &str) -> Option<&str> {
+        match self.entries.get(form) {
+            Some(lemma) => Some(lemma),
+            None => self.entries.get(form.to_lowercase().as_str()).map(|l| &**l),
+        }
+    }
+
+    pub fn contains(&self, form: &str) -> bool {
+        self.get(form).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// All `(form, lemma)` pairs, in no particular order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(form, lemma)| (&**form, &**lemma))
+    }
+
+    /// Bytes of entry text owned by the dictionary rather than borrowed from its source.
+    pub fn owned_bytes(&self) -> usize {
+        let owned = |s: &Cow<str>| match s {
+            Cow::Owned(s) => s.len(),
+            Cow::Borrowed(_) => 0,
+        };
+        self.entries.iter().map(|(f, l)| owned(f) + owned(l)).sum()
+    }
+
+    pub fn into_owned(self) -> Dictionary<'static> {
+        Dictionary {
+            entries: self
+                .entries
+                .into_iter()
+                .map(|(f, l)| (Cow::Owned(f.into_owned()), Cow::Owned(l.into_owned())))
+                .collect(),
+        }
+    }
+}
+
+// entries sorted, so the output (which `Stemmer::fingerprint` hashes) is deterministic
+impl fmt::Debug for Dictionary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut entries: Vec<(&str, &str)> = self.entries().collect();
+        entries.sort_unstable();
+        f.debug_map().entries(entries).finish()
+    }
+}
+
+/// A map of forms to lemmas, sorted.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Dictionary<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut entries: Vec<(&str, &str)> = self.entries().collect();
+        entries.sort_unstable();
+        serializer.collect_map(entries)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Dictionary<'static> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries: HashMap<String, String> = serde::Deserialize::deserialize(deserializer)?;
+        Ok(Dictionary::from_pairs(entries))
+    }
+}
+
+/// Known forms map to their lemma; unknown words are returned unchanged.
+impl Stem for Dictionary<'_> {
+    fn stem<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        match self.get(word) {
+            Some(lemma) => Cow::Owned(lemma.to_string()),
+            None => Cow::Borrowed(word),
+        }
+    }
+}
+
+#[test]
+fn dictionary_test() {
+    let tsv = "# form\tlemma\nлюди\tлюдина\n\nКиєва\tКиїв\n";
+    let dict = Dictionary::load(tsv.as_bytes()).unwrap();
+    assert_eq!(dict.len(), 2);
+    assert_eq!(dict.get("ЛЮДИ"), Some("людина"));
+    assert_eq!(dict.stem("києва"), "Київ");
+    assert!(matches!(dict.stem("слово"), Cow::Borrowed("слово")));
+    assert!(Dictionary::load("без табуляції".as_bytes()).is_err());
+}
+
+#[test]
+fn borrowed_dictionary_test() {
+    static TSV: &str = "люди\tлюдина\nКиєва\tКиїв\n";
+    let dict: Dictionary<'static> = Dictionary::parse(TSV).unwrap();
+    assert_eq!(dict.get("києва"), Some("Київ"));
+    // only the capitalized form had to be lowercased into an owned key
+    assert_eq!(dict.owned_bytes(), "києва".len());
+    assert_eq!(dict, Dictionary::load(TSV.as_bytes()).unwrap());
+    assert_eq!(
+        format!("{:?}", dict),
+        r#"{"києва": "Київ", "люди": "людина"}"#
+    );
+}
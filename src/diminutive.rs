@@ -0,0 +1,32 @@
+use std::borrow::Cow;
+
+use stemmer_uk_core::suffix;
+
+// Productive diminutive suffixes as they end stems, longest first: "-очок" of
+// "листочок" is "-очк" in "листочка".
+const SUFFIXES: [&str; 5] = ["еньк", "оньк", "очок", "очк", "ик"];
+
+// the fewest chars of the root a suffix is removed from, so that "язик" keeps its "ик"
+const MIN_ROOT_CHARS: usize = 3;
+
+/// `stem` without a diminutive suffix, so that "котика" and "кота" share a stem; stems
+/// whose root would be shorter than three chars or have no vowel are returned unchanged.
+/// Roots that merely end in a suffix, like "велик", are cut too.
+pub fn strip(stem: &str) -> Cow<'_, str> {
+    SUFFIXES
+        .iter()
+        .filter_map(|s| stem.strip_suffix(s))
+        .find(|root| root.chars().count() >= MIN_ROOT_CHARS && suffix::rv_start(root).is_some())
+        .map_or(Cow::Borrowed(stem), Cow::Borrowed)
+}
+
+#[test]
+fn strip_test() {
+    assert_eq!(strip("котик"), "кот");
+    assert_eq!(strip("гарненьк"), "гарн");
+    assert_eq!(strip("ручоньк"), "руч");
+    assert_eq!(strip("листочок"), "лист");
+    assert_eq!(strip("листочк"), "лист");
+    assert_eq!(strip("язик"), "язик");
+    assert_eq!(strip("книг"), "книг");
+}
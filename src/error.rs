@@ -0,0 +1,60 @@
+use std::error::Error;
+use std::fmt;
+
+use stemmer_uk_core::MatchError;
+
+/// Why a single input could not be stemmed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StemError {
+    /// The input bytes are not UTF-8; `valid_up_to` bytes are.
+    InvalidUtf8 { valid_up_to: usize },
+    /// Nothing is left of the input after normalization.
+    Empty,
+    /// The input contains a control character, which words never do; usually a sign of
+    /// binary data or a broken decoder upstream.
+    ControlCharacter(char),
+    /// A rule failed to match or left a broken stem; a bug in the rules, not in the input.
+    InternalMatch { rule: &'static str, message: String },
+}
+
+impl From<MatchError> for StemError {
+    fn from(err: MatchError) -> Self {
+        StemError::InternalMatch {
+            rule: err.rule,
+            message: err.message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for StemError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StemError::InvalidUtf8 { valid_up_to } => {
+                write!(f, "invalid UTF-8 after {} bytes", valid_up_to)
+            }
+            StemError::Empty => write!(f, "empty after normalization"),
+            StemError::ControlCharacter(c) => write!(f, "control character {:?}", c),
+            StemError::InternalMatch { rule, message } => {
+                write!(f, "internal error in rule {}: {}", rule, message)
+            }
+        }
+    }
+}
+
+impl Error for StemError {}
+
+/// A `StemError` of one item of a batch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ItemError {
+    /// Position of the item in the input.
+    pub index: usize,
+    pub error: StemError,
+}
+
+impl fmt::Display for ItemError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "item {}: {}", self.index, self.error)
+    }
+}
+
+impl Error for ItemError {}
@@ -0,0 +1,220 @@
+//! Stemming quality against gold lemmas, for comparing configurations such as `Mode::Light`
+//! and `Mode::Aggressive` or custom rule sets in numbers.
+//!
+//! `evaluate` stems every form of a form → lemma dataset, e.g. the pairs of
+//! `corpus::read_conllu`, and counts pairs of distinct forms: forms of one lemma should get
+//! one stem, forms of different lemmas different ones. Paice's understemming index is the
+//! share of the pairs that should merge but don't, his overstemming index the share of
+//! those that shouldn't merge but do; conflation precision and recall count the merged
+//! pairs the same way. A form listed with several lemmas keeps the first.
+//!
+//! Unlike `corpus::ConflationReport`, which counts lemmas and stems, the pair counts weigh
+//! a lemma by its number of forms, and a stem merging two large groups costs more than
+//! one merging two forms.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::Stemmer;
+
+/// The pair counts of `evaluate`, and the indices computed from them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EvalReport {
+    /// Distinct forms.
+    pub words: u64,
+    pub lemmas: u64,
+    pub stems: u64,
+    /// Pairs of forms of the same lemma.
+    pub desired_merges: u64,
+    /// The part of `desired_merges` whose forms got different stems.
+    pub unachieved_merges: u64,
+    /// Pairs of forms of different lemmas.
+    pub desired_non_merges: u64,
+    /// The part of `desired_non_merges` whose forms got the same stem.
+    pub wrong_merges: u64,
+    /// Pairs of forms that got the same stem.
+    pub merges: u64,
+}
+
+impl EvalReport {
+    /// Paice's UI: the share of `desired_merges` the stemmer missed, 0 at best.
+    pub fn understemming_index(&self) -> f64 {
+        ratio(self.unachieved_merges, self.desired_merges)
+    }
+
+    /// Paice's OI: the share of `desired_non_merges` the stemmer merged, 0 at best.
+    pub fn overstemming_index(&self) -> f64 {
+        ratio(self.wrong_merges, self.desired_non_merges)
+    }
+
+    /// OI / UI, higher for heavier stemmers; infinite without understemming.
+    pub fn stemming_weight(&self) -> f64 {
+        self.overstemming_index() / self.understemming_index()
+    }
+
+    /// The share of `merges` whose forms share a lemma, 1 when nothing merged.
+    pub fn precision(&self) -> f64 {
+        match self.merges {
+            0 => 1.0,
+            merges => ratio(merges - self.wrong_merges, merges),
+        }
+    }
+
+    /// The share of `desired_merges` the stemmer made, 1 when none were desired.
+    pub fn recall(&self) -> f64 {
+        match self.desired_merges {
+            0 => 1.0,
+            desired => ratio(desired - self.unachieved_merges, desired),
+        }
+    }
+
+    pub fn f1(&self) -> f64 {
+        let (precision, recall) = (self.precision(), self.recall());
+        if precision + recall == 0.0 {
+            0.0
+        } else {
+            2.0 * precision * recall / (precision + recall)
+        }
+    }
+}
+
+fn ratio(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// One line: the counts, UI, OI, precision, recall and F1.
+impl fmt::Display for EvalReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} words, {} lemmas, {} stems: UI {:.4}, OI {:.6}, precision {:.4}, recall {:.4}, F1 {:.4}",
+            self.words,
+            self.lemmas,
+            self.stems,
+            self.understemming_index(),
+            self.overstemming_index(),
+            self.precision(),
+            self.recall(),
+            self.f1()
+        )
+    }
+}
+
+/// Stems the forms of `gold`, `(form, lemma)` pairs, with `stemmer` and counts how its
+/// stems group them, see the module documentation.
+pub fn evaluate<'a, I>(stemmer: &Stemmer, gold: I) -> EvalReport
+where
+    I: IntoIterator<Item = &'a (String, String)>,
+{
+    let mut lemma_of: HashMap<&str, &str> = HashMap::new();
+    for (form, lemma) in gold {
+        lemma_of.entry(form).or_insert(lemma);
+    }
+    let mut by_lemma: HashMap<&str, u64> = HashMap::new();
+    let mut by_stem: HashMap<String, u64> = HashMap::new();
+    let mut cells: HashMap<(&str, String), u64> = HashMap::new();
+    for (form, lemma) in &lemma_of {
+        let stem = stemmer.stem(form);
+        *by_lemma.entry(lemma).or_insert(0) += 1;
+        *by_stem.entry(stem.clone()).or_insert(0) += 1;
+        *cells.entry((lemma, stem)).or_insert(0) += 1;
+    }
+    // the pairs of a group of n forms are (n² - n) / 2, and summed over the groups of a
+    // partition of the forms (Σn² - Σn) / 2: the pairs of the lemmas not within one
+    // stem, those of the stems not within one lemma, and so on, are differences of
+    // these sums of squares
+    let words = lemma_of.len() as u64;
+    let lemma_squares = squares(by_lemma.values());
+    let stem_squares = squares(by_stem.values());
+    let cell_squares = squares(cells.values());
+    EvalReport {
+        words,
+        lemmas: by_lemma.len() as u64,
+        stems: by_stem.len() as u64,
+        desired_merges: (lemma_squares - words) / 2,
+        unachieved_merges: (lemma_squares - cell_squares) / 2,
+        desired_non_merges: (words * words - lemma_squares) / 2,
+        wrong_merges: (stem_squares - cell_squares) / 2,
+        merges: (stem_squares - words) / 2,
+    }
+}
+
+fn squares<'a>(counts: impl Iterator<Item = &'a u64>) -> u64 {
+    counts.map(|n| n * n).sum()
+}
+
+#[test]
+fn evaluate_test() {
+    use crate::Mode;
+
+    let gold: Vec<(String, String)> = [
+        ("книга", "книга"),
+        ("книги", "книга"),
+        ("книгою", "книга"),
+        ("люди", "людина"),
+        ("людина", "людина"),
+        ("людей", "людина"),
+        ("вода", "вода"),
+        ("водою", "вода"),
+        ("водій", "водій"),
+        ("водія", "водій"),
+        ("читали", "читати"),
+        ("читати", "читати"),
+        // a form keeps its first lemma
+        ("книги", "книжка"),
+    ]
+    .iter()
+    .map(|(form, lemma)| (form.to_string(), lemma.to_string()))
+    .collect();
+
+    // "люди" and "людей" → "люд" miss "людина" → "людин"; "вод" merges "вода" and "водій"
+    let report = evaluate(&Stemmer::new(), &gold);
+    assert_eq!((report.words, report.lemmas, report.stems), (12, 5, 5));
+    assert_eq!((report.desired_merges, report.unachieved_merges), (9, 2));
+    assert_eq!((report.desired_non_merges, report.wrong_merges), (57, 4));
+    assert_eq!(report.merges, 11);
+    assert_eq!(report.understemming_index(), 2.0 / 9.0);
+    assert_eq!(report.overstemming_index(), 4.0 / 57.0);
+    assert_eq!(report.precision(), 7.0 / 11.0);
+    assert_eq!(report.recall(), 7.0 / 9.0);
+
+    // light stemming keeps "читал" and "читат" apart
+    let light = evaluate(&Stemmer::builder().mode(Mode::Light).build(), &gold);
+    assert_eq!(light.unachieved_merges, 3);
+    assert!(light.understemming_index() > report.understemming_index());
+    assert!(light.recall() < report.recall());
+    assert!(light
+        .to_string()
+        .starts_with("12 words, 5 lemmas, 6 stems: UI 0.3333"));
+
+    let empty = evaluate(&Stemmer::new(), &[]);
+    assert_eq!((empty.precision(), empty.recall()), (1.0, 1.0));
+    assert_eq!(empty.understemming_index(), 0.0);
+}
+
+#[test]
+#[ignore]
+fn ud_eval_test() {
+    use crate::corpus::{fetch, read_conllu_file, UD_UKRAINIAN_IU};
+    use crate::Mode;
+
+    let pairs = read_conllu_file(fetch(&UD_UKRAINIAN_IU).unwrap()).unwrap();
+    let aggressive = evaluate(&Stemmer::new(), &pairs);
+    let light = evaluate(&Stemmer::builder().mode(Mode::Light).build(), &pairs);
+    let report = format!("aggressive: {}, light: {}", aggressive, light);
+    assert!(
+        light.understemming_index() > aggressive.understemming_index(),
+        "{}",
+        report
+    );
+    assert!(
+        light.overstemming_index() < aggressive.overstemming_index(),
+        "{}",
+        report
+    );
+}
@@ -0,0 +1,373 @@
+use std::fmt;
+use std::ops::Range;
+
+use stemmer_uk_core::suffix;
+
+use crate::analyzer::{Analyzer, Term};
+use crate::homoglyph;
+use crate::rules::RuleHit;
+use crate::tokenizer::{Script, TokenKind};
+use crate::StemOutcome;
+
+/// A step of the analysis chain that changed a token on its way to the term.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Normalization {
+    /// Latin lookalikes in a Cyrillic word were mapped to Cyrillic.
+    Homoglyph,
+    /// A transliterated Latin word was converted to Cyrillic.
+    Translit,
+    /// An abbreviation was replaced by its expansion.
+    Abbreviation,
+    /// Several words were joined into a phrase term.
+    Phrase,
+    /// The stem came from the exception dictionary.
+    Exception,
+    /// The word is protected and was not stemmed.
+    Protected,
+}
+
+/// A document token that produced one of the query terms.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenMatch {
+    /// Index into `MatchExplanation::query`.
+    pub query_term: usize,
+    /// Byte range of the token in the document.
+    pub span: Range<usize>,
+    pub text: String,
+    pub normalizations: Vec<Normalization>,
+}
+
+/// A query term and how the query token was normalized to it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryTerm {
+    pub text: String,
+    pub term: String,
+    pub normalizations: Vec<Normalization>,
+}
+
+/// Why a document does or does not match a query, see `explain_with`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MatchExplanation {
+    pub query: Vec<QueryTerm>,
+    /// In document order.
+    pub matches: Vec<TokenMatch>,
+}
+
+impl MatchExplanation {
+    /// Indexes of the query terms no document token produced.
+    pub fn missing(&self) -> Vec<usize> {
+        (0..self.query.len())
+            .filter(|i| !self.matches.iter().any(|m| m.query_term == *i))
+            .collect()
+    }
+
+    /// Whether every query term occurs in the document.
+    pub fn matches_all(&self) -> bool {
+        self.missing().is_empty()
+    }
+}
+
+fn normalizations(analyzer: &Analyzer, terms: &[Term], i: usize) -> Vec<Normalization> {
+    let Term { token, term } = &terms[i];
+    let mut res = Vec::new();
+    if token.kind != TokenKind::Word {
+        return res;
+    }
+    let stemmer = analyzer.get_stemmer();
+    let options = stemmer.options();
+    if token.text != homoglyph::repair(token.text, options.homoglyphs) {
+        res.push(Normalization::Homoglyph);
+    }
+    if token.script() == Script::Latin && term.chars().any(|c| c.is_alphabetic() && !c.is_ascii()) {
+        res.push(Normalization::Translit);
+    }
+    let shares_token = |j: usize| terms.get(j).is_some_and(|t| t.token == *token);
+    if token.text.ends_with('.') || shares_token(i + 1) || i > 0 && shares_token(i - 1) {
+        res.push(Normalization::Abbreviation);
+    } else if token.text.contains(char::is_whitespace) {
+        res.push(Normalization::Phrase);
+    } else {
+        let normalized = stemmer.normalize(token.text);
+        if options
+            .exceptions
+            .as_ref()
+            .is_some_and(|d| d.contains(&normalized))
+        {
+            res.push(Normalization::Exception);
+        } else if options
+            .protected
+            .as_ref()
+            .is_some_and(|w| w.contains(&normalized))
+        {
+            res.push(Normalization::Protected);
+        }
+    }
+    res
+}
+
+/// `explain_with` using the default analyzer.
+pub fn explain(query: &str, document: &str) -> MatchExplanation {
+    explain_with(&Analyzer::new(), query, document)
+}
+
+/// Analyzes `query` and `document` with `analyzer` and reports which document tokens
+/// produced which query terms, and the normalizations involved on both sides.
+pub fn explain_with(analyzer: &Analyzer, query: &str, document: &str) -> MatchExplanation {
+    let query_terms = analyzer.analyze(query);
+    let mut res = MatchExplanation::default();
+    for i in 0..query_terms.len() {
+        let term = &query_terms[i];
+        if res.query.iter().any(|q| q.term == term.term) {
+            continue;
+        }
+        res.query.push(QueryTerm {
+            text: term.token.text.to_string(),
+            term: term.term.clone(),
+            normalizations: normalizations(analyzer, &query_terms, i),
+        });
+    }
+
+    let doc_terms = analyzer.analyze(document);
+    for i in 0..doc_terms.len() {
+        let Term { token, term } = &doc_terms[i];
+        if let Some(query_term) = res.query.iter().position(|q| q.term == *term) {
+            res.matches.push(TokenMatch {
+                query_term,
+                span: token.start..token.end,
+                text: token.text.to_string(),
+                normalizations: normalizations(analyzer, &doc_terms, i),
+            });
+        }
+    }
+    res
+}
+
+/// A rule that changed the word, and the form it left.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "SerdeStep", try_from = "SerdeStep")
+)]
+pub struct TraceStep {
+    pub hit: RuleHit,
+    pub form: String,
+}
+
+// `TraceStep` with the fields of its `RuleHit` inline, whose rule names and replacements
+// are `&'static str` and so are looked up when deserialized
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerdeStep {
+    rule: String,
+    suffix: String,
+    replacement: String,
+    pass: usize,
+    form: String,
+}
+
+#[cfg(feature = "serde")]
+impl From<TraceStep> for SerdeStep {
+    fn from(step: TraceStep) -> Self {
+        SerdeStep {
+            rule: step.hit.rule.to_string(),
+            suffix: step.hit.suffix,
+            replacement: step.hit.replacement.to_string(),
+            pass: step.hit.pass,
+            form: step.form,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<SerdeStep> for TraceStep {
+    type Error = String;
+
+    fn try_from(step: SerdeStep) -> Result<Self, String> {
+        let rule = crate::rules::ALL
+            .iter()
+            .find(|rule| **rule == step.rule)
+            .ok_or_else(|| format!("unknown rule group {:?}", step.rule))?;
+        let replacement = match step.replacement.as_str() {
+            "" => "",
+            "н" => "н",
+            other => return Err(format!("unknown replacement {:?}", other)),
+        };
+        Ok(TraceStep {
+            hit: RuleHit {
+                rule,
+                suffix: step.suffix,
+                replacement,
+                pass: step.pass,
+            },
+            form: step.form,
+        })
+    }
+}
+
+/// How a word was stemmed, see `Stemmer::stem_explained`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StemTrace {
+    /// The word as the rules see it, after normalization.
+    pub normalized: String,
+    /// Byte offset in `normalized` of RV, the part after the first vowel, which is all
+    /// the rules may remove; `None` when the word has no vowel.
+    pub rv_start: Option<usize>,
+    /// The rules that changed the word, in the order they applied.
+    pub steps: Vec<TraceStep>,
+    pub outcome: StemOutcome,
+}
+
+impl StemTrace {
+    pub(crate) fn new(normalized: String, hits: Vec<RuleHit>, outcome: StemOutcome) -> Self {
+        let mut form = normalized.clone();
+        let steps = hits
+            .into_iter()
+            .map(|hit| {
+                // the rules match before a final newline and keep it
+                let newline = form.ends_with('\n') && !hit.suffix.ends_with('\n');
+                let end = form.len() - newline as usize;
+                form.truncate(end.saturating_sub(hit.suffix.len()));
+                form.push_str(hit.replacement);
+                if newline {
+                    form.push('\n');
+                }
+                TraceStep {
+                    hit,
+                    form: form.clone(),
+                }
+            })
+            .collect();
+        StemTrace {
+            rv_start: suffix::rv_start(&normalized),
+            normalized,
+            steps,
+            outcome,
+        }
+    }
+
+    pub fn stem(&self) -> &str {
+        self.outcome.stem()
+    }
+
+    /// The part of the word before RV, which the rules never change.
+    pub fn prefix(&self) -> &str {
+        &self.normalized[..self.rv_start.unwrap_or(self.normalized.len())]
+    }
+
+    pub fn rv(&self) -> &str {
+        &self.normalized[self.prefix().len()..]
+    }
+}
+
+/// One line for bug reports: `"ручкається" → ру|чкається, reflexive -ся → ручкаєть, … =
+/// ручкаєт`.
+impl fmt::Display for StemTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} → {}|{}", self.normalized, self.prefix(), self.rv())?;
+        for step in &self.steps {
+            write!(f, ", {} -{}", step.hit.rule, step.hit.suffix)?;
+            if !step.hit.replacement.is_empty() {
+                write!(f, "+{}", step.hit.replacement)?;
+            }
+            if step.hit.pass > 0 {
+                write!(f, " (pass {})", step.hit.pass + 1)?;
+            }
+            write!(f, " → {}", step.form)?;
+        }
+        match &self.outcome {
+            StemOutcome::Stemmed(stem) => write!(f, " = {}", stem),
+            StemOutcome::Listed(stem) => write!(f, " = {} (listed)", stem),
+            StemOutcome::Vowelless(stem) => write!(f, " = {} (no vowel)", stem),
+            StemOutcome::Abbreviation(stem) => write!(f, " = {} (abbreviation)", stem),
+            StemOutcome::Foreign(stem) => write!(f, " = {} (foreign)", stem),
+            StemOutcome::Acronym(stem) => write!(f, " = {} (acronym)", stem),
+        }
+    }
+}
+
+#[test]
+fn stem_trace_test() {
+    use crate::rules;
+
+    let trace = crate::stem_word_explained("Ручкається");
+    assert_eq!(trace.normalized, "ручкається");
+    assert_eq!((trace.prefix(), trace.rv()), ("ру", "чкається"));
+    let steps: Vec<(&str, &str)> = trace
+        .steps
+        .iter()
+        .map(|s| (s.hit.rule, s.form.as_str()))
+        .collect();
+    assert_eq!(
+        steps,
+        [(rules::REFLEXIVE, "ручкаєть"), (rules::NOUN, "ручкаєт")]
+    );
+    assert_eq!(trace.stem(), crate::stem_word("Ручкається".into()));
+    assert_eq!(
+        trace.to_string(),
+        "\"ручкається\" → ру|чкається, reflexive -ся → ручкаєть, noun -ь → ручкаєт = ручкаєт"
+    );
+
+    let trace = crate::stem_word_explained("грн");
+    assert_eq!((trace.rv_start, trace.steps.len()), (None, 0));
+    assert!(trace.to_string().ends_with("(no vowel)"));
+
+    // the final forms are the stems, also with the newline quirk
+    for word in ["туманність", "бездонність\n", "найсильнішої", "книгами\n"].iter()
+    {
+        let trace = crate::stem_word_explained(word);
+        let last = trace.steps.last().map_or(&trace.normalized, |s| &s.form);
+        assert_eq!(last, trace.stem(), "{:?}", word);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_trace_test() {
+    let trace = crate::Stemmer::new().stem_explained("ручкається");
+    let json = serde_json::to_value(&trace).unwrap();
+    assert_eq!(json["outcome"], serde_json::json!({ "stemmed": "ручкаєт" }));
+    assert_eq!(json["steps"][0]["rule"], "reflexive");
+    let restored: StemTrace = serde_json::from_value(json).unwrap();
+    assert_eq!(restored, trace);
+    let step = r#"{"rule": "nouns", "suffix": "и", "replacement": "", "pass": 0, "form": "x"}"#;
+    assert!(serde_json::from_str::<TraceStep>(step).is_err());
+}
+
+#[test]
+fn explain_test() {
+    use crate::homoglyph::HomoglyphRepair;
+    use crate::Stemmer;
+
+    let analyzer = Analyzer::new()
+        .stemmer(
+            Stemmer::builder()
+                .homoglyph_repair(HomoglyphRepair::Letters)
+                .build(),
+        )
+        .translit_repair(crate::analyzer::TranslitRepair::Token)
+        .abbreviation("ст.", "стаття");
+    let document = "Статті закону: вiкно, ст. 5, pryvit";
+    let explanation = explain_with(&analyzer, "вікнами статтю привіт люди", document);
+
+    assert_eq!(explanation.query.len(), 4);
+    assert_eq!(explanation.missing(), [3]);
+    assert!(!explanation.matches_all());
+
+    let matched: Vec<(&str, &[Normalization])> = explanation
+        .matches
+        .iter()
+        .map(|m| (m.text.as_str(), m.normalizations.as_slice()))
+        .collect();
+    assert_eq!(
+        matched,
+        [
+            ("Статті", &[][..]),
+            ("вiкно", &[Normalization::Homoglyph][..]),
+            ("ст.", &[Normalization::Abbreviation][..]),
+            ("pryvit", &[Normalization::Translit][..]),
+        ]
+    );
+    assert_eq!(&document[explanation.matches[1].span.clone()], "вiкно");
+}
@@ -0,0 +1,406 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::Analyzer;
+use crate::config_fingerprint;
+
+const MAGIC: &[u8; 4] = b"UKTR";
+const VERSION: u8 = 1;
+
+/// Sizes of an exported vocabulary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VocabStats {
+    pub stems: usize,
+    pub nodes: usize,
+    pub bytes: usize,
+}
+
+#[derive(Default)]
+struct Node {
+    terminal: bool,
+    children: BTreeMap<char, Node>,
+}
+
+pub(crate) fn push_varint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+pub(crate) fn read_varint(data: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut v = 0u64;
+    let mut shift = 0;
+    loop {
+        let b = *data.get(*pos)?;
+        *pos += 1;
+        v |= u64::from(b & 0x7f) << shift;
+        if b & 0x80 == 0 {
+            return Some(v);
+        }
+        shift += 7;
+        if shift > 63 {
+            return None;
+        }
+    }
+}
+
+// Nodes are written children first, so every child offset is known when its parent is
+// written; identical subtrees are stored once, which turns the trie into a DAWG.
+fn write_node(node: &Node, out: &mut Vec<u8>, seen: &mut HashMap<Vec<u8>, usize>) -> usize {
+    let children: Vec<(char, usize)> = node
+        .children
+        .iter()
+        .map(|(c, child)| (*c, write_node(child, out, seen)))
+        .collect();
+
+    let mut bytes = vec![node.terminal as u8];
+    push_varint(&mut bytes, children.len() as u64);
+    for (c, offset) in children {
+        push_varint(&mut bytes, u64::from(c as u32));
+        push_varint(&mut bytes, offset as u64);
+    }
+    if let Some(offset) = seen.get(&bytes) {
+        return *offset;
+    }
+    let offset = out.len();
+    out.extend_from_slice(&bytes);
+    seen.insert(bytes, offset);
+    offset
+}
+
+/// Serializes `stems` into the compact binary trie format read by `TrieVocab` and `JS_READER`.
+///
+/// Layout: `"UKTR"`, a version byte, the root node offset as little-endian `u32`, then the
+/// nodes. A node is a terminal flag byte, a varint child count and, per child in code point
+/// order, the varint code point and the varint absolute offset of the child node.
+pub fn trie_bytes<I, S>(stems: I) -> (Vec<u8>, VocabStats)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let unique: BTreeSet<String> = stems.into_iter().map(|s| s.as_ref().to_string()).collect();
+    let mut root = Node::default();
+    for stem in &unique {
+        let mut node = &mut root;
+        for c in stem.chars() {
+            node = node.children.entry(c).or_default();
+        }
+        node.terminal = true;
+    }
+
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    out.extend_from_slice(&[0; 4]);
+    let mut seen = HashMap::new();
+    let root_offset = write_node(&root, &mut out, &mut seen) as u32;
+    out[5..9].copy_from_slice(&root_offset.to_le_bytes());
+
+    let stats = VocabStats {
+        stems: unique.len(),
+        nodes: seen.len(),
+        bytes: out.len(),
+    };
+    (out, stats)
+}
+
+/// `s` as a JSON string literal, quoted and escaped.
+pub fn json_string(s: &str) -> String {
+    let mut res = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            c if (c as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+/// The manifest written next to `path` by `js_vocab`.
+pub fn manifest_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    path.as_ref().with_extension("json")
+}
+
+/// Writes the trie of `stems` to `path` and a JSON manifest (format, version, fingerprint of
+/// the default configuration, sizes and the data file name) to `path` with a `.json` extension.
+pub fn js_vocab<I, S, P>(stems: I, path: P) -> io::Result<VocabStats>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let (bytes, stats) = trie_bytes(stems);
+    fs::write(path, &bytes)?;
+
+    let file = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let manifest = format!(
+        "{{\"format\":\"ukstem-trie\",\"version\":{},\"fingerprint\":\"{}\",\"stems\":{},\"nodes\":{},\"bytes\":{},\"data\":{}}}\n",
+        VERSION,
+        config_fingerprint(),
+        stats.stems,
+        stats.nodes,
+        stats.bytes,
+        json_string(&file)
+    );
+    fs::write(manifest_path(path), manifest)?;
+    Ok(stats)
+}
+
+/// Reads a trie written by `trie_bytes` without copying it.
+pub struct TrieVocab<'a> {
+    data: &'a [u8],
+    root: usize,
+}
+
+impl<'a> TrieVocab<'a> {
+    pub fn from_bytes(data: &'a [u8]) -> Option<Self> {
+        if data.len() < 9 || &data[..4] != MAGIC || data[4] != VERSION {
+            return None;
+        }
+        let mut root = [0; 4];
+        root.copy_from_slice(&data[5..9]);
+        Some(TrieVocab {
+            data,
+            root: u32::from_le_bytes(root) as usize,
+        })
+    }
+
+    fn child(&self, node: usize, c: char) -> Option<usize> {
+        let mut pos = node + 1;
+        let count = read_varint(self.data, &mut pos)?;
+        for _ in 0..count {
+            let code = read_varint(self.data, &mut pos)?;
+            let offset = read_varint(self.data, &mut pos)?;
+            if code == u64::from(c as u32) {
+                return Some(offset as usize);
+            }
+        }
+        None
+    }
+
+    pub fn contains(&self, stem: &str) -> bool {
+        let mut node = self.root;
+        for c in stem.chars() {
+            match self.child(node, c) {
+                Some(next) => node = next,
+                None => return false,
+            }
+        }
+        self.data.get(node) == Some(&1)
+    }
+}
+
+/// A dependency-free browser reader for the trie format:
+/// `const vocab = ukstemVocab(await (await fetch("vocab.bin")).arrayBuffer()); vocab.has("автор")`.
+pub const JS_READER: &str = r#"function ukstemVocab(buffer) {
+  const data = new Uint8Array(buffer);
+  if (String.fromCharCode(data[0], data[1], data[2], data[3]) !== "UKTR" || data[4] !== 1) {
+    throw new Error("not an ukstem trie");
+  }
+  const root = new DataView(buffer).getUint32(5, true);
+  function varint(state) {
+    let v = 0, shift = 0, b;
+    do {
+      b = data[state.pos++];
+      v += (b & 0x7f) * Math.pow(2, shift);
+      shift += 7;
+    } while (b & 0x80);
+    return v;
+  }
+  function child(node, code) {
+    const state = { pos: node + 1 };
+    const count = varint(state);
+    for (let i = 0; i < count; i++) {
+      const c = varint(state), offset = varint(state);
+      if (c === code) return offset;
+    }
+    return -1;
+  }
+  return {
+    has(stem) {
+      let node = root;
+      for (const ch of stem) {
+        node = child(node, ch.codePointAt(0));
+        if (node < 0) return false;
+      }
+      return data[node] === 1;
+    }
+  };
+}
+"#;
+
+/// A search field and the weight of its term matches.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub boost: f64,
+}
+
+/// A lunr-style inverted index of stemmed terms for static sites.
+///
+/// `to_json` produces `{"fingerprint", "fields": [{name, boost}], "docs": [{ref, title}],
+/// "index": {term: [[doc, score], ...]}}` where `doc` indexes `docs` and `score` sums
+/// `boost * term frequency` over the fields. Queries must be stemmed with the same analyzer.
+pub struct SiteIndex {
+    analyzer: Analyzer,
+    fields: Vec<Field>,
+    docs: Vec<(String, String)>,
+    index: BTreeMap<String, BTreeMap<usize, f64>>,
+}
+
+impl SiteIndex {
+    pub fn new(analyzer: Analyzer, fields: Vec<Field>) -> Self {
+        SiteIndex {
+            analyzer,
+            fields,
+            docs: Vec::new(),
+            index: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a document; `values` holds the text of each field in the order they were declared,
+    /// missing values are treated as empty.
+    pub fn add(&mut self, doc_ref: &str, title: &str, values: &[&str]) {
+        let doc = self.docs.len();
+        self.docs.push((doc_ref.to_string(), title.to_string()));
+        for (field, text) in self.fields.iter().zip(values) {
+            for term in self.analyzer.terms(text) {
+                *self
+                    .index
+                    .entry(term)
+                    .or_default()
+                    .entry(doc)
+                    .or_insert(0.0) += field.boost;
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// `(ref, title)` of every document, in insertion order.
+    pub fn docs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.docs.iter().map(|(r, t)| (r.as_str(), t.as_str()))
+    }
+
+    /// Every term with the number of documents it occurs in.
+    pub fn postings(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.index
+            .iter()
+            .map(|(term, docs)| (term.as_str(), docs.len()))
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut out = format!(
+            "{{\"fingerprint\":\"{}\",\"fields\":[",
+            self.analyzer.fingerprint()
+        );
+        for (i, field) in self.fields.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str(&format!(
+                "{{\"name\":{},\"boost\":{}}}",
+                json_string(&field.name),
+                field.boost
+            ));
+        }
+        out.push_str("],\"docs\":[");
+        for (i, (doc_ref, title)) in self.docs.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str(&format!(
+                "{{\"ref\":{},\"title\":{}}}",
+                json_string(doc_ref),
+                json_string(title)
+            ));
+        }
+        out.push_str("],\"index\":{");
+        for (i, (term, postings)) in self.index.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str(&json_string(term));
+            out.push_str(":[");
+            for (j, (doc, score)) in postings.iter().enumerate() {
+                if j > 0 {
+                    out.push(',');
+                }
+                out.push_str(&format!("[{},{}]", doc, score));
+            }
+            out.push(']');
+        }
+        out.push_str("}}");
+        out
+    }
+}
+
+#[test]
+fn trie_roundtrip_test() {
+    let stems = ["автор", "автомобіл", "народ", "нар", "автор"];
+    let (bytes, stats) = trie_bytes(stems.iter());
+    assert_eq!(stats.stems, 4);
+
+    let vocab = TrieVocab::from_bytes(&bytes).unwrap();
+    for stem in &stems {
+        assert!(vocab.contains(stem));
+    }
+    assert!(!vocab.contains("авто"));
+    assert!(!vocab.contains("народи"));
+    assert!(!vocab.contains(""));
+}
+
+#[test]
+fn js_vocab_test() {
+    let path = std::env::temp_dir().join(format!("ukstem-vocab-{}.bin", std::process::id()));
+    let stats = js_vocab(["слідств", "народ"], &path).unwrap();
+
+    let manifest = fs::read_to_string(manifest_path(&path)).unwrap();
+    assert!(manifest.contains("\"stems\":2"));
+    assert!(manifest.contains(&config_fingerprint().to_string()));
+    assert_eq!(fs::read(&path).unwrap().len(), stats.bytes);
+
+    fs::remove_file(manifest_path(&path)).unwrap();
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn site_index_test() {
+    let fields = vec![
+        Field {
+            name: "title".into(),
+            boost: 10.0,
+        },
+        Field {
+            name: "body".into(),
+            boost: 1.0,
+        },
+    ];
+    let mut index = SiteIndex::new(Analyzer::new(), fields);
+    index.add("a.md", "Народи", &["Народи", "Про народ і автора"]);
+    index.add("b.md", "Автор", &["Автор"]);
+
+    let json = index.to_json();
+    assert!(json.contains(
+        "\"docs\":[{\"ref\":\"a.md\",\"title\":\"Народи\"},{\"ref\":\"b.md\",\"title\":\"Автор\"}]"
+    ));
+    assert!(json.contains("\"народ\":[[0,11]]"));
+    assert!(json.contains("\"автор\":[[0,1],[1,10]]"));
+}
@@ -0,0 +1,108 @@
+//! Extension traits for stemming strings and iterators of words without naming a
+//! stemmer: `"книгами".stem_uk()` and `words.iter().stems()`.
+
+use std::borrow::Cow;
+
+use crate::stem::Stem;
+use crate::Stemmer;
+
+/// Stems a string, borrowing it when the stem is a prefix of it.
+pub trait StrStemExt {
+    /// The stem with the default configuration, like `stemmer_uk::stem`.
+    fn stem_uk(&self) -> Cow<'_, str>;
+
+    /// The stem with `stemmer`.
+    fn stem_uk_with(&self, stemmer: &Stemmer) -> Cow<'_, str>;
+}
+
+impl StrStemExt for str {
+    fn stem_uk(&self) -> Cow<'_, str> {
+        crate::stem(self)
+    }
+
+    fn stem_uk_with(&self, stemmer: &Stemmer) -> Cow<'_, str> {
+        Stem::stem(stemmer, self)
+    }
+}
+
+/// Adapters stemming the words of an iterator of borrowed strings lazily, e.g. of
+/// `str::split` or `Vec<String>::iter`.
+pub trait StemsExt<'a>: Iterator + Sized {
+    /// The stems with the default configuration.
+    fn stems(self) -> Stems<'static, Self>;
+
+    /// The stems with `stemmer`.
+    fn stems_with(self, stemmer: &Stemmer) -> Stems<'_, Self>;
+}
+
+impl<'a, I, S> StemsExt<'a> for I
+where
+    I: Iterator<Item = &'a S>,
+    S: AsRef<str> + ?Sized + 'a,
+{
+    fn stems(self) -> Stems<'static, Self> {
+        Stems {
+            words: self,
+            stemmer: None,
+        }
+    }
+
+    fn stems_with(self, stemmer: &Stemmer) -> Stems<'_, Self> {
+        Stems {
+            words: self,
+            stemmer: Some(stemmer),
+        }
+    }
+}
+
+/// Iterator returned by `StemsExt::stems` and `StemsExt::stems_with`.
+#[derive(Clone, Debug)]
+pub struct Stems<'s, I> {
+    words: I,
+    // the default configuration when `None`
+    stemmer: Option<&'s Stemmer>,
+}
+
+impl<'a, I, S> Iterator for Stems<'_, I>
+where
+    I: Iterator<Item = &'a S>,
+    S: AsRef<str> + ?Sized + 'a,
+{
+    type Item = Cow<'a, str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let word = self.words.next()?.as_ref();
+        Some(match self.stemmer {
+            Some(stemmer) => word.stem_uk_with(stemmer),
+            None => word.stem_uk(),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.words.size_hint()
+    }
+}
+
+#[test]
+fn str_stem_ext_test() {
+    assert!(matches!("книгами".stem_uk(), Cow::Borrowed("книг")));
+    assert_eq!("Народами".stem_uk(), "народ");
+    let stemmer = Stemmer::builder().preserve_case(true).build();
+    assert_eq!("Народами".stem_uk_with(&stemmer), "Народ");
+}
+
+#[test]
+fn stems_ext_test() {
+    let stems: Vec<Cow<str>> = "Народи автора книгами".split(' ').stems().collect();
+    assert_eq!(stems, ["народ", "автор", "книг"]);
+    let tokens: Vec<String> = ["книгами", "Автора"]
+        .iter()
+        .map(|w| w.to_string())
+        .collect();
+    let stems: Vec<Cow<str>> = tokens.iter().stems().collect();
+    assert!(matches!(stems[0], Cow::Borrowed("книг")));
+    assert_eq!(stems[1], "автор");
+    let stemmer = Stemmer::builder().preserve_case(true).build();
+    let stems: Vec<Cow<str>> = tokens.iter().stems_with(&stemmer).collect();
+    assert_eq!(stems, ["книг", "Автор"]);
+}
@@ -0,0 +1,175 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::Stemmer;
+
+static RUN_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Computes the sorted set of unique stems of a word list that may not fit in memory.
+///
+/// Stems are collected into in-memory sets of at most `chunk_stems` entries, each spilled
+/// to a sorted run file in `tmp_dir`, and the runs are merged into `out` one line per stem.
+/// Blank lines are skipped. Run files are removed afterwards, also on error.
+pub struct ExternalDedup<'a> {
+    stemmer: &'a Stemmer,
+    chunk_stems: usize,
+    tmp_dir: PathBuf,
+}
+
+impl<'a> ExternalDedup<'a> {
+    pub fn new(stemmer: &'a Stemmer) -> Self {
+        ExternalDedup {
+            stemmer,
+            chunk_stems: 1_000_000,
+            tmp_dir: std::env::temp_dir(),
+        }
+    }
+
+    pub fn chunk_stems(mut self, chunk_stems: usize) -> Self {
+        self.chunk_stems = chunk_stems.max(1);
+        self
+    }
+
+    pub fn tmp_dir<P: AsRef<Path>>(mut self, tmp_dir: P) -> Self {
+        self.tmp_dir = tmp_dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Returns the number of unique stems written.
+    pub fn run<R: BufRead, W: Write>(&self, input: R, out: W) -> io::Result<usize> {
+        let mut runs = Vec::new();
+        let res = self
+            .spill_runs(input, &mut runs)
+            .and_then(|_| merge_runs(&runs, out));
+        for run in &runs {
+            let _ = fs::remove_file(run);
+        }
+        res
+    }
+
+    fn spill_runs<R: BufRead>(&self, input: R, runs: &mut Vec<PathBuf>) -> io::Result<()> {
+        let mut chunk = BTreeSet::new();
+        for line in input.lines() {
+            let line = line?;
+            let word = line.trim();
+            if word.is_empty() {
+                continue;
+            }
+            chunk.insert(self.stemmer.stem(word));
+            if chunk.len() >= self.chunk_stems {
+                runs.push(self.write_run(&chunk)?);
+                chunk.clear();
+            }
+        }
+        if !chunk.is_empty() {
+            runs.push(self.write_run(&chunk)?);
+        }
+        Ok(())
+    }
+
+    fn write_run(&self, chunk: &BTreeSet<String>) -> io::Result<PathBuf> {
+        let id = RUN_ID.fetch_add(1, Ordering::Relaxed);
+        let path = self
+            .tmp_dir
+            .join(format!("ukstem-run-{}-{}.txt", std::process::id(), id));
+        let mut file = BufWriter::new(File::create(&path)?);
+        for stem in chunk {
+            writeln!(file, "{}", stem)?;
+        }
+        file.flush()?;
+        Ok(path)
+    }
+}
+
+fn merge_runs<W: Write>(runs: &[PathBuf], out: W) -> io::Result<usize> {
+    let mut out = BufWriter::new(out);
+    let mut readers = Vec::with_capacity(runs.len());
+    let mut heap = BinaryHeap::new();
+    for (i, run) in runs.iter().enumerate() {
+        let mut lines = BufReader::new(File::open(run)?).lines();
+        if let Some(line) = lines.next() {
+            heap.push(Reverse((line?, i)));
+        }
+        readers.push(lines);
+    }
+
+    let mut last: Option<String> = None;
+    let mut written = 0;
+    while let Some(Reverse((stem, i))) = heap.pop() {
+        if let Some(line) = readers[i].next() {
+            heap.push(Reverse((line?, i)));
+        }
+        if last.as_ref() != Some(&stem) {
+            writeln!(out, "{}", stem)?;
+            written += 1;
+            last = Some(stem);
+        }
+    }
+    out.flush()?;
+    Ok(written)
+}
+
+#[test]
+fn external_dedup_test() {
+    let words = "автора\nавтором\n\nнароди\nнародами\nавтор\nслідство\n";
+    let stemmer = Stemmer::new();
+    let mut out = Vec::new();
+    let written = ExternalDedup::new(&stemmer)
+        .chunk_stems(2)
+        .run(words.as_bytes(), &mut out)
+        .unwrap();
+
+    assert_eq!(written, 3);
+    assert_eq!(String::from_utf8(out).unwrap(), "автор\nнарод\nслідств\n");
+}
+
+#[test]
+fn run_files_test() {
+    // counts the run files when the merge starts writing
+    struct Probe<'d> {
+        dir: &'d Path,
+        runs: Option<usize>,
+        out: Vec<u8>,
+    }
+    impl Write for Probe<'_> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.runs.is_none() {
+                self.runs = Some(fs::read_dir(self.dir)?.count());
+            }
+            self.out.write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let dir = std::env::temp_dir().join(format!("ukstem-runs-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let words = "слова\nнароди\nавтора\nнародами\nкнигами\nавтором\nслово\n";
+    let stemmer = Stemmer::new();
+    let dedup = ExternalDedup::new(&stemmer).chunk_stems(1).tmp_dir(&dir);
+    let mut probe = Probe {
+        dir: &dir,
+        runs: None,
+        out: Vec::new(),
+    };
+    assert_eq!(dedup.run(words.as_bytes(), &mut probe).unwrap(), 4);
+    assert_eq!(
+        String::from_utf8(probe.out).unwrap(),
+        "автор\nкниг\nнарод\nслов\n"
+    );
+    // one run per word, as each run holds a single stem
+    assert_eq!(probe.runs, Some(7));
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+    // the runs written before an error are removed too
+    let failing = BufReader::new(io::Read::chain(words.as_bytes(), &b"\xff\n"[..]));
+    assert!(dedup.run(failing, io::sink()).is_err());
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
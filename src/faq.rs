@@ -0,0 +1,92 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::analyzer::Analyzer;
+use crate::stopwords::is_stopword;
+use crate::tokenizer::TokenKind;
+
+/// A candidate question and how well it matches the query.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuestionMatch {
+    /// Index into the candidate list.
+    pub index: usize,
+    /// Cosine similarity of the IDF-weighted stem sets, in `0.0..=1.0`.
+    pub score: f64,
+}
+
+fn stems(analyzer: &Analyzer, text: &str) -> HashSet<String> {
+    analyzer
+        .analyze(text)
+        .into_iter()
+        .filter(|t| t.token.kind == TokenKind::Word && !is_stopword(t.token.text))
+        .map(|t| t.term)
+        .collect()
+}
+
+/// Ranks `candidates` by similarity to `query`, best first, leaving out candidates that
+/// share no stem with it. See `match_questions_with`.
+pub fn match_questions<S: AsRef<str>>(query: &str, candidates: &[S]) -> Vec<QuestionMatch> {
+    match_questions_with(&Analyzer::new(), query, candidates)
+}
+
+/// Ranks `candidates` by similarity to `query` using `analyzer`.
+///
+/// Stop words are ignored and the remaining stems are weighted by their inverse document
+/// frequency among the candidates, so rare stems count more than ones every question
+/// shares. Scores are normalized by the weight of both stem sets, so long candidates are
+/// not favoured. Ties keep the candidate order.
+pub fn match_questions_with<S: AsRef<str>>(
+    analyzer: &Analyzer,
+    query: &str,
+    candidates: &[S],
+) -> Vec<QuestionMatch> {
+    let docs: Vec<HashSet<String>> = candidates
+        .iter()
+        .map(|c| stems(analyzer, c.as_ref()))
+        .collect();
+    let mut df: HashMap<&str, usize> = HashMap::new();
+    for doc in &docs {
+        for stem in doc {
+            *df.entry(stem).or_insert(0) += 1;
+        }
+    }
+    let n = docs.len() as f64;
+    let idf =
+        |stem: &str| ((n + 1.0) / (df.get(stem).copied().unwrap_or(0) as f64 + 1.0)).ln() + 1.0;
+    let norm = |doc: &HashSet<String>| doc.iter().map(|s| idf(s).powi(2)).sum::<f64>().sqrt();
+
+    let query = stems(analyzer, query);
+    let query_norm = norm(&query);
+    let mut matches: Vec<QuestionMatch> = docs
+        .iter()
+        .enumerate()
+        .filter_map(|(index, doc)| {
+            let shared: f64 = query.intersection(doc).map(|s| idf(s).powi(2)).sum();
+            if shared == 0.0 {
+                return None;
+            }
+            Some(QuestionMatch {
+                index,
+                score: shared / (query_norm * norm(doc)),
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    matches
+}
+
+#[test]
+fn match_questions_test() {
+    let faq = [
+        "Як змінити пароль до облікового запису?",
+        "Де знайти історію замовлень?",
+        "Як скасувати замовлення?",
+        "Чи можна змінити адресу доставки замовлення?",
+    ];
+    let ranked = match_questions("як мені скасувати своє замовлення", &faq);
+    assert_eq!(ranked[0].index, 2);
+    assert!(ranked.iter().all(|m| m.index != 0));
+    assert!(ranked.windows(2).all(|w| w[0].score >= w[1].score));
+
+    assert_eq!(match_questions("змінили паролі", &faq)[0].index, 0);
+    assert!(match_questions("що це", &faq).is_empty());
+}
@@ -0,0 +1,61 @@
+use std::borrow::Cow;
+
+// Feminitive stem endings and the endings of the masculine stems they are formed from, as
+// the rules leave them: "директорк" of "директорка" is "директор", the stem of the
+// genitive plural "авторок" is "автор".
+const ENDINGS: [(&str, &str); 20] = [
+    ("орок", "ор"),
+    ("орк", "ор"),
+    ("ерок", "ер"),
+    ("ерк", "ер"),
+    ("арок", "ар"),
+    ("арк", "ар"),
+    ("аток", "ат"),
+    ("атк", "ат"),
+    ("нток", "нт"),
+    ("нтк", "нт"),
+    ("рток", "рт"),
+    ("ртк", "рт"),
+    ("исток", "ист"),
+    ("истк", "ист"),
+    ("тельк", "тел"),
+    ("етес", "ет"),
+    ("огин", "ог"),
+    ("офин", "оф"),
+    ("афин", "аф"),
+    ("кин", ""),
+];
+
+// the fewest chars a conflated stem keeps, so that short stems like "парк" stay
+const MIN_BASE_CHARS: usize = 4;
+
+/// The masculine stem `stem` is the feminitive of, so that "авторка" and "автор" share a
+/// stem; other stems are returned unchanged. The endings are matched without a lexicon,
+/// so a few words that only look like feminitives, like "картка", are conflated too.
+pub fn conflate(stem: &str) -> Cow<'_, str> {
+    for (ending, base) in ENDINGS.iter() {
+        if let Some(root) = stem.strip_suffix(ending) {
+            if root.chars().count() + base.chars().count() >= MIN_BASE_CHARS {
+                return Cow::Owned(format!("{}{}", root, base));
+            }
+        }
+    }
+    Cow::Borrowed(stem)
+}
+
+#[test]
+fn conflate_test() {
+    for (feminitive, stem) in [
+        ("директорк", "директор"),
+        ("членкин", "член"),
+        ("авторок", "автор"),
+        ("вчительк", "вчител"),
+        ("психологин", "психолог"),
+        ("поетес", "поет"),
+        ("студенток", "студент"),
+    ] {
+        assert_eq!(conflate(feminitive), stem);
+    }
+    assert!(matches!(conflate("книг"), Cow::Borrowed("книг")));
+    assert_eq!(conflate("парк"), "парк");
+}
@@ -0,0 +1,521 @@
+//! C ABI for embedding the stemmer in mobile and other non-Rust applications.
+//!
+//! Memory contract: inputs are borrowed `UkstemStr` views that must stay valid for the
+//! duration of the call only. Every `UkstemString` and `UkstemTokens` handed out is owned
+//! by the caller and must be released exactly once with `ukstem_string_free` or
+//! `ukstem_tokens_free`; the outputs are never NUL-terminated. A stemmer handle is
+//! immutable after creation and may be shared between threads freely; it is freed with
+//! `ukstem_stemmer_free` once no call is using it.
+//!
+//! For hosts that work with C strings, such as PostgreSQL extensions and SQLite FTS
+//! tokenizers, `uk_stem` and `uk_stem_batch` stem with the default configuration into
+//! NUL-terminated strings, freed with `uk_stem_free` and `uk_stem_batch_free`.
+//!
+//! No function panics across the boundary: every entry point runs under `catch_unwind`,
+//! and failures are reported as status codes, `UKSTEM_PANIC` for a bug of this library,
+//! after which the handle can still be used. This holds as long as the host links a
+//! build with the default `panic = "unwind"`; under `panic = "abort"` a bug aborts the
+//! process. The declarations for C, Swift and Kotlin/Native are in `include/ukstem.h`,
+//! generated with `cbindgen --config cbindgen.toml --output include/ukstem.h`.
+
+use std::ffi::{c_char, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::str;
+
+use crate::tokenizer::{tokenize, TokenKind};
+use crate::Stemmer;
+
+pub const UKSTEM_OK: i32 = 0;
+pub const UKSTEM_NULL_POINTER: i32 = 1;
+pub const UKSTEM_INVALID_UTF8: i32 = 2;
+pub const UKSTEM_PANIC: i32 = 3;
+/// A C string can't hold the stem: the word, which is valid UTF-8, contains a NUL.
+pub const UKSTEM_INTERIOR_NUL: i32 = 4;
+
+// token kinds of `UkstemToken::kind`
+pub const UKSTEM_WORD: u32 = 0;
+pub const UKSTEM_NUMBER: u32 = 1;
+pub const UKSTEM_WHITESPACE: u32 = 2;
+pub const UKSTEM_PUNCTUATION: u32 = 3;
+pub const UKSTEM_EMOJI: u32 = 4;
+pub const UKSTEM_EMOTICON: u32 = 5;
+pub const UKSTEM_CONTROL: u32 = 6;
+pub const UKSTEM_TAG: u32 = 7;
+pub const UKSTEM_OTHER: u32 = 8;
+
+/// Borrowed UTF-8 input.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct UkstemStr {
+    pub ptr: *const u8,
+    pub len: usize,
+}
+
+/// Owned UTF-8 output, free with `ukstem_string_free`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct UkstemString {
+    pub ptr: *mut u8,
+    pub len: usize,
+}
+
+/// A token as a byte range of the input text; `kind` follows the `TokenKind` order.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UkstemToken {
+    pub kind: u32,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Owned token array, free with `ukstem_tokens_free`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct UkstemTokens {
+    pub ptr: *mut UkstemToken,
+    pub len: usize,
+}
+
+impl UkstemString {
+    fn new(s: String) -> Self {
+        let bytes = Box::into_raw(s.into_bytes().into_boxed_slice());
+        UkstemString {
+            ptr: bytes as *mut u8,
+            len: bytes.len(),
+        }
+    }
+}
+
+fn kind_code(kind: TokenKind) -> u32 {
+    match kind {
+        TokenKind::Word => UKSTEM_WORD,
+        TokenKind::Number => UKSTEM_NUMBER,
+        TokenKind::Whitespace => UKSTEM_WHITESPACE,
+        TokenKind::Punctuation => UKSTEM_PUNCTUATION,
+        TokenKind::Emoji => UKSTEM_EMOJI,
+        TokenKind::Emoticon => UKSTEM_EMOTICON,
+        TokenKind::Control => UKSTEM_CONTROL,
+        TokenKind::Tag => UKSTEM_TAG,
+        TokenKind::Other => UKSTEM_OTHER,
+    }
+}
+
+// safety: `s.ptr` is null or valid for reads of `s.len` bytes
+unsafe fn as_str<'a>(s: UkstemStr) -> Result<&'a str, i32> {
+    if s.ptr.is_null() {
+        return if s.len == 0 {
+            Ok("")
+        } else {
+            Err(UKSTEM_NULL_POINTER)
+        };
+    }
+    str::from_utf8(std::slice::from_raw_parts(s.ptr, s.len)).map_err(|_| UKSTEM_INVALID_UTF8)
+}
+
+fn guard(f: impl FnOnce() -> i32) -> i32 {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(UKSTEM_PANIC)
+}
+
+// for the functions without a status code
+fn guard_void(f: impl FnOnce()) {
+    let _ = catch_unwind(AssertUnwindSafe(f));
+}
+
+/// A stemmer with the default configuration, null only if creating it failed.
+#[no_mangle]
+pub extern "C" fn ukstem_stemmer_new() -> *mut Stemmer {
+    catch_unwind(|| Box::into_raw(Box::new(Stemmer::new()))).unwrap_or(ptr::null_mut())
+}
+
+/// # Safety
+/// `stemmer` must be null or come from `ukstem_stemmer_new` and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ukstem_stemmer_free(stemmer: *mut Stemmer) {
+    if !stemmer.is_null() {
+        guard_void(|| drop(Box::from_raw(stemmer)));
+    }
+}
+
+/// Stems `word` into `*out`, which is left untouched on failure.
+///
+/// # Safety
+/// `stemmer` must be a live handle, `word` a valid view and `out` writable.
+#[no_mangle]
+pub unsafe extern "C" fn ukstem_stem(
+    stemmer: *const Stemmer,
+    word: UkstemStr,
+    out: *mut UkstemString,
+) -> i32 {
+    if stemmer.is_null() || out.is_null() {
+        return UKSTEM_NULL_POINTER;
+    }
+    guard(|| match as_str(word) {
+        Ok(word) => {
+            out.write(UkstemString::new((*stemmer).stem(word)));
+            UKSTEM_OK
+        }
+        Err(code) => code,
+    })
+}
+
+/// Stems `count` words into `out[0..count]`. Either all outputs are written or, on
+/// failure, none are.
+///
+/// # Safety
+/// `words` must point to `count` valid views and `out` to `count` writable slots.
+#[no_mangle]
+pub unsafe extern "C" fn ukstem_stem_batch(
+    stemmer: *const Stemmer,
+    words: *const UkstemStr,
+    count: usize,
+    out: *mut UkstemString,
+) -> i32 {
+    if count == 0 {
+        return UKSTEM_OK;
+    }
+    if stemmer.is_null() || words.is_null() || out.is_null() {
+        return UKSTEM_NULL_POINTER;
+    }
+    guard(|| {
+        let words = std::slice::from_raw_parts(words, count);
+        let words = match words
+            .iter()
+            .map(|w| as_str(*w))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(words) => words,
+            Err(code) => return code,
+        };
+        let stems: Vec<String> = (*stemmer).stem_all(words).collect();
+        for (i, stem) in stems.into_iter().enumerate() {
+            out.add(i).write(UkstemString::new(stem));
+        }
+        UKSTEM_OK
+    })
+}
+
+/// # Safety
+/// `s` must come from this library and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ukstem_string_free(s: UkstemString) {
+    if !s.ptr.is_null() {
+        guard_void(|| drop(Box::from_raw(ptr::slice_from_raw_parts_mut(s.ptr, s.len))));
+    }
+}
+
+/// Tokenizes `text` with the default tokenizer into `*out`.
+///
+/// # Safety
+/// `text` must be a valid view and `out` writable.
+#[no_mangle]
+pub unsafe extern "C" fn ukstem_tokenize(text: UkstemStr, out: *mut UkstemTokens) -> i32 {
+    if out.is_null() {
+        return UKSTEM_NULL_POINTER;
+    }
+    guard(|| match as_str(text) {
+        Ok(text) => {
+            let tokens: Box<[UkstemToken]> = tokenize(text)
+                .map(|t| UkstemToken {
+                    kind: kind_code(t.kind),
+                    start: t.start,
+                    end: t.end,
+                })
+                .collect();
+            let len = tokens.len();
+            out.write(UkstemTokens {
+                ptr: Box::into_raw(tokens) as *mut UkstemToken,
+                len,
+            });
+            UKSTEM_OK
+        }
+        Err(code) => code,
+    })
+}
+
+/// # Safety
+/// `tokens` must come from `ukstem_tokenize` and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ukstem_tokens_free(tokens: UkstemTokens) {
+    if !tokens.ptr.is_null() {
+        guard_void(|| {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                tokens.ptr, tokens.len,
+            )))
+        });
+    }
+}
+
+// the default stem of the `len` bytes at `word` as a C string
+unsafe fn c_stem(word: *const c_char, len: usize) -> Result<CString, i32> {
+    let word = as_str(UkstemStr {
+        ptr: word as *const u8,
+        len,
+    })?;
+    let stem = crate::DEFAULT_ANALYZER.get_stemmer().stem(word);
+    CString::new(stem).map_err(|_| UKSTEM_INTERIOR_NUL)
+}
+
+/// Stems the `len` UTF-8 bytes at `word` with the default configuration. Returns a
+/// NUL-terminated string to free with `uk_stem_free`, or null if `word` is null with a
+/// nonzero `len`, is not UTF-8 or contains a NUL.
+///
+/// # Safety
+/// `word` must be null or valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn uk_stem(word: *const c_char, len: usize) -> *mut c_char {
+    catch_unwind(|| c_stem(word, len).map_or(ptr::null_mut(), CString::into_raw))
+        .unwrap_or(ptr::null_mut())
+}
+
+/// # Safety
+/// `stem` must be null or come from `uk_stem` or `uk_stem_batch` and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn uk_stem_free(stem: *mut c_char) {
+    if !stem.is_null() {
+        guard_void(|| drop(CString::from_raw(stem)));
+    }
+}
+
+/// Stems `count` words, `words[i]` of `lens[i]` bytes, into `out[0..count]` like
+/// `uk_stem`. Either all outputs are written or, on failure, none are; a word with a NUL
+/// fails with `UKSTEM_INTERIOR_NUL`.
+///
+/// # Safety
+/// `words` and `lens` must point to `count` valid entries and `out` to `count`
+/// writable slots.
+#[no_mangle]
+pub unsafe extern "C" fn uk_stem_batch(
+    words: *const *const c_char,
+    lens: *const usize,
+    count: usize,
+    out: *mut *mut c_char,
+) -> i32 {
+    if count == 0 {
+        return UKSTEM_OK;
+    }
+    if words.is_null() || lens.is_null() || out.is_null() {
+        return UKSTEM_NULL_POINTER;
+    }
+    guard(|| {
+        let words = std::slice::from_raw_parts(words, count);
+        let lens = std::slice::from_raw_parts(lens, count);
+        let stems = match words
+            .iter()
+            .zip(lens)
+            .map(|(word, len)| c_stem(*word, *len))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(stems) => stems,
+            Err(code) => return code,
+        };
+        for (i, stem) in stems.into_iter().enumerate() {
+            out.add(i).write(stem.into_raw());
+        }
+        UKSTEM_OK
+    })
+}
+
+/// Frees the `count` stems `uk_stem_batch` wrote to `stems`.
+///
+/// # Safety
+/// `stems` must be null or hold `count` stems of a successful `uk_stem_batch`, not freed
+/// yet.
+#[no_mangle]
+pub unsafe extern "C" fn uk_stem_batch_free(stems: *mut *mut c_char, count: usize) {
+    if !stems.is_null() {
+        for i in 0..count {
+            uk_stem_free(*stems.add(i));
+        }
+    }
+}
+
+#[test]
+fn ffi_test() {
+    fn view(s: &str) -> UkstemStr {
+        UkstemStr {
+            ptr: s.as_ptr(),
+            len: s.len(),
+        }
+    }
+
+    unsafe {
+        let stemmer = ukstem_stemmer_new();
+        let mut out = std::mem::MaybeUninit::uninit();
+        assert_eq!(
+            ukstem_stem(stemmer, view("Народами"), out.as_mut_ptr()),
+            UKSTEM_OK
+        );
+        let stem = out.assume_init();
+        assert_eq!(
+            std::slice::from_raw_parts(stem.ptr, stem.len),
+            "народ".as_bytes()
+        );
+        ukstem_string_free(stem);
+
+        let invalid = UkstemStr {
+            ptr: b"\xff".as_ptr(),
+            len: 1,
+        };
+        assert_eq!(
+            ukstem_stem(
+                stemmer,
+                invalid,
+                std::mem::MaybeUninit::uninit().as_mut_ptr()
+            ),
+            UKSTEM_INVALID_UTF8
+        );
+
+        let words = [view("книгами"), view("автора")];
+        let mut stems: Vec<UkstemString> = Vec::with_capacity(2);
+        assert_eq!(
+            ukstem_stem_batch(stemmer, words.as_ptr(), 2, stems.as_mut_ptr()),
+            UKSTEM_OK
+        );
+        stems.set_len(2);
+        assert_eq!(
+            std::slice::from_raw_parts(stems[1].ptr, stems[1].len),
+            "автор".as_bytes()
+        );
+        stems.into_iter().for_each(|s| ukstem_string_free(s));
+        ukstem_stemmer_free(stemmer);
+
+        let mut tokens = std::mem::MaybeUninit::uninit();
+        assert_eq!(
+            ukstem_tokenize(view("Привіт, світ"), tokens.as_mut_ptr()),
+            UKSTEM_OK
+        );
+        let tokens = tokens.assume_init();
+        let slice = std::slice::from_raw_parts(tokens.ptr, tokens.len);
+        assert_eq!(slice.len(), 4);
+        assert_eq!(
+            slice[0],
+            UkstemToken {
+                kind: 0,
+                start: 0,
+                end: 12
+            }
+        );
+        ukstem_tokens_free(tokens);
+    }
+}
+
+#[test]
+fn malformed_input_test() {
+    // xorshift, so failures reproduce
+    let mut state = 0x2545f4914f6cdd1du64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    let pieces: [&[u8]; 12] = [
+        "книг".as_bytes(),
+        "ами".as_bytes(),
+        "\n".as_bytes(),
+        "'".as_bytes(),
+        "\u{AD}".as_bytes(),
+        "\u{301}".as_bytes(),
+        "İ".as_bytes(),
+        "😀".as_bytes(),
+        b"\xff",
+        b"\xd0",
+        b"\0",
+        b"5.",
+    ];
+    unsafe {
+        let stemmer = ukstem_stemmer_new();
+        assert!(!stemmer.is_null());
+        for _ in 0..2000 {
+            let mut input = Vec::new();
+            for _ in 0..next() % 8 {
+                input.extend_from_slice(pieces[(next() % 12) as usize]);
+            }
+            let view = UkstemStr {
+                ptr: input.as_ptr(),
+                len: input.len(),
+            };
+            let valid = str::from_utf8(&input).is_ok();
+            let mut out = std::mem::MaybeUninit::uninit();
+            let code = ukstem_stem(stemmer, view, out.as_mut_ptr());
+            assert_eq!(
+                code,
+                if valid {
+                    UKSTEM_OK
+                } else {
+                    UKSTEM_INVALID_UTF8
+                }
+            );
+            if code == UKSTEM_OK {
+                ukstem_string_free(out.assume_init());
+            }
+            let mut tokens = std::mem::MaybeUninit::uninit();
+            if ukstem_tokenize(view, tokens.as_mut_ptr()) == UKSTEM_OK {
+                ukstem_tokens_free(tokens.assume_init());
+            }
+        }
+        let null = UkstemStr {
+            ptr: ptr::null(),
+            len: 3,
+        };
+        let mut out = std::mem::MaybeUninit::uninit();
+        assert_eq!(
+            ukstem_stem(stemmer, null, out.as_mut_ptr()),
+            UKSTEM_NULL_POINTER
+        );
+        assert_eq!(
+            ukstem_stem(ptr::null(), null, out.as_mut_ptr()),
+            UKSTEM_NULL_POINTER
+        );
+        ukstem_stemmer_free(stemmer);
+        ukstem_stemmer_free(ptr::null_mut());
+    }
+    assert_eq!(guard(|| panic!("bug")), UKSTEM_PANIC);
+}
+
+#[test]
+fn c_string_test() {
+    use std::ffi::CStr;
+
+    unsafe {
+        let word = "Народами";
+        let stem = uk_stem(word.as_ptr() as *const c_char, word.len());
+        assert_eq!(CStr::from_ptr(stem).to_str(), Ok("народ"));
+        uk_stem_free(stem);
+        assert!(uk_stem(b"\xff".as_ptr() as *const c_char, 1).is_null());
+        assert!(uk_stem(b"a\0".as_ptr() as *const c_char, 2).is_null());
+        assert!(uk_stem(ptr::null(), 1).is_null());
+        let empty = uk_stem(ptr::null(), 0);
+        assert_eq!(CStr::from_ptr(empty).to_bytes(), b"");
+        uk_stem_free(empty);
+
+        let words = ["книгами", "автора"];
+        let ptrs: Vec<*const c_char> = words.iter().map(|w| w.as_ptr() as *const c_char).collect();
+        let lens: Vec<usize> = words.iter().map(|w| w.len()).collect();
+        let mut out = vec![ptr::null_mut(); 2];
+        assert_eq!(
+            uk_stem_batch(ptrs.as_ptr(), lens.as_ptr(), 2, out.as_mut_ptr()),
+            UKSTEM_OK
+        );
+        assert_eq!(CStr::from_ptr(out[1]).to_str(), Ok("автор"));
+        uk_stem_batch_free(out.as_mut_ptr(), 2);
+
+        let lens = [words[0].len(), 1];
+        let ptrs = [ptrs[0], b"\xff".as_ptr() as *const c_char];
+        let mut out = vec![ptr::null_mut(); 2];
+        assert_eq!(
+            uk_stem_batch(ptrs.as_ptr(), lens.as_ptr(), 2, out.as_mut_ptr()),
+            UKSTEM_INVALID_UTF8
+        );
+        assert!(out.iter().all(|s| s.is_null()));
+
+        let ptrs = [ptrs[0], b"a\0".as_ptr() as *const c_char];
+        let lens = [words[0].len(), 2];
+        assert_eq!(
+            uk_stem_batch(ptrs.as_ptr(), lens.as_ptr(), 2, out.as_mut_ptr()),
+            UKSTEM_INTERIOR_NUL
+        );
+        assert!(out.iter().all(|s| s.is_null()));
+    }
+}
@@ -0,0 +1,162 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Bumped whenever the built-in rules change the stems they produce.
+pub const RULES_VERSION: u32 = 2;
+
+/// Hash of the rule version and every option that influences stems.
+///
+/// Artifacts built with one configuration (indexes, stem lists) must only be queried with
+/// a stemmer of the same fingerprint, otherwise stems silently stop matching.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    /// FNV-1a over `config`, stable across platforms and compiler versions.
+    pub fn of(config: &str) -> Self {
+        Fingerprint::of_version(RULES_VERSION, config)
+    }
+
+    /// Like `of`, for a configuration running the built-in rules of `rules_version`.
+    pub fn of_version(rules_version: u32, config: &str) -> Self {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for b in rules_version
+            .to_le_bytes()
+            .iter()
+            .chain(config.as_bytes().iter())
+        {
+            hash ^= u64::from(*b);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        Fingerprint(hash)
+    }
+
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(s.trim(), 16).map(Fingerprint)
+    }
+}
+
+#[derive(Debug)]
+pub enum FingerprintError {
+    Io(io::Error),
+    /// The artifact has no (readable) stamp.
+    Missing(PathBuf),
+    Mismatch {
+        artifact: PathBuf,
+        expected: Fingerprint,
+        found: Fingerprint,
+    },
+}
+
+impl fmt::Display for FingerprintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FingerprintError::Io(err) => write!(f, "{}", err),
+            FingerprintError::Missing(artifact) => {
+                write!(f, "{} has no configuration stamp", artifact.display())
+            }
+            FingerprintError::Mismatch {
+                artifact,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} was built with configuration {}, current is {}",
+                artifact.display(),
+                found,
+                expected
+            ),
+        }
+    }
+}
+
+impl Error for FingerprintError {}
+
+impl From<io::Error> for FingerprintError {
+    fn from(err: io::Error) -> Self {
+        FingerprintError::Io(err)
+    }
+}
+
+/// The sidecar file holding the stamp of `artifact`.
+pub fn stamp_path<P: AsRef<Path>>(artifact: P) -> PathBuf {
+    let mut path = artifact.as_ref().as_os_str().to_owned();
+    path.push(".fingerprint");
+    PathBuf::from(path)
+}
+
+/// Records `fingerprint` next to `artifact`.
+pub fn stamp<P: AsRef<Path>>(artifact: P, fingerprint: Fingerprint) -> io::Result<()> {
+    fs::write(stamp_path(artifact), format!("{}\n", fingerprint))
+}
+
+pub fn read_stamp<P: AsRef<Path>>(artifact: P) -> Result<Fingerprint, FingerprintError> {
+    let artifact = artifact.as_ref();
+    match fs::read_to_string(stamp_path(artifact)) {
+        Ok(text) => text
+            .parse()
+            .map_err(|_| FingerprintError::Missing(artifact.to_path_buf())),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            Err(FingerprintError::Missing(artifact.to_path_buf()))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Fails unless `artifact` was stamped with `expected`.
+pub fn verify<P: AsRef<Path>>(artifact: P, expected: Fingerprint) -> Result<(), FingerprintError> {
+    let found = read_stamp(&artifact)?;
+    if found == expected {
+        Ok(())
+    } else {
+        Err(FingerprintError::Mismatch {
+            artifact: artifact.as_ref().to_path_buf(),
+            expected,
+            found,
+        })
+    }
+}
+
+#[test]
+fn fingerprint_test() {
+    let fp = Fingerprint::of("config");
+    assert_eq!(fp, Fingerprint::of("config"));
+    assert_ne!(fp, Fingerprint::of("config2"));
+    assert_eq!(fp.to_string().parse::<Fingerprint>().unwrap(), fp);
+}
+
+#[test]
+fn stamp_test() {
+    let artifact = std::env::temp_dir().join(format!("ukstem-stamp-{}", std::process::id()));
+    let fp = Fingerprint::of("a");
+
+    assert!(matches!(
+        verify(&artifact, fp),
+        Err(FingerprintError::Missing(_))
+    ));
+    stamp(&artifact, fp).unwrap();
+    assert!(verify(&artifact, fp).is_ok());
+    assert!(matches!(
+        verify(&artifact, Fingerprint::of("b")),
+        Err(FingerprintError::Mismatch { .. })
+    ));
+    fs::remove_file(stamp_path(&artifact)).unwrap();
+}
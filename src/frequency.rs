@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::sync::Arc;
+
+/// Corpus counts of stems, for example written by counting `Stemmer::stem` over a
+/// representative corpus.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct FrequencyList {
+    counts: HashMap<String, u64>,
+}
+
+impl FrequencyList {
+    pub fn new() -> Self {
+        FrequencyList::default()
+    }
+
+    pub fn from_counts<I, K>(counts: I) -> Self
+    where
+        I: IntoIterator<Item = (K, u64)>,
+        K: Into<String>,
+    {
+        let mut list = FrequencyList::new();
+        for (key, count) in counts {
+            list.add(key, count);
+        }
+        list
+    }
+
+    /// Reads `stem<TAB>count` lines; blank lines and lines starting with `#` are skipped.
+    pub fn load<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut list = FrequencyList::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parsed = line
+                .split_once('\t')
+                .and_then(|(stem, count)| Some((stem, count.trim().parse().ok()?)));
+            match parsed {
+                Some((stem, count)) => list.add(stem, count),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("line {}: expected `stem<TAB>count`", i + 1),
+                    ))
+                }
+            }
+        }
+        Ok(list)
+    }
+
+    /// Adds `count` occurrences of the lowercase `stem`.
+    pub fn add<K: Into<String>>(&mut self, stem: K, count: u64) {
+        *self.counts.entry(stem.into().to_lowercase()).or_insert(0) += count;
+    }
+
+    pub fn count(&self, stem: &str) -> u64 {
+        self.counts.get(stem).copied().unwrap_or(0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+}
+
+// part of the stemmer fingerprint, so entries are listed in a stable order
+impl fmt::Debug for FrequencyList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut counts: Vec<_> = self.counts.iter().collect();
+        counts.sort();
+        f.debug_map().entries(counts).finish()
+    }
+}
+
+/// A map of stems to counts, sorted.
+#[cfg(feature = "serde")]
+impl serde::Serialize for FrequencyList {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut counts: Vec<_> = self.counts.iter().collect();
+        counts.sort();
+        serializer.collect_map(counts)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FrequencyList {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let counts: HashMap<String, u64> = serde::Deserialize::deserialize(deserializer)?;
+        Ok(FrequencyList::from_counts(counts))
+    }
+}
+
+/// Only lets the derivational rule groups (`rules::DERIVATIONAL` and
+/// `rules::SUPERLATIVE`) remove a suffix when the stem they leave occurs at least
+/// `min_count` times in the frequency list, so rare technical terms keep their suffix.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrequencyGuard {
+    pub frequencies: Arc<FrequencyList>,
+    pub min_count: u64,
+}
+
+impl FrequencyGuard {
+    pub fn allows(&self, stem: &str) -> bool {
+        self.frequencies.count(stem) >= self.min_count
+    }
+}
+
+#[test]
+fn frequency_list_test() {
+    let list = FrequencyList::load("# stems\nнарод\t10\nНарод\t2\n\nкниг\t3\n".as_bytes()).unwrap();
+    assert_eq!(list.count("народ"), 12);
+    assert_eq!(list.len(), 2);
+    assert!(FrequencyList::load("народ 10\n".as_bytes()).is_err());
+
+    let guard = FrequencyGuard {
+        frequencies: Arc::new(list),
+        min_count: 5,
+    };
+    assert!(guard.allows("народ"));
+    assert!(!guard.allows("книг"));
+    assert!(!guard.allows("автор"));
+}
@@ -0,0 +1,74 @@
+//! Stemming the items of async streams, e.g. words received over the network:
+//!
+//! ```ignore
+//! let stems = StemStream::new(words).collect::<Vec<String>>().await;
+//! ```
+//!
+//! Stemming a word takes microseconds, so items are stemmed in `poll_next` itself rather
+//! than on a blocking thread pool.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::Stemmer;
+
+/// Yields the stems of the items of a stream of words. The stream must be `Unpin`; pin
+/// others with `Box::pin`.
+#[derive(Clone, Debug)]
+pub struct StemStream<S> {
+    words: S,
+    stemmer: Stemmer,
+}
+
+impl<S> StemStream<S> {
+    /// Stems like `stem_word`.
+    pub fn new(words: S) -> Self {
+        StemStream::with_stemmer(words, Stemmer::default())
+    }
+
+    pub fn with_stemmer(words: S, stemmer: Stemmer) -> Self {
+        StemStream { words, stemmer }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.words
+    }
+}
+
+impl<S> Stream for StemStream<S>
+where
+    S: Stream + Unpin,
+    S::Item: AsRef<str>,
+{
+    type Item = String;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<String>> {
+        let this = &mut *self;
+        match Pin::new(&mut this.words).poll_next(cx) {
+            Poll::Ready(Some(word)) => Poll::Ready(Some(this.stemmer.stem(word.as_ref()))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.words.size_hint()
+    }
+}
+
+#[test]
+fn stem_stream_test() {
+    use futures::executor::block_on;
+    use futures::stream::{self, StreamExt};
+
+    let words = stream::iter(vec!["Народами".to_string(), "книгами".to_string()]);
+    let stems: Vec<String> = block_on(StemStream::new(words).collect());
+    assert_eq!(stems, ["народ", "книг"]);
+
+    let stemmer = Stemmer::builder().preserve_case(true).build();
+    let words = stream::iter(["Автора"]).then(|word| async move { word });
+    let stems = StemStream::with_stemmer(Box::pin(words), stemmer);
+    assert_eq!(block_on(stems.collect::<Vec<_>>()), ["Автор"]);
+}
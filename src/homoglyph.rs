@@ -0,0 +1,92 @@
+use std::borrow::Cow;
+
+use crate::tokenizer::{script, Script};
+
+/// How Latin lookalikes inside Cyrillic words are mapped back to Cyrillic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum HomoglyphRepair {
+    #[default]
+    Off,
+    /// Letters that look the same in either case ("a", "o", "і", "Р" ...).
+    Letters,
+    /// Also capitals that only look alike uppercase ("В", "Н", "М", "Т"), and digits
+    /// standing in for letters ("с0бака"), as typed to dodge chat filters.
+    Aggressive,
+}
+
+const LETTERS: [(char, char); 17] = [
+    ('a', 'а'),
+    ('c', 'с'),
+    ('e', 'е'),
+    ('i', 'і'),
+    ('o', 'о'),
+    ('p', 'р'),
+    ('x', 'х'),
+    ('y', 'у'),
+    ('A', 'А'),
+    ('C', 'С'),
+    ('E', 'Е'),
+    ('I', 'І'),
+    ('O', 'О'),
+    ('P', 'Р'),
+    ('X', 'Х'),
+    ('Y', 'У'),
+    ('K', 'К'),
+];
+
+const AGGRESSIVE: [(char, char); 7] = [
+    ('B', 'В'),
+    ('H', 'Н'),
+    ('M', 'М'),
+    ('T', 'Т'),
+    ('0', 'о'),
+    ('3', 'з'),
+    ('6', 'б'),
+];
+
+fn lookup(c: char, mode: HomoglyphRepair) -> Option<char> {
+    let table: &[(char, char)] = &LETTERS;
+    let extra: &[(char, char)] = match mode {
+        HomoglyphRepair::Aggressive => &AGGRESSIVE,
+        _ => &[],
+    };
+    table
+        .iter()
+        .chain(extra)
+        .find(|(from, _)| *from == c)
+        .map(|(_, to)| *to)
+}
+
+/// Maps lookalikes to Cyrillic in words that already contain a Cyrillic letter; words
+/// without one are returned unchanged, so Latin text is never rewritten.
+pub fn repair(word: &str, mode: HomoglyphRepair) -> Cow<'_, str> {
+    let cyrillic = word.chars().any(|c| ('\u{0400}'..='\u{04FF}').contains(&c));
+    if mode == HomoglyphRepair::Off || !cyrillic || !word.chars().any(|c| lookup(c, mode).is_some())
+    {
+        return Cow::Borrowed(word);
+    }
+    Cow::Owned(word.chars().map(|c| lookup(c, mode).unwrap_or(c)).collect())
+}
+
+/// Whether `repair` turns the mixed-script `word` into a fully Cyrillic one.
+pub fn is_repairable(word: &str, mode: HomoglyphRepair) -> bool {
+    mode != HomoglyphRepair::Off && script(&repair(word, mode)) == Script::Cyrillic
+}
+
+#[test]
+fn repair_test() {
+    use HomoglyphRepair::*;
+
+    // Latin "i" and "a"
+    assert_eq!(repair("кнiгa", Letters), "кніга");
+    assert_eq!(repair("вiкно", Letters), "вікно");
+    assert_eq!(repair("window", Aggressive), "window");
+    assert_eq!(repair("вiкно", Off), "вiкно");
+    assert_eq!(repair("Mаріо", Letters), "Mаріо");
+    assert_eq!(repair("Mаріо", Aggressive), "Маріо");
+    assert_eq!(repair("с0бака", Aggressive), "собака");
+    assert!(is_repairable("вiкно", Letters));
+    assert!(!is_repairable("Mаріо", Letters));
+}
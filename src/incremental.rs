@@ -0,0 +1,203 @@
+//! Analysis of text that grows at the end, like live transcription or a document being
+//! typed, without re-analyzing what the new text cannot change.
+
+use crate::analyzer::Analyzer;
+use crate::tokenizer::TokenKind;
+
+/// A term of the text so far. Its `id` stays the same across appends as long as the term
+/// is unchanged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IncrementalTerm {
+    pub id: u64,
+    pub kind: TokenKind,
+    pub term: String,
+    /// Byte offsets of the term's token in the whole text.
+    pub start: usize,
+    pub end: usize,
+}
+
+/// How an append changed the terms.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Update {
+    /// Ids of terms that no longer exist, e.g. "приві" once "т" is appended.
+    pub retracted: Vec<u64>,
+    /// Terms that are new, in text order.
+    pub added: Vec<IncrementalTerm>,
+}
+
+/// Accumulates appended text and keeps its terms, as `Analyzer::analyze` would produce
+/// them for the whole text.
+///
+/// Every append re-analyzes from a few words before its end, as far back as a phrase or
+/// abbreviation ending in the new text can reach. `TranslitRepair::Document` only
+/// considers that tail.
+#[derive(Clone, Debug)]
+pub struct IncrementalAnalyzer {
+    analyzer: Analyzer,
+    text: String,
+    terms: Vec<IncrementalTerm>,
+    // starts of all word tokens, the points analysis can restart from
+    word_starts: Vec<usize>,
+    next_id: u64,
+}
+
+impl IncrementalAnalyzer {
+    pub fn new(analyzer: Analyzer) -> Self {
+        IncrementalAnalyzer {
+            analyzer,
+            text: String::new(),
+            terms: Vec::new(),
+            word_starts: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn terms(&self) -> &[IncrementalTerm] {
+        &self.terms
+    }
+
+    pub fn append(&mut self, text: &str) -> Update {
+        let keep = self
+            .word_starts
+            .len()
+            .saturating_sub(self.analyzer.lookbehind());
+        let mut restart = self.word_starts.get(keep).copied().unwrap_or(0);
+        // a phrase or abbreviation term covering the restart point is redone as a whole
+        for term in self.terms.iter().rev() {
+            if term.start < restart && term.end <= restart {
+                break;
+            }
+            restart = restart.min(term.start);
+        }
+        let keep = self.word_starts.partition_point(|s| *s < restart);
+        let sentence_start = self.sentence_start_at(keep, restart);
+        self.word_starts.truncate(keep);
+        self.text.push_str(text);
+
+        let stale_from = self.terms.partition_point(|t| t.start < restart);
+        let mut stale: Vec<Option<IncrementalTerm>> =
+            self.terms.drain(stale_from..).map(Some).collect();
+        let mut update = Update::default();
+        let terms = self
+            .analyzer
+            .analyze_from(&self.text, restart, sentence_start);
+        for term in terms {
+            let token = term.token;
+            let unchanged = stale.iter_mut().find(|old| {
+                old.as_ref().is_some_and(|old| {
+                    (old.start, old.end, old.kind, old.term.as_str())
+                        == (token.start, token.end, token.kind, term.term.as_str())
+                })
+            });
+            match unchanged.and_then(Option::take) {
+                Some(old) => self.terms.push(old),
+                None => {
+                    let new = IncrementalTerm {
+                        id: self.next_id,
+                        kind: token.kind,
+                        term: term.term,
+                        start: token.start,
+                        end: token.end,
+                    };
+                    self.next_id += 1;
+                    update.added.push(new.clone());
+                    self.terms.push(new);
+                }
+            }
+        }
+        update.retracted = stale.into_iter().flatten().map(|t| t.id).collect();
+
+        let tokenizer = self.analyzer.get_tokenizer();
+        self.word_starts.extend(
+            tokenizer
+                .tokenize_from(&self.text, restart)
+                .filter(|t| t.kind == TokenKind::Word)
+                .map(|t| t.start),
+        );
+        update
+    }
+
+    // whether `analyze` would consider `restart`, the start of word `keep`, the start of
+    // a sentence: decided by the tokens since the previous word
+    fn sentence_start_at(&self, keep: usize, restart: usize) -> bool {
+        let from = match keep.checked_sub(1) {
+            Some(prev) => self.word_starts[prev],
+            None => return true,
+        };
+        let mut sentence_start = false;
+        let tokens = self
+            .analyzer
+            .get_tokenizer()
+            .tokenize_from(&self.text, from);
+        for token in tokens.skip(1).take_while(|t| t.start < restart) {
+            match token.kind {
+                TokenKind::Whitespace | TokenKind::Control => {}
+                TokenKind::Punctuation => sentence_start = Analyzer::ends_sentence(&token),
+                _ => sentence_start = false,
+            }
+        }
+        sentence_start
+    }
+}
+
+#[cfg(test)]
+fn batch_terms(analyzer: &Analyzer, text: &str) -> Vec<(usize, usize, String)> {
+    analyzer
+        .analyze(text)
+        .into_iter()
+        .map(|t| (t.token.start, t.token.end, t.term))
+        .collect()
+}
+
+#[test]
+fn append_test() {
+    let mut incremental = IncrementalAnalyzer::new(Analyzer::new());
+    let update = incremental.append("Слідство три");
+    assert_eq!(update.added.len(), 2);
+    let first = update.added[0].id;
+
+    let update = incremental.append("ває");
+    assert_eq!(update.retracted, vec![1]);
+    assert_eq!(update.added[0].term, "трив");
+    assert_eq!(incremental.terms()[0].id, first);
+
+    let update = incremental.append(" далі");
+    assert!(update.retracted.is_empty());
+    assert_eq!(update.added.len(), 1);
+}
+
+#[test]
+fn matches_batch_test() {
+    let analyzer = Analyzer::new()
+        .proper_nouns(true)
+        .phrase("Верховна Рада")
+        .abbreviation("ст.", "стаття")
+        .synonym("закон", "акт")
+        .phrase("Кабінет Міністрів України")
+        .stopwords(crate::stopwords::default_words());
+    let text = "Верховна Рада ухвалила закон. Київ, ст. 5 3,5 грн! Верховна   Рада, Львів \
+        і Кабінет Міністрів України та Кабінет Міністрів у ст.";
+    let mut incremental = IncrementalAnalyzer::new(analyzer.clone());
+    let mut live: Vec<u64> = Vec::new();
+    for (i, c) in text.char_indices() {
+        let update = incremental.append(&text[i..i + c.len_utf8()]);
+        live.retain(|id| !update.retracted.contains(id));
+        live.extend(update.added.iter().map(|t| t.id));
+
+        let expected = batch_terms(&analyzer, incremental.text());
+        let terms: Vec<_> = incremental
+            .terms()
+            .iter()
+            .map(|t| (t.start, t.end, t.term.clone()))
+            .collect();
+        assert_eq!(terms, expected, "{:?}", incremental.text());
+        let mut ids: Vec<u64> = incremental.terms().iter().map(|t| t.id).collect();
+        ids.sort_unstable();
+        live.sort_unstable();
+        assert_eq!(ids, live);
+    }
+}
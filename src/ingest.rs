@@ -0,0 +1,88 @@
+//! Reading the text files of a directory tree, for indexing a folder of documents.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A text file and its contents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Document {
+    pub path: PathBuf,
+    pub text: String,
+}
+
+/// The files under `dir` with `extension`, compared ignoring ASCII case, sorted by path.
+/// Hidden files and directories, whose names start with a dot, are skipped.
+pub fn text_files(dir: &Path, extension: &str) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect(dir, extension, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn collect(dir: &Path, extension: &str, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            collect(&path, extension, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.to_string_lossy().eq_ignore_ascii_case(extension))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Reads `text_files(dir, extension)`, without the byte order marks editors on Windows
+/// write. A file that isn't UTF-8 fails the whole read, naming the file.
+pub fn read_documents(dir: &Path, extension: &str) -> io::Result<Vec<Document>> {
+    let mut documents = Vec::new();
+    for path in text_files(dir, extension)? {
+        let text = String::from_utf8(fs::read(&path)?).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), err),
+            )
+        })?;
+        let text = match text.strip_prefix('\u{feff}') {
+            Some(text) => text.to_string(),
+            None => text,
+        };
+        documents.push(Document { path, text });
+    }
+    Ok(documents)
+}
+
+#[test]
+fn read_documents_test() {
+    let dir = std::env::temp_dir().join(format!("ukstem-ingest-{}", std::process::id()));
+    fs::create_dir_all(dir.join("b/.git")).unwrap();
+    fs::write(dir.join("a.TXT"), "\u{feff}Слідство").unwrap();
+    fs::write(dir.join("b/c.txt"), "триває").unwrap();
+    fs::write(dir.join("b/.git/d.txt"), "").unwrap();
+    fs::write(dir.join("b/e.md"), "").unwrap();
+
+    let documents = read_documents(&dir, "txt").unwrap();
+    let found: Vec<(&Path, &str)> = documents
+        .iter()
+        .map(|d| (d.path.strip_prefix(&dir).unwrap(), d.text.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (Path::new("a.TXT"), "Слідство"),
+            (Path::new("b/c.txt"), "триває")
+        ]
+    );
+
+    fs::write(dir.join("b/f.txt"), [0xff, 0xfe]).unwrap();
+    let err = read_documents(&dir, "txt").unwrap_err();
+    assert!(err.to_string().contains("f.txt"));
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,95 @@
+//! One shared allocation per distinct stem, for indexers holding the stems of millions of
+//! tokens.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::mem::size_of;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::memory::HeapSize;
+
+// threads interning different stems mostly lock different shards
+const SHARDS: usize = 16;
+
+/// A set of stems handed out as `Arc<str>`, so that all the words with a stem share one
+/// copy of it, see `StemmerBuilder::intern_stems`. Stems are kept until the interner is
+/// dropped; it can be shared between threads.
+#[derive(Default)]
+pub struct StemInterner {
+    shards: [Mutex<HashSet<Arc<str>>>; SHARDS],
+}
+
+impl StemInterner {
+    pub fn new() -> Self {
+        StemInterner::default()
+    }
+
+    // a panic while a shard was locked can't leave a set half-updated
+    fn shard(&self, stem: &str) -> MutexGuard<'_, HashSet<Arc<str>>> {
+        let hash = stem.bytes().fold(0usize, |hash, b| {
+            hash.wrapping_mul(31).wrapping_add(usize::from(b))
+        });
+        lock(&self.shards[hash % SHARDS])
+    }
+
+    /// The shared copy of `stem`, made on first use.
+    pub fn intern(&self, stem: &str) -> Arc<str> {
+        let mut shard = self.shard(stem);
+        if let Some(shared) = shard.get(stem) {
+            return Arc::clone(shared);
+        }
+        let shared: Arc<str> = Arc::from(stem);
+        shard.insert(Arc::clone(&shared));
+        shared
+    }
+
+    /// The number of distinct stems.
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| lock(shard).len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn lock(shard: &Mutex<HashSet<Arc<str>>>) -> MutexGuard<'_, HashSet<Arc<str>>> {
+    shard.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl fmt::Debug for StemInterner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StemInterner")
+            .field("stems", &self.len())
+            .finish()
+    }
+}
+
+// an `Arc<str>` allocation holds its two counts besides the chars
+impl HeapSize for StemInterner {
+    fn heap_bytes(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| {
+                let shard = lock(shard);
+                shard.capacity() * size_of::<Arc<str>>()
+                    + shard
+                        .iter()
+                        .map(|stem| stem.len() + 2 * size_of::<usize>())
+                        .sum::<usize>()
+            })
+            .sum()
+    }
+}
+
+#[test]
+fn stem_interner_test() {
+    let interner = StemInterner::new();
+    let a = interner.intern("народ");
+    let b = interner.intern(&String::from("народ"));
+    assert!(Arc::ptr_eq(&a, &b));
+    interner.intern("книг");
+    assert_eq!(interner.len(), 2);
+    assert!(interner.heap_bytes() >= "народкниг".len());
+    assert!(StemInterner::new().is_empty());
+}
@@ -0,0 +1,266 @@
+//! The keywords of a text, by frequency or by TF-IDF over documents, and watching texts for
+//! keywords in any of their forms.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::analyzer::Analyzer;
+use crate::stopwords::is_stopword;
+
+// stems this short are mostly pronouns and particles the stop-word list misses
+const MIN_KEYWORD_CHARS: usize = 3;
+
+/// A stem of a text and how often its words occur.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Keyword {
+    pub stem: String,
+    /// The most frequent form of the stem in the text, lowercased.
+    pub word: String,
+    pub count: usize,
+}
+
+/// The `count` most frequent stems of `text`, leaving out stop words and stems shorter
+/// than three chars; stems as frequent as each other keep the order they first occur in.
+pub fn keywords(analyzer: &Analyzer, text: &str, count: usize) -> Vec<Keyword> {
+    let mut keywords = count_stems(analyzer, text);
+    keywords.sort_by_key(|k| std::cmp::Reverse(k.count));
+    keywords.truncate(count);
+    keywords
+}
+
+// the keywords of `text` in the order their stems first occur
+fn count_stems(analyzer: &Analyzer, text: &str) -> Vec<Keyword> {
+    // per stem, its forms and their counts in order of occurrence
+    let mut stems: Vec<(String, Vec<(String, usize)>)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for token in analyzer.stem_tokens(text) {
+        if is_stopword(token.original) || token.stem.chars().count() < MIN_KEYWORD_CHARS {
+            continue;
+        }
+        let i = *index.entry(token.stem.to_string()).or_insert_with(|| {
+            stems.push((token.stem.to_string(), Vec::new()));
+            stems.len() - 1
+        });
+        let word = token.original.to_lowercase();
+        let forms = &mut stems[i].1;
+        match forms.iter_mut().find(|(form, _)| *form == word) {
+            Some((_, n)) => *n += 1,
+            None => forms.push((word, 1)),
+        }
+    }
+    stems
+        .into_iter()
+        .map(|(stem, forms)| {
+            let count = forms.iter().map(|(_, n)| n).sum();
+            let mut word = &forms[0];
+            for form in &forms[1..] {
+                if form.1 > word.1 {
+                    word = form;
+                }
+            }
+            Keyword {
+                stem,
+                word: word.0.clone(),
+                count,
+            }
+        })
+        .collect()
+}
+
+/// In how many documents each stem occurs, weighing the keywords of a text by TF-IDF so
+/// that stems common to all the documents rank below those particular to the text.
+#[derive(Clone, Debug, Default)]
+pub struct DocumentFrequencies {
+    documents: usize,
+    stems: HashMap<String, usize>,
+}
+
+impl DocumentFrequencies {
+    pub fn new() -> Self {
+        DocumentFrequencies::default()
+    }
+
+    /// Counts the stems of a document once each; stop words and stems shorter than three
+    /// chars are left out as by `keywords`.
+    pub fn add(&mut self, analyzer: &Analyzer, document: &str) {
+        self.documents += 1;
+        for keyword in count_stems(analyzer, document) {
+            *self.stems.entry(keyword.stem).or_insert(0) += 1;
+        }
+    }
+
+    pub fn documents(&self) -> usize {
+        self.documents
+    }
+
+    /// The number of documents with `stem`.
+    pub fn get(&self, stem: &str) -> usize {
+        self.stems.get(stem).copied().unwrap_or(0)
+    }
+
+    /// The smoothed inverse document frequency `ln((1 + n) / (1 + df)) + 1` of `stem`,
+    /// 1 for a stem of every document and more the rarer it is.
+    pub fn idf(&self, stem: &str) -> f64 {
+        ((1 + self.documents) as f64 / (1 + self.get(stem)) as f64).ln() + 1.0
+    }
+
+    /// The `count` keywords of `text` with the highest TF-IDF, its count times `idf`;
+    /// keywords scoring the same keep the order they first occur in. `text` needn't be one
+    /// of the documents added.
+    pub fn keywords(&self, analyzer: &Analyzer, text: &str, count: usize) -> Vec<ScoredKeyword> {
+        let mut keywords: Vec<ScoredKeyword> = count_stems(analyzer, text)
+            .into_iter()
+            .map(|keyword| ScoredKeyword {
+                score: keyword.count as f64 * self.idf(&keyword.stem),
+                keyword,
+            })
+            .collect();
+        keywords.sort_by(|a, b| b.score.total_cmp(&a.score));
+        keywords.truncate(count);
+        keywords
+    }
+}
+
+/// A keyword with its TF-IDF.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoredKeyword {
+    pub keyword: Keyword,
+    pub score: f64,
+}
+
+/// A word of a text that matched a keyword of a `Watchlist`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeywordMatch<'w, 't> {
+    /// The keyword as it was added.
+    pub keyword: &'w str,
+    pub word: &'t str,
+    /// The byte range of `word` in the text.
+    pub range: Range<usize>,
+}
+
+/// Keywords to look for in texts, e.g. chat messages to alert on: every word with the stem
+/// of a keyword matches it, so "виборів" matches the keyword "вибори".
+pub struct Watchlist {
+    analyzer: Analyzer,
+    // stem → keywords with it
+    keywords: HashMap<String, Vec<String>>,
+}
+
+impl Watchlist {
+    /// One word per keyword; of a phrase, only the first word counts.
+    pub fn new<'k, I>(analyzer: Analyzer, keywords: I) -> Self
+    where
+        I: IntoIterator<Item = &'k str>,
+    {
+        let mut stems: HashMap<String, Vec<String>> = HashMap::new();
+        for keyword in keywords {
+            if let Some(token) = analyzer.stem_tokens(keyword).next() {
+                stems
+                    .entry(token.stem.into_owned())
+                    .or_default()
+                    .push(keyword.to_string());
+            }
+        }
+        Watchlist {
+            analyzer,
+            keywords: stems,
+        }
+    }
+
+    /// The words of `text` matching a keyword, in order; a word matching several
+    /// keywords with the same stem is reported once per keyword.
+    pub fn matches<'w, 't>(&'w self, text: &'t str) -> Vec<KeywordMatch<'w, 't>> {
+        let mut matches = Vec::new();
+        for token in self.analyzer.stem_tokens(text) {
+            for keyword in self.keywords.get(&*token.stem).into_iter().flatten() {
+                matches.push(KeywordMatch {
+                    keyword,
+                    word: token.original,
+                    range: token.start..token.end,
+                });
+            }
+        }
+        matches
+    }
+}
+
+#[test]
+fn keywords_test() {
+    let text = "Вибори завершились. Після виборів результати виборів оприлюднять, \
+                а результати перевірять.";
+    let found = keywords(&Analyzer::new(), text, 2);
+    assert_eq!(
+        found,
+        [
+            Keyword {
+                stem: "вибор".to_string(),
+                word: "виборів".to_string(),
+                count: 3
+            },
+            Keyword {
+                stem: "результ".to_string(),
+                word: "результати".to_string(),
+                count: 2
+            }
+        ]
+    );
+    assert!(keywords(&Analyzer::new(), "і та й", 5).is_empty());
+}
+
+#[test]
+fn tf_idf_test() {
+    let analyzer = Analyzer::new();
+    let documents = [
+        "Вибори завершились, результати виборів оприлюднять.",
+        "Результати матчу оприлюднять завтра.",
+        "Результати перевірять.",
+    ];
+    let mut frequencies = DocumentFrequencies::new();
+    for document in documents.iter() {
+        frequencies.add(&analyzer, document);
+    }
+    assert_eq!(frequencies.documents(), 3);
+    assert_eq!(frequencies.get("результ"), 3);
+    assert_eq!(frequencies.get("вибор"), 1);
+    assert_eq!(frequencies.idf("результ"), 1.0);
+    let found = frequencies.keywords(&analyzer, documents[0], 2);
+    let stems: Vec<&str> = found.iter().map(|k| k.keyword.stem.as_str()).collect();
+    assert_eq!(stems, ["вибор", "завершил"]);
+    assert!(found[0].score > found[1].score);
+    // the most frequent stem of the text by count alone
+    assert_eq!(keywords(&analyzer, documents[0], 1)[0].stem, "вибор");
+}
+
+#[test]
+fn watchlist_test() {
+    let watchlist = Watchlist::new(Analyzer::new(), ["вибори", "Херсон"].iter().copied());
+    let text = "Після виборів у Херсоні";
+    let matches = watchlist.matches(text);
+    let found: Vec<(&str, &str)> = matches.iter().map(|m| (m.keyword, m.word)).collect();
+    assert_eq!(found, [("вибори", "виборів"), ("Херсон", "Херсоні")]);
+    assert_eq!(&text[matches[1].range.clone()], "Херсоні");
+    assert!(watchlist.matches("Слідство триває").is_empty());
+}
+
+#[test]
+fn sentence_keywords_test() {
+    use crate::analyzer::Analyzer;
+    use crate::sentences::sentences;
+
+    let analyzer = Analyzer::new();
+    let text = "Книгами торгують на вул. Петрівка. Книги там дешеві.";
+    let stemmed: Vec<String> = sentences(text)
+        .map(|s| analyzer.stem_text(s.text))
+        .collect();
+    assert_eq!(stemmed.len(), 2);
+    assert_eq!(stemmed[1], analyzer.stem_text("Книги там дешеві."));
+    let first: Vec<Vec<String>> = sentences(text)
+        .map(|s| {
+            keywords(&analyzer, s.text, 1)
+                .into_iter()
+                .map(|k| k.stem)
+                .collect()
+        })
+        .collect();
+    assert_eq!(first[1], ["книг"]);
+}
@@ -0,0 +1,67 @@
+use whatlang::{detect, Lang};
+
+use crate::analyzer::Analyzer;
+
+/// The outcome of `stem_text_auto`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextDecision {
+    /// The text was detected as Ukrainian or Russian (or detection was inconclusive) and stemmed.
+    Stemmed {
+        text: String,
+        /// ISO 639-3 code, `None` when the detector wasn't confident.
+        language: Option<&'static str>,
+    },
+    /// The detector is confident the text is in another language; nothing was stemmed.
+    Skipped {
+        language: &'static str,
+        confidence: f64,
+    },
+}
+
+impl Analyzer {
+    pub fn stem_text_auto(&self, text: &str) -> TextDecision {
+        let info = detect(text).filter(|info| info.is_reliable());
+        match info {
+            Some(info) if info.lang() != Lang::Ukr && info.lang() != Lang::Rus => {
+                TextDecision::Skipped {
+                    language: info.lang().code(),
+                    confidence: info.confidence(),
+                }
+            }
+            _ => TextDecision::Stemmed {
+                text: self.stem_text(text),
+                language: info.map(|info| info.lang().code()),
+            },
+        }
+    }
+}
+
+/// Stems `text` with the default analyzer unless it is reliably detected as a language
+/// other than Ukrainian or Russian.
+pub fn stem_text_auto(text: &str) -> TextDecision {
+    crate::DEFAULT_ANALYZER.stem_text_auto(text)
+}
+
+#[test]
+fn stem_text_auto_test() {
+    let uk = stem_text_auto(
+        "Слідство триває, а народи нашої країни чекають на результати розслідування.",
+    );
+    assert!(matches!(
+        uk,
+        TextDecision::Stemmed {
+            language: Some("ukr"),
+            ..
+        }
+    ));
+
+    let en =
+        stem_text_auto("This page describes how the search index is built and why results are ranked by relevance.");
+    assert!(matches!(
+        en,
+        TextDecision::Skipped {
+            language: "eng",
+            ..
+        }
+    ));
+}
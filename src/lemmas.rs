@@ -0,0 +1,266 @@
+//! Form → lemma dictionaries for `StemmerBuilder::lemmatize`, e.g. compiled from the open
+//! VESUM dictionary of Ukrainian (`dict_corp_lt.txt`).
+//!
+//! The compact format stores one lemma per line, followed by a tab and its forms as edits
+//! of the lemma:
+//!
+//! ```text
+//! # ukstem lemmas 1
+//! людина<TAB>3ей 2 1и 1ою 1у 1і 3ьми 3ям 3ях
+//! ```
+//!
+//! A form is written as the number of chars removed from the end of the lemma, left out
+//! when 0, followed by the chars appended: "2" of "людина" is "люди" and "3ей" is
+//! "людей". The lemma itself is always a form of its own and not listed.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use crate::fingerprint::Fingerprint;
+
+const HEADER: &str = "# ukstem lemmas 1";
+
+fn invalid(line: usize, message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: {}", line, message),
+    )
+}
+
+/// A form → lemma lookup table, keyed and valued on lowercase words like stems. A form of
+/// several lemmas keeps the first one inserted.
+#[derive(Clone, Default)]
+pub struct LemmaDictionary {
+    // a lemma is listed again when its forms are not inserted one after another
+    lemmas: Vec<String>,
+    // form → index into `lemmas`
+    forms: HashMap<String, u32>,
+    // sum of the hashes of the entries, the same whatever their order
+    digest: u64,
+}
+
+impl LemmaDictionary {
+    pub fn new() -> Self {
+        LemmaDictionary::default()
+    }
+
+    /// Maps `form` to `lemma` unless `form` already has a lemma. Words with ASCII digits,
+    /// which the compact format can't store, are left out.
+    pub fn insert(&mut self, form: &str, lemma: &str) {
+        if form.contains(|c: char| c.is_ascii_digit()) || lemma.is_empty() {
+            return;
+        }
+        let (form, lemma) = (form.to_lowercase(), lemma.to_lowercase());
+        self.insert_form(&lemma, &lemma);
+        self.insert_form(&form, &lemma);
+    }
+
+    // maps the lowercase `form` to `lemma` unless it already has a lemma
+    fn insert_form(&mut self, form: &str, lemma: &str) {
+        if self.forms.contains_key(form) {
+            return;
+        }
+        let index = match self.lemmas.last() {
+            Some(last) if last == lemma => self.lemmas.len() - 1,
+            _ => {
+                self.lemmas.push(lemma.to_string());
+                self.lemmas.len() - 1
+            }
+        };
+        let entry = format!("{}\t{}", form, lemma);
+        // a digest of the entries alone, whatever the version of the rules
+        self.digest = self
+            .digest
+            .wrapping_add(Fingerprint::of_version(1, &entry).value());
+        self.forms.insert(form.to_string(), index as u32);
+    }
+
+    pub fn get(&self, form: &str) -> Option<&str> {
+        let index = match self.forms.get(form) {
+            Some(index) => *index,
+            None => *self.forms.get(form.to_lowercase().as_str())?,
+        };
+        Some(&self.lemmas[index as usize])
+    }
+
+    /// The number of forms, lemmas included.
+    pub fn len(&self) -> usize {
+        self.forms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.forms.is_empty()
+    }
+
+    /// Reads VESUM's `dict_corp_lt.txt`: `form lemma tags` lines separated by spaces.
+    /// Blank lines and lines starting with `#` are skipped.
+    pub fn from_vesum<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut dict = LemmaDictionary::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            match (fields.next(), fields.next()) {
+                (Some(form), Some(lemma)) => dict.insert(form, lemma),
+                _ => return Err(invalid(i + 1, "expected `form lemma tags`")),
+            }
+        }
+        Ok(dict)
+    }
+
+    /// Reads the compact format `write` produces.
+    pub fn load<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut lines = reader.lines();
+        match lines.next().transpose()? {
+            Some(header) if header.trim_end() == HEADER => {}
+            _ => return Err(invalid(1, "not an ukstem lemma dictionary")),
+        }
+        let mut dict = LemmaDictionary::new();
+        for (i, line) in lines.enumerate() {
+            let line = line?;
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (lemma, forms) = line.split_once('\t').unwrap_or((line, ""));
+            dict.insert(lemma, lemma);
+            let chars: Vec<char> = lemma.chars().collect();
+            for edit in forms.split(' ').filter(|e| !e.is_empty()) {
+                let digits =
+                    edit.len() - edit.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+                let removed: usize = edit[..digits].parse().unwrap_or(0);
+                if removed > chars.len() {
+                    return Err(invalid(i + 2, "a form removes more than its lemma"));
+                }
+                let mut form: String = chars[..chars.len() - removed].iter().collect();
+                form.push_str(&edit[digits..]);
+                dict.insert(&form, lemma);
+            }
+        }
+        Ok(dict)
+    }
+
+    /// Writes the compact format, lemmas and their forms sorted.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut lemmas: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (form, index) in &self.forms {
+            let lemma = &self.lemmas[*index as usize];
+            lemmas.entry(lemma).or_default().push(form);
+        }
+        writeln!(writer, "{}", HEADER)?;
+        for (lemma, mut forms) in lemmas {
+            forms.sort_unstable();
+            let chars: Vec<char> = lemma.chars().collect();
+            let edits: Vec<String> = forms
+                .iter()
+                .filter(|form| **form != lemma)
+                .map(|form| {
+                    let form: Vec<char> = form.chars().collect();
+                    let common = chars.iter().zip(&form).take_while(|(a, b)| a == b).count();
+                    let appended: String = form[common..].iter().collect();
+                    match chars.len() - common {
+                        0 => appended,
+                        removed => format!("{}{}", removed, appended),
+                    }
+                })
+                .collect();
+            writeln!(writer, "{}\t{}", lemma, edits.join(" "))?;
+        }
+        writer.flush()
+    }
+}
+
+impl PartialEq for LemmaDictionary {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .forms
+                .keys()
+                .all(|form| self.get(form) == other.get(form))
+    }
+}
+
+impl Eq for LemmaDictionary {}
+
+/// A map of forms to lemmas, sorted.
+#[cfg(feature = "serde")]
+impl serde::Serialize for LemmaDictionary {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let entries: BTreeMap<&str, &str> = self
+            .forms
+            .iter()
+            .map(|(form, index)| (form.as_str(), self.lemmas[*index as usize].as_str()))
+            .collect();
+        serializer.collect_map(entries)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for LemmaDictionary {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries: BTreeMap<String, String> = serde::Deserialize::deserialize(deserializer)?;
+        let mut dict = LemmaDictionary::new();
+        for (form, lemma) in &entries {
+            if !form.contains(|c: char| c.is_ascii_digit()) && !lemma.is_empty() {
+                dict.insert_form(&form.to_lowercase(), &lemma.to_lowercase());
+            }
+        }
+        Ok(dict)
+    }
+}
+
+// a summary rather than the entries, which `Stemmer::fingerprint` would otherwise format
+// in full
+impl fmt::Debug for LemmaDictionary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LemmaDictionary")
+            .field("forms", &self.forms.len())
+            .field("digest", &format_args!("{:016x}", self.digest))
+            .finish()
+    }
+}
+
+#[test]
+fn vesum_test() {
+    let vesum = "люди людина noun:anim:p:v_naz\n\
+                 людина людина noun:anim:f:v_naz\n\
+                 людини людина noun:anim:f:v_rod\n\
+                 Києва Київ noun:inanim:m:v_rod:prop:geo\n";
+    let dict = LemmaDictionary::from_vesum(vesum.as_bytes()).unwrap();
+    assert_eq!(dict.len(), 5);
+    assert_eq!(dict.get("Люди"), Some("людина"));
+    assert_eq!(dict.get("києва"), Some("київ"));
+    assert_eq!(dict.get("київ"), Some("київ"));
+    assert_eq!(dict.get("слово"), None);
+    assert!(LemmaDictionary::from_vesum("одне".as_bytes()).is_err());
+}
+
+#[test]
+fn compact_format_test() {
+    let mut dict = LemmaDictionary::new();
+    for (form, lemma) in [
+        ("люди", "людина"),
+        ("людини", "людина"),
+        ("людьми", "людина"),
+        ("пішов", "іти"),
+        ("мати", "мати"),
+        ("матері", "мати"),
+    ] {
+        dict.insert(form, lemma);
+    }
+    let mut compact = Vec::new();
+    dict.write(&mut compact).unwrap();
+    let compact = String::from_utf8(compact).unwrap();
+    assert_eq!(
+        compact,
+        "# ukstem lemmas 1\nлюдина\t2 1и 3ьми\nмати\t1ері\nіти\t3пішов\n"
+    );
+    let loaded = LemmaDictionary::load(compact.as_bytes()).unwrap();
+    assert_eq!(loaded, dict);
+    assert_eq!(format!("{:?}", loaded), format!("{:?}", dict));
+    assert!(LemmaDictionary::load("людина\tи\n".as_bytes()).is_err());
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+
+use crate::analyzer::Analyzer;
+use crate::tokenizer::{Token, TokenKind};
+
+/// A lexicon entry found in a text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LexiconMatch<'a> {
+    pub token: Token<'a>,
+    pub stem: String,
+    pub score: f64,
+}
+
+/// Aggregated scores of the matches in a text.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LexiconSummary {
+    /// Word tokens in the text.
+    pub words: usize,
+    pub matched: usize,
+    pub sum: f64,
+    pub positive: f64,
+    /// Sum of the negative scores, as a positive number.
+    pub negative: f64,
+}
+
+impl LexiconSummary {
+    /// Mean score of the matched words, 0 without matches.
+    pub fn mean(&self) -> f64 {
+        if self.matched == 0 {
+            0.0
+        } else {
+            self.sum / self.matched as f64
+        }
+    }
+
+    /// Share of the words that are in the lexicon.
+    pub fn coverage(&self) -> f64 {
+        if self.words == 0 {
+            0.0
+        } else {
+            self.matched as f64 / self.words as f64
+        }
+    }
+}
+
+/// Word → score lexicon (e.g. sentiment polarity) matched by stem, so every inflected form
+/// of a lexicon word gets its score. Words stemming to the same key average their scores.
+pub struct Lexicon {
+    analyzer: Analyzer,
+    scores: HashMap<String, (f64, usize)>,
+}
+
+impl Lexicon {
+    pub fn new(analyzer: Analyzer) -> Self {
+        Lexicon {
+            analyzer,
+            scores: HashMap::new(),
+        }
+    }
+
+    pub fn from_pairs<I, S>(analyzer: Analyzer, pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (S, f64)>,
+        S: AsRef<str>,
+    {
+        let mut lexicon = Lexicon::new(analyzer);
+        for (word, score) in pairs {
+            lexicon.insert(word.as_ref(), score);
+        }
+        lexicon
+    }
+
+    /// Reads tab-separated `word<TAB>score` lines; blank lines and lines starting with `#`
+    /// are skipped.
+    pub fn load<R: BufRead>(analyzer: Analyzer, reader: R) -> io::Result<Self> {
+        let mut lexicon = Lexicon::new(analyzer);
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let score = line
+                .split_once('\t')
+                .and_then(|(word, score)| Some((word, score.trim().parse::<f64>().ok()?)));
+            match score {
+                Some((word, score)) => lexicon.insert(word, score),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("line {}: expected `word<TAB>score`", i + 1),
+                    ))
+                }
+            }
+        }
+        Ok(lexicon)
+    }
+
+    pub fn insert(&mut self, word: &str, score: f64) {
+        if let Some(stem) = self.analyzer.terms(word).into_iter().next() {
+            let entry = self.scores.entry(stem).or_insert((0.0, 0));
+            entry.0 += score;
+            entry.1 += 1;
+        }
+    }
+
+    /// The score of `word` or any other form with the same stem.
+    pub fn score(&self, word: &str) -> Option<f64> {
+        let stem = self.analyzer.terms(word).into_iter().next()?;
+        self.stem_score(&stem)
+    }
+
+    fn stem_score(&self, stem: &str) -> Option<f64> {
+        self.scores
+            .get(stem)
+            .map(|(sum, count)| sum / *count as f64)
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn matches<'a>(&self, text: &'a str) -> Vec<LexiconMatch<'a>> {
+        self.analyzer
+            .analyze(text)
+            .into_iter()
+            .filter(|t| t.token.kind == TokenKind::Word)
+            .filter_map(|t| {
+                let score = self.stem_score(&t.term)?;
+                Some(LexiconMatch {
+                    token: t.token,
+                    stem: t.term,
+                    score,
+                })
+            })
+            .collect()
+    }
+
+    pub fn summarize(&self, text: &str) -> LexiconSummary {
+        let mut summary = LexiconSummary::default();
+        for term in self.analyzer.analyze(text) {
+            if term.token.kind != TokenKind::Word {
+                continue;
+            }
+            summary.words += 1;
+            if let Some(score) = self.stem_score(&term.term) {
+                summary.matched += 1;
+                summary.sum += score;
+                if score > 0.0 {
+                    summary.positive += score;
+                } else {
+                    summary.negative -= score;
+                }
+            }
+        }
+        summary
+    }
+}
+
+#[test]
+fn lexicon_test() {
+    let tsv = "# word\tscore\nчудовий\t2\nпоганий\t-1.5\nжахливий\t-2\n";
+    let lexicon = Lexicon::load(Analyzer::new(), tsv.as_bytes()).unwrap();
+    assert_eq!(lexicon.score("чудовому"), Some(2.0));
+
+    let text = "Чудовий концерт, але погані місця";
+    let matches = lexicon.matches(text);
+    assert_eq!(
+        matches.iter().map(|m| m.token.text).collect::<Vec<_>>(),
+        ["Чудовий", "погані"]
+    );
+
+    let summary = lexicon.summarize(text);
+    assert_eq!((summary.words, summary.matched), (5, 2));
+    assert_eq!((summary.positive, summary.negative), (2.0, 1.5));
+    assert_eq!(summary.mean(), 0.25);
+    assert!(Lexicon::load(Analyzer::new(), "слово\tбагато".as_bytes()).is_err());
+}
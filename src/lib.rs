@@ -1,168 +1,552 @@
-use bytes::Bytes;
 use lazy_static::lazy_static;
-use pcre2::bytes::{Regex, RegexBuilder};
-use std::str::from_utf8;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
+use stemmer_uk_core::normalize::{is_normalized, normalize, Normalization};
+use stemmer_uk_core::{Cut, Inflection};
+
+pub mod analyzer;
+pub mod anonymize;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod audit;
+pub mod cache;
+#[cfg(feature = "charabia")]
+pub mod charabia;
+#[doc(hidden)]
+pub mod codegen;
+pub mod collation;
+pub mod compat;
+pub mod corpus;
+#[cfg(feature = "fst")]
+pub mod dict;
+pub mod dictionary;
+pub mod diminutive;
+pub mod error;
+pub mod eval;
+pub mod explain;
+pub mod export;
+pub mod ext;
+pub mod extsort;
+pub mod faq;
+pub mod feminitive;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fingerprint;
+pub mod frequency;
+#[cfg(feature = "futures")]
+pub mod futures;
+pub mod homoglyph;
+pub mod incremental;
+pub mod ingest;
+pub mod intern;
+pub mod keywords;
+#[cfg(feature = "whatlang")]
+pub mod language;
+pub mod lemmas;
+pub mod lexicon;
+pub mod memory;
+pub mod metrics;
+pub mod mining;
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+pub mod ngrams;
+pub mod normalizer;
+pub mod ocr;
+pub mod offsets;
+pub mod packs;
+pub mod paired;
+#[cfg(feature = "paradigms")]
+pub mod paradigms;
+pub mod pos;
+#[cfg(feature = "precomputed")]
+pub mod precomputed;
+pub mod preprocess;
+pub mod presets;
+#[cfg(feature = "profanity")]
+pub mod profanity;
+#[cfg(feature = "python")]
+mod python;
+pub mod resources;
+pub mod rules;
+pub mod shared;
+pub mod similar;
+pub mod snippet;
+#[cfg(feature = "alloc-stats")]
+pub mod stats;
+pub mod stem;
+mod stemmer;
+pub mod stopwords;
+pub mod stream;
+#[cfg(feature = "tantivy")]
+pub mod tantivy;
+#[allow(clippy::invisible_characters)]
 mod test_stemmer_uk;
+pub mod token_stream;
+pub mod translit;
+pub mod trigram_model;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "watch")]
+pub mod watch;
+pub mod wordlist;
+
+pub use error::{ItemError, StemError};
+pub use ext::{StemsExt, StrStemExt};
+pub use pos::PosGuess;
+pub use shared::{global_config, set_global_config};
+pub use snippet::{ellipsize_uk, truncate_uk};
+pub use stem::Stem;
+pub use stemmer::{
+    fit_bytes, restore_case, Acronyms, Algorithm, Apostrophes, Compounds, Foreign, Language, Mode,
+    StemAll, StemKey, StemOutcome, Stemmer, StemmerBuilder, StemmerOptions, TryStemAll, Vowelless,
+    SUPERLATIVE_PREFIXES,
+};
+pub use stemmer_uk_analysis::{sentences, tokenizer};
+pub use stemmer_uk_core as core;
+pub use stream::stem_lines;
+
+#[cfg(feature = "whatlang")]
+pub use language::stem_text_auto;
+
+// the UniFFI items of `mobile`, which must be declared at the crate root
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
+fn ukstemmer_search_preprocess(word: String, normalization: Normalization) -> String {
+    match normalize(&word, normalization) {
+        Cow::Borrowed(_) => word,
+        Cow::Owned(normalized) => normalized,
+    }
+}
+
+// counts lazily initialized statics, so tests can check that `warm_up` left none behind
+static LAZY_INITS: AtomicUsize = AtomicUsize::new(0);
 
-fn ukstemmer_search_preprocess(word: String) -> String {
-    word.to_lowercase()
-        .replace("'", "")
-        .replace("ё", "е")
-        .replace("ъ", "ї")
+pub(crate) fn lazy<T>(init: impl FnOnce() -> T) -> T {
+    LAZY_INITS.fetch_add(1, Ordering::Relaxed);
+    init()
 }
 
+// Each ending group removes the longest suffix of its table the word ends with, whatever
+// the order of the table (see `suffix_priority_test`). The built-in tables are generated
+// from rules/suffixes.txt.
 lazy_static! {
-    // http://uk.wikipedia.org/wiki/Голосний_звук
-    static ref VOVEL: Regex = RegexBuilder::new()
-            .utf(true)
-            .ucp(true)
-            .build(r"аеиоуюяіїє").unwrap();
-    static ref PERFECTIVEGROUND: Regex = RegexBuilder::new()
-            .utf(true)
-            .ucp(true)
-            .build(r"(ив|ивши|ившись|ыв|ывши|ывшись((?<=[ая])(в|вши|вшись)))$").unwrap();
-    //  http://uk.wikipedia.org/wiki/Рефлексивне_дієслово
-    static ref REFLEXIVE: Regex = RegexBuilder::new()
-            .utf(true)
-            .ucp(true)
-            .build(r"(с[яьи])$").unwrap();
-    // http://uk.wikipedia.org/wiki/Прикметник + http://wapedia.mobi/uk/Прикметник
-    static ref ADJECTIVE: Regex = RegexBuilder::new()
-            .utf(true)
-            .ucp(true)
-            .build(r"(ими|ій|ий|а|е|ова|ове|ів|є|їй|єє|еє|я|ім|ем|им|ім|их|іх|ою|йми|іми|у|ю|ого|ому|ої)$").unwrap();
-    // http://uk.wikipedia.org/wiki/Дієприкметник
-    static ref PARTICIPLE: Regex = RegexBuilder::new()
-            .utf(true)
-            .ucp(true)
-            .build(r"(ий|ого|ому|им|ім|а|ій|у|ою|ій|і|их|йми|их)$").unwrap();
-    // http://uk.wikipedia.org/wiki/Дієслово
-    static ref VERB: Regex = RegexBuilder::new()
-            .utf(true)
-            .ucp(true)
-            .build(r"(сь|ся|ив|ать|ять|у|ю|ав|али|учи|ячи|вши|ши|е|ме|ати|яти|є)$").unwrap();
-    // http://uk.wikipedia.org/wiki/Іменник
-    static ref NOUN: Regex = RegexBuilder::new()
-            .utf(true)
-            .ucp(true)
-            .build(r"(а|ев|ов|е|ями|ами|еи|и|ей|ой|ий|й|иям|ям|ием|ем|ам|ом|о|у|ах|иях|ях|ы|ь|ию|ью|ю|ия|ья|я|і|ові|ї|ею|єю|ою|є|еві|ем|єм|ів|їв|ю)$").unwrap();
-    // http://uk.wikipedia.org/wiki/Голосний_звук
-    static ref RVRE: Regex = RegexBuilder::new()
-            .utf(true)
-            .ucp(true)
-            .build(r"[аеиоуюяіїє]").unwrap();
-    static ref DERIVATIONAL: Regex = RegexBuilder::new()
-            .utf(true)
-            .ucp(true)
-            .build(r"[^аеиоуюяіїє][аеиоуюяіїє]+[^аеиоуюяіїє]+[аеиоуюяіїє].*(?<=о)сть?$").unwrap();
-    static ref N1_RE: Regex = RegexBuilder::new()
-            .utf(true)
-            .ucp(true)
-            .build(r"и$").unwrap();
-    static ref N2_RE: Regex = RegexBuilder::new()
-            .utf(true)
-            .ucp(true)
-            .build(r"ость$").unwrap();
-    static ref N3_RE: Regex = RegexBuilder::new()
-            .utf(true)
-            .ucp(true)
-            .build(r"ь$").unwrap();
-    static ref N4_RE: Regex = RegexBuilder::new()
-            .utf(true)
-            .ucp(true)
-            .build(r"ейше?$").unwrap();
-    static ref N5_RE: Regex = RegexBuilder::new()
-            .utf(true)
-            .ucp(true)
-            .build(r"нн$").unwrap();
-}
-
-fn s<'a>(st: &[u8], reg: &Regex, to: &[u8], rv: &mut Bytes) -> bool {
-    let orig = st;
-    let res = reg.find(st).unwrap();
-    if let Some(m) = res {
-        let result = replace(st, to, m.start(), m.end());
-        *rv = result;
+    static ref BUILTIN_RULES: rules::RuleSet = lazy(rules::RuleSet::builtin);
+}
+
+/// Stems a single word with the default configuration.
+///
+/// This is the original API and stays supported through `compat::v0`; new code should
+/// prefer `Stemmer::stem`, which also takes the configured options into account.
+pub fn stem_word(word: String) -> String {
+    stem_preprocessed(ukstemmer_search_preprocess(word, Normalization::default()))
+}
+
+/// How `stem_word` stems `word`: its RV, the rules that applied and the forms they left.
+pub fn stem_word_explained(word: &str) -> explain::StemTrace {
+    DEFAULT_ANALYZER.get_stemmer().stem_explained(word)
+}
+
+/// `Stemmer::stem_with_pos` with the default configuration.
+pub fn stem_with_pos(word: &str) -> (String, PosGuess) {
+    DEFAULT_ANALYZER.get_stemmer().stem_with_pos(word)
+}
+
+/// Like `stem_word`, reporting internal failures of the rules instead of returning the
+/// word unchanged, and rejecting input `Stemmer::try_stem` rejects.
+pub fn try_stem_word(word: &str) -> Result<String, StemError> {
+    Stemmer::default().try_stem(word.as_bytes())
+}
+
+/// Stems a single word with the default configuration, borrowing from `word` when it
+/// is already normalized (lowercase, no apostrophes): the rules only ever remove a
+/// suffix, so the stem is then a prefix of the input.
+pub fn stem(word: &str) -> Cow<'_, str> {
+    if !is_normalized(word, Normalization::default()) {
+        return Cow::Owned(stem_preprocessed(ukstemmer_search_preprocess(
+            word.to_string(),
+            Normalization::default(),
+        )));
+    }
+    match stem_cut(word, &BUILTIN_RULES, &mut None, None, 0, 0, None) {
+        Ok(cut) => cut.of(word),
+        Err(_) => Cow::Borrowed(word),
     }
+}
+
+lazy_static! {
+    static ref DEFAULT_ANALYZER: analyzer::Analyzer = lazy(analyzer::Analyzer::new);
+}
 
-    !orig.eq(rv)
+/// Compiles the suffix rules and builds the default tokenizer, analyzer and
+/// transliteration models now, e.g. at service startup, instead of on first use.
+pub fn warm_up() {
+    lazy_static::initialize(&BUILTIN_RULES);
+    lazy_static::initialize(&DEFAULT_ANALYZER);
+    #[cfg(feature = "paradigms")]
+    paradigms::warm_up();
+    shared::warm_up();
+    trigram_model::warm_up();
+    stemmer_uk_analysis::warm_up();
+    translit::warm_up();
 }
 
-fn replace<'a>(st: &'a [u8], replacer: &'a [u8], start: usize, end: usize) -> Bytes {
-    let mut bytes = Bytes::with_capacity(start + replacer.len() + st[end..].len());
-    bytes.extend_from_slice(&st[..start]);
-    bytes.extend_from_slice(replacer);
-    bytes.extend_from_slice(&st[end..]);
+/// The fingerprint of the default configuration used by `stem_word` and `stem_all`.
+pub fn config_fingerprint() -> fingerprint::Fingerprint {
+    Stemmer::default().fingerprint()
+}
 
-    return bytes;
+/// Lazily stems every word of `words` with the default configuration.
+pub fn stem_all<I>(words: I) -> StemAll<'static, I::IntoIter>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    DEFAULT_ANALYZER.get_stemmer().stem_all(words)
 }
 
-#[test]
-fn replace_test() {
-    let reg = RegexBuilder::new()
-        .utf(true)
-        .ucp(true)
-        .build(r"123")
-        .unwrap();
-    let s = "012345678".as_bytes();
-    let v = reg.find(s).unwrap().unwrap();
+/// Stems every word of `words` with the default configuration, in order.
+pub fn stem_words<'w, I>(words: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'w str>,
+{
+    DEFAULT_ANALYZER.get_stemmer().stem_words(words)
+}
 
-    assert_eq!(
-        replace(s, "_".as_bytes(), v.start(), v.end()),
-        "0_45678".as_bytes()
-    );
+/// `Stemmer::par_stem_words` with the default configuration.
+#[cfg(feature = "rayon")]
+pub fn par_stem_words<'w, I>(words: I) -> Vec<String>
+where
+    I: rayon::iter::IntoParallelIterator<Item = &'w str>,
+{
+    DEFAULT_ANALYZER.get_stemmer().par_stem_words(words)
 }
 
-fn as_str(b: &[u8]) -> String {
-    from_utf8(b).expect("not correct utf8 bytes").to_string()
+/// `Stemmer::unique_stems` with the default configuration.
+pub fn unique_stems<'w, I>(words: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'w str>,
+{
+    DEFAULT_ANALYZER.get_stemmer().unique_stems(words)
 }
 
-pub fn stem_word(word: String) -> String {
-    let word = ukstemmer_search_preprocess(word.clone());
+/// `Stemmer::unique_stems_sorted` with the default configuration.
+pub fn unique_stems_sorted<'w, I>(words: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'w str>,
+{
+    DEFAULT_ANALYZER.get_stemmer().unique_stems_sorted(words)
+}
+
+/// `Stemmer::try_stem_all` with the default configuration.
+pub fn try_stem_all<I>(words: I) -> TryStemAll<'static, I::IntoIter>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    DEFAULT_ANALYZER.get_stemmer().try_stem_all(words)
+}
 
-    if let Ok(Some(m)) = RVRE.find(word.clone().as_bytes()) {
-        let m_end = m.end();
+/// `Stemmer::conflate` with the global configuration, see `set_global_config`.
+pub fn conflate<'w, I>(words: I) -> HashMap<String, Vec<String>>
+where
+    I: IntoIterator<Item = &'w str>,
+{
+    global_config().get_stemmer().conflate(words)
+}
 
-        let start = Bytes::from(word.clone().as_bytes()[0..m_end].as_ref());
-        let mut rv = Bytes::from(word.clone().as_bytes()[m_end..].as_ref());
+/// `Stemmer::same_stem` with the global configuration, see `set_global_config`.
+pub fn same_stem(a: &str, b: &str) -> bool {
+    global_config().get_stemmer().same_stem(a, b)
+}
 
-        // Step 1
-        if !s(&rv.clone()[..], &PERFECTIVEGROUND, "".as_bytes(), &mut rv) {
-            s(&rv.clone()[..], &REFLEXIVE, "".as_bytes(), &mut rv);
+/// `Stemmer::stem_bytes` with the global configuration, see `set_global_config`.
+pub fn stem_bytes(word: &[u8]) -> Result<Vec<u8>, StemError> {
+    global_config().get_stemmer().stem_bytes(word)
+}
+
+/// `Stemmer::stem_into` with the global configuration, see `set_global_config`.
+pub fn stem_into(word: &str, out: &mut String) {
+    global_config().get_stemmer().stem_into(word, out)
+}
+
+/// `Stemmer::stem_key` with the global configuration, see `set_global_config`.
+pub fn stem_key(word: &str) -> StemKey {
+    global_config().get_stemmer().stem_key(word)
+}
+
+/// Stems every word of running text, keeping punctuation, numbers and whitespace. Uses
+/// the global configuration, see `set_global_config`.
+pub fn stem_text(text: &str) -> String {
+    global_config().stem_text(text)
+}
+
+/// `stem_text` piece by piece, see `Analyzer::stem_text_iter`.
+pub fn stem_text_iter(text: &str) -> analyzer::StemText<'_, Arc<analyzer::Analyzer>> {
+    analyzer::StemText::new(global_config(), text)
+}
+
+/// The words of `text` with their stems and byte ranges, see `Analyzer::stem_tokens`.
+pub fn stem_tokens(text: &str) -> analyzer::StemTokens<'_, Arc<analyzer::Analyzer>> {
+    analyzer::StemTokens::new(global_config(), text)
+}
 
-            if s(&rv.clone()[..], &ADJECTIVE, "".as_bytes(), &mut rv) {
-                s(&rv.clone()[..], &PARTICIPLE, "".as_bytes(), &mut rv);
-            } else {
-                if !s(&rv.clone()[..], &VERB, "".as_bytes(), &mut rv) {
-                    s(&rv.clone()[..], &NOUN, "".as_bytes(), &mut rv);
-                }
+/// `Analyzer::find_inflections` with the global configuration, see `set_global_config`.
+pub fn find_inflections(text: &str, word: &str) -> Vec<Range<usize>> {
+    global_config().find_inflections(text, word)
+}
+
+/// `Analyzer::word_at` with the global configuration, see `set_global_config`.
+pub fn word_at(text: &str, pos: usize) -> Option<analyzer::StemmedToken<'_>> {
+    global_config().word_at(text, pos)
+}
+
+fn stem_preprocessed(word: String) -> String {
+    stem_rules(word, &BUILTIN_RULES, &mut None, None, 0, 0, None)
+}
+
+// The rules never fail on valid input; should a bad slice ever occur, the infallible API
+// returns the normalized word unchanged rather than panicking.
+fn stem_rules(
+    mut word: String,
+    rule_set: &rules::RuleSet,
+    fired: &mut Option<Vec<rules::RuleHit>>,
+    guard: Option<&frequency::FrequencyGuard>,
+    restrip: usize,
+    min_chars: usize,
+    inflection: Option<Inflection>,
+) -> String {
+    let hits = fired.as_ref().map_or(0, Vec::len);
+    match stem_cut(
+        &word, rule_set, fired, guard, restrip, min_chars, inflection,
+    ) {
+        Ok(cut) => cut.truncate(&mut word),
+        Err(_) => {
+            if let Some(fired) = fired {
+                fired.truncate(hits);
             }
         }
-        // Step 2
-        s(&rv.clone()[..], &N1_RE, "".as_bytes(), &mut rv);
+    }
+    word
+}
 
-        // Step 3
-        if let Ok(Some(_)) = DERIVATIONAL.find(&rv.clone()[..]) {
-            s(&rv.clone()[..], &N2_RE, "".as_bytes(), &mut rv);
-        }
+fn try_stem_rules(
+    mut word: String,
+    rule_set: &rules::RuleSet,
+    fired: &mut Option<Vec<rules::RuleHit>>,
+    guard: Option<&frequency::FrequencyGuard>,
+    restrip: usize,
+    min_chars: usize,
+    inflection: Option<Inflection>,
+) -> Result<String, StemError> {
+    stem_cut(
+        &word, rule_set, fired, guard, restrip, min_chars, inflection,
+    )?
+    .truncate(&mut word);
+    Ok(word)
+}
 
-        // Step 4
-        if s(&rv.clone()[..], &N3_RE, "".as_bytes(), &mut rv) {
-            s(&rv.clone()[..], &N4_RE, "".as_bytes(), &mut rv);
-            s(&rv.clone()[..], &N5_RE, "н".as_bytes(), &mut rv);
+// `stemmer_uk_core::stem_cut_as` with the groups of `rule_set`, asking `guard` about the
+// stems of derivational groups.
+fn stem_cut(
+    word: &str,
+    rule_set: &rules::RuleSet,
+    fired: &mut Option<Vec<rules::RuleHit>>,
+    guard: Option<&frequency::FrequencyGuard>,
+    restrip: usize,
+    min_chars: usize,
+    inflection: Option<Inflection>,
+) -> Result<Cut, StemError> {
+    // the table holds the stems of the built-in rules without a trace or constraints
+    #[cfg(feature = "precomputed")]
+    if fired.is_none()
+        && guard.is_none()
+        && (restrip, min_chars, inflection) == (0, 0, None)
+        && std::ptr::eq(rule_set, &*BUILTIN_RULES)
+    {
+        if let Some(stem) = precomputed::stem(word) {
+            return Ok(Cut {
+                end: stem.len(),
+                newline: false,
+            });
         }
-        let mut res = Vec::with_capacity(start.len() + &rv.len());
-        res.append(&mut start.to_vec());
-        res.append(&mut rv.to_vec());
-
-        as_str(res.as_ref())
-    } else {
-        word
     }
+    let allows = guard.map(|guard| move |stem: &str| guard.allows(stem));
+    let allows = allows.as_ref().map(|f| f as &dyn Fn(&str) -> bool);
+    stemmer_uk_core::stem_cut_as(
+        word,
+        rule_set.groups(),
+        fired,
+        allows,
+        restrip,
+        min_chars,
+        inflection,
+    )
+    .map_err(StemError::from)
 }
 
 #[test]
 fn stem_word_test() {
     assert_eq!(stem_word("ручкається".into()), "ручкаєт",);
 }
+
+#[test]
+fn stem_test() {
+    let word = "ручкається";
+    assert!(matches!(stem(word), Cow::Borrowed("ручкаєт")));
+    assert!(matches!(stem("хліб"), Cow::Borrowed("хліб")));
+    assert!(matches!(stem("Ручкається"), Cow::Owned(ref s) if s == "ручкаєт"));
+    assert!(matches!(stem("здоров'я"), Cow::Owned(_)));
+    for word in ["Києва", "здоров'я", "їжачок", "ёлка"].iter() {
+        assert_eq!(stem(word), stem_word(word.to_string()));
+    }
+}
+
+#[test]
+fn vocative_test() {
+    let vocatives = [
+        ("Петро", "Петре"),
+        ("Оксана", "Оксано"),
+        ("Іван", "Іване"),
+        ("Микола", "Миколо"),
+        ("Тарас", "Тарасе"),
+        ("Ігор", "Ігоре"),
+        ("Андрій", "Андрію"),
+        ("Марія", "Маріє"),
+        ("Наталка", "Наталко"),
+        ("мама", "мамо"),
+        ("доня", "доню"),
+        ("пан", "пане"),
+        ("син", "сину"),
+        ("батько", "батьку"),
+        ("брат", "брате"),
+        ("сестра", "сестро"),
+        ("лікар", "лікарю"),
+        ("вчитель", "вчителю"),
+        ("колега", "колего"),
+    ];
+    for (nominative, vocative) in vocatives.iter() {
+        assert_eq!(
+            stem_word(vocative.to_string()),
+            stem_word(nominative.to_string())
+        );
+    }
+    // vocatives with a consonant alternation need `packs::vocative`
+    let stemmer = Stemmer::builder().pack(packs::vocative()).build();
+    for (nominative, vocative) in [
+        ("друг", "друже"),
+        ("козак", "козаче"),
+        ("чоловік", "чоловіче"),
+    ] {
+        assert_ne!(
+            stem_word(vocative.to_string()),
+            stem_word(nominative.to_string())
+        );
+        assert_eq!(stemmer.stem(vocative), stemmer.stem(nominative));
+    }
+}
+
+#[test]
+fn apostrophe_variants_test() {
+    // as the original stemmer, `stem_word` only takes the ASCII "'" for an apostrophe
+    assert_eq!(stem_word("п'ять".into()), "пят");
+    assert_eq!(stem_word("п’ять".into()), "п’ят");
+    for stemmer in [
+        Stemmer::builder().algorithm(Algorithm::V2).build(),
+        Stemmer::builder().fold_unicode(true).build(),
+    ] {
+        for word in ["п’ять", "пʼять", "п`ять", "П’ять"].iter() {
+            assert_eq!(stemmer.stem(word), "пят");
+        }
+        // "й" decomposed into "и" and a combining breve
+        assert_eq!(stemmer.stem("Чаи\u{306}ками"), stemmer.stem("чайками"));
+    }
+    let unfolded = Stemmer::builder()
+        .algorithm(Algorithm::V2)
+        .fold_unicode(false)
+        .build();
+    assert_eq!(unfolded.stem("п’ять"), "п’ят");
+}
+
+#[test]
+fn final_newline_test() {
+    assert_eq!(stem("книгами\n"), "книг\n");
+    assert!(matches!(stem("мова\n"), Cow::Owned(_)));
+    assert!(matches!(stem("хліб\n"), Cow::Borrowed("хліб\n")));
+    // a newline that becomes final after a suffix is removed is kept as well
+    assert_eq!(stem("\nоь\nом"), "\nо\n");
+    assert_eq!(stem_word("книгами\n\n".into()), "книгами\n\n");
+}
+
+#[test]
+fn try_stem_word_test() {
+    assert_eq!(try_stem_word("Ручкається"), Ok("ручкаєт".to_string()));
+    assert_eq!(try_stem_word("  "), Err(StemError::Empty));
+    assert_eq!(
+        try_stem_word("а\u{0}б"),
+        Err(StemError::ControlCharacter('\u{0}'))
+    );
+    let long = "ї".repeat(10_000);
+    for word in [
+        "\u{306}\u{306}",
+        "ь",
+        "'",
+        "🙂ся",
+        long.as_str(),
+        "ааааааааа",
+    ]
+    .iter()
+    {
+        // the infallible API returns something for anything
+        let _ = stem_word(word.to_string());
+        let _ = try_stem_word(word);
+    }
+}
+
+#[test]
+fn warm_up_test() {
+    warm_up();
+    let before = LAZY_INITS.load(Ordering::SeqCst);
+
+    stem_word("ручкається".into());
+    stem_text("Слідство триває :)");
+    analyzer::Analyzer::new()
+        .translit_repair(analyzer::TranslitRepair::Document)
+        .terms("pryvit z Kyieva");
+    translit::is_translit("dyakuyu");
+
+    assert_eq!(LAZY_INITS.load(Ordering::SeqCst), before);
+}
+
+#[test]
+fn no_panic_test() {
+    let pieces = [
+        "Книг", "ами", "\n", "'", "\u{AD}", "\u{301}", "İ", "😀", "\0", "5.", " ", "-", "ь",
+        "\u{200F}", "ст.", "pryvit",
+    ];
+    let stemmer = Stemmer::builder()
+        .ocr_tolerant(true)
+        .homoglyph_repair(homoglyph::HomoglyphRepair::Letters)
+        .build();
+    let mut state = 0x9e3779b97f4a7c15u64;
+    for _ in 0..2000 {
+        let mut text = String::new();
+        for _ in 0..state % 9 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            text.push_str(pieces[(state % pieces.len() as u64) as usize]);
+        }
+        let _ = (stem(&text), stem_word(text.clone()), try_stem_word(&text));
+        let _ = (stemmer.stem(&text), stemmer.try_stem(text.as_bytes()));
+        let _ = (stem_text(&text), stem_word_explained(&text).to_string());
+        assert_eq!(preprocess::fold_text(&text).len(), text.len());
+        for pos in 0..=text.len() + 1 {
+            let _ = word_at(&text, pos);
+        }
+    }
+}
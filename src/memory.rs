@@ -0,0 +1,118 @@
+use std::borrow::Cow;
+use std::mem::size_of;
+
+use crate::dictionary::Dictionary;
+use crate::export::SiteIndex;
+use crate::wordlist::WordList;
+
+/// An estimate of the heap memory held by a value, excluding allocator overhead.
+pub trait HeapSize {
+    fn heap_bytes(&self) -> usize;
+}
+
+impl HeapSize for String {
+    fn heap_bytes(&self) -> usize {
+        self.capacity()
+    }
+}
+
+// borrowed entries only cost their map slot
+impl HeapSize for Dictionary<'_> {
+    fn heap_bytes(&self) -> usize {
+        self.owned_bytes() + self.len() * 2 * size_of::<Cow<str>>()
+    }
+}
+
+impl HeapSize for WordList<'_> {
+    fn heap_bytes(&self) -> usize {
+        if self.is_borrowed() {
+            0
+        } else {
+            self.owned_bytes() + self.len() * size_of::<Cow<str>>()
+        }
+    }
+}
+
+impl HeapSize for SiteIndex {
+    fn heap_bytes(&self) -> usize {
+        self.docs()
+            .map(|(doc_ref, title)| doc_ref.len() + title.len() + 2 * size_of::<String>())
+            .sum::<usize>()
+            + self
+                .postings()
+                .map(|(term, docs)| {
+                    term.len() + size_of::<String>() + docs * size_of::<(usize, f64)>()
+                })
+                .sum::<usize>()
+    }
+}
+
+/// Bytes held by the optional subsystems a service embeds, grouped by kind.
+///
+/// Built by adding every cache, dictionary and index in use; compare `total` against a
+/// budget and shrink caches with `StemCache::set_max_bytes` when it is exceeded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    pub caches: usize,
+    pub dictionaries: usize,
+    pub indexes: usize,
+}
+
+impl MemoryReport {
+    pub fn new() -> Self {
+        MemoryReport::default()
+    }
+
+    /// Adds a `StemCache` or a `ShardedCache`.
+    pub fn cache<C: HeapSize>(mut self, cache: &C) -> Self {
+        self.caches += cache.heap_bytes();
+        self
+    }
+
+    pub fn dictionary<D: HeapSize>(mut self, dictionary: &D) -> Self {
+        self.dictionaries += dictionary.heap_bytes();
+        self
+    }
+
+    pub fn index<I: HeapSize>(mut self, index: &I) -> Self {
+        self.indexes += index.heap_bytes();
+        self
+    }
+
+    pub fn total(&self) -> usize {
+        self.caches + self.dictionaries + self.indexes
+    }
+
+    pub fn within(&self, budget: usize) -> bool {
+        self.total() <= budget
+    }
+}
+
+#[test]
+fn memory_report_test() {
+    use crate::analyzer::Analyzer;
+    use crate::cache::StemCache;
+    use crate::export::Field;
+
+    let cache = StemCache::new(1 << 20);
+    cache.insert("народи", "народ");
+    let dict = Dictionary::from_pairs([("люди", "людина")]);
+    let mut index = SiteIndex::new(
+        Analyzer::new(),
+        vec![Field {
+            name: "body".into(),
+            boost: 1.0,
+        }],
+    );
+    index.add("a.md", "A", &["народи автора"]);
+
+    let report = MemoryReport::new()
+        .cache(&cache)
+        .dictionary(&dict)
+        .index(&index);
+    assert_eq!(report.caches, cache.stats().bytes);
+    assert!(report.dictionaries >= "людилюдина".len());
+    assert!(report.indexes > 0);
+    assert!(report.within(report.total()));
+    assert!(!report.within(report.total() - 1));
+}
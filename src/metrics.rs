@@ -0,0 +1,87 @@
+//! Counters of what a stemmer did with the words it was given, see
+//! `StemmerBuilder::stats`, for tuning custom rule sets on real corpora.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use crate::cache::CacheStats;
+use crate::rules::RuleHit;
+
+/// A snapshot of the counters of `Stemmer::stats`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StemStats {
+    /// Words given to `Stemmer::stem` and the methods built on it.
+    pub words: u64,
+    /// The part of `words` returned as given, such as foreign words passed through or
+    /// words the rules found no ending in.
+    pub unchanged: u64,
+    /// Words the rules ran on, i.e. neither passed through nor answered by the cache.
+    pub stemmed: u64,
+    /// How often each rule group fired, by name (see the `rules` module).
+    pub rules: BTreeMap<&'static str, u64>,
+    /// The stats of the built-in cache, if `StemmerBuilder::cache` enabled it.
+    pub cache: Option<CacheStats>,
+}
+
+impl StemStats {
+    /// The share of `words` returned unchanged, 0 for no words.
+    pub fn unchanged_rate(&self) -> f64 {
+        ratio(self.unchanged, self.words)
+    }
+
+    /// The share of cache lookups answered from the cache, `None` without a cache.
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let cache = self.cache.as_ref()?;
+        Some(ratio(cache.hits, cache.hits + cache.misses))
+    }
+}
+
+fn ratio(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+// shared by the clones of a stemmer, like its cache
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    words: AtomicU64,
+    unchanged: AtomicU64,
+    stemmed: AtomicU64,
+    rules: Mutex<HashMap<&'static str, u64>>,
+}
+
+impl Counters {
+    pub(crate) fn record_word(&self, word: &str, stem: &str) {
+        self.words.fetch_add(1, Ordering::Relaxed);
+        if word == stem {
+            self.unchanged.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // one run of the rules, locking the map once for all its hits
+    pub(crate) fn record_rules(&self, hits: &[RuleHit]) {
+        self.stemmed.fetch_add(1, Ordering::Relaxed);
+        if hits.is_empty() {
+            return;
+        }
+        let mut rules = self.rules.lock().unwrap_or_else(PoisonError::into_inner);
+        for hit in hits {
+            *rules.entry(hit.rule).or_insert(0) += 1;
+        }
+    }
+
+    pub(crate) fn snapshot(&self, cache: Option<CacheStats>) -> StemStats {
+        let rules = self.rules.lock().unwrap_or_else(PoisonError::into_inner);
+        StemStats {
+            words: self.words.load(Ordering::Relaxed),
+            unchanged: self.unchanged.load(Ordering::Relaxed),
+            stemmed: self.stemmed.load(Ordering::Relaxed),
+            rules: rules.iter().map(|(rule, count)| (*rule, *count)).collect(),
+            cache,
+        }
+    }
+}
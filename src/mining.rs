@@ -0,0 +1,155 @@
+//! Exception dictionaries mined from lemma-annotated text, such as the pairs of
+//! `corpus::read_conllu`.
+//!
+//! The forms of a lemma should share a stem. `mine_exceptions` takes the stem most of a
+//! lemma's occurrences get as the right one and proposes an exception for every other
+//! form, most frequent first, in the format `StemmerBuilder::exceptions` loads.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+
+use crate::dictionary::Dictionary;
+use crate::Stemmer;
+
+/// A form the rules stem differently from the other forms of its lemma.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MinedException {
+    /// The normalized form, the key the stemmer looks exceptions up by.
+    pub form: String,
+    /// What the rules make of the form.
+    pub stem: String,
+    /// The stem of the other forms of its lemma.
+    pub expected: String,
+    /// Occurrences of the form in the input.
+    pub count: u64,
+}
+
+/// Exceptions that would give every form of a lemma in `pairs` (`(form, lemma)`, one
+/// per occurrence) the same stem, at most `max_entries` of them, most frequent first.
+///
+/// Lemmas whose occurrences have no majority stem are skipped, and so are forms annotated
+/// with more than one lemma, since no single stem is right for them.
+pub fn mine_exceptions<'a, I>(
+    stemmer: &Stemmer,
+    pairs: I,
+    max_entries: usize,
+) -> Vec<MinedException>
+where
+    I: IntoIterator<Item = &'a (String, String)>,
+{
+    // lemma -> normalized form -> (stem, occurrences)
+    let mut lemmas: BTreeMap<&str, BTreeMap<String, (String, u64)>> = BTreeMap::new();
+    let mut lemmas_of: BTreeMap<String, BTreeSet<&str>> = BTreeMap::new();
+    for (form, lemma) in pairs {
+        let form = stemmer.normalize(form);
+        let entry = lemmas.entry(lemma).or_default().entry(form.clone());
+        entry.or_insert_with_key(|form| (stemmer.stem(form), 0)).1 += 1;
+        lemmas_of.entry(form).or_default().insert(lemma);
+    }
+
+    let mut mined = Vec::new();
+    for forms in lemmas.values() {
+        let mut votes: BTreeMap<&str, u64> = BTreeMap::new();
+        for (stem, count) in forms.values() {
+            *votes.entry(stem).or_insert(0) += count;
+        }
+        let total: u64 = votes.values().sum();
+        let expected = match votes.into_iter().find(|(_, count)| 2 * count > total) {
+            Some((stem, _)) => stem,
+            None => continue,
+        };
+        for (form, (stem, count)) in forms {
+            if stem != expected && lemmas_of[form].len() == 1 {
+                mined.push(MinedException {
+                    form: form.clone(),
+                    stem: stem.clone(),
+                    expected: expected.to_string(),
+                    count: *count,
+                });
+            }
+        }
+    }
+    mined.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.form.cmp(&b.form)));
+    mined.truncate(max_entries);
+    mined
+}
+
+/// The exceptions as a dictionary for `StemmerBuilder::exceptions`.
+pub fn to_dictionary(mined: &[MinedException]) -> Dictionary<'static> {
+    Dictionary::from_pairs(mined.iter().map(|e| (e.form.clone(), e.expected.clone())))
+}
+
+/// Writes the exceptions in the `Dictionary::parse` format, with their counts and the
+/// stems they replace as comments.
+pub fn write_exceptions<W: Write>(mut out: W, mined: &[MinedException]) -> io::Result<()> {
+    writeln!(out, "# form\tstem")?;
+    for e in mined {
+        writeln!(out, "# count {}, rules give {}", e.count, e.stem)?;
+        writeln!(out, "{}\t{}", e.form, e.expected)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+fn pairs(counts: &[(&str, &str, usize)]) -> Vec<(String, String)> {
+    counts
+        .iter()
+        .flat_map(|(form, lemma, count)| {
+            std::iter::repeat_n((form.to_string(), lemma.to_string()), *count)
+        })
+        .collect()
+}
+
+#[test]
+fn mine_exceptions_test() {
+    let pairs = pairs(&[
+        ("Дитина", "дитина", 3),
+        ("дитини", "дитина", 2),
+        ("діти", "дитина", 2),
+        ("дітей", "дитина", 1),
+        ("рік", "рік", 1),
+        ("року", "рік", 2),
+        ("роки", "рік", 1),
+        ("років", "рік", 1),
+        ("лист", "лист", 4),
+        // no majority
+        ("люди", "людина", 1),
+        ("людина", "людина", 1),
+    ]);
+    let stemmer = Stemmer::new();
+    let mined = mine_exceptions(&stemmer, &pairs, 10);
+    let found: Vec<(&str, &str, u64)> = mined
+        .iter()
+        .map(|e| (e.form.as_str(), e.expected.as_str(), e.count))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("діти", "дитин", 2),
+            ("дітей", "дитин", 1),
+            ("рік", "рок", 1)
+        ]
+    );
+    assert_eq!(mined[0].stem, "діт");
+    assert_eq!(mine_exceptions(&stemmer, &pairs, 2).len(), 2);
+
+    let tuned = Stemmer::builder().exceptions(to_dictionary(&mined)).build();
+    assert_eq!(tuned.stem("Діти"), tuned.stem("дитина"));
+
+    let mut tsv = Vec::new();
+    write_exceptions(&mut tsv, &mined).unwrap();
+    let parsed = Dictionary::parse(std::str::from_utf8(&tsv).unwrap()).unwrap();
+    assert_eq!(parsed, to_dictionary(&mined));
+}
+
+#[test]
+fn ambiguous_forms_test() {
+    // "мати" is both the noun "mother" and the verb "to have"
+    let pairs = pairs(&[
+        ("мати", "мати", 1),
+        ("матір", "мати", 2),
+        ("мати", "мати_verb", 1),
+    ]);
+    let mined = mine_exceptions(&Stemmer::new(), &pairs, 10);
+    assert!(mined.iter().all(|e| e.form != "мати"));
+}
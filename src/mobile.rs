@@ -0,0 +1,85 @@
+//! UniFFI bindings for on-device stemming in iOS and Android apps, such as keyboards and
+//! readers. The Swift and Kotlin wrappers are generated from the built library:
+//!
+//! ```text
+//! cargo build --release --features uniffi
+//! cargo run --features uniffi-bindgen --bin uniffi-bindgen -- generate \
+//!     --library target/release/libstemmer_uk.so --language kotlin --out-dir bindings
+//! ```
+
+use crate::analyzer::Analyzer;
+use crate::tokenizer::TokenKind;
+
+/// `stem_word` with the default configuration.
+#[uniffi::export]
+pub fn stem_word(word: String) -> String {
+    crate::stem_word(word)
+}
+
+/// `stem_text` with the default configuration.
+#[uniffi::export]
+pub fn stem_text(text: String) -> String {
+    crate::stem_text(&text)
+}
+
+/// A stemmer and analyzer kept between calls, so their tables are built once; it can be
+/// shared between threads.
+#[derive(uniffi::Object)]
+pub struct Stemmer {
+    analyzer: Analyzer,
+}
+
+#[uniffi::export]
+impl Stemmer {
+    #[uniffi::constructor]
+    pub fn new() -> Self {
+        Stemmer {
+            analyzer: Analyzer::new(),
+        }
+    }
+
+    pub fn stem(&self, word: String) -> String {
+        self.analyzer.get_stemmer().stem(&word)
+    }
+
+    /// The stems of `words`, in order.
+    pub fn stem_all(&self, words: Vec<String>) -> Vec<String> {
+        self.analyzer.get_stemmer().stem_all(&words).collect()
+    }
+
+    pub fn stem_text(&self, text: String) -> String {
+        self.analyzer.stem_text(&text)
+    }
+
+    /// The search terms of the words of `text`, for indexing or querying.
+    pub fn terms(&self, text: String) -> Vec<String> {
+        self.analyzer
+            .analyze(&text)
+            .into_iter()
+            .filter(|t| t.token.kind == TokenKind::Word)
+            .map(|t| t.term)
+            .collect()
+    }
+}
+
+impl Default for Stemmer {
+    fn default() -> Self {
+        Stemmer::new()
+    }
+}
+
+#[test]
+fn mobile_api_test() {
+    assert_eq!(stem_word("Народами".into()), "народ");
+    assert_eq!(
+        stem_text("Слідство триває".into()),
+        crate::stem_text("Слідство триває")
+    );
+    let stemmer = Stemmer::new();
+    assert_eq!(stemmer.stem("книгами".into()), "книг");
+    assert_eq!(
+        stemmer.stem_all(vec!["книгами".into(), "автора".into()]),
+        ["книг", "автор"]
+    );
+    assert_eq!(stemmer.terms("Слідство, 5 грн".into()), ["слідств", "грн"]);
+}
@@ -0,0 +1,185 @@
+//! Character n-grams of stems or words for typo-tolerant search: a query word with a
+//! wrong letter still shares most grams of its stem with the indexed one. Not to be
+//! confused with `ngram`, the trigram model telling words from random strings.
+
+use std::iter::FusedIterator;
+
+use crate::analyzer::StemmedToken;
+
+/// Which runs of chars are grams.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GramMode {
+    /// Every run of `min..=max` chars.
+    #[default]
+    Sliding,
+    /// Only the runs at the start of the word, for search as you type.
+    Edge,
+}
+
+/// What `NgramOptions::tokens` cuts into grams.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GramSource {
+    #[default]
+    Stem,
+    /// The lowercased word, for matching what stemming would conflate.
+    Word,
+}
+
+/// Grams of `min..=max` chars, trigrams of stems by default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NgramOptions {
+    pub min: usize,
+    pub max: usize,
+    pub mode: GramMode,
+    pub source: GramSource,
+}
+
+impl Default for NgramOptions {
+    fn default() -> Self {
+        NgramOptions {
+            min: 3,
+            max: 3,
+            mode: GramMode::Sliding,
+            source: GramSource::Stem,
+        }
+    }
+}
+
+impl NgramOptions {
+    /// Grams of exactly `n` chars.
+    pub fn new(n: usize) -> Self {
+        NgramOptions {
+            min: n,
+            max: n,
+            ..NgramOptions::default()
+        }
+    }
+
+    /// The grams of `word` by position, the shorter first at each; a word shorter than
+    /// `min` chars is its only gram, so that short stems still match. A `min` of 0 counts
+    /// as 1 and a `max` below `min` as `min`.
+    pub fn grams<'w>(&self, word: &'w str) -> Vec<&'w str> {
+        let bounds: Vec<usize> = word
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(word.len()))
+            .collect();
+        let chars = bounds.len() - 1;
+        let min = self.min.max(1);
+        let max = self.max.max(min);
+        if chars == 0 {
+            return Vec::new();
+        }
+        if chars < min {
+            return vec![word];
+        }
+        let last_start = match self.mode {
+            GramMode::Sliding => chars - min,
+            GramMode::Edge => 0,
+        };
+        let mut grams = Vec::new();
+        for start in 0..=last_start {
+            for n in min..=max.min(chars - start) {
+                grams.push(&word[bounds[start]..bounds[start + n]]);
+            }
+        }
+        grams
+    }
+
+    /// The tokens of e.g. `Analyzer::stem_tokens` with their grams, so that one pass over
+    /// a text yields both.
+    pub fn tokens<'a, I>(&self, tokens: I) -> GramTokens<I::IntoIter>
+    where
+        I: IntoIterator<Item = StemmedToken<'a>>,
+    {
+        GramTokens {
+            tokens: tokens.into_iter(),
+            options: *self,
+        }
+    }
+}
+
+/// A token with the grams of its stem or word.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GramToken<'a> {
+    pub token: StemmedToken<'a>,
+    pub grams: Vec<String>,
+}
+
+/// Iterator returned by `NgramOptions::tokens`.
+pub struct GramTokens<I> {
+    tokens: I,
+    options: NgramOptions,
+}
+
+impl<'a, I: Iterator<Item = StemmedToken<'a>>> Iterator for GramTokens<I> {
+    type Item = GramToken<'a>;
+
+    fn next(&mut self) -> Option<GramToken<'a>> {
+        let token = self.tokens.next()?;
+        let word;
+        let source = match self.options.source {
+            GramSource::Stem => &*token.stem,
+            GramSource::Word => {
+                word = token.original.to_lowercase();
+                &word
+            }
+        };
+        let grams = self
+            .options
+            .grams(source)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        Some(GramToken { token, grams })
+    }
+}
+
+impl<'a, I: FusedIterator<Item = StemmedToken<'a>>> FusedIterator for GramTokens<I> {}
+
+#[test]
+fn grams_test() {
+    let trigrams = NgramOptions::default();
+    assert_eq!(trigrams.grams("книг"), ["кни", "ниг"]);
+    assert_eq!(trigrams.grams("ліс"), ["ліс"]);
+    assert_eq!(trigrams.grams("я"), ["я"]);
+    assert!(trigrams.grams("").is_empty());
+    let sliding = NgramOptions {
+        min: 2,
+        max: 3,
+        ..NgramOptions::default()
+    };
+    assert_eq!(sliding.grams("п'ят"), ["п'", "п'я", "'я", "'ят", "ят"]);
+    let edge = NgramOptions {
+        mode: GramMode::Edge,
+        ..sliding
+    };
+    assert_eq!(edge.grams("народ"), ["на", "нар"]);
+    assert_eq!(NgramOptions::new(0).grams("ї𐐨"), ["ї", "𐐨"]);
+}
+
+#[test]
+fn gram_tokens_test() {
+    use crate::analyzer::Analyzer;
+
+    let analyzer = Analyzer::new();
+    let text = "Народами книгами";
+    let tokens: Vec<GramToken> = NgramOptions::default()
+        .tokens(analyzer.stem_tokens(text))
+        .collect();
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(tokens[0].token.stem, "народ");
+    assert_eq!(tokens[0].grams, ["нар", "аро", "род"]);
+    assert_eq!(tokens[1].grams, ["кни", "ниг"]);
+    let words = NgramOptions {
+        min: 5,
+        max: 5,
+        mode: GramMode::Edge,
+        source: GramSource::Word,
+    };
+    let grams: Vec<Vec<String>> = words
+        .tokens(analyzer.stem_tokens(text))
+        .map(|t| t.grams)
+        .collect();
+    assert_eq!(grams, [["народ"], ["книга"]]);
+}
@@ -0,0 +1,278 @@
+//! The steps of the normalization words get before the suffix rules, which
+//! `StemmerBuilder::normalizers` can reorder, extend or replace, e.g. to remove stress
+//! marks or fix domain-specific spellings.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
+use stemmer_uk_core::normalize::Normalization;
+
+use crate::homoglyph::{self, HomoglyphRepair};
+use crate::ocr::Confusions;
+use crate::packs::LanguagePack;
+use crate::tokenizer::strip_invisible;
+use crate::{Apostrophes, Language, StemmerOptions};
+
+/// A normalization step, given the word as the previous steps left it.
+pub trait Normalizer: Send + Sync {
+    fn normalize<'w>(&self, word: &'w str) -> Cow<'w, str>;
+
+    /// Names the step in the stemmer fingerprint; custom steps that change behavior
+    /// between runs should return distinct ids.
+    fn id(&self) -> Cow<'_, str> {
+        Cow::Borrowed("custom")
+    }
+}
+
+impl<F> Normalizer for F
+where
+    F: Fn(&str) -> String + Send + Sync,
+{
+    fn normalize<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        Cow::Owned(self(word))
+    }
+}
+
+/// Removes the characters of `tokenizer::is_invisible`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StripInvisible;
+
+impl Normalizer for StripInvisible {
+    fn normalize<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        strip_invisible(word)
+    }
+
+    fn id(&self) -> Cow<'_, str> {
+        Cow::Borrowed("strip-invisible")
+    }
+}
+
+/// Maps Latin lookalikes in Cyrillic words to Cyrillic, see `HomoglyphRepair`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RepairHomoglyphs(pub HomoglyphRepair);
+
+impl Normalizer for RepairHomoglyphs {
+    fn normalize<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        homoglyph::repair(word, self.0)
+    }
+
+    fn id(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("homoglyphs {:?}", self.0))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Lowercase;
+
+impl Normalizer for Lowercase {
+    fn normalize<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        if word.chars().any(char::is_uppercase) {
+            Cow::Owned(word.to_lowercase())
+        } else {
+            Cow::Borrowed(word)
+        }
+    }
+
+    fn id(&self) -> Cow<'_, str> {
+        Cow::Borrowed("lowercase")
+    }
+}
+
+impl Normalizer for LanguagePack {
+    fn normalize<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        Cow::Owned(self.apply(word))
+    }
+
+    fn id(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("{:?}", self))
+    }
+}
+
+/// The preprocessing of `stem_word`: lowercasing, the apostrophes and, for
+/// `Language::Mixed`, the Russian "ё" and "ъ"; with `fold_unicode`, NFC and the
+/// typographic apostrophes too, see `StemmerBuilder::fold_unicode`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Preprocess {
+    pub apostrophes: Apostrophes,
+    pub fold_unicode: bool,
+    pub language: Language,
+}
+
+impl Normalizer for Preprocess {
+    fn normalize<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        crate::normalize(
+            word,
+            Normalization {
+                strip_apostrophes: self.apostrophes == Apostrophes::Strip,
+                fold_unicode: self.fold_unicode,
+                strip_invisible: false,
+                fold_russian: self.language == Language::Mixed,
+            },
+        )
+    }
+
+    fn id(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("{:?}", self))
+    }
+}
+
+/// Replaces "ґ" with "г", see `StemmerOptions::fold_ge`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FoldGe;
+
+impl Normalizer for FoldGe {
+    fn normalize<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        if word.contains('ґ') {
+            Cow::Owned(word.replace('ґ', "г"))
+        } else {
+            Cow::Borrowed(word)
+        }
+    }
+
+    fn id(&self) -> Cow<'_, str> {
+        Cow::Borrowed("fold-ge")
+    }
+}
+
+impl Normalizer for Confusions {
+    fn normalize<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        self.apply(word)
+    }
+
+    fn id(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("{:?}", self))
+    }
+}
+
+/// Normalization steps applied in order. Chains are equal when their steps have the same
+/// ids.
+#[derive(Clone, Default)]
+pub struct NormalizerChain {
+    steps: Vec<Arc<dyn Normalizer>>,
+}
+
+impl NormalizerChain {
+    pub fn new() -> Self {
+        NormalizerChain::default()
+    }
+
+    /// The steps a stemmer with `options` and no chain of its own normalizes words
+    /// with: `StripInvisible` if `StemmerOptions::strips_invisible`, `RepairHomoglyphs`,
+    /// `Lowercase`, the packs, `Preprocess`, `FoldGe` if `fold_ge` and the OCR confusions.
+    /// The prefixes of `strip_prefixes` are removed after any chain.
+    pub fn of(options: &StemmerOptions) -> Self {
+        let mut chain = NormalizerChain::new();
+        if options.strips_invisible() {
+            chain = chain.then(StripInvisible);
+        }
+        chain = chain
+            .then(RepairHomoglyphs(options.homoglyphs))
+            .then(Lowercase);
+        for pack in &options.packs {
+            chain = chain.then(pack.clone());
+        }
+        chain = chain.then(Preprocess {
+            apostrophes: options.apostrophes,
+            fold_unicode: options.folds_unicode(),
+            language: options.language,
+        });
+        if options.fold_ge {
+            chain = chain.then(FoldGe);
+        }
+        if let Some(confusions) = &options.ocr_confusions {
+            chain = chain.then(confusions.clone());
+        }
+        chain
+    }
+
+    /// Appends `step`.
+    pub fn then<N: Normalizer + 'static>(mut self, step: N) -> Self {
+        self.steps.push(Arc::new(step));
+        self
+    }
+
+    /// Inserts `step` before the step at `index`, e.g. 0 to run it first.
+    pub fn insert<N: Normalizer + 'static>(mut self, index: usize, step: N) -> Self {
+        self.steps
+            .insert(index.min(self.steps.len()), Arc::new(step));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn ids(&self) -> Vec<Cow<'_, str>> {
+        self.steps.iter().map(|step| step.id()).collect()
+    }
+
+    pub fn apply(&self, word: &str) -> String {
+        self.apply_with(word, |_| {})
+    }
+
+    // `apply`, passing the result of every step that changed the word to `step`
+    pub(crate) fn apply_with(&self, word: &str, mut step: impl FnMut(&str)) -> String {
+        let mut word = word.to_string();
+        for normalizer in &self.steps {
+            if let Cow::Owned(changed) = normalizer.normalize(&word) {
+                if changed != word {
+                    step(&changed);
+                    word = changed;
+                }
+            }
+        }
+        word
+    }
+}
+
+impl fmt::Debug for NormalizerChain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.ids()).finish()
+    }
+}
+
+impl PartialEq for NormalizerChain {
+    fn eq(&self, other: &Self) -> bool {
+        self.ids() == other.ids()
+    }
+}
+
+#[test]
+fn default_chain_test() {
+    use crate::Stemmer;
+
+    let options = StemmerOptions {
+        packs: vec![crate::packs::surzhyk()],
+        homoglyphs: HomoglyphRepair::Letters,
+        ocr_confusions: Some(Confusions::default()),
+        fold_ge: true,
+        algorithm: crate::Algorithm::V2,
+        ..StemmerOptions::default()
+    };
+    let stemmer = Stemmer::from_options(options.clone());
+    let chain = NormalizerChain::of(&options);
+    assert_eq!(chain.len(), 7);
+    for word in [
+        "Кни\u{AD}гами",
+        "вiкно",
+        "П’ЯТЬ",
+        "Ёлка",
+        "информация",
+        "Украіни",
+        "Ґуґлом",
+    ] {
+        assert_eq!(chain.apply(word), stemmer.normalize(word));
+    }
+    assert_eq!(NormalizerChain::of(&options), chain.clone());
+    assert_ne!(NormalizerChain::of(&StemmerOptions::default()), chain);
+    let v1 = StemmerOptions {
+        algorithm: crate::Algorithm::V1,
+        ..options
+    };
+    assert_eq!(NormalizerChain::of(&v1).len(), 6);
+}
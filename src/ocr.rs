@@ -0,0 +1,114 @@
+use std::borrow::Cow;
+
+// Common confusions of Cyrillic OCR engines. Digits are only repaired inside words that
+// already contain Cyrillic letters, so real numbers are left alone.
+const DIGITS: [(char, char); 4] = [('0', 'о'), ('1', 'і'), ('3', 'з'), ('6', 'б')];
+// letters folded into one class: lost dots and п/н stroke confusion
+const FOLDS: [(char, char); 3] = [('ї', 'і'), ('й', 'и'), ('п', 'н')];
+
+fn is_cyrillic(c: char) -> bool {
+    ('\u{0400}'..='\u{04FF}').contains(&c)
+}
+
+/// Replaces digits OCR engines commonly produce instead of letters in Cyrillic words.
+pub fn repair_digits(word: &str) -> String {
+    if !word.chars().any(is_cyrillic) {
+        return word.to_string();
+    }
+    word.chars()
+        .map(|c| {
+            DIGITS
+                .iter()
+                .find(|(from, _)| *from == c)
+                .map_or(c, |(_, to)| *to)
+        })
+        .collect()
+}
+
+/// Folds characters OCR engines confuse with each other into a single representative,
+/// so a clean stem and its noisy scan map to the same fuzzy key.
+pub fn fold(stem: &str) -> String {
+    stem.chars()
+        .map(|c| {
+            FOLDS
+                .iter()
+                .find(|(from, _)| *from == c)
+                .map_or(c, |(_, to)| *to)
+        })
+        .collect()
+}
+
+/// Letters OCR engines of Ukrainian scans confuse, folded in normalized words before the
+/// rules see them, see `StemmerBuilder::ocr_confusions`. The default folds "ї" into "і"
+/// and "й" into "и", whose dots and breve scans lose, and drops the soft signs they lose;
+/// clean and noisy text then get the same stems.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Confusions {
+    // a letter and what it becomes, `None` to drop it
+    folds: Vec<(char, Option<char>)>,
+}
+
+impl Default for Confusions {
+    fn default() -> Self {
+        Confusions::new().fold('ї', 'і').fold('й', 'и').ignore('ь')
+    }
+}
+
+impl Confusions {
+    /// No confusions, to list them with `fold` and `ignore`.
+    pub fn new() -> Self {
+        Confusions { folds: Vec::new() }
+    }
+
+    /// Replaces the lowercase `from` with `to`.
+    pub fn fold(mut self, from: char, to: char) -> Self {
+        self.folds.retain(|(c, _)| *c != from);
+        self.folds.push((from, Some(to)));
+        self
+    }
+
+    /// Removes the lowercase `letter`.
+    pub fn ignore(mut self, letter: char) -> Self {
+        self.folds.retain(|(c, _)| *c != letter);
+        self.folds.push((letter, None));
+        self
+    }
+
+    /// `word` with the confused letters folded, borrowed when it has none.
+    pub fn apply<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        let folded = |c: &char| self.folds.iter().find(|(from, _)| from == c);
+        if !word.chars().any(|c| folded(&c).is_some()) {
+            return Cow::Borrowed(word);
+        }
+        Cow::Owned(
+            word.chars()
+                .filter_map(|c| folded(&c).map_or(Some(c), |(_, to)| *to))
+                .collect(),
+        )
+    }
+}
+
+#[test]
+fn repair_digits_test() {
+    assert_eq!(repair_digits("с0бака"), "собака");
+    assert_eq!(repair_digits("1сторія"), "історія");
+    assert_eq!(repair_digits("2024"), "2024");
+}
+
+#[test]
+fn fold_test() {
+    assert_eq!(fold("україн"), fold("украін"));
+    assert_eq!(fold("парод"), "народ");
+}
+
+#[test]
+fn confusions_test() {
+    let confusions = Confusions::default();
+    assert_eq!(confusions.apply("україні"), "украіні");
+    assert_eq!(confusions.apply("сільський"), "сілскии");
+    assert!(matches!(confusions.apply("книга"), Cow::Borrowed("книга")));
+    let custom = Confusions::new().fold('є', 'е').ignore('ь');
+    assert_eq!(custom.apply("їсть є"), "їст е");
+    assert_eq!(Confusions::new().fold('ї', 'і').ignore('ї').apply("її"), "");
+}
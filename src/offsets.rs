@@ -0,0 +1,157 @@
+//! Provenance of stems: which input position every character of a stem came from.
+
+/// A stem together with the input position of each of its characters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MappedStem {
+    pub stem: String,
+    // byte offset in the input of the character each stem character derives from
+    origins: Vec<usize>,
+    // input offset right after the character the last stem character derives from
+    end: usize,
+}
+
+impl MappedStem {
+    /// The input byte offset that the stem byte offset `stemmed_pos` derives from. Offsets
+    /// inside a character map to the start of its origin; the end of the stem maps to the
+    /// end of the input character its last character came from.
+    pub fn map_offset(&self, stemmed_pos: usize) -> usize {
+        if stemmed_pos >= self.stem.len() {
+            return self.end;
+        }
+        let i = self
+            .stem
+            .char_indices()
+            .take_while(|(start, _)| *start <= stemmed_pos)
+            .count();
+        self.origins[i - 1]
+    }
+
+    /// The input byte offset of every character of the stem.
+    pub fn origins(&self) -> &[usize] {
+        &self.origins
+    }
+}
+
+/// Text rewritten step by step, remembering the input offset of every character.
+#[derive(Clone, Debug)]
+pub(crate) struct Tracked {
+    text: String,
+    origins: Vec<usize>,
+    // every input character boundary, including the end
+    boundaries: Vec<usize>,
+}
+
+impl Tracked {
+    /// `text` found at byte offset `base` of the input.
+    pub(crate) fn new(text: &str, base: usize) -> Self {
+        let origins: Vec<usize> = text.char_indices().map(|(i, _)| base + i).collect();
+        let mut boundaries = origins.clone();
+        boundaries.push(base + text.len());
+        Tracked {
+            text: text.to_string(),
+            origins,
+            boundaries,
+        }
+    }
+
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the text by a rewritten version of it. Characters are aligned by their
+    /// longest common subsequence; the changed characters between two aligned ones take
+    /// the origins of the replaced characters position by position, which is exact for
+    /// substitutions and removals.
+    pub(crate) fn rewrite(&mut self, new: &str) {
+        if new == self.text {
+            return;
+        }
+        let old: Vec<char> = self.text.chars().collect();
+        let new_chars: Vec<char> = new.chars().collect();
+        let (n, m) = (old.len(), new_chars.len());
+        // lcs[i][j]: common subsequence length of old[i..] and new[j..]
+        let mut lcs = vec![vec![0u16; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if old[i] == new_chars[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let mut origins = Vec::with_capacity(m);
+        let (mut i, mut j) = (0, 0);
+        let (mut gap_i, mut gap_j) = (0, 0);
+        while j < m {
+            if i < n && old[i] == new_chars[j] {
+                origins.push(self.origins[i]);
+                i += 1;
+                j += 1;
+                gap_i = i;
+                gap_j = j;
+            } else if i < n && lcs[i + 1][j] >= lcs[i][j + 1] {
+                i += 1;
+            } else {
+                let k = (gap_i + (j - gap_j)).min(n.saturating_sub(1));
+                origins.push(self.origins.get(k).copied().unwrap_or(self.end()));
+                j += 1;
+            }
+        }
+        self.text = new.to_string();
+        self.origins = origins;
+    }
+
+    /// Replaces the text by `new`, whose characters derive from the characters at the
+    /// byte offsets `sources` of the current text.
+    pub(crate) fn rewrite_from(&mut self, new: String, sources: &[usize]) {
+        let origins = sources
+            .iter()
+            .map(|&pos| {
+                let i = self.text[..pos].chars().count();
+                self.origins.get(i).copied().unwrap_or(self.end())
+            })
+            .collect();
+        self.text = new;
+        self.origins = origins;
+    }
+
+    fn end(&self) -> usize {
+        *self.boundaries.last().unwrap()
+    }
+
+    pub(crate) fn finish(self) -> MappedStem {
+        let end = match self.origins.last() {
+            Some(last) => {
+                let i = self.boundaries.partition_point(|b| b <= last);
+                self.boundaries.get(i).copied().unwrap_or(self.end())
+            }
+            None => self.boundaries[0],
+        };
+        MappedStem {
+            stem: self.text,
+            origins: self.origins,
+            end,
+        }
+    }
+}
+
+#[test]
+fn tracked_test() {
+    // apostrophe removed, one letter substituted, suffix dropped
+    let mut t = Tracked::new("Пір'ячкам", 10);
+    t.rewrite(&t.text().to_lowercase());
+    t.rewrite("пірячкам");
+    t.rewrite("пірячк");
+    let mapped = t.finish();
+    assert_eq!(mapped.stem, "пірячк");
+    // "я" comes after the 1-byte apostrophe
+    assert_eq!(mapped.map_offset("пір".len()), 10 + "Пір'".len());
+    assert_eq!(mapped.map_offset(0), 10);
+    assert_eq!(mapped.map_offset(mapped.stem.len()), 10 + "Пір'ячк".len());
+
+    let mut t = Tracked::new("shcho", 0);
+    t.rewrite_from("що".to_string(), &[0, 4]);
+    assert_eq!(t.finish().origins(), [0, 4]);
+}
@@ -0,0 +1,296 @@
+/// An ordered set of rewrites applied to a preprocessed (lowercased) word before stemming.
+///
+/// Rewrites are tried in this order: whole-word replacements, letter substitutions,
+/// rewrites anywhere in the word, word-start rewrites and finally a single word-ending
+/// rewrite (longest match wins).
+/// Letter substitutions registered with `letter_after_consonant` only fire when the
+/// previous character is a consonant.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LanguagePack {
+    name: String,
+    words: Vec<(String, String)>,
+    letters: Vec<(char, String)>,
+    consonant_letters: Vec<(char, String)>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    infixes: Vec<(String, String)>,
+    prefixes: Vec<(String, String)>,
+    endings: Vec<(String, String)>,
+}
+
+// an ending rewrite never leaves fewer characters than this in front of it
+const MIN_ENDING_BASE: usize = 2;
+
+impl LanguagePack {
+    pub fn new(name: &str) -> Self {
+        LanguagePack {
+            name: name.to_string(),
+            words: Vec::new(),
+            letters: Vec::new(),
+            consonant_letters: Vec::new(),
+            infixes: Vec::new(),
+            prefixes: Vec::new(),
+            endings: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn word(mut self, from: &str, to: &str) -> Self {
+        self.words.push((from.to_string(), to.to_string()));
+        self
+    }
+
+    pub fn letter(mut self, from: char, to: &str) -> Self {
+        self.letters.push((from, to.to_string()));
+        self
+    }
+
+    pub fn letter_after_consonant(mut self, from: char, to: &str) -> Self {
+        self.consonant_letters.push((from, to.to_string()));
+        self
+    }
+
+    /// Rewrites every occurrence of `from`, in the order the rewrites were added.
+    pub fn infix(mut self, from: &str, to: &str) -> Self {
+        self.infixes.push((from.to_string(), to.to_string()));
+        self
+    }
+
+    pub fn prefix(mut self, from: &str, to: &str) -> Self {
+        self.prefixes.push((from.to_string(), to.to_string()));
+        self
+    }
+
+    pub fn ending(mut self, from: &str, to: &str) -> Self {
+        self.endings.push((from.to_string(), to.to_string()));
+        self
+    }
+
+    pub fn apply(&self, word: &str) -> String {
+        if let Some((_, to)) = self.words.iter().find(|(from, _)| from == word) {
+            return to.clone();
+        }
+
+        let mut res = String::with_capacity(word.len());
+        let mut prev = None;
+        for c in word.chars() {
+            let after_consonant = prev.is_some_and(is_consonant);
+            let to = self
+                .letters
+                .iter()
+                .chain(self.consonant_letters.iter().filter(|_| after_consonant))
+                .find(|(from, _)| *from == c);
+            match to {
+                Some((_, to)) => res.push_str(to),
+                None => res.push(c),
+            }
+            prev = Some(c);
+        }
+        for (from, to) in &self.infixes {
+            if res.contains(from.as_str()) {
+                res = res.replace(from.as_str(), to);
+            }
+        }
+
+        if let Some((from, to)) = self
+            .prefixes
+            .iter()
+            .filter(|(from, _)| res.starts_with(from.as_str()))
+            .max_by_key(|(from, _)| from.len())
+        {
+            res = format!("{}{}", to, &res[from.len()..]);
+        }
+
+        if let Some((from, to)) = self
+            .endings
+            .iter()
+            .filter(|(from, _)| {
+                res.ends_with(from.as_str())
+                    && res[..res.len() - from.len()].chars().count() >= MIN_ENDING_BASE
+            })
+            .max_by_key(|(from, _)| from.len())
+        {
+            res.truncate(res.len() - from.len());
+            res.push_str(to);
+        }
+
+        res
+    }
+}
+
+fn is_consonant(c: char) -> bool {
+    c.is_alphabetic() && !"аеиоуюяіїєыэёѣ".contains(c) && c != 'ь' && c != 'ъ'
+}
+
+/// Maps common Surzhyk and Russianized spellings to their Ukrainian equivalents, including
+/// the double consonants of Russian loan words ("программа", "класса").
+pub fn surzhyk() -> LanguagePack {
+    LanguagePack::new("surzhyk")
+        .word("шо", "що")
+        .word("што", "що")
+        .word("ето", "це")
+        .word("это", "це")
+        .word("тоже", "теж")
+        .word("щас", "зараз")
+        .word("сейчас", "зараз")
+        .word("сєйчас", "зараз")
+        .word("вообще", "взагалі")
+        .word("вобще", "взагалі")
+        .word("всьо", "все")
+        .word("тіпа", "типу")
+        .word("прівєт", "привіт")
+        .letter('ы', "и")
+        .letter('э', "е")
+        // Ukrainian words almost never start with "и", Russian ones do
+        .infix("грамм", "грам")
+        .infix("класс", "клас")
+        .infix("комм", "ком")
+        .infix("масс", "мас")
+        .prefix("и", "і")
+        .prefix("проект", "проєкт")
+        .ending("ов", "ів")
+        .ending("ев", "ів")
+        .ending("ость", "ість")
+        .ending("ение", "ення")
+        .ending("ание", "ання")
+        .ending("ия", "ія")
+        .ending("ии", "ії")
+        .ending("ию", "ію")
+        .ending("ией", "ією")
+        .ending("ое", "е")
+        .ending("ие", "і")
+}
+
+/// Maps pre-reform and Soviet-era spellings to modern orthography: obsolete letters,
+/// Zhelekhivka "ї" after consonants, the final hard sign, and restores "ґ" in common roots.
+pub fn historical() -> LanguagePack {
+    LanguagePack::new("historical")
+        .letter('ѣ', "і")
+        .letter('ѳ', "ф")
+        .letter('ѵ', "і")
+        .letter('ы', "и")
+        .letter_after_consonant('ї', "і")
+        .prefix("грунт", "ґрунт")
+        .prefix("ганок", "ґанок")
+        .prefix("ганк", "ґанк")
+        .prefix("гудзик", "ґудзик")
+        .prefix("гедз", "ґедз")
+        .prefix("гринджол", "ґринджол")
+        .prefix("гратк", "ґратк")
+        .prefix("гава", "ґава")
+        .prefix("гвалт", "ґвалт")
+        .prefix("дзиг", "дзиґ")
+        .prefix("гелгот", "ґелгот")
+        .ending("ъ", "")
+}
+
+/// Maps the classical spellings of the 1928 Kharkiv orthography, used before 1933 and by
+/// diaspora publications, to modern ones: "ля" and "ґ" in loan words ("кляса",
+/// "ґеоґрафія"), "т" for the Greek theta ("мітологія", "катедра") and the genitive "-и" of
+/// feminine nouns ("радости", "любови").
+pub fn kharkiv() -> LanguagePack {
+    LanguagePack::new("kharkiv")
+        .word("любови", "любові")
+        .word("крови", "крові")
+        .word("осени", "осені")
+        .word("соли", "солі")
+        .word("матери", "матері")
+        // verbs in "-ости", which are modern spellings too
+        .word("прости", "прости")
+        .word("зрости", "зрости")
+        .word("вирости", "вирости")
+        .word("підрости", "підрости")
+        .infix("льоґ", "лог")
+        .infix("льог", "лог")
+        .infix("оґі", "огі")
+        .infix("оґр", "огр")
+        .infix("міто", "міфо")
+        .infix("катедр", "кафедр")
+        .infix("ортограф", "орфограф")
+        .prefix("кляс", "клас")
+        .prefix("плян", "план")
+        .prefix("лямп", "ламп")
+        .prefix("клюб", "клуб")
+        .prefix("пляц", "плац")
+        .prefix("фльот", "флот")
+        .prefix("бльок", "блок")
+        .prefix("ґео", "гео")
+        .prefix("ґазет", "газет")
+        .prefix("ґеній", "геній")
+        .prefix("ґімназ", "гімназ")
+        .prefix("проґрам", "програм")
+        .prefix("европ", "європ")
+        .prefix("авдитор", "аудитор")
+        .prefix("павз", "пауз")
+        .prefix("атен", "афін")
+        .ending("ости", "ості")
+}
+
+/// Maps vocatives whose stem alternates a consonant, which no suffix rule removes, to
+/// their nominative: "друже" → "друг", "козаче" → "козак". Vocatives without an
+/// alternation ("Петре", "Оксано") conflate without the pack.
+pub fn vocative() -> LanguagePack {
+    LanguagePack::new("vocative")
+        .word("друже", "друг")
+        .word("боже", "бог")
+        .word("вовче", "вовк")
+        .word("земляче", "земляк")
+        .word("пастуше", "пастух")
+        // adverbs of adjectives in "-ачий"
+        .word("ледаче", "ледаче")
+        .word("одначе", "одначе")
+        .ending("аче", "ак")
+        .ending("іче", "ік")
+}
+
+#[test]
+fn surzhyk_test() {
+    let pack = surzhyk();
+    assert_eq!(pack.apply("шо"), "що");
+    assert_eq!(pack.apply("информация"), "інформація");
+    assert_eq!(pack.apply("студентов"), "студентів");
+    assert_eq!(pack.apply("проекту"), "проєкту");
+    assert_eq!(pack.apply("новые"), "нові");
+    assert_eq!(pack.apply("программами"), "програмами");
+    assert_eq!(pack.apply("телеграмма"), "телеграма");
+    assert_eq!(pack.apply("классы"), "класи");
+    assert_eq!(pack.apply("комментария"), "коментарія");
+    // too short to carry a rewritten ending
+    assert_eq!(pack.apply("ов"), "ов");
+}
+
+#[test]
+fn historical_test() {
+    let pack = historical();
+    assert_eq!(pack.apply("сїно"), "сіно");
+    assert_eq!(pack.apply("її"), "її");
+    assert_eq!(pack.apply("вѣра"), "віра");
+    assert_eq!(pack.apply("грунту"), "ґрунту");
+    assert_eq!(pack.apply("народъ"), "народ");
+}
+
+#[test]
+fn kharkiv_test() {
+    let pack = kharkiv();
+    assert_eq!(pack.apply("кляса"), "класа");
+    assert_eq!(pack.apply("ґеоґрафія"), "географія");
+    assert_eq!(pack.apply("мітології"), "міфології");
+    assert_eq!(pack.apply("біольоґія"), "біологія");
+    assert_eq!(pack.apply("радости"), "радості");
+    assert_eq!(pack.apply("любови"), "любові");
+    assert_eq!(pack.apply("прости"), "прости");
+    assert_eq!(pack.apply("поля"), "поля");
+}
+
+#[test]
+fn vocative_test() {
+    let pack = vocative();
+    assert_eq!(pack.apply("друже"), "друг");
+    assert_eq!(pack.apply("козаче"), "козак");
+    assert_eq!(pack.apply("чоловіче"), "чоловік");
+    assert_eq!(pack.apply("ледаче"), "ледаче");
+    assert_eq!(pack.apply("гаряче"), "гаряче");
+}
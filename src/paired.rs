@@ -0,0 +1,142 @@
+//! Index-time and query-time analyzers that are guaranteed to agree on stems.
+//!
+//! Analyzing documents and queries with different stemming is the most common cause of
+//! searches that find nothing. A `PairedConfig` derives the query analyzer from the index
+//! analyzer through `QuerySide`, which only offers options that add or drop query terms,
+//! never ones that change the term a word becomes.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::analyzer::{Analyzer, TranslitRepair};
+use crate::fingerprint::Fingerprint;
+use crate::wordlist::WordList;
+
+/// The query-time options of a `PairedConfig`.
+#[derive(Clone, Debug)]
+pub struct QuerySide {
+    analyzer: Analyzer,
+}
+
+impl QuerySide {
+    pub fn translit_repair(mut self, mode: TranslitRepair) -> Self {
+        self.analyzer = self.analyzer.translit_repair(mode);
+        self
+    }
+
+    pub fn synonym(mut self, word: &str, synonym: &str) -> Self {
+        self.analyzer = self.analyzer.synonym(word, synonym);
+        self
+    }
+
+    pub fn stopwords(mut self, words: WordList<'static>) -> Self {
+        self.analyzer = self.analyzer.stopwords(words);
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PairedConfig {
+    index: Analyzer,
+    query: Analyzer,
+}
+
+/// `PairedConfig::from_parts` was given analyzers that stem differently.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CoreMismatch {
+    pub index: Fingerprint,
+    pub query: Fingerprint,
+}
+
+impl fmt::Display for CoreMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "query analyzer core {} differs from index analyzer core {}",
+            self.query, self.index
+        )
+    }
+}
+
+impl Error for CoreMismatch {}
+
+impl PairedConfig {
+    /// Pairs `index` with a query analyzer identical to it.
+    pub fn new(index: Analyzer) -> Self {
+        PairedConfig {
+            query: index.clone(),
+            index,
+        }
+    }
+
+    /// Sets the query-time options, starting from those of the index analyzer.
+    pub fn query_side(mut self, configure: impl FnOnce(QuerySide) -> QuerySide) -> Self {
+        let side = QuerySide {
+            analyzer: self.index.clone(),
+        };
+        self.query = configure(side).analyzer;
+        self
+    }
+
+    /// Pairs analyzers configured elsewhere, e.g. loaded from separate config files,
+    /// failing unless their `core_fingerprint`s match.
+    pub fn from_parts(index: Analyzer, query: Analyzer) -> Result<Self, CoreMismatch> {
+        let (index_core, query_core) = (index.core_fingerprint(), query.core_fingerprint());
+        if index_core != query_core {
+            return Err(CoreMismatch {
+                index: index_core,
+                query: query_core,
+            });
+        }
+        Ok(PairedConfig { index, query })
+    }
+
+    pub fn index(&self) -> &Analyzer {
+        &self.index
+    }
+
+    pub fn query(&self) -> &Analyzer {
+        &self.query
+    }
+
+    /// The fingerprint to stamp indexes with: that of the index analyzer.
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.index.fingerprint()
+    }
+}
+
+#[test]
+fn paired_config_test() {
+    let paired = PairedConfig::new(Analyzer::new().proper_nouns(true)).query_side(|q| {
+        q.translit_repair(TranslitRepair::Token)
+            .synonym("авто", "автомобіль")
+    });
+    assert_eq!(
+        paired.index().core_fingerprint(),
+        paired.query().core_fingerprint()
+    );
+    assert_ne!(paired.index().fingerprint(), paired.query().fingerprint());
+
+    assert_eq!(paired.index().terms("нове авто"), vec!["нов", "авт"]);
+    assert_eq!(
+        paired.query().terms("нове авто"),
+        vec!["нов", "авт", "автомобіл"]
+    );
+    // query terms still match the index after translit repair
+    assert_eq!(
+        paired.query().terms("pryvit"),
+        paired.index().terms("привіт")
+    );
+}
+
+#[test]
+fn from_parts_test() {
+    let index = Analyzer::new();
+    let query = Analyzer::new().translit_repair(TranslitRepair::Document);
+    assert!(PairedConfig::from_parts(index.clone(), query).is_ok());
+
+    let query = Analyzer::new().proper_nouns(true);
+    let err = PairedConfig::from_parts(index.clone(), query.clone()).unwrap_err();
+    assert_eq!(err.index, index.core_fingerprint());
+    assert_eq!(err.query, query.core_fingerprint());
+}
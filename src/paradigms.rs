@@ -0,0 +1,260 @@
+//! Declension tables of the Ukrainian noun paradigm classes.
+//!
+//! The tables are parsed from rules/paradigms.txt. They are used to check that the noun
+//! suffix group of `Algorithm::V2` covers every case ending, and can serve as test data
+//! for code built on top of the stemmer.
+
+use lazy_static::lazy_static;
+
+pub const SOURCE: &str = include_str!("../rules/paradigms.txt");
+
+pub const CASES: [&str; 7] = [
+    "nominative",
+    "genitive",
+    "dative",
+    "accusative",
+    "instrumental",
+    "locative",
+    "vocative",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Number {
+    Singular,
+    Plural,
+}
+
+/// One paradigm class: the endings of every case, in the order of `CASES`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Paradigm {
+    pub class: &'static str,
+    /// Stem of the example noun the class is declined on.
+    pub example: &'static str,
+    /// Variants of every case ending; the zero ending is `""`.
+    pub singular: Vec<Vec<&'static str>>,
+    pub plural: Vec<Vec<&'static str>>,
+}
+
+/// One inflected form of a paradigm's example noun.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Form {
+    pub case: &'static str,
+    pub number: Number,
+    pub ending: &'static str,
+    pub word: String,
+}
+
+impl Paradigm {
+    /// Every form of the example noun, variants included.
+    pub fn decline(&self) -> Vec<Form> {
+        let numbers = [
+            (Number::Singular, &self.singular),
+            (Number::Plural, &self.plural),
+        ];
+        let mut forms = Vec::new();
+        for (number, cases) in numbers.iter() {
+            for (case, endings) in CASES.iter().zip(cases.iter()) {
+                for ending in endings {
+                    forms.push(Form {
+                        case,
+                        number: *number,
+                        ending,
+                        word: format!("{}{}", self.example, ending),
+                    });
+                }
+            }
+        }
+        forms
+    }
+
+    /// The distinct non-zero endings of the class.
+    pub fn endings(&self) -> Vec<&'static str> {
+        let mut endings: Vec<_> = self
+            .singular
+            .iter()
+            .chain(self.plural.iter())
+            .flatten()
+            .copied()
+            .filter(|e| !e.is_empty())
+            .collect();
+        endings.sort_unstable();
+        endings.dedup();
+        endings
+    }
+}
+
+fn parse_endings(field: &'static str) -> Result<Vec<Vec<&'static str>>, String> {
+    let cases: Vec<Vec<&str>> = field
+        .split_whitespace()
+        .map(|case| {
+            case.split('/')
+                .map(|e| if e == "-" { "" } else { e })
+                .collect()
+        })
+        .collect();
+    if cases.len() == CASES.len() {
+        Ok(cases)
+    } else {
+        Err(format!(
+            "expected {} cases, found {}",
+            CASES.len(),
+            cases.len()
+        ))
+    }
+}
+
+/// Parses the paradigm source format described in rules/paradigms.txt.
+pub fn parse(source: &'static str) -> Result<Vec<Paradigm>, String> {
+    let mut paradigms = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let err = |msg: String| format!("paradigms.txt:{}: {}", i + 1, msg);
+        let (class, rest) = line
+            .split_once(':')
+            .ok_or_else(|| err("expected `<class>: <stem> | <singular> | <plural>`".into()))?;
+        let fields: Vec<&str> = rest.split('|').collect();
+        if fields.len() != 3 {
+            return Err(err(
+                "expected `<class>: <stem> | <singular> | <plural>`".into()
+            ));
+        }
+        paradigms.push(Paradigm {
+            class: class.trim(),
+            example: fields[0].trim(),
+            singular: parse_endings(fields[1]).map_err(err)?,
+            plural: parse_endings(fields[2]).map_err(err)?,
+        });
+    }
+    Ok(paradigms)
+}
+
+lazy_static! {
+    static ref PARADIGMS: Vec<Paradigm> = crate::lazy(|| parse(SOURCE).unwrap());
+}
+
+pub(crate) fn warm_up() {
+    lazy_static::initialize(&PARADIGMS);
+}
+
+/// The built-in paradigm classes.
+pub fn paradigms() -> &'static [Paradigm] {
+    &PARADIGMS
+}
+
+pub fn paradigm(class: &str) -> Option<&'static Paradigm> {
+    PARADIGMS.iter().find(|p| p.class == class)
+}
+
+#[test]
+fn parse_test() {
+    let all = paradigms();
+    assert_eq!(all.len(), 20);
+    let hard = paradigm("hard_masculine").unwrap();
+    assert_eq!(hard.example, "дуб");
+    assert_eq!(hard.singular[1], ["а", "у"]);
+    assert_eq!(hard.singular[0], [""]);
+    let forms = hard.decline();
+    assert_eq!(forms[0].word, "дуб");
+    assert!(forms
+        .iter()
+        .any(|f| f.word == "дубові" && f.case == "dative" && f.number == Number::Singular));
+
+    assert!(parse("x: ст | а и | и").is_err());
+    assert!(parse("x ст а").is_err());
+}
+
+// Forms the suffix rules cannot conflate with the rest of their paradigm: stem
+// alternations (IV declension -ят-/-ен-, lengthened consonants) and endings whose removal
+// would break other words (-єві collides with adjectival plurals like "дієві").
+#[cfg(test)]
+const KNOWN_GAPS: [&str; 8] = [
+    "тінню",
+    "теля",
+    "теляти",
+    "телям",
+    "ім'я",
+    "ім'ям",
+    "чергові",
+    "краєві",
+];
+
+// the rules that cover the paradigms, run once, as the gaps are those of the rules;
+// version 1 misses the endings of the nouns in "-ія"
+#[cfg(test)]
+fn v2() -> crate::Stemmer {
+    crate::Stemmer::builder()
+        .algorithm(crate::Algorithm::V2)
+        .idempotent(false)
+        .build()
+}
+
+#[test]
+fn conflation_test() {
+    let stemmer = v2();
+    for paradigm in paradigms() {
+        let mut stems: Vec<String> = paradigm
+            .decline()
+            .into_iter()
+            .filter(|f| !KNOWN_GAPS.contains(&f.word.as_str()))
+            .map(|f| stemmer.stem(&f.word))
+            .collect();
+        stems.dedup();
+        assert_eq!(stems.len(), 1, "{}: {:?}", paradigm.class, stems);
+    }
+    for gap in KNOWN_GAPS.iter() {
+        let paradigm = paradigms()
+            .iter()
+            .find(|p| p.decline().iter().any(|f| f.word == *gap))
+            .unwrap();
+        let stem = stemmer.stem(&format!("{}{}", paradigm.example, paradigm.plural[2][0]));
+        assert_ne!(stemmer.stem(gap), stem, "{} is no longer a gap", gap);
+    }
+}
+
+// IV declension forms outside the nominative carry a stem extension before the ending
+#[cfg(test)]
+const STEM_EXTENSIONS: [(&str, &str); 2] = [("at_neuter", "ят"), ("en_neuter", "ен")];
+
+#[test]
+fn noun_suffix_test() {
+    use crate::rules::{RuleSet, ADJECTIVE, NOUN};
+
+    // endings the adjective group takes before the noun group is tried
+    let adjective = ["ею", "єю", "ією"];
+    let rules = RuleSet::at_version(2).unwrap();
+    let (adjectives, nouns) = (
+        rules.suffixes(ADJECTIVE).unwrap(),
+        rules.suffixes(NOUN).unwrap(),
+    );
+    for paradigm in paradigms() {
+        if paradigm.class.starts_with("adjectival") {
+            continue;
+        }
+        let extension = STEM_EXTENSIONS
+            .iter()
+            .find(|(class, _)| *class == paradigm.class)
+            .map_or("", |(_, extension)| extension);
+        for form in paradigm.decline() {
+            let ending = form.ending.replace('\'', "");
+            let ending = ending.strip_prefix(extension).unwrap_or(&ending);
+            if ending.is_empty() || KNOWN_GAPS.contains(&form.word.as_str()) {
+                continue;
+            }
+            let group = if adjective.contains(&ending) {
+                &adjectives
+            } else {
+                &nouns
+            };
+            assert!(
+                group.contains(&ending),
+                "{}: {} ends with {}",
+                paradigm.class,
+                form.word,
+                ending
+            );
+        }
+    }
+}
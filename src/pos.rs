@@ -0,0 +1,87 @@
+//! A guess of the part of speech of a word from the ending the rules removed, see
+//! `Stemmer::stem_with_pos`: a cheap hint for weighting query terms or filtering
+//! keywords, not a tagger. The guess is only as good as the endings: "синього" is an
+//! adjective but "того" too, and words whose ending no group knows get no guess.
+
+use std::fmt;
+
+use crate::rules::{RuleHit, ADJECTIVE, NOUN, PARTICIPLE, PERFECTIVE_GROUND, REFLEXIVE, VERB};
+
+/// The part of speech the inflectional rule group that removed the ending of a word
+/// stands for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PosGuess {
+    Noun,
+    Adjective,
+    /// An adjective ending the participle group found a second ending before; most
+    /// participles, as "читаючого", end like adjectives and are guessed `Adjective`.
+    Participle,
+    /// A verb ending, or the reflexive "-ся" of verbs and their participles.
+    Verb,
+    /// A perfective adverbial participle (дієприслівник), as "зробивши".
+    Gerund,
+    /// No inflectional ending was removed: the word is a stem already, an uninflected
+    /// word, or was not stemmed by the rules, e.g. an exception.
+    #[default]
+    Unknown,
+}
+
+impl PosGuess {
+    /// The guess for the rule groups of a trace, as `Stemmer::stem_with_trace` reports
+    /// them; the first pass of the inflectional groups decides.
+    pub fn from_trace(trace: &[RuleHit]) -> Self {
+        let mut guess = PosGuess::Unknown;
+        for hit in trace {
+            match hit.rule {
+                PERFECTIVE_GROUND => return PosGuess::Gerund,
+                // only verbs and their participles end in "-ся", whatever ending the
+                // groups after it remove
+                REFLEXIVE | VERB => return PosGuess::Verb,
+                NOUN => return PosGuess::Noun,
+                PARTICIPLE => return PosGuess::Participle,
+                ADJECTIVE => guess = PosGuess::Adjective,
+                _ if guess != PosGuess::Unknown => break,
+                _ => {}
+            }
+        }
+        guess
+    }
+}
+
+impl fmt::Display for PosGuess {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            PosGuess::Noun => "noun",
+            PosGuess::Adjective => "adjective",
+            PosGuess::Participle => "participle",
+            PosGuess::Verb => "verb",
+            PosGuess::Gerund => "gerund",
+            PosGuess::Unknown => "unknown",
+        })
+    }
+}
+
+#[test]
+fn pos_test() {
+    use crate::Stemmer;
+
+    let stemmer = Stemmer::new();
+    let guess = |word| stemmer.stem_with_pos(word).1;
+    assert_eq!(
+        stemmer.stem_with_pos("книгами"),
+        ("книг".into(), PosGuess::Noun)
+    );
+    assert_eq!(guess("зеленого"), PosGuess::Adjective);
+    assert_eq!(guess("читали"), PosGuess::Verb);
+    assert_eq!(guess("сміявся"), PosGuess::Verb);
+    assert_eq!(guess("сміялися"), PosGuess::Verb);
+    assert_eq!(guess("зробивши"), PosGuess::Gerund);
+    assert_eq!(guess("хліб"), PosGuess::Unknown);
+    assert_eq!(guess("ЗСУ"), PosGuess::Unknown);
+    assert_eq!(
+        crate::stem_with_pos("книгами"),
+        stemmer.stem_with_pos("книгами")
+    );
+}
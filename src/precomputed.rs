@@ -0,0 +1,45 @@
+//! Stems of the frequent forms of `rules/frequent.txt`, computed ahead of time so that
+//! the tokens among them skip the rules.
+//!
+//! The shipped list is only the stop words and the test vocabulary, not a corpus frequency
+//! list, so it covers few tokens of running text beyond the stop words. A listed form
+//! stems about four times faster; other forms gain nothing.
+//!
+//! build.rs generates the table when the crate is built, from the list
+//! `UKSTEM_FREQUENT_WORDS` names if set; it holds the stems under the default rules, so
+//! only stemmers running them look it up.
+
+include!(concat!(env!("OUT_DIR"), "/precomputed_table.rs"));
+
+/// The stem of the normalized `word` under the default rules, if it is a frequent form.
+pub fn stem(word: &str) -> Option<&str> {
+    STEMS.get(word).map(|&end| &word[..end as usize])
+}
+
+/// The number of frequent forms with a stem.
+pub fn len() -> usize {
+    STEMS.len()
+}
+
+#[test]
+fn table_matches_rules_test() {
+    use crate::BUILTIN_RULES;
+
+    for (form, &end) in STEMS.entries() {
+        // with a trace, so that the rules run rather than look up the table
+        let cut = crate::stem_cut(
+            form,
+            &BUILTIN_RULES,
+            &mut Some(Vec::new()),
+            None,
+            0,
+            0,
+            None,
+        );
+        assert_eq!(cut.unwrap().end, end as usize, "{}", form);
+    }
+    if option_env!("UKSTEM_FREQUENT_WORDS").is_none() {
+        assert_eq!(stem("народами"), Some("народ"));
+    }
+    assert_eq!(stem("ʼнародами"), None);
+}
@@ -0,0 +1,90 @@
+//! Case folding of whole text buffers, done once before tokenization instead of for every
+//! word.
+//!
+//! `fold_text` keeps the byte layout of the text: every char is replaced by one of the same
+//! UTF-8 length, so a token's byte range in the original text is also its range in the
+//! folded one. Chars whose lowercase form has another length are left alone, and the words
+//! containing them take the per-word path.
+
+use std::borrow::Cow;
+
+// bytes compared at once on the ASCII fast path
+const CHUNK: usize = 16;
+
+/// Lowercases `text` and maps "ё" to "е" and "ъ" to "ї", as the stemmer's preprocessing
+/// does, without changing the byte offset of any char. Borrows `text` when nothing changes.
+pub fn fold_text(text: &str) -> Cow<'_, str> {
+    let first = match first_unfolded(text) {
+        Some(first) => first,
+        None => return Cow::Borrowed(text),
+    };
+    let mut out = String::with_capacity(text.len());
+    out.push_str(&text[..first]);
+    let mut rest = &text[first..];
+    while !rest.is_empty() {
+        let bytes = rest.as_bytes();
+        // ASCII without uppercase letters goes verbatim, a chunk at a time
+        let plain = bytes
+            .chunks(CHUNK)
+            .take_while(|chunk| {
+                chunk
+                    .iter()
+                    .all(|b| b.is_ascii() && !b.is_ascii_uppercase())
+            })
+            .map(<[u8]>::len)
+            .sum::<usize>();
+        let plain = plain
+            + bytes[plain..]
+                .iter()
+                .take_while(|b| b.is_ascii() && !b.is_ascii_uppercase())
+                .count();
+        out.push_str(&rest[..plain]);
+        rest = &rest[plain..];
+        let mut chars = rest.chars();
+        if let Some(c) = chars.next() {
+            out.push(fold_char(c));
+            rest = chars.as_str();
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// The char `fold_text` replaces `c` with.
+pub fn fold_char(c: char) -> char {
+    match c {
+        'a'..='z' => c,
+        'A'..='Z' => c.to_ascii_lowercase(),
+        'а'..='щ' | 'ь' | 'ю' | 'я' | 'і' | 'ї' | 'є' | 'ґ' => c,
+        'ё' | 'Ё' => 'е',
+        'ъ' | 'Ъ' => 'ї',
+        c => {
+            let mut lower = c.to_lowercase();
+            match (lower.next(), lower.next()) {
+                (Some(l), None) if l.len_utf8() == c.len_utf8() => l,
+                _ => c,
+            }
+        }
+    }
+}
+
+// the byte offset of the first char `fold_char` changes
+fn first_unfolded(text: &str) -> Option<usize> {
+    text.char_indices()
+        .find(|(_, c)| fold_char(*c) != *c)
+        .map(|(i, _)| i)
+}
+
+#[test]
+fn fold_text_test() {
+    assert!(matches!(fold_text("слідство триває"), Cow::Borrowed(_)));
+    let text = "Слідство ТРИВАЄ, Ёлка з'Їзд Ґанок ABC İstanbul ǅ";
+    let folded = fold_text(text);
+    assert_eq!(folded, "слідство триває, елка з'їзд ґанок abc İstanbul ǆ");
+    assert_eq!(folded.len(), text.len());
+    assert!(text
+        .char_indices()
+        .all(|(i, c)| folded[i..].chars().next().unwrap().len_utf8() == c.len_utf8()));
+    assert_eq!(fold_text(&"x".repeat(40)), "x".repeat(40));
+    assert_eq!(fold_text(&"Ab".repeat(20)), "ab".repeat(20));
+    assert_eq!(fold_text(""), "");
+}
@@ -0,0 +1,121 @@
+//! Ready-made analyzer configurations for common kinds of text.
+
+use crate::analyzer::Analyzer;
+use crate::homoglyph::HomoglyphRepair;
+use crate::packs;
+use crate::tokenizer::{CharClasses, EmojiPolicy, Tokenizer};
+use crate::{stopwords, Stemmer};
+
+// institutions and political terms news text refers to as one unit
+const NEWS_PHRASES: [&str; 16] = [
+    "Верховна Рада",
+    "Кабінет Міністрів",
+    "Офіс Президента",
+    "Рада національної безпеки і оборони",
+    "Збройні Сили",
+    "Генеральний штаб",
+    "Служба безпеки України",
+    "Національна поліція",
+    "Національний банк",
+    "Конституційний Суд",
+    "Верховний Суд",
+    "Міністерство закордонних справ",
+    "Європейський Союз",
+    "Організація Об'єднаних Націй",
+    "Сполучені Штати",
+    "народний депутат",
+];
+
+const LEGAL_ABBREVIATIONS: [(&str, &str); 14] = [
+    ("ст.", "стаття"),
+    ("ч.", "частина"),
+    ("п.", "пункт"),
+    ("пп.", "підпункт"),
+    ("абз.", "абзац"),
+    ("розд.", "розділ"),
+    ("гл.", "глава"),
+    ("р.", "рік"),
+    ("ЦКУ", "Цивільний кодекс України"),
+    ("ЦК", "Цивільний кодекс"),
+    ("КК", "Кримінальний кодекс"),
+    ("ЦПК", "Цивільний процесуальний кодекс"),
+    ("КПК", "Кримінальний процесуальний кодекс"),
+    ("КУпАП", "Кодекс України про адміністративні правопорушення"),
+];
+
+/// Messenger and bot text: emoji and emoticons become sentiment terms, `@mentions` and
+/// `#hashtags` are kept verbatim, Surzhyk forms are conflated with standard ones and
+/// Latin or digit lookalikes typed into Cyrillic words are repaired.
+pub fn chat() -> Analyzer {
+    Analyzer::new()
+        .tokenizer(
+            Tokenizer::new()
+                .emoji_policy(EmojiPolicy::Sentiment)
+                .tags(true),
+        )
+        .stemmer(
+            Stemmer::builder()
+                .pack(packs::surzhyk())
+                .homoglyph_repair(HomoglyphRepair::Aggressive)
+                .build(),
+        )
+}
+
+/// News and article indexing: stop words are dropped, names of institutions become
+/// single phrase terms and capitalized names inside sentences are left unstemmed.
+pub fn news() -> Analyzer {
+    Analyzer::new()
+        .stopwords(stopwords::default_words())
+        .phrases(NEWS_PHRASES.iter().copied())
+        .proper_nouns(true)
+}
+
+/// Legislation and court decisions, where precision matters more than recall: common
+/// abbreviations are expanded, capitalized names are left unstemmed and article
+/// numbers like "15-1" or "2/3" stay single terms.
+pub fn legal() -> Analyzer {
+    let numbers = CharClasses::default()
+        .number_separator('-')
+        .number_separator('/');
+    LEGAL_ABBREVIATIONS
+        .iter()
+        .fold(Analyzer::new(), |analyzer, (abbreviation, expansion)| {
+            analyzer.abbreviation(abbreviation, expansion)
+        })
+        .tokenizer(Tokenizer::new().char_classes(numbers))
+        .proper_nouns(true)
+}
+
+#[test]
+fn chat_test() {
+    let terms = chat().terms("@oleh_b дякую за нoвини 👍 #Київ");
+    assert_eq!(
+        terms,
+        ["@oleh_b", "дяк", "за", "новин", "<positive>", "#Київ"]
+    );
+    assert_ne!(chat().fingerprint(), Analyzer::new().fingerprint());
+}
+
+#[test]
+fn news_test() {
+    let terms = news().terms("Народний депутат і Кабінет Міністрів обговорили візит до Брюсселя.");
+    assert_eq!(
+        terms,
+        [
+            "народн_депутат",
+            "кабінет_міністр",
+            "обговорил",
+            "візит",
+            "брюсселя"
+        ]
+    );
+}
+
+#[test]
+fn legal_test() {
+    assert_eq!(
+        legal().terms("ч. 2 ст. 15-1 КУпАП"),
+        legal().terms("частина 2 стаття 15-1 кодекс україни про адміністративні правопорушення")
+    );
+    assert_eq!(legal().terms("п. 2/3")[1], "2/3");
+}
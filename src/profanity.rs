@@ -0,0 +1,243 @@
+//! Detection of offensive Ukrainian (and common Russian-origin) words across inflected and
+//! obfuscated spellings, for moderation pipelines.
+
+use std::collections::HashSet;
+
+use crate::Stemmer;
+
+// Latin letters and symbols used in place of the Cyrillic letters they look like.
+const LOOKALIKES: [(char, char); 22] = [
+    ('a', 'а'),
+    ('b', 'в'),
+    ('c', 'с'),
+    ('e', 'е'),
+    ('h', 'н'),
+    ('i', 'і'),
+    ('k', 'к'),
+    ('m', 'м'),
+    ('o', 'о'),
+    ('p', 'р'),
+    ('t', 'т'),
+    ('x', 'х'),
+    ('y', 'у'),
+    ('u', 'и'),
+    ('0', 'о'),
+    ('1', 'і'),
+    ('3', 'з'),
+    ('4', 'ч'),
+    ('6', 'б'),
+    ('@', 'а'),
+    ('$', 'с'),
+    ('|', 'і'),
+];
+
+// Characters inserted between letters to dodge exact lists: "х.у.й", "с-у-к-а".
+const SEPARATORS: [char; 5] = ['.', '-', '_', '*', '\''];
+
+const DEFAULT: [&str; 16] = [
+    "блядь",
+    "блядство",
+    "виблядок",
+    "гівно",
+    "гімно",
+    "дрочити",
+    "курва",
+    "мудак",
+    "падлюка",
+    "пизда",
+    "підар",
+    "підор",
+    "срака",
+    "сука",
+    "хуй",
+    "хуйня",
+];
+
+/// An offensive word in a text, as byte offsets of its (possibly obfuscated) spelling.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProfanityMatch {
+    pub start: usize,
+    pub end: usize,
+    /// The stem of the list entry that matched.
+    pub stem: String,
+}
+
+/// Matches words by stem after undoing lookalike substitutions, letter separators and
+/// stretched letters, so "Сук@м", "с.у.к.и" and "сууукою" all hit the entry "сука".
+#[derive(Clone, Debug)]
+pub struct ProfanityFilter {
+    stemmer: Stemmer,
+    stems: HashSet<String>,
+}
+
+impl Default for ProfanityFilter {
+    fn default() -> Self {
+        ProfanityFilter::new().words(DEFAULT)
+    }
+}
+
+impl ProfanityFilter {
+    /// A filter with an empty list; `ProfanityFilter::default()` has the built-in one.
+    pub fn new() -> Self {
+        ProfanityFilter {
+            stemmer: Stemmer::new(),
+            stems: HashSet::new(),
+        }
+    }
+
+    pub fn stemmer(mut self, stemmer: Stemmer) -> Self {
+        let words: Vec<String> = self.stems.drain().collect();
+        self.stemmer = stemmer;
+        self.words(words)
+    }
+
+    pub fn word(mut self, word: &str) -> Self {
+        self.stems.insert(self.key(&deobfuscate(word)));
+        self
+    }
+
+    pub fn words<I, S>(self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        words
+            .into_iter()
+            .fold(self, |filter, word| filter.word(word.as_ref()))
+    }
+
+    // stems with stretched letters collapsed, so "сууука" and "сука" share a key
+    fn key(&self, word: &str) -> String {
+        let mut key = String::new();
+        for c in self.stemmer.stem(word).chars() {
+            if !key.ends_with(c) {
+                key.push(c);
+            }
+        }
+        key
+    }
+
+    pub fn is_offensive(&self, word: &str) -> bool {
+        self.stems.contains(&self.key(&deobfuscate(word)))
+    }
+
+    pub fn find(&self, text: &str) -> Vec<ProfanityMatch> {
+        candidates(text)
+            .into_iter()
+            .filter_map(|(start, end)| {
+                let stem = self.key(&deobfuscate(&text[start..end]));
+                if self.stems.contains(&stem) {
+                    Some(ProfanityMatch { start, end, stem })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Replaces every match with `mask` repeated once per character.
+    pub fn mask(&self, text: &str, mask: char) -> String {
+        let mut res = String::with_capacity(text.len());
+        let mut last = 0;
+        for m in self.find(text) {
+            res.push_str(&text[last..m.start]);
+            res.extend(std::iter::repeat_n(
+                mask,
+                text[m.start..m.end].chars().count(),
+            ));
+            last = m.end;
+        }
+        res.push_str(&text[last..]);
+        res
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || LOOKALIKES.iter().any(|(from, _)| *from == c)
+}
+
+/// Lowercases, maps lookalikes to Cyrillic and drops separators. Words without any
+/// Cyrillic letter are only lowercased, so English text is never rewritten.
+fn deobfuscate(word: &str) -> String {
+    let word = word.to_lowercase();
+    let cyrillic = word.chars().any(|c| ('\u{0400}'..='\u{04FF}').contains(&c));
+    let letters = word.chars().filter(|c| !SEPARATORS.contains(c));
+    if !cyrillic {
+        return letters.collect();
+    }
+    letters
+        .map(|c| {
+            LOOKALIKES
+                .iter()
+                .find(|(from, _)| *from == c)
+                .map_or(c, |(_, to)| *to)
+        })
+        .collect()
+}
+
+// Runs of word characters, continued over separators that sit between two word
+// characters. Runs glued from single letters ("х.у.й") stay whole; other runs are split
+// at separators, so "будь-який" yields "будь" and "який".
+fn candidates(text: &str) -> Vec<(usize, usize)> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut res = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if !is_word_char(chars[i].1) {
+            i += 1;
+            continue;
+        }
+        let mut segments = vec![(chars[i].0, chars[i].0)];
+        let mut j = i;
+        while j < chars.len() {
+            let c = chars[j].1;
+            if is_word_char(c) {
+                segments.last_mut().unwrap().1 = chars[j].0 + c.len_utf8();
+                j += 1;
+            } else if SEPARATORS.contains(&c)
+                && chars
+                    .get(j + 1)
+                    .is_some_and(|(_, next)| is_word_char(*next))
+            {
+                segments.push((chars[j + 1].0, chars[j + 1].0));
+                j += 1;
+            } else {
+                break;
+            }
+        }
+        let spelled = segments.len() > 1
+            && segments
+                .iter()
+                .all(|(start, end)| text[*start..*end].chars().count() == 1);
+        if spelled {
+            res.push((segments[0].0, segments.last().unwrap().1));
+        } else {
+            res.extend(segments);
+        }
+        i = j;
+    }
+    res
+}
+
+#[test]
+fn profanity_test() {
+    let filter = ProfanityFilter::default();
+    assert!(filter.is_offensive("Сукою"));
+    assert!(filter.is_offensive("cук@"));
+    assert!(filter.is_offensive("сууука"));
+    assert!(!filter.is_offensive("сукня"));
+    assert!(!filter.is_offensive("pizza"));
+
+    let text = "Ну ти й с.у.к.а, та й мудаки всі";
+    let found: Vec<&str> = filter
+        .find(text)
+        .iter()
+        .map(|m| &text[m.start..m.end])
+        .collect();
+    assert_eq!(found, ["с.у.к.а", "мудаки"]);
+    assert_eq!(filter.mask("Ну й cука!", '*'), "Ну й ****!");
+
+    let custom = ProfanityFilter::new().word("бовдур");
+    assert!(custom.is_offensive("бовдурами"));
+    assert!(!custom.is_offensive("сука"));
+}
@@ -0,0 +1,55 @@
+//! Python bindings, built into an importable `stemmer_uk` module with
+//! `maturin build --release` (see pyproject.toml).
+//!
+//! Batches are stemmed with the GIL released, so other Python threads keep running while a
+//! long list of words or a long text is processed.
+
+use pyo3::prelude::*;
+
+/// `stem_word` with the default configuration.
+#[pyfunction]
+fn stem_word(word: &str) -> String {
+    crate::stem_word(word.to_string())
+}
+
+/// The stems of `words`, in order.
+#[pyfunction]
+fn stem_words(py: Python<'_>, words: Vec<String>) -> Vec<String> {
+    py.detach(|| crate::DEFAULT_ANALYZER.get_stemmer().stem_words(&words))
+}
+
+/// `stem_text` with the default configuration.
+#[pyfunction]
+fn stem_text(py: Python<'_>, text: &str) -> String {
+    py.detach(|| crate::stem_text(text))
+}
+
+#[pymodule]
+fn stemmer_uk(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(stem_word, m)?)?;
+    m.add_function(wrap_pyfunction!(stem_words, m)?)?;
+    m.add_function(wrap_pyfunction!(stem_text, m)?)?;
+    m.add("RULES_VERSION", crate::fingerprint::RULES_VERSION)?;
+    Ok(())
+}
+
+#[test]
+fn python_api_test() {
+    Python::initialize();
+    Python::attach(|py| {
+        let module = PyModule::new(py, "stemmer_uk").unwrap();
+        stemmer_uk(&module).unwrap();
+        let stem = module.getattr("stem_word").unwrap().call1(("Народами",));
+        assert_eq!(stem.unwrap().extract::<String>().unwrap(), "народ");
+        let words = vec!["книгами", "автора"];
+        let stems = module.getattr("stem_words").unwrap().call1((words,));
+        assert_eq!(
+            stems.unwrap().extract::<Vec<String>>().unwrap(),
+            ["книг", "автор"]
+        );
+        assert_eq!(
+            stem_text(py, "Слідство триває"),
+            crate::stem_text("Слідство триває")
+        );
+    });
+}
@@ -0,0 +1,116 @@
+use std::io;
+
+use crate::dictionary::Dictionary;
+use crate::wordlist::WordList;
+use crate::Stemmer;
+
+/// Resource files embedded into the binary by `stemmer_resources!`.
+///
+/// The texts live in read-only data; the accessors parse them into structures that
+/// borrow from it, so nothing but the lookup tables is allocated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EmbeddedResources {
+    /// `form<TAB>stem` lines, see `Dictionary::parse`.
+    pub exceptions: Option<&'static str>,
+    /// One word per line, see `WordList::from_lines`.
+    pub stopwords: Option<&'static str>,
+    /// One word per line.
+    pub protected: Option<&'static str>,
+}
+
+impl EmbeddedResources {
+    pub fn exceptions(&self) -> io::Result<Option<Dictionary<'static>>> {
+        self.exceptions.map(Dictionary::parse).transpose()
+    }
+
+    pub fn stopwords(&self) -> Option<WordList<'static>> {
+        self.stopwords.map(WordList::from_lines)
+    }
+
+    pub fn protected(&self) -> Option<WordList<'static>> {
+        self.protected.map(WordList::from_lines)
+    }
+
+    /// A stemmer using the embedded exceptions and protected terms.
+    pub fn stemmer(&self) -> io::Result<Stemmer> {
+        let mut builder = Stemmer::builder();
+        if let Some(exceptions) = self.exceptions()? {
+            builder = builder.exceptions(exceptions);
+        }
+        if let Some(protected) = self.protected() {
+            builder = builder.protected(protected);
+        }
+        Ok(builder.build())
+    }
+}
+
+/// Embeds resource files at compile time into a `static` `EmbeddedResources`, so a
+/// binary carries its custom configuration without any files next to it.
+///
+/// Paths are relative to the file invoking the macro, as with `include_str!`; every
+/// entry is optional but they must come in this order:
+///
+/// ```ignore
+/// stemmer_uk::stemmer_resources! {
+///     pub static RESOURCES = {
+///         exceptions: "../data/exceptions.tsv",
+///         stopwords: "../data/stopwords.txt",
+///         protected: "../data/protected.txt",
+///     };
+/// }
+///
+/// let stemmer = RESOURCES.stemmer()?;
+/// ```
+#[macro_export]
+macro_rules! stemmer_resources {
+    (
+        $(#[$meta:meta])*
+        $vis:vis static $name:ident = {
+            $(exceptions: $exceptions:literal,)?
+            $(stopwords: $stopwords:literal,)?
+            $(protected: $protected:literal,)?
+        };
+    ) => {
+        $(#[$meta])*
+        $vis static $name: $crate::resources::EmbeddedResources =
+            $crate::resources::EmbeddedResources {
+                exceptions: $crate::stemmer_resources!(@file $($exceptions)?),
+                stopwords: $crate::stemmer_resources!(@file $($stopwords)?),
+                protected: $crate::stemmer_resources!(@file $($protected)?),
+            };
+    };
+    (@file) => {
+        None
+    };
+    (@file $path:literal) => {
+        Some(include_str!($path))
+    };
+}
+
+#[test]
+fn stemmer_resources_test() {
+    stemmer_resources! {
+        static RESOURCES = {
+            exceptions: "../examples/resources/exceptions.tsv",
+            stopwords: "../examples/resources/stopwords.txt",
+            protected: "../examples/resources/protected.txt",
+        };
+    }
+    stemmer_resources! {
+        static ONLY_STOPWORDS = {
+            stopwords: "../examples/resources/stopwords.txt",
+        };
+    }
+
+    let stemmer = RESOURCES.stemmer().unwrap();
+    assert_eq!(stemmer.stem("люди"), "люд");
+    assert_eq!(stemmer.stem("ПриватБанк"), "приватбанк");
+    assert!(RESOURCES.stopwords().unwrap().contains("ось"));
+    assert_eq!(RESOURCES.exceptions().unwrap().unwrap().owned_bytes(), 0);
+
+    assert!(ONLY_STOPWORDS.exceptions().unwrap().is_none());
+    assert_eq!(
+        ONLY_STOPWORDS.stemmer().unwrap().fingerprint(),
+        Stemmer::new().fingerprint()
+    );
+}
@@ -0,0 +1,929 @@
+use std::fmt;
+use std::io;
+use std::iter::Peekable;
+use std::str::CharIndices;
+use std::sync::Arc;
+
+use stemmer_uk_core::suffix::Suffixes;
+use stemmer_uk_core::{ExtraRule, RuleGroups};
+
+use crate::export::json_string;
+use crate::fingerprint::RULES_VERSION;
+
+pub use stemmer_uk_core::{
+    Rule, RuleHit, RulePosition, ADJECTIVE, ALL, DERIVATIONAL, DOUBLE_N, I_ENDING, NOUN,
+    PARTICIPLE, PERFECTIVE_GROUND, REFLEXIVE, SOFT_SIGN, SUPERLATIVE, VERB,
+};
+
+// the endings of Russian words the built-in rules remove besides the Ukrainian ones
+const RUSSIAN: [(&str, &[&str]); 2] = [
+    (
+        NOUN,
+        &[
+            "ы", "ев", "ов", "ой", "ием", "ию", "ия", "ья", "ью", "иям", "иях",
+        ],
+    ),
+    (DERIVATIONAL, &["ость"]),
+];
+
+/// The suffix alternatives of every rule group, which `StemmerBuilder::rules` replaces the
+/// built-in ones with.
+///
+/// The pipeline itself is fixed: which groups run after which, that "derivational" only
+/// applies to words ending in "ость" or "ост", and that "double_n" replaces its suffix
+/// with "н", so its suffixes must start with "н". Rule sets can be read from and written
+/// as TOML (`group = ["suffix", …]` lines) or a JSON object of arrays; groups a file leaves
+/// out keep the built-in suffixes, and an empty list disables a group. Rules of `add_rule`
+/// run among the groups but are left out of these formats.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RuleSet {
+    groups: RuleGroups,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet::builtin()
+    }
+}
+
+impl RuleSet {
+    /// The rules `stem_word` uses, those of rule version 1, generated from
+    /// rules/suffixes.txt.
+    pub fn builtin() -> Self {
+        RuleSet {
+            groups: RuleGroups::builtin(),
+        }
+    }
+
+    /// The groups as the pipeline of `stemmer_uk_core` takes them.
+    pub fn groups(&self) -> &RuleGroups {
+        &self.groups
+    }
+
+    fn group(&self, group: &str) -> Option<&Suffixes> {
+        self.groups.group(group)
+    }
+
+    fn group_mut(&mut self, group: &str) -> Option<&mut Suffixes> {
+        self.groups.group_mut(group)
+    }
+
+    /// These rules without the endings of Russian words, see `Language::Ukrainian`.
+    pub(crate) fn ukrainian(&self) -> RuleSet {
+        let mut ukrainian = self.clone();
+        for (group, russian) in RUSSIAN.iter() {
+            let kept: Vec<String> = self
+                .suffixes(group)
+                .unwrap()
+                .into_iter()
+                .filter(|suffix| !russian.contains(suffix))
+                .map(String::from)
+                .collect();
+            ukrainian.set_suffixes(group, kept).unwrap();
+        }
+        ukrainian
+    }
+
+    /// These rules with only the groups removing case endings, see `Mode::Light`.
+    pub(crate) fn light(&self) -> RuleSet {
+        let mut light = self.clone();
+        for group in ALL
+            .iter()
+            .filter(|g| ![ADJECTIVE, PARTICIPLE, NOUN].contains(g))
+        {
+            light.set_suffixes(group, Vec::<String>::new()).unwrap();
+        }
+        light
+    }
+
+    /// Runs `rule` at `position`, after the rules already added there.
+    pub fn add_rule<R: Rule + 'static>(
+        &mut self,
+        position: RulePosition,
+        rule: R,
+    ) -> io::Result<()> {
+        let (RulePosition::Before(group) | RulePosition::After(group)) = position;
+        if !ALL.contains(&group) {
+            return Err(invalid(format!("unknown rule group {:?}", group)));
+        }
+        self.groups.extra.push(ExtraRule {
+            position,
+            rule: Arc::new(rule),
+        });
+        Ok(())
+    }
+
+    /// The names of the rules of `add_rule` and where they run, in order.
+    pub fn extra_rules(&self) -> impl Iterator<Item = (RulePosition, &'static str)> + '_ {
+        self.groups
+            .extra
+            .iter()
+            .map(|extra| (extra.position, extra.rule.name()))
+    }
+
+    /// The suffixes of `group`, one of `ALL`, as listed.
+    pub fn suffixes(&self, group: &str) -> Option<Vec<&str>> {
+        self.group(group).map(|s| s.listed().collect())
+    }
+
+    /// Replaces the suffixes of `group`. Suffixes are lowercase letters, each listed once.
+    pub fn set_suffixes<I, S>(&mut self, group: &str, suffixes: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let suffixes: Vec<String> = suffixes.into_iter().map(Into::into).collect();
+        for (i, suffix) in suffixes.iter().enumerate() {
+            if suffix.is_empty()
+                || !suffix
+                    .chars()
+                    .all(|c| c.is_alphabetic() && !c.is_uppercase())
+            {
+                return Err(invalid(format!(
+                    "{}: {:?} is not a lowercase suffix",
+                    group, suffix
+                )));
+            }
+            if suffixes[..i].contains(suffix) {
+                return Err(invalid(format!("{}: {} is listed twice", group, suffix)));
+            }
+            if group == DOUBLE_N && !suffix.starts_with('н') {
+                return Err(invalid(format!(
+                    "{}: {} does not start with н",
+                    group, suffix
+                )));
+            }
+        }
+        let slot = self
+            .group_mut(group)
+            .ok_or_else(|| invalid(format!("unknown rule group {:?}", group)))?;
+        *slot = Suffixes::new(suffixes);
+        Ok(())
+    }
+
+    /// Reads the TOML `to_toml` writes: `group = ["suffix", …]` per group, arrays may
+    /// span lines, `#` starts a comment.
+    pub fn from_toml(text: &str) -> io::Result<RuleSet> {
+        let mut rules = RuleSet::builtin();
+        let mut scanner = Scanner::new(text, true);
+        while scanner.skip_space() {
+            let group = scanner.key()?;
+            scanner.expect('=')?;
+            let suffixes = scanner.array()?;
+            rules.set_suffixes(&group, suffixes)?;
+            if scanner.skip_line_space() && !scanner.eat('\n') {
+                return Err(scanner.error("expected a new line"));
+            }
+        }
+        Ok(rules)
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut out = String::from("# stemmer_uk rule set: suffix alternatives per rule group\n");
+        for group in ALL.iter() {
+            out.push_str(&format!("{} = {}\n", group, self.json_array(group)));
+        }
+        out
+    }
+
+    /// Reads a JSON object of arrays of strings, `{"group": ["suffix", …], …}`.
+    pub fn from_json(text: &str) -> io::Result<RuleSet> {
+        let mut rules = RuleSet::builtin();
+        let mut scanner = Scanner::new(text, false);
+        scanner.skip_space();
+        scanner.expect('{')?;
+        scanner.skip_space();
+        if !scanner.eat('}') {
+            loop {
+                scanner.skip_space();
+                let group = scanner.string()?;
+                scanner.skip_space();
+                scanner.expect(':')?;
+                let suffixes = scanner.array()?;
+                rules.set_suffixes(&group, suffixes)?;
+                scanner.skip_space();
+                if scanner.eat('}') {
+                    break;
+                }
+                scanner.expect(',')?;
+            }
+        }
+        if scanner.skip_space() {
+            return Err(scanner.error("unexpected text after the object"));
+        }
+        Ok(rules)
+    }
+
+    pub fn to_json(&self) -> String {
+        let groups: Vec<String> = ALL
+            .iter()
+            .map(|group| format!("  {}: {}", json_string(group), self.json_array(group)))
+            .collect();
+        format!("{{\n{}\n}}\n", groups.join(",\n"))
+    }
+
+    /// A Snowball program with these rules, to cross-check them against other
+    /// implementations: a routine per group removing its suffixes in RV and `stem` running
+    /// them in the order of the pipeline. The derivational pattern is simplified to the
+    /// "ость"/"ост" test; the rules of `add_rule`, only named in a comment, and
+    /// `StemmerBuilder` options beyond the rules are left out.
+    pub fn to_snowball(&self) -> String {
+        let mut out = String::from("// stemmer_uk rule set, written by RuleSet::to_snowball\n");
+        for (position, name) in self.extra_rules() {
+            out.push_str(&format!(
+                "// left out: the rule {} at {:?}\n",
+                name, position
+            ));
+        }
+        out.push('\n');
+        out.push_str(&format!(
+            "routines ( mark_regions {} )\nexternals ( stem )\nintegers ( pV )\n",
+            ALL.join(" ")
+        ));
+        out.push_str("groupings ( v )\n\ndefine v 'аеиоуюяіїє'\n\n");
+        out.push_str(SNOWBALL_MARK_REGIONS);
+        out.push_str("backwardmode (\n");
+        for group in ALL.iter() {
+            let suffixes: Vec<String> = self
+                .group(group)
+                .unwrap()
+                .listed()
+                .map(|suffix| format!("'{}'", suffix))
+                .collect();
+            if suffixes.is_empty() {
+                out.push_str(&format!("    define {} as false\n", group));
+                continue;
+            }
+            out.push_str(&format!(
+                "    define {} as (\n        [substring] among (\n",
+                group
+            ));
+            for line in suffixes.chunks(10) {
+                out.push_str(&format!("            {}\n", line.join(" ")));
+            }
+            let action = if *group == DOUBLE_N {
+                "<- 'н'"
+            } else {
+                "delete"
+            };
+            out.push_str(&format!("                ({})\n        )\n    )\n", action));
+        }
+        out.push_str(")\n\n");
+        out.push_str(SNOWBALL_STEM);
+        out
+    }
+
+    /// Reads the suffix lists of a Snowball program such as `to_snowball` writes: the
+    /// strings in the `among` of `define <group> as`, for every group of `ALL`, become
+    /// its suffixes, and a group defined without one is disabled. Other routines are
+    /// skipped, and groups the program leaves out keep the built-in suffixes.
+    pub fn from_snowball(text: &str) -> io::Result<RuleSet> {
+        let mut rules = RuleSet::builtin();
+        let tokens = snowball_tokens(text)?;
+        let mut i = 0;
+        while i < tokens.len() {
+            let group = match (&tokens[i].1, tokens.get(i + 1).map(|t| &t.1)) {
+                (SnowballToken::Word("define"), Some(SnowballToken::Word(name))) => *name,
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            };
+            i += 2;
+            if tokens.get(i).map(|t| &t.1) != Some(&SnowballToken::Word("as")) {
+                continue;
+            }
+            i += 1;
+            // the body: a parenthesized sequence or a single command
+            let mut end = i + 1;
+            if tokens.get(i).map(|t| &t.1) == Some(&SnowballToken::Punct('(')) {
+                let mut depth = 0;
+                end = i;
+                while end < tokens.len() {
+                    match tokens[end].1 {
+                        SnowballToken::Punct('(') => depth += 1,
+                        SnowballToken::Punct(')') => depth -= 1,
+                        _ => {}
+                    }
+                    end += 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                if depth != 0 {
+                    let line = tokens[i].0;
+                    return Err(invalid(format!("line {}: unclosed definition", line)));
+                }
+            }
+            let body = &tokens[i..end.min(tokens.len())];
+            i = end;
+            if !ALL.contains(&group) {
+                continue;
+            }
+            let mut suffixes = Vec::new();
+            let mut among = body
+                .iter()
+                .position(|t| t.1 == SnowballToken::Word("among"))
+                .map(|at| at + 2);
+            let mut depth = 0;
+            while let Some(at) = among.filter(|at| *at < body.len()) {
+                match &body[at].1 {
+                    SnowballToken::Str(suffix) if depth == 0 => suffixes.push(suffix.to_string()),
+                    SnowballToken::Punct('(') => depth += 1,
+                    SnowballToken::Punct(')') if depth == 0 => break,
+                    SnowballToken::Punct(')') => depth -= 1,
+                    _ => {}
+                }
+                among = Some(at + 1);
+            }
+            rules.set_suffixes(group, suffixes)?;
+        }
+        Ok(rules)
+    }
+
+    fn json_array(&self, group: &str) -> String {
+        let suffixes: Vec<String> = self
+            .group(group)
+            .unwrap()
+            .listed()
+            .map(json_string)
+            .collect();
+        format!("[{}]", suffixes.join(", "))
+    }
+}
+
+impl fmt::Debug for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut map = f.debug_map();
+        for group in ALL.iter() {
+            map.entry(group, &self.suffixes(group).unwrap());
+        }
+        if !self.groups.extra.is_empty() {
+            map.entry(&"extra", &self.groups.extra);
+        }
+        map.finish()
+    }
+}
+
+/// The object of arrays `to_json` writes; groups left out keep the built-in suffixes, as
+/// with `from_json`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for RuleSet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            ALL.iter()
+                .map(|group| (group, self.suffixes(group).unwrap())),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RuleSet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let groups: std::collections::BTreeMap<String, Vec<String>> =
+            serde::Deserialize::deserialize(deserializer)?;
+        let mut rules = RuleSet::builtin();
+        for (group, suffixes) in groups {
+            rules
+                .set_suffixes(&group, suffixes)
+                .map_err(serde::de::Error::custom)?;
+        }
+        Ok(rules)
+    }
+}
+
+/// Whether a suffix was added to or removed from a group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+}
+
+/// A change of the built-in suffix tables.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RuleChange {
+    /// The `RULES_VERSION` that made the change.
+    pub version: u32,
+    pub group: &'static str,
+    pub suffix: String,
+    pub kind: ChangeKind,
+}
+
+// the changes of rules/changes.txt, oldest first
+fn changes() -> Vec<RuleChange> {
+    parse_changes(include_str!("../rules/changes.txt"))
+}
+
+fn parse_changes(source: &str) -> Vec<RuleChange> {
+    let mut changes = Vec::new();
+    for line in source.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (head, suffixes) = line.split_once(':').expect("rules/changes.txt: no `:`");
+        let (version, group) = head.split_once(' ').expect("rules/changes.txt: no group");
+        let version = version.parse().expect("rules/changes.txt: bad version");
+        let group = ALL
+            .iter()
+            .find(|g| **g == group.trim())
+            .expect("rules/changes.txt: unknown group");
+        for suffix in suffixes.split_whitespace() {
+            let (kind, suffix) = match suffix.split_at(1) {
+                ("+", suffix) => (ChangeKind::Added, suffix),
+                ("-", suffix) => (ChangeKind::Removed, suffix),
+                _ => panic!(
+                    "rules/changes.txt: {} is neither +added nor -removed",
+                    suffix
+                ),
+            };
+            changes.push(RuleChange {
+                version,
+                group,
+                suffix: suffix.to_string(),
+                kind,
+            });
+        }
+    }
+    changes
+}
+
+/// The changes of the built-in suffix tables between rule versions `from` and `to`, in the
+/// order they apply, or `None` if either is not a version from 1 to `RULES_VERSION`. Going
+/// back (`from > to`), added suffixes are reported as removed and vice versa.
+///
+/// The changes are empty when the versions give the same stems, so an index built with
+/// `from` need not be rebuilt for `to`.
+pub fn diff(from: u32, to: u32) -> Option<Vec<RuleChange>> {
+    if !(1..=RULES_VERSION).contains(&from) || !(1..=RULES_VERSION).contains(&to) {
+        return None;
+    }
+    Some(between(changes(), from, to))
+}
+
+// the `changes` from version `from` to `to`, undone if `from > to`
+fn between(changes: Vec<RuleChange>, from: u32, to: u32) -> Vec<RuleChange> {
+    let (low, high) = (from.min(to), from.max(to));
+    let mut changes: Vec<RuleChange> = changes
+        .into_iter()
+        .filter(|c| low < c.version && c.version <= high)
+        .collect();
+    if from > to {
+        changes.reverse();
+        for change in &mut changes {
+            change.kind = match change.kind {
+                ChangeKind::Added => ChangeKind::Removed,
+                ChangeKind::Removed => ChangeKind::Added,
+            };
+        }
+    }
+    changes
+}
+
+impl RuleSet {
+    /// The built-in rules as of rule version `version`: those of version 1 with the
+    /// changes `diff` lists since.
+    pub fn at_version(version: u32) -> Option<RuleSet> {
+        let mut rules = RuleSet::builtin();
+        for change in diff(1, version)? {
+            let mut suffixes = rules.suffixes(change.group).unwrap();
+            match change.kind {
+                ChangeKind::Added => suffixes.push(&change.suffix),
+                ChangeKind::Removed => suffixes.retain(|s| *s != change.suffix),
+            }
+            let suffixes: Vec<String> = suffixes.into_iter().map(str::to_string).collect();
+            rules.set_suffixes(change.group, suffixes).unwrap();
+        }
+        Some(rules)
+    }
+}
+
+const SNOWBALL_MARK_REGIONS: &str = "define mark_regions as (
+    $pV = limit
+    do ( gopast v setmark pV )
+)
+
+";
+
+const SNOWBALL_STEM: &str = "define stem as (
+    do mark_regions
+    backwards setlimit tomark pV for (
+        do ( perfective_ground or ( try reflexive ( adjective try participle ) or verb or noun ) )
+        try i_ending
+        do ( test ( 'ость' or 'ост' ) derivational )
+        do ( soft_sign try superlative try double_n )
+    )
+)
+";
+
+#[derive(Debug, PartialEq, Eq)]
+enum SnowballToken<'t> {
+    Word(&'t str),
+    Str(&'t str),
+    Punct(char),
+}
+
+// the words, strings and punctuation of a Snowball program with their lines, comments
+// left out
+fn snowball_tokens(text: &str) -> io::Result<Vec<(usize, SnowballToken<'_>)>> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let len = if c == '\n' {
+            line += 1;
+            1
+        } else if c.is_whitespace() {
+            c.len_utf8()
+        } else if rest.starts_with("//") {
+            rest.find('\n').unwrap_or(rest.len())
+        } else if rest.starts_with("/*") {
+            let end = rest
+                .find("*/")
+                .ok_or_else(|| invalid(format!("line {}: unclosed comment", line)))?;
+            line += rest[..end].matches('\n').count();
+            end + 2
+        } else if c == '\'' {
+            let end = rest[1..]
+                .find(['\'', '\n'])
+                .filter(|end| rest[1 + end..].starts_with('\''))
+                .ok_or_else(|| invalid(format!("line {}: unterminated string", line)))?;
+            tokens.push((line, SnowballToken::Str(&rest[1..1 + end])));
+            end + 2
+        } else if c.is_alphanumeric() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push((line, SnowballToken::Word(&rest[..end])));
+            end
+        } else {
+            tokens.push((line, SnowballToken::Punct(c)));
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    Ok(tokens)
+}
+
+type Predicate = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// A `Rule` removing the longest of its suffixes a word ends with, if `when` allows it.
+pub struct SuffixRule {
+    name: &'static str,
+    suffixes: Suffixes,
+    replacement: &'static str,
+    condition: Option<Predicate>,
+}
+
+impl SuffixRule {
+    pub fn new<I>(name: &'static str, suffixes: I) -> Self
+    where
+        I: IntoIterator<Item = &'static str>,
+    {
+        SuffixRule {
+            name,
+            suffixes: Suffixes::new(suffixes),
+            replacement: "",
+            condition: None,
+        }
+    }
+
+    /// Replaces the suffix with `to` instead of removing it; every suffix must start
+    /// with `to`.
+    pub fn replacement(mut self, to: &'static str) -> Self {
+        self.replacement = to;
+        self
+    }
+
+    /// Applies the rule only when `condition` holds for what it would leave of RV.
+    pub fn when<F>(mut self, condition: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.condition = Some(Box::new(condition));
+        self
+    }
+}
+
+impl Rule for SuffixRule {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn find(&self, rv: &[char]) -> Option<usize> {
+        let rv: String = rv.iter().collect();
+        let start = self.suffixes.find(&rv)?;
+        match &self.condition {
+            Some(allows) if !allows(&rv[..start]) => None,
+            _ => Some(rv[..start].chars().count()),
+        }
+    }
+
+    fn replacement(&self) -> &'static str {
+        self.replacement
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// The part of TOML and JSON rule sets are written in: keys, strings with JSON escapes and
+// arrays of strings.
+struct Scanner<'t> {
+    chars: Peekable<CharIndices<'t>>,
+    line: usize,
+    // TOML: `#` comments, and new lines separate entries
+    toml: bool,
+}
+
+impl<'t> Scanner<'t> {
+    fn new(text: &'t str, toml: bool) -> Self {
+        Scanner {
+            chars: text.char_indices().peekable(),
+            line: 1,
+            toml,
+        }
+    }
+
+    fn error(&self, message: &str) -> io::Error {
+        invalid(format!("line {}: {}", self.line, message))
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|(_, c)| *c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() != Some(c) {
+            return false;
+        }
+        self.chars.next();
+        if c == '\n' {
+            self.line += 1;
+        }
+        true
+    }
+
+    fn expect(&mut self, c: char) -> io::Result<()> {
+        self.skip_line_space();
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {:?}", c)))
+        }
+    }
+
+    // skips spaces and comments up to the end of the line; whether anything is left
+    fn skip_line_space(&mut self) -> bool {
+        while let Some(c) = self.peek() {
+            match c {
+                '#' if self.toml => {
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.chars.next();
+                    }
+                }
+                '\n' if self.toml => return true,
+                c if c.is_whitespace() => {
+                    self.eat(c);
+                }
+                _ => return true,
+            }
+        }
+        false
+    }
+
+    // skips spaces, new lines and comments; whether anything is left
+    fn skip_space(&mut self) -> bool {
+        while self.skip_line_space() {
+            if !self.eat('\n') {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn key(&mut self) -> io::Result<String> {
+        let mut key = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_ascii_lowercase() || *c == '_') {
+            key.push(c);
+            self.chars.next();
+        }
+        if key.is_empty() {
+            return Err(self.error("expected a rule group name"));
+        }
+        Ok(key)
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.chars.next().map(|(_, c)| c) {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.chars.next().map(|(_, c)| c) {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('/') => s.push('/'),
+                    Some('u') => {
+                        let hex: String = (0..4)
+                            .filter_map(|_| self.chars.next())
+                            .map(|(_, c)| c)
+                            .collect();
+                        let c = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32);
+                        s.push(c.ok_or_else(|| self.error("bad \\u escape"))?);
+                    }
+                    _ => return Err(self.error("unsupported escape")),
+                },
+                Some('\n') | None => return Err(self.error("unterminated string")),
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn array(&mut self) -> io::Result<Vec<String>> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_space();
+            if self.eat(']') {
+                return Ok(items);
+            }
+            items.push(self.string()?);
+            self.skip_space();
+            if self.eat(']') {
+                return Ok(items);
+            }
+            self.expect(',')?;
+        }
+    }
+}
+
+#[test]
+fn builtin_rule_set_test() {
+    let rules = RuleSet::builtin();
+    assert_eq!(rules.suffixes(REFLEXIVE).unwrap(), ["ся", "сь", "си"]);
+    assert_eq!(rules.suffixes("prefix"), None);
+    assert!(ALL.iter().all(|group| rules.group(group).is_some()));
+    assert_eq!(RuleSet::from_toml(&rules.to_toml()).unwrap(), rules);
+    assert_eq!(RuleSet::from_json(&rules.to_json()).unwrap(), rules);
+    assert_eq!(RuleSet::from_toml("").unwrap(), rules);
+    assert_eq!(RuleSet::from_json(" {} ").unwrap(), rules);
+}
+
+#[test]
+fn wide_suffixes_test() {
+    // suffixes of 1 to 4-byte chars cut whole chars, so stems stay valid prefixes
+    let rules = RuleSet::from_toml("noun = [\"a\", \"ꙗ\", \"𐐨\", \"ї\"]").unwrap();
+    let stemmer = crate::Stemmer::builder().rules(rules).build();
+    for (word, stem) in [
+        ("книгa", "книг"),
+        ("книгꙗ", "книг"),
+        ("книг𐐨", "книг"),
+        ("краї", "кра"),
+    ] {
+        assert_eq!(stemmer.try_stem(word.as_bytes()).unwrap(), stem, "{}", word);
+    }
+    assert_eq!(stemmer.stem("краі"), "краі");
+}
+
+#[test]
+fn parse_rule_set_test() {
+    let toml = "# no superlatives\nsuperlative = []\nreflexive = [\n  \"ся\", # the common one\n  \"сь\",\n]\n";
+    let rules = RuleSet::from_toml(toml).unwrap();
+    assert_eq!(rules.suffixes(SUPERLATIVE).unwrap(), Vec::<&str>::new());
+    assert_eq!(rules.suffixes(REFLEXIVE).unwrap(), ["ся", "сь"]);
+    assert_eq!(rules.suffixes(NOUN), RuleSet::builtin().suffixes(NOUN));
+
+    let json = r#"{"superlative": [], "reflexive": ["ся", "сь"]}"#;
+    assert_eq!(RuleSet::from_json(json).unwrap(), rules);
+
+    let errors = [
+        RuleSet::from_toml("prefix = [\"пре\"]"),
+        RuleSet::from_toml("noun = [\"А\"]"),
+        RuleSet::from_toml("noun = [\"а\", \"а\"]"),
+        RuleSet::from_toml("double_n = [\"мм\"]"),
+        RuleSet::from_toml("noun = [\"а\"] verb = []"),
+        RuleSet::from_toml("noun = \"а\""),
+        RuleSet::from_json("{\"noun\": [\"а\"]"),
+        RuleSet::from_json("{\"noun\": [\"а\"]} {}"),
+    ];
+    for error in errors.iter() {
+        assert_eq!(
+            error.as_ref().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+    let message = RuleSet::from_toml("\n\nnoun = [\"а\"")
+        .unwrap_err()
+        .to_string();
+    assert!(message.starts_with("line 3:"), "{}", message);
+}
+
+#[test]
+fn snowball_test() {
+    let builtin = RuleSet::builtin();
+    let program = builtin.to_snowball();
+    assert!(program.contains("define double_n as (\n        [substring] among (\n"));
+    assert!(program.contains("(<- 'н')"));
+    assert_eq!(RuleSet::from_snowball(&program).unwrap(), builtin);
+
+    let mut light = builtin.light();
+    light.set_suffixes(NOUN, ["ами", "и"]).unwrap();
+    assert!(light.to_snowball().contains("define verb as false"));
+    assert_eq!(RuleSet::from_snowball(&light.to_snowball()).unwrap(), light);
+
+    let program = "/* from another stemmer */
+        routines ( noun tidy_up )
+        backwardmode (
+            define noun as ( [substring] among ( 'ами' 'ями' // plural
+                'і' (delete) ) )
+            define tidy_up as ( ['н'] among ( 'x' ) )
+        )";
+    let rules = RuleSet::from_snowball(program).unwrap();
+    assert_eq!(rules.suffixes(NOUN).unwrap(), ["ами", "ями", "і"]);
+    assert_eq!(rules.suffixes(VERB), builtin.suffixes(VERB));
+    assert!(RuleSet::from_snowball("define noun as ( among ( 'а ) )").is_err());
+    assert!(RuleSet::from_snowball("define noun as ( among ( 'A' ) )").is_err());
+    assert!(RuleSet::from_snowball("define noun as ( among ( 'а' )").is_err());
+}
+
+#[test]
+fn extra_rules_test() {
+    use crate::Stemmer;
+
+    let mut rules = RuleSet::builtin();
+    // the names of inflammations keep their root with any case ending
+    let inflammation = SuffixRule::new("inflammation", ["ит"]).when(|rest| rest.len() > 4);
+    rules
+        .add_rule(RulePosition::Before(I_ENDING), inflammation)
+        .unwrap();
+    assert!(rules
+        .add_rule(RulePosition::Before("step4"), SuffixRule::new("x", ["и"]))
+        .is_err());
+    assert_eq!(
+        rules.extra_rules().collect::<Vec<_>>(),
+        [(RulePosition::Before(I_ENDING), "inflammation")]
+    );
+    let medical = Stemmer::builder().rules(rules.clone()).build();
+    for word in ["гастрит", "гастриту", "гастритом"] {
+        assert_eq!(medical.stem(word), "гастр");
+    }
+    assert_eq!(medical.stem("кит"), "кит");
+    assert_eq!(
+        medical.stem_with_rules("гастриту").1,
+        [ADJECTIVE, "inflammation"]
+    );
+    assert_ne!(medical.fingerprint(), Stemmer::new().fingerprint());
+    assert_eq!(
+        RuleSet::from_toml(&rules.to_toml()).unwrap(),
+        RuleSet::builtin()
+    );
+    assert_ne!(rules, RuleSet::builtin());
+    assert!(rules
+        .to_snowball()
+        .contains("// left out: the rule inflammation at Before(\"i_ending\")\n"));
+    assert_eq!(
+        format!("{:?}", RuleSet::builtin()),
+        format!("{:?}", RuleSet::from_toml("").unwrap())
+    );
+}
+
+#[test]
+fn diff_test() {
+    let log = "# c\n2 noun: +ії +іями\n3 adjective: +ею -ою\n";
+    let changes = parse_changes(log);
+    assert_eq!(changes.len(), 4);
+    assert_eq!(
+        changes[3],
+        RuleChange {
+            version: 3,
+            group: ADJECTIVE,
+            suffix: "ою".to_string(),
+            kind: ChangeKind::Removed,
+        }
+    );
+    assert_eq!(between(changes.clone(), 1, 2).len(), 2);
+    assert_eq!(between(changes.clone(), 2, 3).len(), 2);
+    assert_eq!(between(changes.clone(), 3, 3), []);
+    let back = between(changes.clone(), 3, 1);
+    assert_eq!(back.len(), 4);
+    assert_eq!(back[0].suffix, "ою");
+    assert_eq!(back[0].kind, ChangeKind::Added);
+    assert!(back[1..].iter().all(|c| c.kind == ChangeKind::Removed));
+
+    // the built-in tables are those of version 1, the log their changes since
+    assert_eq!(
+        diff(1, RULES_VERSION).map(|c| c.len()),
+        Some(self::changes().len())
+    );
+    assert_eq!(diff(RULES_VERSION, RULES_VERSION), Some(Vec::new()));
+    assert_eq!(diff(0, 1), None);
+    assert_eq!(diff(1, RULES_VERSION + 1), None);
+    let latest = RuleSet::at_version(RULES_VERSION).unwrap();
+    assert!(latest.suffixes(NOUN).unwrap().contains(&"іями"));
+    assert!(latest.suffixes(ADJECTIVE).unwrap().contains(&"ією"));
+    assert!(!RuleSet::builtin().suffixes(NOUN).unwrap().contains(&"іями"));
+    assert_eq!(RuleSet::at_version(1).unwrap(), RuleSet::builtin());
+    assert!(self::changes().iter().all(|c| c.version <= RULES_VERSION));
+}
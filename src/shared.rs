@@ -0,0 +1,73 @@
+use lazy_static::lazy_static;
+use std::sync::{Arc, RwLock};
+
+use crate::analyzer::Analyzer;
+
+/// An analyzer that can be replaced while other threads use it.
+///
+/// Readers take a cheap snapshot with `load` and keep working with it; `store` only
+/// affects snapshots taken afterwards, so a reload never changes the configuration in
+/// the middle of a document.
+#[derive(Debug, Default)]
+pub struct SharedAnalyzer {
+    current: RwLock<Arc<Analyzer>>,
+}
+
+impl SharedAnalyzer {
+    pub fn new(analyzer: Analyzer) -> Self {
+        SharedAnalyzer {
+            current: RwLock::new(Arc::new(analyzer)),
+        }
+    }
+
+    pub fn load(&self) -> Arc<Analyzer> {
+        // the lock is only held to clone the Arc, a poisoned one still holds a valid value
+        match self.current.read() {
+            Ok(current) => Arc::clone(&current),
+            Err(poisoned) => Arc::clone(&poisoned.into_inner()),
+        }
+    }
+
+    /// Replaces the analyzer, returning the previous one.
+    pub fn store(&self, analyzer: Analyzer) -> Arc<Analyzer> {
+        let analyzer = Arc::new(analyzer);
+        let mut current = match self.current.write() {
+            Ok(current) => current,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        std::mem::replace(&mut *current, analyzer)
+    }
+}
+
+lazy_static! {
+    static ref GLOBAL: SharedAnalyzer = crate::lazy(SharedAnalyzer::default);
+}
+
+pub(crate) fn warm_up() {
+    lazy_static::initialize(&GLOBAL);
+}
+
+/// Replaces the configuration used by `stem_text` and `conflate`, e.g. to hot-reload
+/// exception or stop-word lists. Calls already running finish with the old one.
+pub fn set_global_config(config: Analyzer) {
+    GLOBAL.store(config);
+}
+
+/// A snapshot of the configuration used by `stem_text` and `conflate`.
+pub fn global_config() -> Arc<Analyzer> {
+    GLOBAL.load()
+}
+
+#[test]
+fn shared_analyzer_test() {
+    use crate::dictionary::Dictionary;
+    use crate::Stemmer;
+
+    let shared = SharedAnalyzer::default();
+    let before = shared.load();
+    let exceptions = Dictionary::from_pairs(vec![("люди", "людина")]);
+    shared.store(Analyzer::new().stemmer(Stemmer::builder().exceptions(exceptions).build()));
+
+    assert_eq!(before.stem_text("люди"), "люд");
+    assert_eq!(shared.load().stem_text("люди"), "людина");
+}
@@ -0,0 +1,269 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::analyzer::Analyzer;
+use crate::stopwords::is_stopword;
+use crate::tokenizer::TokenKind;
+
+/// A document as a stem → weight map, for cosine similarity between documents.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DocVector {
+    weights: BTreeMap<String, f64>,
+}
+
+impl DocVector {
+    /// Every occurrence of a stem adds the weight of its field, so a title word counts
+    /// twice as much as a body word in `from_fields([(title, 2.0), (body, 1.0)])`.
+    /// Stop words are skipped.
+    pub fn from_fields<'a, I>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, f64)>,
+    {
+        DocVector::from_fields_with(&Analyzer::new(), fields)
+    }
+
+    pub fn from_fields_with<'a, I>(analyzer: &Analyzer, fields: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, f64)>,
+    {
+        let mut weights = BTreeMap::new();
+        for (text, weight) in fields {
+            for term in analyzer.analyze(text) {
+                if term.token.kind == TokenKind::Word && !is_stopword(term.token.text) {
+                    *weights.entry(term.term).or_insert(0.0) += weight;
+                }
+            }
+        }
+        DocVector { weights }
+    }
+
+    pub fn from_weights<I: IntoIterator<Item = (String, f64)>>(weights: I) -> Self {
+        DocVector {
+            weights: weights.into_iter().collect(),
+        }
+    }
+
+    pub fn weight(&self, stem: &str) -> f64 {
+        self.weights.get(stem).copied().unwrap_or(0.0)
+    }
+
+    /// `(stem, weight)` pairs in stem order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f64)> {
+        self.weights.iter().map(|(stem, w)| (stem.as_str(), *w))
+    }
+
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    pub fn norm(&self) -> f64 {
+        self.weights.values().map(|w| w * w).sum::<f64>().sqrt()
+    }
+
+    pub fn dot(&self, other: &DocVector) -> f64 {
+        let (small, large) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        small.iter().map(|(stem, w)| w * large.weight(stem)).sum()
+    }
+
+    /// Cosine similarity; 0 if either vector is empty.
+    pub fn cosine(&self, other: &DocVector) -> f64 {
+        let norms = self.norm() * other.norm();
+        if norms == 0.0 {
+            0.0
+        } else {
+            self.dot(other) / norms
+        }
+    }
+}
+
+// 64-bit FNV-1a; the bits of a stem's hash are its random ±1 projections
+fn stem_hash(stem: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in stem.bytes() {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    // FNV mixes the last bytes poorly into the high bits
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^ (hash >> 33)
+}
+
+// SimHash: bit i is set when the weighted sum of the stems' i-th projections is positive,
+// so vectors at a small angle agree on most bits.
+fn signature(doc: &DocVector) -> u64 {
+    let mut sums = [0.0f64; 64];
+    for (stem, weight) in doc.iter() {
+        let hash = stem_hash(stem);
+        for (bit, sum) in sums.iter_mut().enumerate() {
+            if hash >> bit & 1 == 1 {
+                *sum += weight;
+            } else {
+                *sum -= weight;
+            }
+        }
+    }
+    sums.iter()
+        .enumerate()
+        .filter(|(_, sum)| **sum > 0.0)
+        .fold(0, |sig, (bit, _)| sig | 1 << bit)
+}
+
+/// A document returned by `Similar::query`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Neighbor {
+    /// Index of the document in the order it was indexed.
+    pub index: usize,
+    /// Exact cosine similarity to the query.
+    pub score: f64,
+}
+
+/// Approximate nearest-neighbor search over `DocVector`s with locality-sensitive hashing.
+///
+/// Each document gets a 64-bit SimHash signature split into `bands` of `rows` bits; two
+/// documents become candidates when any band matches, and candidates are ranked by their
+/// exact cosine similarity. More bands find more true neighbors, more rows per band
+/// examine fewer unrelated documents.
+pub struct Similar {
+    docs: Vec<DocVector>,
+    rows: usize,
+    buckets: Vec<HashMap<u64, Vec<usize>>>,
+}
+
+impl Similar {
+    /// Indexes `docs` with 16 bands of 4 bits.
+    pub fn index<I: IntoIterator<Item = DocVector>>(docs: I) -> Self {
+        Similar::with_bands(docs, 16, 4)
+    }
+
+    /// `bands * rows` must not exceed 64.
+    pub fn with_bands<I: IntoIterator<Item = DocVector>>(
+        docs: I,
+        bands: usize,
+        rows: usize,
+    ) -> Self {
+        assert!(
+            bands > 0 && rows > 0 && bands * rows <= 64,
+            "at most 64 signature bits"
+        );
+        let mut similar = Similar {
+            docs: Vec::new(),
+            rows,
+            buckets: vec![HashMap::new(); bands],
+        };
+        for doc in docs {
+            similar.add(doc);
+        }
+        similar
+    }
+
+    fn bands(&self, doc: &DocVector) -> impl Iterator<Item = u64> {
+        let sig = signature(doc);
+        let rows = self.rows;
+        let mask = if rows == 64 {
+            u64::MAX
+        } else {
+            (1 << rows) - 1
+        };
+        (0..self.buckets.len()).map(move |band| sig >> (band * rows) & mask)
+    }
+
+    /// Adds a document and returns its index.
+    pub fn add(&mut self, doc: DocVector) -> usize {
+        let index = self.docs.len();
+        let keys: Vec<u64> = self.bands(&doc).collect();
+        for (buckets, key) in self.buckets.iter_mut().zip(keys) {
+            buckets.entry(key).or_default().push(index);
+        }
+        self.docs.push(doc);
+        index
+    }
+
+    pub fn get(&self, index: usize) -> Option<&DocVector> {
+        self.docs.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Up to `k` indexed documents most similar to `doc`, best first. Documents sharing no
+    /// stem with `doc` are never returned.
+    pub fn query(&self, doc: &DocVector, k: usize) -> Vec<Neighbor> {
+        let mut candidates: Vec<usize> = self
+            .buckets
+            .iter()
+            .zip(self.bands(doc))
+            .filter_map(|(buckets, key)| buckets.get(&key))
+            .flatten()
+            .copied()
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let mut res: Vec<Neighbor> = candidates
+            .into_iter()
+            .map(|index| Neighbor {
+                index,
+                score: doc.cosine(&self.docs[index]),
+            })
+            .filter(|n| n.score > 0.0)
+            .collect();
+        res.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap()
+                .then(a.index.cmp(&b.index))
+        });
+        res.truncate(k);
+        res
+    }
+}
+
+#[test]
+fn doc_vector_test() {
+    let a = DocVector::from_fields([("Народи чекають", 2.0), ("Народ чекає змін", 1.0)]);
+    assert_eq!(a.weight("народ"), 3.0);
+    assert_eq!(a.weight("змін"), 1.0);
+
+    let b = DocVector::from_fields([("Чекали на народ", 1.0)]);
+    let c = DocVector::from_fields([("Погода в Києві", 1.0)]);
+    assert!(a.cosine(&b) > a.cosine(&c));
+    assert_eq!(a.cosine(&c), 0.0);
+    assert!((a.cosine(&a) - 1.0).abs() < 1e-9);
+    assert_eq!(a.cosine(&DocVector::default()), 0.0);
+}
+
+#[test]
+fn similar_test() {
+    let articles = [
+        "Уряд ухвалив рішення про підтримку освіти та шкіл",
+        "Погода: завтра в Києві піде дощ, вранці туман",
+        "Освіта і школи отримають підтримку уряду",
+        "У Львові завтра сонячна погода без дощу",
+    ];
+    let similar = Similar::index(articles.iter().map(|a| DocVector::from_fields([(*a, 1.0)])));
+    assert_eq!(similar.len(), 4);
+
+    let query = DocVector::from_fields([("Підтримка шкіл і освіти від уряду", 1.0)]);
+    let found = similar.query(&query, 2);
+    let mut indexes: Vec<usize> = found.iter().map(|n| n.index).collect();
+    indexes.sort_unstable();
+    assert_eq!(indexes, [0, 2]);
+    assert!(found[0].score >= found[1].score);
+
+    let itself = similar.query(similar.get(1).unwrap(), 1);
+    assert_eq!(itself[0].index, 1);
+    assert!((itself[0].score - 1.0).abs() < 1e-9);
+}
@@ -0,0 +1,86 @@
+//! Truncation for result snippets that never splits what the tokenizer keeps together.
+
+use std::borrow::Cow;
+
+use unicode_normalization::char::is_combining_mark;
+
+use stemmer_uk_core::normalize::APOSTROPHES;
+
+// whether `text` can be cut at byte offset `at` (a char boundary)
+fn is_safe_cut(text: &str, at: usize) -> bool {
+    let (before, after) = text.split_at(at);
+    let mut after = after.chars();
+    let next = match after.next() {
+        Some(c) => c,
+        None => return true,
+    };
+    if is_combining_mark(next) {
+        return false;
+    }
+    let mut before = before.chars().rev();
+    let prev = match before.next() {
+        Some(c) => c,
+        None => return true,
+    };
+    // "м|'ясо" and "м'|ясо" both tear an apostrophe from its word
+    if APOSTROPHES.contains(&next) {
+        return !(prev.is_alphabetic() && after.next().is_some_and(char::is_alphabetic));
+    }
+    if APOSTROPHES.contains(&prev) {
+        return !(next.is_alphabetic() && before.next().is_some_and(char::is_alphabetic));
+    }
+    true
+}
+
+/// The longest prefix of `text` of at most `max_chars` chars that doesn't end inside a
+/// combining sequence or next to an apostrophe within a word.
+pub fn truncate_uk(text: &str, max_chars: usize) -> &str {
+    let mut end = match text.char_indices().nth(max_chars) {
+        Some((at, _)) => at,
+        None => return text,
+    };
+    while end > 0 && !is_safe_cut(text, end) {
+        end = text[..end]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(at, _)| at);
+    }
+    &text[..end]
+}
+
+/// `text` shortened to at most `max_chars` chars, ending in "…" when anything was cut.
+pub fn ellipsize_uk(text: &str, max_chars: usize) -> Cow<'_, str> {
+    if text.chars().nth(max_chars).is_none() {
+        return Cow::Borrowed(text);
+    }
+    if max_chars == 0 {
+        return Cow::Borrowed("");
+    }
+    let kept = truncate_uk(text, max_chars - 1).trim_end();
+    Cow::Owned(format!("{}…", kept))
+}
+
+#[test]
+fn truncate_test() {
+    assert_eq!(truncate_uk("привіт", 10), "привіт");
+    assert_eq!(truncate_uk("привіт", 3), "при");
+    assert_eq!(truncate_uk("м'ясо", 1), "");
+    assert_eq!(truncate_uk("м'ясо", 2), "");
+    assert_eq!(truncate_uk("м'ясо", 3), "м'я");
+    assert_eq!(truncate_uk("це м’ясо", 4), "це ");
+    assert_eq!(truncate_uk("'цитата'", 1), "'");
+    // "й" written as "и" + combining breve
+    assert_eq!(truncate_uk("ми\u{306}", 2), "м");
+    assert_eq!(truncate_uk("", 0), "");
+}
+
+#[test]
+fn ellipsize_test() {
+    assert!(matches!(
+        ellipsize_uk("коротко", 7),
+        Cow::Borrowed("коротко")
+    ));
+    assert_eq!(ellipsize_uk("довгий текст", 8), "довгий…");
+    assert_eq!(ellipsize_uk("з'їзд делегатів", 3), "…");
+    assert_eq!(ellipsize_uk("текст", 0), "");
+}
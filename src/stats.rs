@@ -0,0 +1,278 @@
+//! Allocation counting for benchmarks and regression tests.
+//!
+//! Enabling the `alloc-stats` feature installs `CountingAlloc` as the global allocator of
+//! the final binary, so it should only be used in tests and benchmarks.
+//!
+//! `soak` stems a long stream of words across threads and reports what memory is held
+//! after every round, for checking that long-running services don't grow. The full-size
+//! run is an ignored test:
+//! `cargo test --release --features alloc-stats -- --ignored soak`, with
+//! `STEMMER_UK_SOAK_TOKENS` setting the number of tokens (300 million by default).
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use crate::Stemmer;
+
+thread_local! {
+    static ALLOCS: Cell<u64> = const { Cell::new(0) };
+    static NET_BYTES: Cell<i64> = const { Cell::new(0) };
+}
+
+fn add_net_bytes(bytes: i64) {
+    let _ = NET_BYTES.try_with(|n| n.set(n.get() + bytes));
+}
+
+/// Forwards to the system allocator, counting allocations and allocated bytes per thread.
+pub struct CountingAlloc;
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCS.try_with(|n| n.set(n.get() + 1));
+        add_net_bytes(layout.size() as i64);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        add_net_bytes(-(layout.size() as i64));
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCS.try_with(|n| n.set(n.get() + 1));
+        add_net_bytes(new_size as i64 - layout.size() as i64);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Allocations (including reallocations) made by the current thread so far.
+pub fn allocations() -> u64 {
+    ALLOCS.with(Cell::get)
+}
+
+/// Allocations made by the current thread while running `f`.
+pub fn count_allocs<T, F: FnOnce() -> T>(f: F) -> (T, u64) {
+    let before = allocations();
+    let res = f();
+    (res, allocations() - before)
+}
+
+/// Bytes allocated minus bytes freed by the current thread while running `f`. Memory one
+/// thread allocates and another frees counts on both, so the sum over all threads involved
+/// is what they left allocated.
+pub fn count_net_bytes<T, F: FnOnce() -> T>(f: F) -> (T, i64) {
+    let before = NET_BYTES.with(Cell::get);
+    let res = f();
+    (res, NET_BYTES.with(Cell::get) - before)
+}
+
+const SOAK_STEMS: [&str; 16] = [
+    "книг",
+    "народ",
+    "слідств",
+    "автор",
+    "університет",
+    "громад",
+    "кошт",
+    "закон",
+    "рішен",
+    "депутат",
+    "місц",
+    "податк",
+    "молод",
+    "чесн",
+    "дорог",
+    "лікарн",
+];
+
+const SOAK_ENDINGS: [&str; 16] = [
+    "",
+    "а",
+    "и",
+    "ами",
+    "ові",
+    "ою",
+    "ах",
+    "ість",
+    "остями",
+    "ський",
+    "ськими",
+    "ає",
+    "ається",
+    "ували",
+    "'ям",
+    "ом",
+];
+
+const SOAK_LETTERS: [char; 8] = ['б', 'в', 'д', 'з', 'к', 'л', 'м', 'н'];
+
+/// The `i`-th word `soak` stems. The words vary in case, apostrophes and length and repeat
+/// every `period` words, so a cache sees both hits and a steady stream of new words.
+pub fn soak_word(i: u64, period: u64) -> String {
+    let i = i % period.max(1);
+    let stem = SOAK_STEMS[(i % 16) as usize];
+    let ending = SOAK_ENDINGS[(i / 16 % 16) as usize];
+    let mut word = String::new();
+    let mut prefix = i / 256;
+    while prefix > 0 {
+        word.push(SOAK_LETTERS[(prefix % 8) as usize]);
+        word.push('о');
+        prefix /= 8;
+    }
+    word.push_str(stem);
+    word.push_str(ending);
+    if i.is_multiple_of(7) {
+        word = word.to_uppercase();
+    }
+    word
+}
+
+/// What `soak` observed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SoakReport {
+    pub tokens: u64,
+    /// Bytes the stemming left allocated after each round, cumulative.
+    pub net_bytes: Vec<i64>,
+    /// Entries of the stemmer's cache after each round.
+    pub cache_entries: Vec<usize>,
+}
+
+impl SoakReport {
+    /// How much the memory held grew after the first round, which fills the caches.
+    pub fn growth(&self) -> i64 {
+        match self.net_bytes.first() {
+            Some(first) => self.net_bytes.iter().max().unwrap() - first,
+            None => 0,
+        }
+    }
+}
+
+/// Stems `tokens` words of `soak_word` with `stemmer` on `threads` threads, in `rounds`
+/// rounds whose memory use is recorded in the report.
+pub fn soak(stemmer: &Stemmer, threads: usize, rounds: usize, tokens: u64) -> SoakReport {
+    // a new word every few tokens
+    const PERIOD: u64 = 1 << 20;
+    let threads = threads.max(1) as u64;
+    let per_thread = tokens / rounds.max(1) as u64 / threads;
+    let mut report = SoakReport::default();
+    let mut net = 0;
+    for round in 0..rounds as u64 {
+        net += std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|thread| {
+                    scope.spawn(move || {
+                        let first = (round * threads + thread) * per_thread;
+                        count_net_bytes(|| {
+                            for i in first..first + per_thread {
+                                let stem = stemmer.stem(&soak_word(i, PERIOD));
+                                std::hint::black_box(stem);
+                            }
+                        })
+                        .1
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .sum::<i64>()
+        });
+        report.tokens += per_thread * threads;
+        report.net_bytes.push(net);
+        report
+            .cache_entries
+            .push(stemmer.cache_stats().map_or(0, |stats| stats.entries));
+    }
+    report
+}
+
+/// Average allocations per `Stemmer::stem` call of the default stemmer over `words`.
+/// Lazy initialization is done by an uncounted first pass.
+pub fn alloc_per_word(words: &[&str]) -> f64 {
+    if words.is_empty() {
+        return 0.0;
+    }
+    crate::warm_up();
+    let stemmer = Stemmer::new();
+    for word in words {
+        stemmer.stem(word);
+    }
+    let (_, allocs) = count_allocs(|| {
+        for word in words {
+            stemmer.stem(word);
+        }
+    });
+    allocs as f64 / words.len() as f64
+}
+
+#[test]
+fn alloc_per_word_test() {
+    let words = [
+        "ручкається",
+        "народи",
+        "слідство",
+        "триває",
+        "автора",
+        "чекають",
+        "університеті",
+        "грн",
+    ];
+    let per_word = alloc_per_word(&words);
+    assert!(per_word > 0.0);
+    // lower this bound when the pipeline allocates less, never raise it
+    // the returned stem is the only allocation left
+    assert!(per_word <= 1.0, "{} allocations per word", per_word);
+}
+
+#[test]
+fn borrowed_stem_test() {
+    crate::warm_up();
+    let (stems, allocs) = count_allocs(|| {
+        ["ручкається", "слідство", "університеті", "грн"]
+            .iter()
+            .map(|word| crate::stem(word).len())
+            .sum::<usize>()
+    });
+    assert!(stems > 0);
+    assert_eq!(allocs, 0);
+}
+
+#[cfg(test)]
+fn check_soak(tokens: u64, rounds: usize) -> SoakReport {
+    const CACHE_ENTRIES: usize = 50_000;
+    crate::warm_up();
+    let stemmer = Stemmer::builder().cache(CACHE_ENTRIES).build();
+    let report = soak(&stemmer, 4, rounds, tokens);
+    // words outnumber the entries, so the cache got full but not fuller
+    assert!(report.cache_entries.iter().all(|n| *n <= CACHE_ENTRIES));
+    assert_eq!(report.cache_entries.last(), Some(&CACHE_ENTRIES));
+    // a full cache holds about 15 MB; what varies after that is the maps resizing
+    assert!(
+        report.growth() < 1 << 20,
+        "memory grew by {} bytes: {:?}",
+        report.growth(),
+        report.net_bytes
+    );
+    report
+}
+
+#[test]
+fn soak_test() {
+    assert!(soak_word(0, 1 << 20).chars().all(char::is_uppercase));
+    assert_eq!(soak_word(1 << 20, 1 << 20), soak_word(0, 1 << 20));
+    let report = check_soak(400_000, 4);
+    assert_eq!(report.tokens, 400_000);
+}
+
+#[test]
+#[ignore]
+fn soak_long_test() {
+    let tokens = std::env::var("STEMMER_UK_SOAK_TOKENS")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(300_000_000);
+    check_soak(tokens, 30);
+}
@@ -0,0 +1,214 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::analyzer::{Lowercase, PassThrough};
+use crate::dictionary::Dictionary;
+use crate::Stemmer;
+
+/// Anything that maps a word to its stem or lemma.
+///
+/// The trait is object safe, so frameworks can hold `Box<dyn Stem>` and pick the
+/// implementation from configuration. Implementations borrow the input when it is
+/// already its own stem.
+pub trait Stem: Send + Sync {
+    fn stem<'a>(&self, word: &'a str) -> Cow<'a, str>;
+}
+
+impl Stem for Stemmer {
+    fn stem<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        let stem = Stemmer::stem(self, word);
+        if stem == word {
+            Cow::Borrowed(word)
+        } else {
+            Cow::Owned(stem)
+        }
+    }
+}
+
+impl Stem for PassThrough {
+    fn stem<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        Cow::Borrowed(word)
+    }
+}
+
+impl Stem for Lowercase {
+    fn stem<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        if word.chars().any(char::is_uppercase) {
+            Cow::Owned(word.to_lowercase())
+        } else {
+            Cow::Borrowed(word)
+        }
+    }
+}
+
+/// User plugins can be plain functions or closures.
+impl<F> Stem for F
+where
+    F: Fn(&str) -> String + Send + Sync,
+{
+    fn stem<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        Cow::Owned(self(word))
+    }
+}
+
+type Predicate = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// One step of a `FallbackChain`: a named `Stem` implementation, optionally restricted
+/// to the words accepted by a predicate.
+pub struct Stage {
+    name: String,
+    stem: Box<dyn Stem>,
+    when: Option<Predicate>,
+}
+
+impl Stage {
+    pub fn new<S: Stem + 'static>(name: &str, stem: S) -> Self {
+        Stage {
+            name: name.to_string(),
+            stem: Box::new(stem),
+            when: None,
+        }
+    }
+
+    /// A stage that only handles the forms listed in `dictionary`.
+    pub fn dictionary(name: &str, dictionary: Dictionary<'static>) -> Self {
+        let dictionary = Arc::new(dictionary);
+        let known = Arc::clone(&dictionary);
+        Stage::new(name, SharedDictionary(dictionary)).when(move |word| known.contains(word))
+    }
+
+    /// Only words for which `predicate` returns `true` reach this stage.
+    pub fn when<P>(mut self, predicate: P) -> Self
+    where
+        P: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.when = Some(Box::new(predicate));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn accepts(&self, word: &str) -> bool {
+        self.when.as_ref().is_none_or(|when| when(word))
+    }
+}
+
+impl fmt::Debug for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Stage")
+            .field("name", &self.name)
+            .field("conditional", &self.when.is_some())
+            .finish()
+    }
+}
+
+struct SharedDictionary(Arc<Dictionary<'static>>);
+
+impl Stem for SharedDictionary {
+    fn stem<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        self.0.stem(word)
+    }
+}
+
+/// A stem together with the chain stage that produced it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Staged<'a, 's> {
+    pub stem: Cow<'a, str>,
+    /// `None` when no stage accepted the word and it was returned unchanged.
+    pub stage: Option<&'s str>,
+}
+
+/// Tries its stages in order; the first stage whose predicate accepts a word stems it.
+///
+/// The usual production topology is a dictionary for known words, the rule-based
+/// stemmer for Ukrainian words and a pass-through for everything else:
+/// `FallbackChain::new([Stage::dictionary("dictionary", dict), Stage::new("rules", stemmer),
+/// Stage::new("pass-through", PassThrough)])`.
+#[derive(Debug, Default)]
+pub struct FallbackChain {
+    stages: Vec<Stage>,
+}
+
+impl FallbackChain {
+    pub fn new<I: IntoIterator<Item = Stage>>(stages: I) -> Self {
+        FallbackChain {
+            stages: stages.into_iter().collect(),
+        }
+    }
+
+    pub fn then(mut self, stage: Stage) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    pub fn stages(&self) -> &[Stage] {
+        &self.stages
+    }
+
+    pub fn stem_staged<'a>(&self, word: &'a str) -> Staged<'a, '_> {
+        match self.stages.iter().find(|stage| stage.accepts(word)) {
+            Some(stage) => Staged {
+                stem: stage.stem.stem(word),
+                stage: Some(&stage.name),
+            },
+            None => Staged {
+                stem: Cow::Borrowed(word),
+                stage: None,
+            },
+        }
+    }
+}
+
+impl Stem for FallbackChain {
+    fn stem<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        self.stem_staged(word).stem
+    }
+}
+
+#[test]
+fn dyn_stem_test() {
+    let configured: Vec<Box<dyn Stem>> = vec![
+        Box::new(Stemmer::new()),
+        Box::new(Dictionary::from_pairs([("люди", "людина")])),
+        Box::new(PassThrough),
+        Box::new(|w: &str| w.chars().take(4).collect::<String>()),
+    ];
+    let stems: Vec<String> = configured
+        .iter()
+        .map(|s| s.stem("люди").into_owned())
+        .collect();
+    assert_eq!(stems, ["люд", "людина", "люди", "люди"]);
+
+    assert!(matches!(
+        Stem::stem(&Stemmer::new(), "грн"),
+        Cow::Borrowed(_)
+    ));
+}
+
+#[test]
+fn fallback_chain_test() {
+    use crate::tokenizer::{script, Script};
+
+    let chain = FallbackChain::new([
+        Stage::dictionary("dictionary", Dictionary::from_pairs([("люди", "людина")])),
+        Stage::new("rules", Stemmer::new()).when(|w| script(w) == Script::Cyrillic),
+        Stage::new("pass-through", PassThrough),
+    ]);
+    let staged = |w| {
+        let res = chain.stem_staged(w);
+        (res.stem.into_owned(), res.stage.map(str::to_string))
+    };
+    assert_eq!(staged("люди"), ("людина".into(), Some("dictionary".into())));
+    assert_eq!(staged("народи"), ("народ".into(), Some("rules".into())));
+    assert_eq!(
+        staged("Tokyo"),
+        ("Tokyo".into(), Some("pass-through".into()))
+    );
+
+    let empty = FallbackChain::default();
+    assert_eq!(empty.stem_staged("слово").stage, None);
+    assert_eq!(Stem::stem(&chain, "народи"), "народ");
+}
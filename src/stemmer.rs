@@ -0,0 +1,2946 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::iter::FusedIterator;
+use std::sync::Arc;
+
+use stemmer_uk_core::normalize::{is_normalized, Normalization, APOSTROPHES};
+use stemmer_uk_core::suffix;
+use stemmer_uk_core::Inflection;
+
+use crate::cache::{CacheStats, ShardedCache, StemCache};
+use crate::collation;
+#[cfg(feature = "fst")]
+use crate::dict::CompiledDictionary;
+use crate::dictionary::Dictionary;
+use crate::diminutive;
+use crate::error::{ItemError, StemError};
+use crate::explain::StemTrace;
+use crate::feminitive;
+use crate::fingerprint::Fingerprint;
+use crate::frequency::{FrequencyGuard, FrequencyList};
+use crate::homoglyph::{self, HomoglyphRepair};
+use crate::intern::StemInterner;
+use crate::lemmas::LemmaDictionary;
+use crate::metrics::{Counters, StemStats};
+use crate::normalizer::NormalizerChain;
+use crate::ocr::{self, Confusions};
+use crate::offsets::{MappedStem, Tracked};
+use crate::packs::LanguagePack;
+use crate::pos::PosGuess;
+use crate::rules::{RuleHit, RuleSet};
+use crate::tokenizer::{is_acronym, is_foreign, is_invisible, strip_invisible};
+use crate::translit;
+use crate::wordlist::WordList;
+use crate::{stem_rules, try_stem_rules, ukstemmer_search_preprocess, BUILTIN_RULES};
+
+/// What the stemmer does with apostrophes inside words.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Apostrophes {
+    /// Removes them, so "м'ясо" and "мясо" share a stem, as `stem_word` does; "м’ясо" too
+    /// with `StemmerBuilder::fold_unicode`.
+    #[default]
+    Strip,
+    /// Keeps them in the stem, written as the ASCII "'" with `StemmerBuilder::fold_unicode`,
+    /// for output shown to people or indexes that distinguish "з'їзд" from "зїзд".
+    Keep,
+}
+
+/// The prefixes of superlatives and intensifiers, for `StemmerBuilder::strip_prefixes`:
+/// "найкращий", "якнайкращий" and "кращий" then share a stem.
+pub const SUPERLATIVE_PREFIXES: [&str; 3] = ["якнай", "щонай", "най"];
+
+// the fewest chars `strip_prefixes` leaves after a prefix
+const MIN_PREFIXED_CHARS: usize = 4;
+
+// the most times `StemmerBuilder::idempotent` stems a word, bounding the work on words
+// whose every stem loses another suffix; no word of the frequent forms needs more than 3
+const MAX_IDEMPOTENT_PASSES: usize = 6;
+
+/// How much of a word the stemmer removes, trading recall for precision.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Mode {
+    /// Only removes the case endings of nouns and adjectives, leaving verb endings,
+    /// derivational suffixes and the other groups alone, so fewer distinct words share a
+    /// stem: "читали" becomes "читал" rather than "чит".
+    Light,
+    /// Runs every rule group, as `stem_word` does.
+    #[default]
+    Aggressive,
+}
+
+/// The version of the built-in rules, numbered like `fingerprint::RULES_VERSION`, so that
+/// a deployment pins the stems of its index across upgrades of the crate and migrates when
+/// it chooses; `rules::diff` lists what changes between two versions. All versions find
+/// the RV region as the reference algorithm does, right after the first vowel.
+///
+/// The default stays `V1`, so upgrading the crate never changes stems unasked; newer
+/// versions are chosen with `StemmerBuilder::algorithm`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Algorithm {
+    /// The original stemmer, as `stem_word` stems.
+    #[default]
+    V1,
+    /// Adds the endings "-ії", "-ією", "-іям", "-іями" and "-іях" of nouns, and "-ею",
+    /// "-єю" and "-ією" to the adjective group, which would otherwise only remove their
+    /// "ю" (see rules/changes.txt). Words are composed to NFC, all the apostrophes of
+    /// `normalize::APOSTROPHES` are taken for apostrophes and invisible characters are
+    /// removed, and stems are stemmed again until they no longer change, see
+    /// `StemmerBuilder::fold_unicode`, `StemmerBuilder::keep_invisible` and
+    /// `StemmerBuilder::idempotent`.
+    V2,
+}
+
+impl Algorithm {
+    /// The newest version.
+    pub const LATEST: Algorithm = Algorithm::V2;
+
+    pub fn rules_version(self) -> u32 {
+        match self {
+            Algorithm::V1 => 1,
+            Algorithm::V2 => 2,
+        }
+    }
+
+    pub fn from_rules_version(version: u32) -> Option<Self> {
+        match version {
+            1 => Some(Algorithm::V1),
+            2 => Some(Algorithm::V2),
+            _ => None,
+        }
+    }
+
+    /// The built-in suffix rules of the version, see `RuleSet::at_version`.
+    pub fn rule_set(self) -> RuleSet {
+        RuleSet::at_version(self.rules_version()).expect("rules/changes.txt covers every version")
+    }
+
+    /// Whether the version composes words to NFC and folds their apostrophes, unless
+    /// `StemmerOptions::fold_unicode` says otherwise.
+    pub fn folds_unicode(self) -> bool {
+        self != Algorithm::V1
+    }
+
+    /// Whether the version removes the invisible characters of words, unless
+    /// `StemmerOptions::keep_invisible` says otherwise.
+    pub fn strips_invisible(self) -> bool {
+        self != Algorithm::V1
+    }
+
+    /// Whether the version stems stems again until they no longer change, unless
+    /// `StemmerOptions::idempotent` says otherwise.
+    pub fn idempotent(self) -> bool {
+        self != Algorithm::V1
+    }
+}
+
+/// The language of the words the stemmer expects.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Language {
+    /// Ukrainian mixed with Russian, as `stem_word` expects: "ё" becomes "е", "ъ" becomes
+    /// "ї", and the rules also remove Russian endings like "ы", "ов" and "ость".
+    #[default]
+    Mixed,
+    /// Ukrainian only: Russian letters are left alone and the rules only remove Ukrainian
+    /// endings, so "студентов" keeps its "ов".
+    Ukrainian,
+}
+
+/// How the stemmer handles hyphenated compounds like "інтернет-магазину".
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Compounds {
+    /// Stems them as one word, so the rules only see the end of the last part:
+    /// "жовто-блакитного" → "жовто-блакитн", for indexes treating compounds as atoms.
+    #[default]
+    Whole,
+    /// Stems every part and joins the stems with hyphens: "жовто-блакитного" →
+    /// "жовт-блакитн".
+    Parts,
+}
+
+/// What the stemmer does with words without a vowel, which the rules cannot apply to:
+/// abbreviations like "грн" and "см", or consonant clusters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Vowelless {
+    /// Returns them normalized, as `stem_word` does.
+    #[default]
+    PassThrough,
+    /// Returns the stem of their expansion in `StemmerBuilder::abbreviations` ("грн" →
+    /// "гривн"), passing through the others.
+    Expand,
+}
+
+/// What the stemmer does with the acronyms of `tokenizer::is_acronym`, such as "НАТО",
+/// which the rules take for inflected words.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Acronyms {
+    /// Stems them like any word, as `stem_word` does: "НАТО" gets the stem "нат".
+    #[default]
+    Stem,
+    /// Returns them normalized and unstemmed, "нато", so that they only match themselves.
+    Keep,
+}
+
+/// What the stemmer does with the tokens of `tokenizer::is_foreign`: Latin words, numbers,
+/// URLs and e-mail addresses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Foreign {
+    /// Stems them like any word, as `stem_word` does: they come back normalized, and the
+    /// Cyrillic words of a URL lose their endings.
+    #[default]
+    Stem,
+    /// Returns them unchanged, so raw token streams need no filtering first.
+    PassThrough,
+    /// Converts Latin words, as typed in KMU or informal romanization, to Cyrillic with
+    /// `translit::to_cyrillic` before stemming, so that "pryvit" and "Kyieva" get the
+    /// stems of "привіт" and "Києва"; numbers, URLs and e-mail addresses are returned
+    /// unchanged.
+    Transliterate,
+}
+
+/// How `Stemmer::stem_outcome` arrived at a stem.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum StemOutcome {
+    /// The rules ran. The stem is the normalized word when no rule applied: the word is
+    /// already a stem.
+    Stemmed(String),
+    /// A lemma from `StemmerBuilder::lemmatize`, an exception or a protected word.
+    Listed(String),
+    /// The word has no vowel, so it could not be stemmed; it is returned normalized.
+    Vowelless(String),
+    /// A word without a vowel, replaced by the stem of its expansion.
+    Abbreviation(String),
+    /// A token `Foreign::PassThrough` returned unchanged.
+    Foreign(String),
+    /// An acronym `Acronyms::Keep` returned normalized.
+    Acronym(String),
+}
+
+impl StemOutcome {
+    pub fn stem(&self) -> &str {
+        match self {
+            StemOutcome::Stemmed(stem)
+            | StemOutcome::Listed(stem)
+            | StemOutcome::Vowelless(stem)
+            | StemOutcome::Abbreviation(stem)
+            | StemOutcome::Foreign(stem)
+            | StemOutcome::Acronym(stem) => stem,
+        }
+    }
+
+    pub fn into_stem(self) -> String {
+        match self {
+            StemOutcome::Stemmed(stem)
+            | StemOutcome::Listed(stem)
+            | StemOutcome::Vowelless(stem)
+            | StemOutcome::Abbreviation(stem)
+            | StemOutcome::Foreign(stem)
+            | StemOutcome::Acronym(stem) => stem,
+        }
+    }
+
+    fn map(self, f: impl FnOnce(String) -> String) -> Self {
+        match self {
+            StemOutcome::Stemmed(stem) => StemOutcome::Stemmed(f(stem)),
+            StemOutcome::Listed(stem) => StemOutcome::Listed(f(stem)),
+            StemOutcome::Vowelless(stem) => StemOutcome::Vowelless(f(stem)),
+            StemOutcome::Abbreviation(stem) => StemOutcome::Abbreviation(f(stem)),
+            StemOutcome::Foreign(stem) => StemOutcome::Foreign(f(stem)),
+            StemOutcome::Acronym(stem) => StemOutcome::Acronym(f(stem)),
+        }
+    }
+}
+
+// hex digits of the hash `fit_bytes` ends overflowing stems with
+const BUDGET_HASH_LEN: usize = 8;
+
+/// `stem` if it has at most `max_bytes` bytes, otherwise its longest prefix that leaves
+/// room for 8 hex digits of a hash of the whole stem, followed by them. Stems over the
+/// budget sharing a prefix still get distinct keys, and the same stem always the same key.
+/// Budgets under 8 bytes are filled with the hash alone.
+pub fn fit_bytes(stem: String, max_bytes: usize) -> String {
+    if stem.len() <= max_bytes {
+        return stem;
+    }
+    // seeded with rule version 1, so that keys don't change when the rules do
+    let hash = format!("{:08x}", Fingerprint::of_version(1, &stem).value() as u32);
+    let mut end = max_bytes.saturating_sub(BUDGET_HASH_LEN);
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut key = stem[..end].to_string();
+    key.push_str(&hash[..max_bytes.min(BUDGET_HASH_LEN)]);
+    key
+}
+
+/// `stem` cased like `word`, the word it is the stem of: its letters take the case of the
+/// letters of `word` in the same position, so "Київськ" for "Київського" and "КИЇВСЬК"
+/// for "КИЇВСЬКОГО".
+/// Letters beyond the end of `word`, as in expanded abbreviations, are uppercase if `word`
+/// is all uppercase and lowercase otherwise.
+pub fn restore_case(word: &str, stem: &str) -> String {
+    let mut letters = word.chars().filter(|c| c.is_alphabetic());
+    let all_upper = word.chars().filter(|c| c.is_alphabetic()).count() > 1
+        && !word.chars().any(char::is_lowercase);
+    let mut cased = String::with_capacity(stem.len());
+    for c in stem.chars() {
+        let upper = if c.is_alphabetic() {
+            letters.next().map_or(all_upper, char::is_uppercase)
+        } else {
+            false
+        };
+        if upper {
+            cased.extend(c.to_uppercase());
+        } else {
+            cased.push(c);
+        }
+    }
+    cased
+}
+
+/// Every option of a `Stemmer`. With the `serde` feature they can be read from
+/// configuration files, in which left out options keep their defaults:
+///
+/// ```ignore
+/// let options: StemmerOptions = serde_json::from_str(r#"{"mode": "light", "restrip": 1}"#)?;
+/// let stemmer = Stemmer::from_options(options);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct StemmerOptions {
+    /// Applied in order to the lowercased word, before apostrophe stripping and the
+    /// suffix rules, so packs still see characters the default preprocessing rewrites.
+    pub packs: Vec<LanguagePack>,
+    /// Replaces the normalization steps the other options configure, see
+    /// `NormalizerChain::of`; the prefixes are still stripped after it. Left out of
+    /// configuration files.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub normalizers: Option<Arc<NormalizerChain>>,
+    /// Set for low-confidence OCR input: repairs digit/letter confusions before stemming
+    /// and folds confusable letters in the result, producing fuzzy keys rather than stems.
+    pub ocr_tolerant: bool,
+    /// Letters folded in normalized words before the rules, see `ocr::Confusions`.
+    pub ocr_confusions: Option<Confusions>,
+    /// Maps Latin lookalikes in Cyrillic words to Cyrillic, before lowercasing.
+    pub homoglyphs: HomoglyphRepair,
+    /// Normalized forms whose stem is looked up instead of computed by the rules.
+    pub exceptions: Option<Arc<Dictionary<'static>>>,
+    /// Normalized forms whose stem is looked up in a compiled dictionary after the
+    /// exceptions, see `StemmerBuilder::dictionary`. Left out of configuration files.
+    #[cfg(feature = "fst")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub dictionary: Option<Arc<CompiledDictionary>>,
+    /// Normalized forms returned as they are.
+    pub protected: Option<Arc<WordList<'static>>>,
+    /// Restricts the derivational rule groups to attested stems.
+    pub frequency_guard: Option<FrequencyGuard>,
+    /// Stem counts that choose among the stems of the alternatives of step 1, see
+    /// `StemmerBuilder::disambiguate`.
+    pub disambiguation: Option<Arc<FrequencyList>>,
+    /// Whether apostrophes inside words are removed or kept in the stem.
+    pub apostrophes: Apostrophes,
+    /// Composes words to NFC and takes the typographic apostrophes "’", "ʼ" and "`" for
+    /// apostrophes like the ASCII one, so that "п’ять" and "п'ять" get the same stem;
+    /// `None` does as `algorithm` does.
+    pub fold_unicode: Option<bool>,
+    /// Keeps soft hyphens and zero-width characters (`tokenizer::is_invisible`) in words
+    /// rather than removing them before anything else; `None` does as `algorithm` does.
+    pub keep_invisible: Option<bool>,
+    /// Replaces "ґ" with "г" after lowercasing, so that loanwords spelled either way,
+    /// "ґанок" and "ганок" or "Ґуґл" and "Гугл", get the same stem; the letters are kept
+    /// apart by default.
+    pub fold_ge: bool,
+    pub vowelless: Vowelless,
+    pub foreign: Foreign,
+    pub acronyms: Acronyms,
+    /// Expansions of words without a vowel, keyed on normalized forms, see
+    /// `Vowelless::Expand`.
+    pub abbreviations: Option<Arc<Dictionary<'static>>>,
+    /// Suffix rules replacing the built-in `RuleSet`.
+    pub rules: Option<Arc<RuleSet>>,
+    /// The most bytes a stem may take, for fixed-width key columns; longer stems are
+    /// shortened by `fit_bytes`.
+    pub max_stem_bytes: Option<usize>,
+    /// Gives stems the casing of the words they come from, see `restore_case`, for stems
+    /// shown to people; stems are lowercase otherwise, as indexes need them.
+    pub preserve_case: bool,
+    /// How many more times the inflectional rule groups may run to remove a repeated
+    /// ending, like the OCR doubling "книгамиами"; 0 runs them once.
+    pub restrip: usize,
+    /// The fewest chars a rule may leave of a word; rules that would leave fewer remove
+    /// nothing. 0 lets them shorten words down to their RV region.
+    pub min_stem_chars: usize,
+    /// Stems the stems of the rules again until they no longer change, so that
+    /// `stem(stem(w)) == stem(w)`, see `StemmerBuilder::idempotent`; `None` does as
+    /// `algorithm` does.
+    pub idempotent: Option<bool>,
+    pub compounds: Compounds,
+    pub mode: Mode,
+    pub language: Language,
+    /// The version of the built-in rules, and of the normalization options left `None`;
+    /// `rules` replaces the rules whatever the version.
+    pub algorithm: Algorithm,
+    /// Removed from the start of normalized words, the longest that matches, before the
+    /// RV region is found; exceptions and protected words are looked up without them.
+    pub prefixes: Vec<String>,
+    /// Conflates the stems of feminitives with the stems they are formed from, see
+    /// `feminitive::conflate`.
+    pub feminitives: bool,
+    /// Removes diminutive suffixes from stems, see `diminutive::strip`; with a
+    /// `frequency_guard`, only where the guard allows the shorter stem.
+    pub diminutives: bool,
+    /// Normalized forms whose lemma is returned instead of a stem, before the exceptions
+    /// are looked up.
+    pub lemmas: Option<Arc<LemmaDictionary>>,
+}
+
+impl StemmerOptions {
+    /// `fold_unicode`, or what `algorithm` does when unset.
+    pub fn folds_unicode(&self) -> bool {
+        self.fold_unicode
+            .unwrap_or_else(|| self.algorithm.folds_unicode())
+    }
+
+    /// The opposite of `keep_invisible`, or what `algorithm` does when unset.
+    pub fn strips_invisible(&self) -> bool {
+        self.keep_invisible
+            .map_or_else(|| self.algorithm.strips_invisible(), |keep| !keep)
+    }
+
+    /// `idempotent`, or what `algorithm` does when unset.
+    pub fn is_idempotent(&self) -> bool {
+        self.idempotent
+            .unwrap_or_else(|| self.algorithm.idempotent())
+    }
+
+    // what `ukstemmer_search_preprocess` does with words for these options
+    pub(crate) fn normalization(&self) -> Normalization {
+        Normalization {
+            strip_apostrophes: self.apostrophes == Apostrophes::Strip,
+            fold_unicode: self.folds_unicode(),
+            strip_invisible: false,
+            fold_russian: self.language == Language::Mixed,
+        }
+    }
+}
+
+// the cache of `Stemmer::stem`, shared by clones, which stem the same
+#[derive(Clone, Debug)]
+enum BuiltinCache {
+    Lru(Arc<StemCache>),
+    Sharded(Arc<ShardedCache>),
+}
+
+impl BuiltinCache {
+    fn get_or_insert_normalized<F: FnOnce() -> String>(
+        &self,
+        word: &str,
+        key: &str,
+        stem: F,
+    ) -> String {
+        match self {
+            BuiltinCache::Lru(cache) => cache.get_or_insert_normalized(word, key, stem),
+            BuiltinCache::Sharded(cache) => cache.get_or_insert_normalized(word, key, stem),
+        }
+    }
+
+    fn stats(&self) -> CacheStats {
+        match self {
+            BuiltinCache::Lru(cache) => cache.stats(),
+            BuiltinCache::Sharded(cache) => cache.stats(),
+        }
+    }
+}
+
+/// A configured stemmer. `Stemmer::default()` behaves exactly like `stem_word`.
+#[derive(Clone, Debug, Default)]
+pub struct Stemmer {
+    options: StemmerOptions,
+    cache: Option<BuiltinCache>,
+    interner: Option<Arc<StemInterner>>,
+    counters: Option<Arc<Counters>>,
+    // the rules of a newer `Algorithm`, without the Russian endings of `Language::Mixed`
+    // and with only the groups `Mode::Light` runs, when they differ from the configured ones
+    own_rules: Option<Arc<RuleSet>>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct StemmerBuilder {
+    options: StemmerOptions,
+    cache_entries: usize,
+    sharded_cache: Option<Arc<ShardedCache>>,
+    intern: bool,
+    stats: bool,
+}
+
+impl Stemmer {
+    pub fn new() -> Self {
+        Stemmer::default()
+    }
+
+    pub fn builder() -> StemmerBuilder {
+        StemmerBuilder::default()
+    }
+
+    /// A stemmer with `options` and no cache.
+    pub fn from_options(options: StemmerOptions) -> Self {
+        StemmerBuilder {
+            options,
+            ..StemmerBuilder::default()
+        }
+        .build()
+    }
+
+    /// A builder starting from this stemmer's options.
+    pub fn to_builder(&self) -> StemmerBuilder {
+        StemmerBuilder {
+            options: self.options.clone(),
+            cache_entries: self.cache().map_or(0, |cache| cache.max_entries()),
+            sharded_cache: match &self.cache {
+                Some(BuiltinCache::Sharded(cache)) => Some(Arc::clone(cache)),
+                _ => None,
+            },
+            intern: self.interner.is_some(),
+            stats: self.counters.is_some(),
+        }
+    }
+
+    pub fn options(&self) -> &StemmerOptions {
+        &self.options
+    }
+
+    /// Identifies the rules and options, see `fingerprint::Fingerprint`.
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::of_version(
+            self.options.algorithm.rules_version(),
+            &format!("{:?}", self.options),
+        )
+    }
+
+    pub fn normalize(&self, word: &str) -> String {
+        self.normalize_with(word, |_| {})
+    }
+
+    // `normalize`, passing the result of every step to `step`
+    pub(crate) fn normalize_with(&self, word: &str, mut step: impl FnMut(&str)) -> String {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("normalize").entered();
+        let cyrillic;
+        let word = if self.transliterates(word) {
+            cyrillic = translit::to_cyrillic(word);
+            step(&cyrillic);
+            cyrillic.as_str()
+        } else {
+            word
+        };
+        if let Some(chain) = &self.options.normalizers {
+            let word = chain.apply_with(word, &mut step);
+            return match self.strip_prefix(&word) {
+                Some(rest) => {
+                    let rest = rest.to_string();
+                    step(&rest);
+                    rest
+                }
+                None => word,
+            };
+        }
+        let word = if self.options.strips_invisible() {
+            strip_invisible(word)
+        } else {
+            Cow::Borrowed(word)
+        };
+        if let Cow::Owned(scrubbed) = &word {
+            step(scrubbed);
+        }
+        let mut word = homoglyph::repair(&word, self.options.homoglyphs).to_lowercase();
+        step(&word);
+        for pack in &self.options.packs {
+            word = pack.apply(&word);
+            step(&word);
+        }
+        let mut word = ukstemmer_search_preprocess(word, self.options.normalization());
+        step(&word);
+        if self.options.fold_ge && word.contains('ґ') {
+            word = word.replace('ґ', "г");
+            step(&word);
+        }
+        if let Some(Cow::Owned(folded)) =
+            self.options.ocr_confusions.as_ref().map(|c| c.apply(&word))
+        {
+            word = folded;
+            step(&word);
+        }
+        match self.strip_prefix(&word) {
+            Some(rest) => {
+                let rest = rest.to_string();
+                step(&rest);
+                rest
+            }
+            None => word,
+        }
+    }
+
+    // what is left of `word` after the longest of `StemmerOptions::prefixes`, if it still
+    // has a vowel and `MIN_PREFIXED_CHARS` chars
+    fn strip_prefix<'w>(&self, word: &'w str) -> Option<&'w str> {
+        self.options
+            .prefixes
+            .iter()
+            .filter_map(|prefix| word.strip_prefix(prefix.as_str()))
+            .filter(|rest| {
+                rest.chars().count() >= MIN_PREFIXED_CHARS && suffix::rv_start(rest).is_some()
+            })
+            .min_by_key(|rest| rest.len())
+    }
+
+    /// Stems `word`, through the built-in cache if `StemmerBuilder::cache` enabled it.
+    pub fn stem(&self, word: &str) -> String {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("stem", word).entered();
+        let stem = if self.passes_through(word) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("passed through");
+            word.to_string()
+        } else {
+            match &self.cache {
+                Some(cache) => self.stem_through(cache, word),
+                None => self.stem_uncached(word),
+            }
+        };
+        self.record_word(word, &stem);
+        stem
+    }
+
+    // `stem_uncached` through the built-in cache, keyed on the normalized form as in
+    // `stem_cached`
+    fn stem_through(&self, cache: &BuiltinCache, word: &str) -> String {
+        if self.splits(word) {
+            let stems: Vec<String> = word
+                .split('-')
+                .map(|part| self.stem_through(cache, part))
+                .collect();
+            return stems.join("-");
+        }
+        let key = self.normalize(word);
+        if let Some(acronym) = self.acronym(word, &key) {
+            return self.recase(word, acronym.into_stem());
+        }
+        let stem = cache.get_or_insert_normalized(word, &key, || self.stem_recorded(key.clone()));
+        self.recase(word, stem)
+    }
+
+    fn record_word(&self, word: &str, stem: &str) {
+        if let Some(counters) = &self.counters {
+            counters.record_word(word, stem);
+        }
+    }
+
+    /// What the stemmer did with the words given to `stem` and the methods built on it,
+    /// such as `stem_into` and the `Analyzer`, if `StemmerBuilder::stats` enabled the
+    /// counters. Clones share one set of counters.
+    pub fn stats(&self) -> Option<StemStats> {
+        let counters = self.counters.as_ref()?;
+        Some(counters.snapshot(self.cache_stats()))
+    }
+
+    /// Writes the stem of `word` to `out` in place of its contents, so that a loop can
+    /// reuse one buffer. Normalized words (lowercase, no apostrophes) then allocate nothing
+    /// unless options such as exceptions, packs or the OCR repairs apply.
+    pub fn stem_into(&self, word: &str, out: &mut String) {
+        out.clear();
+        match self.cut_stem(word) {
+            Some(stem) => out.push_str(stem),
+            None => out.push_str(&self.stem(word)),
+        }
+    }
+
+    /// `stem_bytes` writing the stem to `out` in place of its contents, like `stem_into`;
+    /// `out` is left empty on errors.
+    pub fn stem_bytes_into(&self, word: &[u8], out: &mut Vec<u8>) -> Result<(), StemError> {
+        out.clear();
+        let text = std::str::from_utf8(word).map_err(|e| StemError::InvalidUtf8 {
+            valid_up_to: e.valid_up_to(),
+        })?;
+        let checked =
+            !text.is_empty() && text.trim() == text && !text.contains(|c: char| c.is_control());
+        match self.cut_stem(text).filter(|_| checked) {
+            Some(stem) => out.extend_from_slice(stem.as_bytes()),
+            None => out.extend_from_slice(self.try_stem(word)?.as_bytes()),
+        }
+        Ok(())
+    }
+
+    // the stem of `word` when it is a normalized word the options stem by the rules alone,
+    // which only cut suffixes
+    fn cut_stem<'w>(&self, word: &'w str) -> Option<&'w str> {
+        let options = &self.options;
+        let rules_alone = options.packs.is_empty()
+            && options.normalizers.is_none()
+            && !options.fold_ge
+            && options.prefixes.is_empty()
+            && options.ocr_confusions.is_none()
+            && !options.ocr_tolerant
+            && options.lemmas.is_none()
+            && options.exceptions.is_none()
+            && !self.has_dictionary()
+            && options.protected.is_none()
+            && options.disambiguation.is_none()
+            && options.max_stem_bytes.is_none()
+            && !options.is_idempotent()
+            && !(options.preserve_case || options.feminitives || options.diminutives)
+            && self.counters.is_none()
+            && !self.splits(word)
+            && !self.passes_through(word)
+            && !self.transliterates(word);
+        if !rules_alone
+            || !is_normalized(word, options.normalization())
+            || (options.strips_invisible() && word.chars().any(is_invisible))
+            || !matches!(
+                homoglyph::repair(word, options.homoglyphs),
+                Cow::Borrowed(_)
+            )
+            || suffix::rv_start(word).is_none()
+        {
+            return None;
+        }
+        let cut = crate::stem_cut(
+            word,
+            self.rule_set(),
+            &mut None,
+            options.frequency_guard.as_ref(),
+            options.restrip,
+            options.min_stem_chars,
+            None,
+        )
+        .ok()?;
+        match cut.of(word) {
+            Cow::Borrowed(stem) => Some(stem),
+            Cow::Owned(_) => None,
+        }
+    }
+
+    // `stem(word)` given `folded`, its range of the `preprocess::fold_text` of the text, so
+    // that words whose normalization is just the fold skip it
+    pub(crate) fn stem_prefolded(&self, word: &str, folded: &str) -> String {
+        if self.passes_through(word) {
+            self.record_word(word, word);
+            return word.to_string();
+        }
+        let options = &self.options;
+        let plain = options.packs.is_empty()
+            && options.normalizers.is_none()
+            && !options.fold_ge
+            && options.prefixes.is_empty()
+            && options.ocr_confusions.is_none()
+            && options.language == Language::Mixed
+            && is_normalized(folded, options.normalization())
+            && !(options.strips_invisible() && folded.chars().any(is_invisible))
+            && matches!(
+                homoglyph::repair(word, options.homoglyphs),
+                Cow::Borrowed(_)
+            );
+        if !plain
+            || options.preserve_case
+            || self.splits(word)
+            || self.transliterates(word)
+            || (options.acronyms == Acronyms::Keep && is_acronym(word))
+        {
+            return self.stem(word);
+        }
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("stem", word).entered();
+        let stem = || self.stem_recorded(folded.to_string());
+        let stem = match &self.cache {
+            Some(cache) => cache.get_or_insert_normalized(word, folded, stem),
+            None => stem(),
+        };
+        self.record_word(word, &stem);
+        stem
+    }
+
+    fn stem_uncached(&self, word: &str) -> String {
+        if self.splits(word) {
+            let stems: Vec<String> = word
+                .split('-')
+                .map(|part| self.stem_uncached(part))
+                .collect();
+            return stems.join("-");
+        }
+        let normalized = self.normalize(word);
+        let stem = match self.acronym(word, &normalized) {
+            Some(acronym) => acronym.into_stem(),
+            None => self.stem_recorded(normalized),
+        };
+        self.recase(word, stem)
+    }
+
+    // `stem_normalized`, counting the rules that fired if `StemmerBuilder::stats` is on
+    fn stem_recorded(&self, word: String) -> String {
+        let counters = match &self.counters {
+            Some(counters) => counters,
+            None => return self.stem_normalized(word, &mut None),
+        };
+        let mut fired = Some(Vec::new());
+        let stem = self.stem_normalized(word, &mut fired);
+        counters.record_rules(&fired.unwrap_or_default());
+        stem
+    }
+
+    // the outcome of `word`, normalized as `normalized`, if it is an acronym
+    // `Acronyms::Keep` leaves unstemmed
+    fn acronym(&self, word: &str, normalized: &str) -> Option<StemOutcome> {
+        (self.options.acronyms == Acronyms::Keep && is_acronym(word))
+            .then(|| StemOutcome::Acronym(self.fit(normalized.to_string())))
+    }
+
+    // whether `StemmerBuilder::dictionary` set a compiled dictionary
+    fn has_dictionary(&self) -> bool {
+        #[cfg(feature = "fst")]
+        return self.options.dictionary.is_some();
+        #[cfg(not(feature = "fst"))]
+        false
+    }
+
+    // whether `word` is returned unchanged, see `Foreign::PassThrough`
+    fn passes_through(&self, word: &str) -> bool {
+        match self.options.foreign {
+            Foreign::Stem => false,
+            Foreign::PassThrough => is_foreign(word),
+            Foreign::Transliterate => is_foreign(word) && !is_latin_word(word),
+        }
+    }
+
+    // whether `word` is converted to Cyrillic, see `Foreign::Transliterate`
+    pub(crate) fn transliterates(&self, word: &str) -> bool {
+        self.options.foreign == Foreign::Transliterate && is_latin_word(word)
+    }
+
+    // whether `word` is stemmed part by part, see `Compounds::Parts`
+    fn splits(&self, word: &str) -> bool {
+        self.options.compounds == Compounds::Parts && word.contains('-')
+    }
+
+    fn recase(&self, word: &str, stem: String) -> String {
+        if self.options.preserve_case {
+            restore_case(word, &stem)
+        } else {
+            stem
+        }
+    }
+
+    /// The built-in cache, for its `stats`.
+    pub fn cache(&self) -> Option<&StemCache> {
+        match &self.cache {
+            Some(BuiltinCache::Lru(cache)) => Some(cache),
+            _ => None,
+        }
+    }
+
+    /// The cache of `StemmerBuilder::sharded_cache`.
+    pub fn sharded_cache(&self) -> Option<&ShardedCache> {
+        match &self.cache {
+            Some(BuiltinCache::Sharded(cache)) => Some(cache),
+            _ => None,
+        }
+    }
+
+    /// The stats of whichever cache `stem` goes through.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(BuiltinCache::stats)
+    }
+
+    /// Stems `word` as an `Arc<str>`. With `StemmerBuilder::intern_stems`, all the words
+    /// with a stem get the same allocation, and normalized words allocate nothing once
+    /// their stem is known.
+    pub fn stem_interned(&self, word: &str) -> Arc<str> {
+        match (&self.interner, self.cut_stem(word)) {
+            (Some(interner), Some(stem)) => interner.intern(stem),
+            (Some(interner), None) => interner.intern(&self.stem(word)),
+            (None, _) => Arc::from(self.stem(word)),
+        }
+    }
+
+    /// The interner of `StemmerBuilder::intern_stems`, e.g. for its `len`.
+    pub fn interner(&self) -> Option<&StemInterner> {
+        self.interner.as_deref()
+    }
+
+    /// Stems `word`, recording its RV, the rules applied and the forms they left.
+    pub fn stem_explained(&self, word: &str) -> StemTrace {
+        if self.passes_through(word) {
+            let outcome = StemOutcome::Foreign(word.to_string());
+            return StemTrace::new(word.to_string(), Vec::new(), outcome);
+        }
+        let normalized = self.normalize(word);
+        let mut hits = Some(Vec::new());
+        let outcome = match self.acronym(word, &normalized) {
+            Some(acronym) => acronym,
+            None => self.outcome_normalized(normalized.clone(), &mut hits, true),
+        };
+        let normalized = if self.options.ocr_tolerant {
+            ocr::repair_digits(&normalized)
+        } else {
+            normalized
+        };
+        StemTrace::new(normalized, hits.unwrap_or_default(), outcome)
+    }
+
+    /// Stems `word`, telling whether the rules ran, the word was listed, or it had no
+    /// vowel to stem.
+    pub fn stem_outcome(&self, word: &str) -> StemOutcome {
+        if self.passes_through(word) {
+            return StemOutcome::Foreign(word.to_string());
+        }
+        let normalized = self.normalize(word);
+        match self.acronym(word, &normalized) {
+            Some(acronym) => acronym,
+            None => self.outcome_normalized(normalized, &mut None, true),
+        }
+    }
+
+    /// Stems `word`, keeping track of the position in `word` every stem character comes
+    /// from through all normalization steps.
+    pub fn stem_mapped(&self, word: &str) -> MappedStem {
+        let mut tracked = Tracked::new(word, 0);
+        self.stem_tracked(&mut tracked);
+        tracked.finish()
+    }
+
+    pub(crate) fn stem_tracked(&self, tracked: &mut Tracked) {
+        let word = tracked.text().to_string();
+        if self.passes_through(&word) {
+            return;
+        }
+        let normalized = self.normalize_with(&word, |step| tracked.rewrite(step));
+        let stem = match self.acronym(&word, &normalized) {
+            Some(acronym) => acronym.into_stem(),
+            None => self.stem_normalized(normalized, &mut None),
+        };
+        tracked.rewrite(&stem);
+    }
+
+    /// Stems `word` and reports the names of the rule groups that fired, in order
+    /// (see the `rules` module).
+    pub fn stem_with_rules(&self, word: &str) -> (String, Vec<&'static str>) {
+        let (stem, trace) = self.stem_with_trace(word);
+        (stem, trace.into_iter().map(|hit| hit.rule).collect())
+    }
+
+    /// Like `stem_with_rules`, also reporting the suffix each rule group removed.
+    pub fn stem_with_trace(&self, word: &str) -> (String, Vec<RuleHit>) {
+        if self.passes_through(word) {
+            return (word.to_string(), Vec::new());
+        }
+        let normalized = self.normalize(word);
+        if let Some(acronym) = self.acronym(word, &normalized) {
+            return (acronym.into_stem(), Vec::new());
+        }
+        let mut trace = Some(Vec::new());
+        let stem = self.stem_normalized(normalized, &mut trace);
+        (stem, trace.unwrap_or_default())
+    }
+
+    /// Stems `word` and guesses its part of speech from the inflectional rule group that
+    /// removed its ending, see `PosGuess`.
+    pub fn stem_with_pos(&self, word: &str) -> (String, PosGuess) {
+        let (stem, trace) = self.stem_with_trace(word);
+        (stem, PosGuess::from_trace(&trace))
+    }
+
+    /// Stems `word` through `cache`, keyed on the normalized form so that "Києва",
+    /// "києва" and "КИЄВА" share one entry. A cache must only be used with stemmers of
+    /// the same fingerprint.
+    pub fn stem_cached(&self, word: &str, cache: &StemCache) -> String {
+        if self.passes_through(word) {
+            return word.to_string();
+        }
+        if self.splits(word) {
+            let stems: Vec<String> = word
+                .split('-')
+                .map(|part| self.stem_cached(part, cache))
+                .collect();
+            return stems.join("-");
+        }
+        let key = self.normalize(word);
+        if let Some(acronym) = self.acronym(word, &key) {
+            return self.recase(word, acronym.into_stem());
+        }
+        let stem = cache
+            .get_or_insert_normalized(word, &key, || self.stem_normalized(key.clone(), &mut None));
+        self.recase(word, stem)
+    }
+
+    /// Lazily stems every word of `words`; the iterator keeps the size hint of the input.
+    pub fn stem_all<I>(&self, words: I) -> StemAll<'_, I::IntoIter>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        StemAll {
+            stemmer: self,
+            words: words.into_iter(),
+        }
+    }
+
+    /// Stems every word of `words`, in order.
+    pub fn stem_words<I>(&self, words: I) -> Vec<String>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.stem_all(words).collect()
+    }
+
+    /// Like `stem_words`, spreading the words over the rayon thread pool. The stems are
+    /// still in input order.
+    #[cfg(feature = "rayon")]
+    pub fn par_stem_words<I>(&self, words: I) -> Vec<String>
+    where
+        I: rayon::iter::IntoParallelIterator,
+        I::Item: AsRef<str>,
+    {
+        use rayon::iter::ParallelIterator;
+
+        words
+            .into_par_iter()
+            .map(|word| self.stem(word.as_ref()))
+            .collect()
+    }
+
+    /// The distinct stems of `words`, in the order they first occur.
+    pub fn unique_stems<I>(&self, words: I) -> Vec<String>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut seen = HashSet::new();
+        self.stem_all(words)
+            .filter(|stem| seen.insert(stem.clone()))
+            .collect()
+    }
+
+    /// The distinct stems of `words` in Ukrainian alphabetical order, see `collation`.
+    pub fn unique_stems_sorted<I>(&self, words: I) -> Vec<String>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut stems = self.unique_stems(words);
+        collation::sort(&mut stems);
+        stems
+    }
+
+    /// Groups the distinct surface forms of `words` by stem, forms in first-seen order.
+    pub fn conflate<'w, I>(&self, words: I) -> HashMap<String, Vec<String>>
+    where
+        I: IntoIterator<Item = &'w str>,
+    {
+        let mut classes: HashMap<String, Vec<String>> = HashMap::new();
+        for word in words {
+            let forms = classes.entry(self.stem(word)).or_default();
+            if !forms.iter().any(|form| form == word) {
+                forms.push(word.to_string());
+            }
+        }
+        classes
+    }
+
+    /// The stem of `word` as a key for hash maps and sets, for grouping forms without
+    /// comparing strings by hand.
+    pub fn stem_key(&self, word: &str) -> StemKey {
+        StemKey(self.stem(word))
+    }
+
+    /// Whether `a` and `b` get the same stem, e.g. "народами" and "Народи".
+    pub fn same_stem(&self, a: &str, b: &str) -> bool {
+        a == b || self.stem(a) == self.stem(b)
+    }
+
+    /// Stems `word` given as bytes, failing instead of producing a meaningless key for
+    /// input that is not UTF-8, contains control characters or normalizes to nothing.
+    pub fn try_stem(&self, word: &[u8]) -> Result<String, StemError> {
+        let word = std::str::from_utf8(word).map_err(|e| StemError::InvalidUtf8 {
+            valid_up_to: e.valid_up_to(),
+        })?;
+        if let Some(c) = word.chars().find(|c| c.is_control()) {
+            return Err(StemError::ControlCharacter(c));
+        }
+        if self.passes_through(word.trim()) {
+            return Ok(word.trim().to_string());
+        }
+        if self.splits(word.trim()) {
+            let stems = word
+                .trim()
+                .split('-')
+                .map(|part| match part {
+                    "" => Ok(String::new()),
+                    part => self.try_stem(part.as_bytes()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(stems.join("-"));
+        }
+        let normalized = self.normalize(word.trim());
+        if normalized.is_empty() {
+            return Err(StemError::Empty);
+        }
+        if let Some(acronym) = self.acronym(word.trim(), &normalized) {
+            return Ok(self.recase(word, acronym.into_stem()));
+        }
+        self.try_stem_normalized(normalized, &mut None)
+            .map(|stem| self.recase(word, stem))
+    }
+
+    /// `try_stem` returning the UTF-8 bytes of the stem, for tokens read as bytes, e.g.
+    /// from memory-mapped index segments, that should not go through `String` first.
+    pub fn stem_bytes(&self, word: &[u8]) -> Result<Vec<u8>, StemError> {
+        self.try_stem(word).map(String::into_bytes)
+    }
+
+    /// Like `stem_all`, with a `Result` per item so one bad input doesn't abort a batch;
+    /// errors carry the index of their item.
+    pub fn try_stem_all<I>(&self, words: I) -> TryStemAll<'_, I::IntoIter>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        TryStemAll {
+            stemmer: self,
+            words: words.into_iter().enumerate(),
+        }
+    }
+
+    fn stem_normalized(&self, word: String, fired: &mut Option<Vec<RuleHit>>) -> String {
+        self.outcome_normalized(word, fired, true).into_stem()
+    }
+
+    fn outcome_normalized(
+        &self,
+        word: String,
+        fired: &mut Option<Vec<RuleHit>>,
+        expand: bool,
+    ) -> StemOutcome {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(normalized = word.as_str());
+        let outcome = match self.shortcut(word, expand) {
+            Ok(outcome) => outcome,
+            Err(word) => {
+                let outcome = StemOutcome::Stemmed(self.stem_by_rules(word, fired));
+                self.fixed_point(outcome, fired, expand)
+            }
+        };
+        // the stems of the words of an expansion are only fitted together
+        let outcome = if expand {
+            outcome.map(|stem| self.fit(stem))
+        } else {
+            outcome
+        };
+        #[cfg(feature = "tracing")]
+        tracing::debug!(?outcome);
+        outcome
+    }
+
+    // the folded stem the rules leave of a normalized word with a vowel
+    fn stem_by_rules(&self, word: String, fired: &mut Option<Vec<RuleHit>>) -> String {
+        let inflection = self.disambiguate(&word);
+        let stem = stem_rules(
+            word,
+            self.rule_set(),
+            fired,
+            self.options.frequency_guard.as_ref(),
+            self.options.restrip,
+            self.options.min_stem_chars,
+            inflection,
+        );
+        self.fold(stem)
+    }
+
+    // `outcome` stemmed again until it no longer changes if `StemmerBuilder::idempotent` is
+    // on; the stem of a pass the shortcuts answer, such as an exception, is final
+    fn fixed_point(
+        &self,
+        mut outcome: StemOutcome,
+        fired: &mut Option<Vec<RuleHit>>,
+        expand: bool,
+    ) -> StemOutcome {
+        let passes = if self.options.is_idempotent() {
+            MAX_IDEMPOTENT_PASSES
+        } else {
+            1
+        };
+        for _ in 1..passes {
+            let stem = match &outcome {
+                StemOutcome::Stemmed(stem) => self.normalize(stem),
+                _ => break,
+            };
+            let again = match self.shortcut(stem, expand) {
+                Ok(listed) => listed,
+                Err(stem) => StemOutcome::Stemmed(self.stem_by_rules(stem, fired)),
+            };
+            if again.stem() == outcome.stem() {
+                break;
+            }
+            outcome = again;
+        }
+        outcome
+    }
+
+    fn try_stem_normalized(
+        &self,
+        word: String,
+        fired: &mut Option<Vec<RuleHit>>,
+    ) -> Result<String, StemError> {
+        match self.shortcut(word, true) {
+            Ok(outcome) => Ok(self.fit(outcome.into_stem())),
+            Err(word) => {
+                let inflection = self.disambiguate(&word);
+                try_stem_rules(
+                    word,
+                    self.rule_set(),
+                    fired,
+                    self.options.frequency_guard.as_ref(),
+                    self.options.restrip,
+                    self.options.min_stem_chars,
+                    inflection,
+                )
+                .map(|stem| {
+                    let outcome = StemOutcome::Stemmed(self.fold(stem));
+                    self.fit(self.fixed_point(outcome, fired, true).into_stem())
+                })
+            }
+        }
+    }
+
+    // The outcome of a normalized word that doesn't go through the rules, or the word to
+    // give the rules. Abbreviations are only expanded when `expand`, so that expansions
+    // aren't expanded again.
+    fn shortcut(&self, word: String, expand: bool) -> Result<StemOutcome, String> {
+        if let Some(lemma) = self.options.lemmas.as_ref().and_then(|l| l.get(&word)) {
+            return Ok(StemOutcome::Listed(lemma.to_string()));
+        }
+        if let Some(stem) = self.options.exceptions.as_ref().and_then(|e| e.get(&word)) {
+            return Ok(StemOutcome::Listed(stem.to_string()));
+        }
+        #[cfg(feature = "fst")]
+        if let Some(stem) = self.options.dictionary.as_ref().and_then(|d| d.get(&word)) {
+            return Ok(StemOutcome::Listed(stem));
+        }
+        if self.is_protected(&word) {
+            return Ok(StemOutcome::Listed(word));
+        }
+        let word = if self.options.ocr_tolerant {
+            ocr::repair_digits(&word)
+        } else {
+            word
+        };
+        if suffix::rv_start(&word).is_some() {
+            return Err(word);
+        }
+        let expansion = match self.options.vowelless {
+            Vowelless::Expand if expand => self.options.abbreviations.as_ref(),
+            _ => None,
+        }
+        .and_then(|abbreviations| abbreviations.get(&word));
+        Ok(match expansion {
+            Some(expansion) => StemOutcome::Abbreviation(
+                expansion
+                    .split_whitespace()
+                    .map(|part| {
+                        self.outcome_normalized(self.normalize(part), &mut None, false)
+                            .into_stem()
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            None => StemOutcome::Vowelless(self.fold(word)),
+        })
+    }
+
+    // The alternative of step 1 whose stem `disambiguation` counts more often than the
+    // stem of the pipeline order, the most often counted one when several are; `None`
+    // keeps the pipeline order.
+    fn disambiguate(&self, word: &str) -> Option<Inflection> {
+        let frequencies = self.options.disambiguation.as_ref()?;
+        let count = |inflection| {
+            let stem = stem_rules(
+                word.to_string(),
+                self.rule_set(),
+                &mut None,
+                self.options.frequency_guard.as_ref(),
+                self.options.restrip,
+                self.options.min_stem_chars,
+                inflection,
+            );
+            frequencies.count(&self.fold(stem))
+        };
+        let mut best = (count(None), None);
+        for inflection in Inflection::ALL.iter().copied() {
+            let count = count(Some(inflection));
+            if count > best.0 {
+                best = (count, Some(inflection));
+            }
+        }
+        best.1
+    }
+
+    fn strip_diminutive(&self, stem: String) -> String {
+        let root = diminutive::strip(&stem);
+        let guard = self.options.frequency_guard.as_ref();
+        if root.len() < stem.len() && guard.is_none_or(|guard| guard.allows(&root)) {
+            root.into_owned()
+        } else {
+            stem
+        }
+    }
+
+    fn fold(&self, stem: String) -> String {
+        let stem = if self.options.diminutives {
+            self.strip_diminutive(stem)
+        } else {
+            stem
+        };
+        let stem = if self.options.feminitives {
+            feminitive::conflate(&stem).into_owned()
+        } else {
+            stem
+        };
+        if self.options.ocr_tolerant {
+            ocr::fold(&stem)
+        } else {
+            stem
+        }
+    }
+
+    fn fit(&self, stem: String) -> String {
+        match self.options.max_stem_bytes {
+            Some(max_bytes) => fit_bytes(stem, max_bytes),
+            None => stem,
+        }
+    }
+
+    /// The suffix rules the stemmer runs: `StemmerBuilder::rules`, or the built-in rules of
+    /// its `Algorithm`, without the groups its `Mode` and `Language` leave out.
+    pub fn rule_set(&self) -> &RuleSet {
+        self.own_rules
+            .as_deref()
+            .or(self.options.rules.as_deref())
+            .unwrap_or(&BUILTIN_RULES)
+    }
+
+    fn is_protected(&self, word: &str) -> bool {
+        self.options
+            .protected
+            .as_ref()
+            .is_some_and(|p| p.contains(word))
+    }
+}
+
+/// Iterator returned by `Stemmer::stem_all` and `stem_all`.
+#[derive(Clone, Debug)]
+pub struct StemAll<'s, I> {
+    stemmer: &'s Stemmer,
+    words: I,
+}
+
+impl<I> Iterator for StemAll<'_, I>
+where
+    I: Iterator,
+    I::Item: AsRef<str>,
+{
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.words.next().map(|w| self.stemmer.stem(w.as_ref()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.words.size_hint()
+    }
+}
+
+impl<I> DoubleEndedIterator for StemAll<'_, I>
+where
+    I: DoubleEndedIterator,
+    I::Item: AsRef<str>,
+{
+    fn next_back(&mut self) -> Option<String> {
+        self.words
+            .next_back()
+            .map(|w| self.stemmer.stem(w.as_ref()))
+    }
+}
+
+impl<I> ExactSizeIterator for StemAll<'_, I>
+where
+    I: ExactSizeIterator,
+    I::Item: AsRef<str>,
+{
+}
+
+impl<I> FusedIterator for StemAll<'_, I>
+where
+    I: FusedIterator,
+    I::Item: AsRef<str>,
+{
+}
+
+/// The stem of a word from `Stemmer::stem_key`: words with equal keys conflate. Keys of
+/// stemmers with different fingerprints don't compare meaningfully.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StemKey(String);
+
+impl StemKey {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for StemKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Iterator returned by `Stemmer::try_stem_all` and `try_stem_all`.
+#[derive(Clone, Debug)]
+pub struct TryStemAll<'s, I> {
+    stemmer: &'s Stemmer,
+    words: std::iter::Enumerate<I>,
+}
+
+impl<I> Iterator for TryStemAll<'_, I>
+where
+    I: Iterator,
+    I::Item: AsRef<[u8]>,
+{
+    type Item = Result<String, ItemError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, word) = self.words.next()?;
+        Some(
+            self.stemmer
+                .try_stem(word.as_ref())
+                .map_err(|error| ItemError { index, error }),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.words.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for TryStemAll<'_, I>
+where
+    I: ExactSizeIterator,
+    I::Item: AsRef<[u8]>,
+{
+}
+
+impl StemmerBuilder {
+    pub fn pack(mut self, pack: LanguagePack) -> Self {
+        self.options.packs.push(pack);
+        self
+    }
+
+    /// Normalizes words with `chain` instead of the steps the other options configure,
+    /// e.g. `NormalizerChain::of(&options)` with a step of its own inserted.
+    pub fn normalizers(mut self, chain: NormalizerChain) -> Self {
+        self.options.normalizers = Some(Arc::new(chain));
+        self
+    }
+
+    pub fn ocr_tolerant(mut self, enabled: bool) -> Self {
+        self.options.ocr_tolerant = enabled;
+        self
+    }
+
+    /// Folds the letters of `confusions` before stemming, e.g. `Confusions::default()` for
+    /// "ї", "й" and "ь" lost in scans.
+    pub fn ocr_confusions(mut self, confusions: Confusions) -> Self {
+        self.options.ocr_confusions = Some(confusions);
+        self
+    }
+
+    pub fn homoglyph_repair(mut self, mode: HomoglyphRepair) -> Self {
+        self.options.homoglyphs = mode;
+        self
+    }
+
+    /// Lemmatizes the forms of `lemmas`, e.g. a `LemmaDictionary::load` of a dictionary
+    /// compiled from VESUM, and stems the other words with the rules. Terms then mix lemmas
+    /// and stems, so an index must be built and queried with the same dictionary.
+    pub fn lemmatize(mut self, lemmas: LemmaDictionary) -> Self {
+        self.options.lemmas = Some(Arc::new(lemmas));
+        self
+    }
+
+    /// Stems for forms the rules get wrong; a borrowed `Dictionary::parse` of
+    /// `include_str!` data keeps the entries in read-only memory.
+    pub fn exceptions(mut self, exceptions: Dictionary<'static>) -> Self {
+        self.options.exceptions = Some(Arc::new(exceptions));
+        self
+    }
+
+    /// Stems for the forms of a `CompiledDictionary`, e.g. one `ukstem build-dict` wrote,
+    /// looked up after the exceptions: for exception or lemma lists too large for a
+    /// `Dictionary`, which the dictionary keeps memory-mapped rather than in memory.
+    #[cfg(feature = "fst")]
+    pub fn dictionary(mut self, dictionary: CompiledDictionary) -> Self {
+        self.options.dictionary = Some(Arc::new(dictionary));
+        self
+    }
+
+    /// Words the stemmer must leave alone, such as brand names.
+    pub fn protected(mut self, words: WordList<'static>) -> Self {
+        self.options.protected = Some(Arc::new(words));
+        self
+    }
+
+    /// Lets the derivational rule groups only produce stems counted at least `min_count`
+    /// times in `frequencies`, see `frequency::FrequencyGuard`.
+    pub fn frequency_guard(mut self, frequencies: FrequencyList, min_count: u64) -> Self {
+        self.options.frequency_guard = Some(FrequencyGuard {
+            frequencies: Arc::new(frequencies),
+            min_count,
+        });
+        self
+    }
+
+    /// Stems words with the alternative of step 1 (the perfective gerund, adjective, verb or
+    /// noun groups, see `core::Inflection`) whose stem `frequencies` counts most, rather
+    /// than with the first that matches: with "ліс" counted, "ліси" gets "ліс" instead
+    /// of "лі", its "си" taken for a reflexive ending. The pipeline order wins ties, and
+    /// words whose stems aren't counted keep its stem.
+    pub fn disambiguate(mut self, frequencies: FrequencyList) -> Self {
+        self.options.disambiguation = Some(Arc::new(frequencies));
+        self
+    }
+
+    /// Remembers the stems of the last `max_entries` distinct words, so repeated words
+    /// skip the rules; 0, the default, disables the cache. Entries are keyed on the
+    /// normalized form, so "Києва" and "КИЄВА" share one. An entry takes about 100 bytes
+    /// besides the word and its stem.
+    pub fn cache(mut self, max_entries: usize) -> Self {
+        self.cache_entries = max_entries;
+        self
+    }
+
+    /// Stems words through the shared `cache` instead of a cache of the stemmer's own, for
+    /// stemmers used by many threads at once; stemmers built with the same configuration
+    /// may share it. The cache keeps the stems of the first configuration built with it: a
+    /// stemmer built with other options, e.g. through `Stemmer::to_builder`, gets an empty
+    /// cache with the same bounds instead.
+    pub fn sharded_cache(mut self, cache: Arc<ShardedCache>) -> Self {
+        self.sharded_cache = Some(cache);
+        self
+    }
+
+    /// Counts the words stemmed, those returned unchanged and the rule groups that fired,
+    /// for `Stemmer::stats`; the rules are traced for every word stemmed, which slows
+    /// stemming a little.
+    pub fn stats(mut self, enabled: bool) -> Self {
+        self.stats = enabled;
+        self
+    }
+
+    /// Makes `Stemmer::stem_interned` share one `Arc<str>` per distinct stem, through an
+    /// interner shared by the clones of the stemmer; it keeps every stem it has seen.
+    pub fn intern_stems(mut self, enabled: bool) -> Self {
+        self.intern = enabled;
+        self
+    }
+
+    pub fn acronyms(mut self, policy: Acronyms) -> Self {
+        self.options.acronyms = policy;
+        self
+    }
+
+    pub fn vowelless(mut self, policy: Vowelless) -> Self {
+        self.options.vowelless = policy;
+        self
+    }
+
+    /// Stems with `rules` instead of the built-in suffix rules, e.g. a `RuleSet::from_toml`
+    /// file with adjusted suffix lists.
+    pub fn rules(mut self, rules: RuleSet) -> Self {
+        self.options.rules = Some(Arc::new(rules));
+        self
+    }
+
+    /// Expansions for `Vowelless::Expand`, e.g. "грн" → "гривня"; multi-word expansions
+    /// are stemmed word by word.
+    pub fn abbreviations(mut self, abbreviations: Dictionary<'static>) -> Self {
+        self.options.abbreviations = Some(Arc::new(abbreviations));
+        self
+    }
+
+    /// Runs the inflectional rule groups up to `max_passes` more times while they remove
+    /// the ending they removed before again, stripping endings doubled by OCR or
+    /// normalization errors. A pass removing another ending or leaving fewer than 3 chars
+    /// is undone; `stem_explained` shows every pass.
+    pub fn restrip(mut self, max_passes: usize) -> Self {
+        self.options.restrip = max_passes;
+        self
+    }
+
+    /// Keeps the rules from leaving stems shorter than `min_chars` chars, as "іде" →
+    /// "ід" or "осі" → "ос" would; a suffix that would leave fewer is not removed. 0, the
+    /// default, is no limit.
+    pub fn min_stem_chars(mut self, min_chars: usize) -> Self {
+        self.options.min_stem_chars = min_chars;
+        self
+    }
+
+    /// Keeps every stem within `max_bytes` bytes, see `fit_bytes`.
+    pub fn max_stem_bytes(mut self, max_bytes: usize) -> Self {
+        self.options.max_stem_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Gives stems the casing of their words, "Київського" → "Київськ", instead of
+    /// lowercasing them.
+    pub fn preserve_case(mut self, enabled: bool) -> Self {
+        self.options.preserve_case = enabled;
+        self
+    }
+
+    /// Removes the longest of `prefixes` that starts a word before stemming it, e.g.
+    /// `SUPERLATIVE_PREFIXES`. Off by default: ordinary words like "наймати" start with
+    /// the same letters.
+    pub fn strip_prefixes<I, S>(mut self, prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.prefixes = prefixes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Conflates diminutives with the words they are formed from, "котика" with "кота",
+    /// for informal text.
+    pub fn diminutives(mut self, enabled: bool) -> Self {
+        self.options.diminutives = enabled;
+        self
+    }
+
+    /// Gives feminitives the stem of the words they are formed from, so a search for
+    /// "авторка" also finds "автор".
+    pub fn feminitives(mut self, enabled: bool) -> Self {
+        self.options.feminitives = enabled;
+        self
+    }
+
+    pub fn foreign(mut self, mode: Foreign) -> Self {
+        self.options.foreign = mode;
+        self
+    }
+
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.options.mode = mode;
+        self
+    }
+
+    /// Pins the built-in rules to `algorithm`, e.g. the one an index was built with.
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.options.algorithm = algorithm;
+        self
+    }
+
+    pub fn language(mut self, language: Language) -> Self {
+        self.options.language = language;
+        self
+    }
+
+    /// Stems stems again until they reach a fixed point, for search engines that need
+    /// `stem(stem(w)) == stem(w)` so that stemmed queries meet stemmed index terms. The
+    /// rules alone don't guarantee it: "голови" → "голов" → "гол". The later passes remove
+    /// more than the first, so for about one word in twenty the stem is shorter, as
+    /// "відома" → "від". On by default from `Algorithm::V2`; `Algorithm::V1` keeps the
+    /// stems of `stem_word`.
+    pub fn idempotent(mut self, enabled: bool) -> Self {
+        self.options.idempotent = Some(enabled);
+        self
+    }
+
+    pub fn compounds(mut self, compounds: Compounds) -> Self {
+        self.options.compounds = compounds;
+        self
+    }
+
+    /// Keeps the soft hyphens and zero-width characters `Algorithm::V2` removes from words,
+    /// or removes them with `Algorithm::V1`, the default, which keeps them as the original
+    /// stemmer did.
+    pub fn keep_invisible(mut self, keep: bool) -> Self {
+        self.options.keep_invisible = Some(keep);
+        self
+    }
+
+    /// Composes words to NFC and takes "’", "ʼ" and "`" for apostrophes, as `Algorithm::V2`
+    /// does, so that "п’ять", "пʼять" and "п'ять" get the same stem; `Algorithm::V1`, the
+    /// default, only takes the ASCII "'" for one, as the original stemmer did.
+    pub fn fold_unicode(mut self, enabled: bool) -> Self {
+        self.options.fold_unicode = Some(enabled);
+        self
+    }
+
+    /// Stems words spelled with "ґ" like their spelling with "г", see
+    /// `StemmerOptions::fold_ge`.
+    pub fn fold_ge(mut self, enabled: bool) -> Self {
+        self.options.fold_ge = enabled;
+        self
+    }
+
+    pub fn apostrophes(mut self, apostrophes: Apostrophes) -> Self {
+        self.options.apostrophes = apostrophes;
+        self
+    }
+
+    pub fn build(self) -> Stemmer {
+        let cache = match self.cache_entries {
+            0 => None,
+            max_entries => Some(BuiltinCache::Lru(Arc::new(StemCache::with_max_entries(
+                max_entries,
+            )))),
+        };
+        let versioned = match self.options.algorithm {
+            Algorithm::V1 => None,
+            _ if self.options.rules.is_some() => None,
+            algorithm => Some(algorithm.rule_set()),
+        };
+        let rules = self
+            .options
+            .rules
+            .as_deref()
+            .or(versioned.as_ref())
+            .unwrap_or(&BUILTIN_RULES);
+        let own_rules = match (self.options.language, self.options.mode) {
+            (Language::Mixed, Mode::Aggressive) => versioned,
+            (Language::Mixed, Mode::Light) => Some(rules.light()),
+            (Language::Ukrainian, Mode::Aggressive) => Some(rules.ukrainian()),
+            (Language::Ukrainian, Mode::Light) => Some(rules.ukrainian().light()),
+        };
+        let mut stemmer = Stemmer {
+            options: self.options,
+            cache,
+            interner: self.intern.then(|| Arc::new(StemInterner::new())),
+            counters: self.stats.then(|| Arc::new(Counters::default())),
+            own_rules: own_rules.map(Arc::new),
+        };
+        if let Some(shared) = self.sharded_cache {
+            let fingerprint = stemmer.fingerprint();
+            let shared = if shared.bind(fingerprint) {
+                shared
+            } else {
+                let fresh = shared.empty_like();
+                fresh.bind(fingerprint);
+                Arc::new(fresh)
+            };
+            stemmer.cache = Some(BuiltinCache::Sharded(shared));
+        }
+        stemmer
+    }
+}
+
+// a word of ASCII letters, apostrophes and hyphens, such as "Ivano-Frankivsk"
+pub(crate) fn is_latin_word(word: &str) -> bool {
+    word.chars().any(|c| c.is_ascii_alphabetic())
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphabetic() || c == '-' || APOSTROPHES.contains(&c))
+}
+
+#[test]
+fn stemmer_pack_test() {
+    let plain = Stemmer::new();
+    let surzhyk = Stemmer::builder().pack(crate::packs::surzhyk()).build();
+
+    assert_eq!(
+        plain.stem("ручкається"),
+        crate::stem_word("ручкається".into())
+    );
+    assert_ne!(plain.stem("информация"), plain.stem("інформація"));
+    assert_eq!(surzhyk.stem("информация"), surzhyk.stem("інформація"));
+    assert_eq!(surzhyk.stem("Студентов"), surzhyk.stem("студентів"));
+    assert_eq!(surzhyk.stem("программы"), surzhyk.stem("програми"));
+    assert_ne!(
+        Stemmer::new().stem("программы"),
+        Stemmer::new().stem("програми")
+    );
+
+    let historical = Stemmer::builder().pack(crate::packs::historical()).build();
+    assert_eq!(historical.stem("НАРОДЪ"), historical.stem("народ"));
+    assert_eq!(historical.stem("дїло"), plain.stem("діло"));
+
+    let kharkiv = Stemmer::builder().pack(crate::packs::kharkiv()).build();
+    for (classical, modern) in [
+        ("кляси", "класи"),
+        ("радости", "радості"),
+        ("мітологією", "міфологією"),
+    ] {
+        assert_eq!(kharkiv.stem(classical), kharkiv.stem(modern));
+    }
+}
+
+#[test]
+fn stemmer_ocr_test() {
+    let ocr = Stemmer::builder().ocr_tolerant(true).build();
+
+    assert_eq!(ocr.stem("укра1ни"), ocr.stem("України"));
+    assert_eq!(ocr.stem("пар0ду"), ocr.stem("народу"));
+    assert_ne!(Stemmer::new().stem("пар0ду"), Stemmer::new().stem("народу"));
+}
+
+#[test]
+fn fold_ge_test() {
+    use crate::analyzer::Analyzer;
+
+    let stemmer = Stemmer::builder().fold_ge(true).build();
+    assert_eq!(stemmer.stem("ґанку"), "ганк");
+    assert_eq!(stemmer.stem("Ґуґлом"), stemmer.stem("Гуглом"));
+    let mut out = String::new();
+    stemmer.stem_into("ґанок", &mut out);
+    assert_eq!(out, stemmer.stem("ганок"));
+    let analyzer = Analyzer::new().stemmer(stemmer);
+    assert_eq!(analyzer.stem_text("ґанок"), analyzer.stem_text("ганок"));
+    assert_ne!(Stemmer::new().stem("ґанку"), Stemmer::new().stem("ганку"));
+}
+
+#[test]
+fn acronyms_test() {
+    use crate::analyzer::Analyzer;
+
+    let keep = Stemmer::builder().acronyms(Acronyms::Keep).build();
+    assert_eq!(Stemmer::new().stem("НАТО"), "нат");
+    for (word, kept) in [("НАТО", "нато"), ("ЗСУ", "зсу"), ("ОБСЄ", "обсє")] {
+        assert_eq!(keep.stem(word), kept);
+        assert_eq!(keep.try_stem(word.as_bytes()).unwrap(), kept);
+        assert_eq!(keep.stem_mapped(word).stem, kept);
+    }
+    assert_eq!(
+        keep.stem_outcome("НАТО"),
+        StemOutcome::Acronym("нато".into())
+    );
+    assert_eq!(keep.stem_with_rules("ЗСУ"), ("зсу".into(), Vec::new()));
+    // too long for an acronym, or not all uppercase
+    assert_eq!(keep.stem("НАРОДАМИ"), "народ");
+    assert_eq!(keep.stem("Нато"), "нат");
+    let analyzer = Analyzer::new().stemmer(keep);
+    assert_eq!(analyzer.stem_text("Саміт НАТО"), "саміт нато");
+}
+
+#[test]
+fn idempotent_test() {
+    let words: Vec<&str> = include_str!("../rules/frequent.txt")
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    let plain = Stemmer::new();
+    assert_eq!(plain.stem("голови"), "голов");
+    assert_eq!(plain.stem("голов"), "гол");
+    assert!(words
+        .iter()
+        .any(|w| plain.stem(&plain.stem(w)) != plain.stem(w)));
+
+    let stemmers = [
+        Stemmer::builder().idempotent(true).build(),
+        Stemmer::builder().idempotent(true).cache(64).build(),
+        Stemmer::builder()
+            .idempotent(true)
+            .mode(Mode::Light)
+            .restrip(2)
+            .build(),
+        Stemmer::builder()
+            .idempotent(true)
+            .language(Language::Ukrainian)
+            .diminutives(true)
+            .feminitives(true)
+            .ocr_tolerant(true)
+            .build(),
+    ];
+    for stemmer in &stemmers {
+        for word in &words {
+            let stem = stemmer.stem(word);
+            assert_eq!(stemmer.stem(&stem), stem, "{}", word);
+            assert_eq!(stemmer.try_stem(word.as_bytes()).unwrap(), stem, "{}", word);
+            assert_eq!(stemmer.stem_explained(word).stem(), stem, "{}", word);
+            let mut out = String::new();
+            stemmer.stem_into(word, &mut out);
+            assert_eq!(out, stem, "{}", word);
+        }
+    }
+    let stemmer = &stemmers[0];
+    assert_eq!(stemmer.stem("голови"), "гол");
+    assert_eq!(stemmer.stem("книгами"), "книг");
+    let trace = stemmer.stem_explained("голови");
+    assert_eq!(trace.steps.last().unwrap().form, "гол");
+    assert_ne!(stemmer.fingerprint(), plain.fingerprint());
+
+    // on by default from `Algorithm::V2`
+    let v2 = Stemmer::builder().algorithm(Algorithm::V2).build();
+    for word in &words {
+        let stem = v2.stem(word);
+        assert_eq!(v2.stem(&stem), stem, "{}", word);
+    }
+    assert_eq!(v2.stem("голови"), "гол");
+    let v2_plain = v2.to_builder().idempotent(false).build();
+    assert_eq!(v2_plain.stem("голови"), "голов");
+}
+
+#[test]
+fn stem_interned_test() {
+    fn shared<T: Send + Sync>(_: &T) {}
+
+    let stemmer = Stemmer::builder().intern_stems(true).build();
+    shared(&stemmer);
+    let first = stemmer.stem_interned("народами");
+    assert_eq!(&*first, "народ");
+    assert!(Arc::ptr_eq(&first, &stemmer.stem_interned("Народи")));
+    let clone = stemmer.clone();
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for word in ["книгами", "книги", "народу", "автора"] {
+                    clone.stem_interned(word);
+                }
+            });
+        }
+    });
+    assert_eq!(stemmer.interner().unwrap().len(), 3);
+    assert!(Arc::ptr_eq(&first, &clone.stem_interned("народові")));
+    assert_eq!(&*Stemmer::new().stem_interned("книгами"), "книг");
+    assert!(Stemmer::new().interner().is_none());
+}
+
+#[test]
+fn stats_test() {
+    use crate::rules::NOUN;
+
+    let stemmer = Stemmer::builder()
+        .stats(true)
+        .cache(100)
+        .foreign(Foreign::PassThrough)
+        .build();
+    for word in ["книгами", "книгами", "ліс", "Windows", "народу"] {
+        stemmer.stem(word);
+    }
+    let mut out = String::new();
+    stemmer.clone().stem_into("народами", &mut out);
+    let stats = stemmer.stats().unwrap();
+    assert_eq!(stats.words, 6);
+    assert_eq!(stats.unchanged, 2);
+    // the second "книгами" and the passed-through word skip the rules
+    assert_eq!(stats.stemmed, 4);
+    assert_eq!(stats.rules.get(NOUN), Some(&2));
+    assert_eq!(stats.rules.values().sum::<u64>(), 3);
+    assert!((stats.unchanged_rate() - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(stats.cache.unwrap().hits, 1);
+    assert_eq!(stats.cache_hit_rate(), Some(0.2));
+    let fresh = stemmer.to_builder().build().stats().unwrap();
+    assert_eq!((fresh.words, fresh.cache.unwrap().hits), (0, 0));
+    assert!(Stemmer::new().stats().is_none());
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn tracing_test() {
+    use std::sync::Mutex;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata};
+
+    // the `rule` fields of the events and the names of the spans entered
+    #[derive(Default)]
+    struct Collect(Mutex<Vec<String>>);
+
+    struct RuleField(Option<String>);
+
+    impl Visit for RuleField {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == "rule" {
+                self.0 = Some(value.to_string());
+            }
+        }
+
+        fn record_debug(&mut self, _: &Field, _: &dyn fmt::Debug) {}
+    }
+
+    impl tracing::Subscriber for Collect {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes) -> Id {
+            self.0
+                .lock()
+                .unwrap()
+                .push(span.metadata().name().to_string());
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event) {
+            let mut rule = RuleField(None);
+            event.record(&mut rule);
+            self.0.lock().unwrap().extend(rule.0);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    let collect = Arc::new(Collect::default());
+    tracing::subscriber::with_default(Arc::clone(&collect), || {
+        Stemmer::new().stem("Пароплавами");
+    });
+    let seen = collect.0.lock().unwrap();
+    assert_eq!(*seen, ["stem", "normalize", "rules", crate::rules::NOUN]);
+}
+
+#[test]
+fn foreign_test() {
+    let raw = Stemmer::builder().foreign(Foreign::PassThrough).build();
+    for token in [
+        "Kyiv",
+        "COVID-19",
+        "https://uk.wikipedia.org/wiki/Книгами",
+        "a@b.ua",
+    ] {
+        assert_eq!(raw.stem(token), token);
+        assert_eq!(raw.try_stem(token.as_bytes()).unwrap(), token);
+        assert_eq!(
+            raw.stem_outcome(token),
+            StemOutcome::Foreign(token.to_string())
+        );
+        assert_eq!(raw.stem_explained(token).stem(), token);
+        assert_eq!(raw.stem_mapped(token).stem, token);
+        let mut out = String::new();
+        raw.stem_into(token, &mut out);
+        assert_eq!(out, token);
+    }
+    assert_eq!(raw.stem("Книгами"), "книг");
+    assert_eq!(Stemmer::new().stem("Kyiv"), "kyiv");
+    assert_eq!(
+        Stemmer::new().stem("https://uk.wikipedia.org/wiki/Книгами"),
+        "https://uk.wikipedia.org/wiki/книг"
+    );
+}
+
+#[test]
+fn transliterate_test() {
+    let stemmer = Stemmer::builder().foreign(Foreign::Transliterate).build();
+    let cyrillic = Stemmer::new();
+    for (latin, word) in [
+        ("pryvit", "привіт"),
+        ("Kyieva", "Києва"),
+        ("knyhamy", "книгами"),
+        ("Zaporizhzhia", "Запоріжжя"),
+    ] {
+        assert_eq!(stemmer.stem(latin), cyrillic.stem(word));
+        assert_eq!(stemmer.stem_outcome(latin), cyrillic.stem_outcome(word));
+        let mut out = String::new();
+        stemmer.stem_into(latin, &mut out);
+        assert_eq!(out, cyrillic.stem(word));
+    }
+    assert_eq!(stemmer.stem_mapped("knyhamy").stem, "книг");
+    for token in ["COVID-19", "2024", "a@b.ua", "https://kyiv.ua"] {
+        assert_eq!(stemmer.stem(token), token);
+    }
+    assert_eq!(stemmer.stem("Книгами"), "книг");
+    let analyzer = crate::analyzer::Analyzer::new().stemmer(stemmer);
+    assert_eq!(analyzer.terms("Pryvit z Kyieva"), ["привіт", "з", "києв"]);
+}
+
+#[test]
+fn normalizers_test() {
+    use crate::normalizer::Lowercase;
+
+    // stress marks of dictionaries and textbooks
+    let unstressed = |word: &str| word.replace('\u{301}', "");
+    let chain = NormalizerChain::of(&StemmerOptions::default()).insert(0, unstressed);
+    let stemmer = Stemmer::builder().normalizers(chain.clone()).build();
+    assert_eq!(stemmer.stem("доро\u{301}гою"), "дорог");
+    assert_eq!(stemmer.stem_mapped("доро\u{301}гою").stem, "дорог");
+    assert_ne!(Stemmer::new().stem("доро\u{301}гою"), "дорог");
+    assert_eq!(stemmer.stem("Книгами"), Stemmer::new().stem("Книгами"));
+    assert_ne!(stemmer.fingerprint(), Stemmer::new().fingerprint());
+
+    // only lowercasing: apostrophes stay and "ё" isn't folded
+    let lowercase = Stemmer::builder()
+        .normalizers(NormalizerChain::new().then(Lowercase))
+        .build();
+    assert_eq!(lowercase.normalize("П'ять"), "п'ять");
+    let mut out = String::new();
+    lowercase.stem_into("п'ять", &mut out);
+    assert_eq!(out, lowercase.stem("П'ять"));
+    assert_eq!(
+        Stemmer::builder()
+            .normalizers(NormalizerChain::of(&StemmerOptions::default()))
+            .build()
+            .stem("П’ятьох"),
+        Stemmer::new().stem("П’ятьох")
+    );
+}
+
+#[test]
+fn algorithm_test() {
+    use crate::fingerprint::RULES_VERSION;
+
+    assert_eq!(Algorithm::LATEST.rules_version(), RULES_VERSION);
+    assert_eq!(Algorithm::default(), Algorithm::V1);
+    let pinned = Stemmer::builder().algorithm(Algorithm::V1).build();
+    let v2 = Stemmer::builder().algorithm(Algorithm::V2).build();
+    assert_eq!(Stemmer::new().stem("енергією"), "енергіє");
+    assert_eq!(v2.stem("енергією"), "енерг");
+    assert_eq!(v2.stem("енергії"), v2.stem("енергія"));
+    assert_eq!(
+        v2.stem("енергією"),
+        Stemmer::builder()
+            .rules(RuleSet::at_version(2).unwrap())
+            .build()
+            .stem("енергією")
+    );
+    assert_eq!(pinned.fingerprint(), Stemmer::new().fingerprint());
+    assert_ne!(v2.fingerprint(), Stemmer::new().fingerprint());
+    let light = Stemmer::builder()
+        .algorithm(Algorithm::V2)
+        .mode(Mode::Light)
+        .build();
+    assert_eq!(light.stem("енергією"), v2.stem("енергією"));
+    assert_eq!(Algorithm::from_rules_version(1), Some(Algorithm::V1));
+    assert_eq!(Algorithm::from_rules_version(RULES_VERSION + 1), None);
+}
+
+/// The `(word, stem)` pairs of the original stemmer that `stem_word_test` checks, read
+/// from its two lists.
+#[cfg(test)]
+fn baseline_pairs() -> Vec<(&'static str, &'static str)> {
+    let source = include_str!("test_stemmer_uk.rs");
+    let list = |start: &str| -> Vec<&'static str> {
+        source[source.find(start).unwrap() + start.len()..]
+            .lines()
+            .skip(1)
+            .take_while(|line| line.trim() != "];")
+            .map(|line| line.trim().trim_end_matches(',').trim_matches('"'))
+            .collect()
+    };
+    let (words, stems) = (list("let val = ["), list("let res = ["));
+    assert_eq!(words.len(), stems.len());
+    words.into_iter().zip(stems).collect()
+}
+
+#[test]
+fn baseline_v1_test() {
+    // the stems of the original stemmer, which `Algorithm::V1` keeps bit for bit
+    let v1 = Stemmer::builder().algorithm(Algorithm::V1).build();
+    let pairs = baseline_pairs();
+    assert!(pairs.len() > 3000);
+    let mut out = String::new();
+    for (word, stem) in pairs {
+        assert_eq!(v1.stem(word), stem);
+        assert_eq!(crate::stem(word), stem);
+        v1.stem_into(word, &mut out);
+        assert_eq!(out, stem);
+        // the rules only ever shorten RV, which starts after the first vowel
+        let trace = crate::stem_word_explained(word);
+        assert!(stem.starts_with(trace.prefix()), "{}", trace);
+    }
+}
+
+#[test]
+fn language_test() {
+    let ukrainian = Stemmer::builder().language(Language::Ukrainian).build();
+    let mixed = Stemmer::new();
+    assert_eq!(mixed.stem("студентов"), "студент");
+    assert_eq!(ukrainian.stem("студентов"), "студентов");
+    assert_eq!(ukrainian.stem("новой"), "ново");
+    assert_eq!(mixed.stem("ёлка"), mixed.stem("елка"));
+    assert_eq!(ukrainian.stem("ёлка"), "ёлка");
+    assert_eq!(ukrainian.stem("объём"), "объём");
+    for word in ["народами", "інформацію", "людей", "молодість", "читали"]
+    {
+        assert_eq!(ukrainian.stem(word), mixed.stem(word), "{}", word);
+    }
+    let light = Stemmer::builder()
+        .language(Language::Ukrainian)
+        .mode(Mode::Light)
+        .build();
+    assert_eq!(light.stem("студентов"), "студентов");
+    assert_eq!(light.stem("читали"), "читал");
+    let analyzer = crate::analyzer::Analyzer::new().stemmer(ukrainian.clone());
+    assert_eq!(analyzer.stem_text("Ёлка"), "ёлка");
+    assert_ne!(ukrainian.fingerprint(), mixed.fingerprint());
+}
+
+#[test]
+fn stem_into_test() {
+    let configs = [
+        Stemmer::new(),
+        Stemmer::builder().pack(crate::packs::surzhyk()).build(),
+        Stemmer::builder().preserve_case(true).build(),
+        Stemmer::builder().min_stem_chars(3).build(),
+    ];
+    let words = [
+        "народами",
+        "Народами",
+        "пʼятьох",
+        "информация",
+        "ліси",
+        "вдв",
+        "уха",
+    ];
+    let (mut out, mut bytes) = (String::from("старе"), Vec::new());
+    for stemmer in &configs {
+        for word in words {
+            stemmer.stem_into(word, &mut out);
+            assert_eq!(out, stemmer.stem(word), "{}", word);
+            stemmer
+                .stem_bytes_into(word.as_bytes(), &mut bytes)
+                .unwrap();
+            assert_eq!(
+                bytes,
+                stemmer.stem_bytes(word.as_bytes()).unwrap(),
+                "{}",
+                word
+            );
+        }
+    }
+    let stemmer = Stemmer::new();
+    assert_eq!(
+        stemmer.stem_bytes_into(b" \xff", &mut bytes),
+        Err(StemError::InvalidUtf8 { valid_up_to: 1 })
+    );
+    assert!(bytes.is_empty());
+    assert_eq!(
+        stemmer.stem_bytes_into(b" ", &mut bytes),
+        Err(StemError::Empty)
+    );
+    stemmer
+        .stem_bytes_into(" книгами ".as_bytes(), &mut bytes)
+        .unwrap();
+    assert_eq!(bytes, "книг".as_bytes());
+}
+
+#[test]
+fn stem_bytes_test() {
+    let stemmer = Stemmer::new();
+    assert_eq!(
+        stemmer.stem_bytes("Народами".as_bytes()).unwrap(),
+        "народ".as_bytes()
+    );
+    assert_eq!(
+        stemmer.stem_bytes(b"\xd0\xbd\xff"),
+        Err(StemError::InvalidUtf8 { valid_up_to: 2 })
+    );
+    assert_eq!(stemmer.stem_bytes(b" "), Err(StemError::Empty));
+    assert_eq!(
+        crate::stem_bytes("книгами".as_bytes()).unwrap(),
+        "книг".as_bytes()
+    );
+}
+
+#[test]
+fn ocr_confusions_test() {
+    let ocr = Stemmer::builder()
+        .ocr_confusions(Confusions::default())
+        .build();
+    for (noisy, clean) in [
+        ("Украіни", "України"),
+        ("сілськии", "сільський"),
+        ("великии", "великий"),
+        ("осін", "осінь"),
+    ] {
+        assert_eq!(ocr.stem(noisy), ocr.stem(clean), "{}", noisy);
+    }
+    assert_ne!(
+        Stemmer::new().stem("сілськии"),
+        Stemmer::new().stem("сільський")
+    );
+    assert_ne!(ocr.fingerprint(), Stemmer::new().fingerprint());
+
+    let custom = Stemmer::builder()
+        .ocr_confusions(Confusions::new().fold('є', 'е'))
+        .build();
+    assert_eq!(custom.stem("сьогоднішнє"), custom.stem("сьогоднішне"));
+    assert_ne!(custom.stem("сілськии"), custom.stem("сільський"));
+}
+
+#[test]
+fn apostrophes_test() {
+    let keep = Stemmer::builder().apostrophes(Apostrophes::Keep).build();
+    assert_eq!(keep.stem("М'ясо"), "м'яс");
+    assert_eq!(keep.stem("з'їздами"), "з'їзд");
+    assert_eq!(Stemmer::new().stem("М'ясо"), "мяс");
+    assert_ne!(keep.fingerprint(), Stemmer::new().fingerprint());
+}
+
+#[test]
+fn invisible_test() {
+    let stemmer = Stemmer::builder().algorithm(Algorithm::V2).build();
+    for word in [
+        "ін\u{AD}фор\u{AD}ма\u{AD}ці\u{AD}єю",
+        "інфор\u{200B}мацією",
+        "інформаці\u{2060}єю",
+        "\u{FEFF}інформацією",
+    ]
+    .iter()
+    {
+        assert_eq!(
+            stemmer.stem(word),
+            stemmer.stem("інформацією"),
+            "{:?}",
+            word
+        );
+    }
+    let mapped = stemmer.stem_mapped("кни\u{AD}га");
+    assert_eq!(mapped.stem, "книг");
+    assert_eq!(mapped.origins()[3], "кни\u{AD}".len());
+
+    // `Algorithm::V1` keeps them, as the original stemmer did
+    assert_eq!(crate::stem_word("кни\u{AD}гами".into()), "кни\u{AD}г");
+    assert_eq!(Stemmer::new().stem("\u{200B}голови"), "\u{200B}голов");
+    let keep = stemmer.to_builder().keep_invisible(true).build();
+    assert_eq!(keep.stem("кни\u{AD}гами"), "кни\u{AD}г");
+    let strip = Stemmer::builder().keep_invisible(false).build();
+    assert_eq!(strip.stem("кни\u{AD}гами"), "книг");
+}
+
+#[test]
+fn stem_prefolded_test() {
+    let plain = Stemmer::new();
+    let stemmers = [
+        plain.clone(),
+        Stemmer::builder().apostrophes(Apostrophes::Keep).build(),
+        Stemmer::builder()
+            .homoglyph_repair(HomoglyphRepair::Letters)
+            .cache(10)
+            .build(),
+    ];
+    let text = "Книгами З'ЇЗДУ Bікно Ёлки на\u{AD}родів İnfo";
+    let folded = crate::preprocess::fold_text(text);
+    for stemmer in stemmers.iter() {
+        for (start, word) in text.split(' ').scan(0, |pos, w| {
+            let start = *pos;
+            *pos += w.len() + 1;
+            Some((start, w))
+        }) {
+            let prefolded = stemmer.stem_prefolded(word, &folded[start..start + word.len()]);
+            assert_eq!(prefolded, stemmer.stem(word), "{}", word);
+        }
+    }
+}
+
+#[test]
+fn rule_set_test() {
+    let mut rules = RuleSet::builtin();
+    let stemmer = Stemmer::builder().rules(rules.clone()).build();
+    assert_eq!(stemmer.stem("університетами"), "університет");
+    assert_eq!(
+        stemmer.fingerprint(),
+        Stemmer::builder()
+            .rules(rules.clone())
+            .build()
+            .fingerprint()
+    );
+
+    let mut noun = rules.suffixes(crate::rules::NOUN).unwrap();
+    noun.retain(|s| *s != "ами");
+    let noun: Vec<String> = noun.into_iter().map(str::to_string).collect();
+    rules.set_suffixes(crate::rules::NOUN, noun).unwrap();
+    let stemmer = Stemmer::builder().rules(rules).build();
+    assert_eq!(stemmer.stem("університетами"), "університетам");
+    assert_eq!(
+        stemmer.try_stem("університетами".as_bytes()).unwrap(),
+        "університетам"
+    );
+    assert_ne!(stemmer.fingerprint(), Stemmer::new().fingerprint());
+
+    let rules = RuleSet::from_toml("reflexive = []").unwrap();
+    let stemmer = Stemmer::builder().rules(rules).build();
+    let (stem, trace) = stemmer.stem_with_trace("ручкається");
+    assert_eq!(stem, "ручкаєтьс");
+    assert!(trace.iter().all(|hit| hit.rule != crate::rules::REFLEXIVE));
+}
+
+#[test]
+fn vowelless_test() {
+    let plain = Stemmer::new();
+    assert_eq!(
+        plain.stem_outcome("ГРН"),
+        StemOutcome::Vowelless("грн".into())
+    );
+    assert_eq!(
+        plain.stem_outcome("хліб"),
+        StemOutcome::Stemmed("хліб".into())
+    );
+    assert_eq!(
+        plain.stem_outcome("Книгами"),
+        StemOutcome::Stemmed("книг".into())
+    );
+
+    let expanding = Stemmer::builder()
+        .vowelless(Vowelless::Expand)
+        .abbreviations(Dictionary::from_pairs(vec![
+            ("грн", "гривня"),
+            ("ЦВК", "Центральна виборча комісія"),
+            ("ст", "ст"),
+        ]))
+        .build();
+    assert_eq!(
+        expanding.stem_outcome("грн"),
+        StemOutcome::Abbreviation("гривн".into())
+    );
+    assert_eq!(expanding.stem("ЦВК"), "центральн виборч коміс");
+    assert_eq!(expanding.stem("ст"), "ст");
+    assert_eq!(
+        expanding.stem_outcome("км"),
+        StemOutcome::Vowelless("км".into())
+    );
+    assert_eq!(expanding.try_stem(b"grn").unwrap(), "grn");
+    assert_eq!(expanding.try_stem("грн".as_bytes()).unwrap(), "гривн");
+    // the abbreviations only apply under `Vowelless::Expand`
+    let listed = expanding
+        .to_builder()
+        .vowelless(Vowelless::PassThrough)
+        .build();
+    assert_eq!(listed.stem("грн"), "грн");
+}
+
+#[test]
+fn stemmer_fingerprint_test() {
+    assert_eq!(Stemmer::new().fingerprint(), crate::config_fingerprint());
+    assert_ne!(
+        Stemmer::new().fingerprint(),
+        Stemmer::builder().ocr_tolerant(true).build().fingerprint()
+    );
+    assert_ne!(
+        Stemmer::builder()
+            .pack(crate::packs::surzhyk())
+            .build()
+            .fingerprint(),
+        Stemmer::builder()
+            .pack(crate::packs::surzhyk().word("ваще", "взагалі"))
+            .build()
+            .fingerprint()
+    );
+}
+
+#[test]
+fn stem_with_rules_test() {
+    use crate::rules;
+
+    let (stem, fired) = Stemmer::new().stem_with_rules("ручкається");
+    assert_eq!(stem, "ручкаєт");
+    assert_eq!(fired, [rules::REFLEXIVE, rules::NOUN]);
+    assert!(Stemmer::new().stem_with_rules("грн").1.is_empty());
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_stem_words_test() {
+    let words: Vec<String> = (0..10_000)
+        .map(|i| ["книга", "Києва", "ручкається", "грн"][i % 4].repeat(1 + i % 3))
+        .collect();
+    let stemmer = Stemmer::new();
+    assert_eq!(stemmer.par_stem_words(&words), stemmer.stem_words(&words));
+}
+
+#[test]
+fn builtin_cache_test() {
+    let stemmer = Stemmer::builder().cache(2).build();
+    for word in ["Києва", "Києва", "народи", "Києва", "слова"].iter() {
+        assert_eq!(stemmer.stem(word), Stemmer::new().stem(word));
+    }
+    let stats = stemmer.cache().unwrap().stats();
+    assert_eq!((stats.entries, stats.hits, stats.misses), (2, 2, 3));
+    assert!(Stemmer::new().cache().is_none());
+
+    // options changed through the builder don't see stems cached under the old ones
+    let rebuilt = stemmer.to_builder().apostrophes(Apostrophes::Keep).build();
+    assert_eq!(rebuilt.cache().unwrap().stats().entries, 0);
+    assert_eq!(rebuilt.cache().unwrap().max_entries(), 2);
+}
+
+#[test]
+fn builtin_cache_key_test() {
+    // case and apostrophe variants share the entry of their normalized form
+    let stemmer = Stemmer::builder()
+        .algorithm(Algorithm::V2)
+        .cache(16)
+        .build();
+    let words = ["Києва", "києва", "КИЄВА", "п'ять", "п’ять", "пʼять"];
+    for word in words.iter() {
+        assert_eq!(stemmer.stem(word), stemmer.to_builder().build().stem(word));
+    }
+    let stats = stemmer.cache().unwrap().stats();
+    assert_eq!((stats.entries, stats.hits, stats.misses), (2, 4, 2));
+    assert_eq!(stats.normalized_hits, 3);
+
+    let cased = Stemmer::builder().cache(16).preserve_case(true).build();
+    assert_eq!(cased.stem("Києва"), "Києв");
+    assert_eq!(cased.stem("КИЄВА"), "КИЄВ");
+    assert_eq!(cased.stem("києва"), "києв");
+    assert_eq!(cased.cache().unwrap().stats().entries, 1);
+}
+
+#[test]
+fn sharded_cache_stemmer_test() {
+    let shared = Arc::new(ShardedCache::with_max_entries(100));
+    let stemmer = Stemmer::builder()
+        .sharded_cache(Arc::clone(&shared))
+        .stats(true)
+        .build();
+    let other = Stemmer::builder()
+        .sharded_cache(Arc::clone(&shared))
+        .build();
+    assert_eq!(stemmer.stem("Києва"), Stemmer::new().stem("Києва"));
+    assert_eq!(other.clone().stem("Києва"), Stemmer::new().stem("Києва"));
+    assert_eq!(shared.stats().hits, 1);
+    assert_eq!(stemmer.stats().unwrap().cache, Some(shared.stats()));
+    assert!(stemmer.cache().is_none());
+    assert_eq!(stemmer.cache_stats(), other.cache_stats());
+
+    // another configuration gets a cache of its own, so does not see these stems
+    let rebuilt = stemmer.to_builder().apostrophes(Apostrophes::Keep).build();
+    let cache = rebuilt.sharded_cache().unwrap();
+    assert_eq!(cache.stats().entries, 0);
+    assert_eq!(cache.max_entries(), 100);
+    let again = rebuilt.to_builder().build();
+    rebuilt.stem("Києва");
+    assert_eq!(again.cache_stats().unwrap().entries, 1);
+    assert_eq!(shared.stats().entries, 1);
+}
+
+#[test]
+fn stem_cached_test() {
+    let stemmer = Stemmer::new();
+    let cache = StemCache::new(1 << 20);
+    for word in ["києва", "Києва", "КИЄВА", "києва", "народи"] {
+        assert_eq!(stemmer.stem_cached(word, &cache), stemmer.stem(word));
+    }
+    let stats = cache.stats();
+    assert_eq!(stats.entries, 2);
+    assert_eq!((stats.hits, stats.normalized_hits, stats.misses), (3, 2, 2));
+}
+
+#[test]
+fn stem_with_trace_test() {
+    use crate::rules;
+
+    let suffixes = |word| {
+        Stemmer::new()
+            .stem_with_trace(word)
+            .1
+            .into_iter()
+            .map(|hit| (hit.rule, hit.suffix))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(suffixes("книгами"), [(rules::NOUN, "ами".to_string())]);
+    assert_eq!(suffixes("батькові"), [(rules::NOUN, "ові".to_string())]);
+    assert_eq!(
+        suffixes("ручкається"),
+        [
+            (rules::REFLEXIVE, "ся".to_string()),
+            (rules::NOUN, "ь".to_string())
+        ]
+    );
+}
+
+#[test]
+fn stem_all_test() {
+    let stemmer = Stemmer::new();
+    let words = vec!["народи", "автора", "слідство"];
+    let stems = stemmer.stem_all(&words);
+    assert_eq!(stems.len(), 3);
+    assert_eq!(
+        stems.rev().collect::<Vec<_>>(),
+        ["слідств", "автор", "народ"]
+    );
+
+    let owned: std::collections::BTreeSet<String> = stemmer
+        .stem_all(vec!["народи".to_string(), "народ".to_string()])
+        .collect();
+    assert_eq!(owned.len(), 1);
+    assert_eq!(crate::stem_all(["народи"]).size_hint(), (1, Some(1)));
+}
+
+#[test]
+fn try_stem_all_test() {
+    let batch: Vec<&[u8]> = vec![
+        "народи".as_bytes(),
+        b"\xd0\xbd\xff",
+        b"  '  ",
+        "сло\u{0}во".as_bytes(),
+        "автора".as_bytes(),
+    ];
+    let results: Vec<_> = Stemmer::new().try_stem_all(batch).collect();
+    assert_eq!(results[0], Ok("народ".to_string()));
+    assert_eq!(results[4], Ok("автор".to_string()));
+
+    let errors: Vec<ItemError> = results.into_iter().filter_map(Result::err).collect();
+    assert_eq!(
+        errors,
+        [
+            ItemError {
+                index: 1,
+                error: StemError::InvalidUtf8 { valid_up_to: 2 }
+            },
+            ItemError {
+                index: 2,
+                error: StemError::Empty
+            },
+            ItemError {
+                index: 3,
+                error: StemError::ControlCharacter('\u{0}')
+            },
+        ]
+    );
+    assert_eq!(crate::try_stem_all(["народи"]).len(), 1);
+}
+
+#[test]
+fn borrowed_resources_test() {
+    static EXCEPTIONS: &str = "люди\tлюд\nдіти\tдит\n";
+    static PROTECTED: [&str; 2] = ["зорі", "приватбанк"];
+
+    let stemmer = Stemmer::builder()
+        .exceptions(Dictionary::parse(EXCEPTIONS).unwrap())
+        .protected(WordList::from_slice(&PROTECTED))
+        .build();
+    assert_eq!(stemmer.stem("Люди"), "люд");
+    assert_eq!(stemmer.stem("ПриватБанк"), "приватбанк");
+    assert_eq!(stemmer.stem("зорі"), "зорі");
+    assert_ne!(Stemmer::new().stem("зорі"), "зорі");
+    assert_ne!(stemmer.fingerprint(), Stemmer::new().fingerprint());
+}
+
+#[test]
+fn unique_stems_test() {
+    let words = "Народи автора народами ґанок гуска автором".split(' ');
+    let stemmer = Stemmer::new();
+    assert_eq!(
+        stemmer.unique_stems(words.clone()),
+        ["народ", "автор", "ґанок", "гуск"]
+    );
+    assert_eq!(
+        stemmer.unique_stems_sorted(words),
+        ["автор", "гуск", "ґанок", "народ"]
+    );
+}
+
+#[test]
+fn conflate_test() {
+    let classes = Stemmer::new().conflate("Народи народами народи автора автором".split(' '));
+    assert_eq!(classes.len(), 2);
+    assert_eq!(classes["народ"], ["Народи", "народами", "народи"]);
+    assert_eq!(classes["автор"], ["автора", "автором"]);
+}
+
+#[test]
+fn stem_key_test() {
+    let stemmer = Stemmer::new();
+    assert!(stemmer.same_stem("народами", "Народи"));
+    assert!(!stemmer.same_stem("народами", "автора"));
+    let mut groups: HashMap<StemKey, Vec<&str>> = HashMap::new();
+    for word in "Народи автора народами автором".split(' ') {
+        groups.entry(stemmer.stem_key(word)).or_default().push(word);
+    }
+    assert_eq!(groups.len(), 2);
+    let key = stemmer.stem_key("автором");
+    assert_eq!(groups[&key], ["автора", "автором"]);
+    assert_eq!(key.to_string(), "автор");
+    assert_eq!(key.into_string(), "автор");
+}
+
+#[test]
+fn stem_mapped_test() {
+    let stemmer = Stemmer::builder()
+        .homoglyph_repair(HomoglyphRepair::Letters)
+        .build();
+    let word = "Пiр'ячками";
+    let mapped = stemmer.stem_mapped(word);
+    assert_eq!(mapped.stem, "пірячк");
+    let origins: Vec<&str> = mapped
+        .origins()
+        .iter()
+        .map(|&i| &word[i..i + word[i..].chars().next().unwrap().len_utf8()])
+        .collect();
+    assert_eq!(origins, ["П", "i", "р", "я", "ч", "к"]);
+    assert_eq!(&word[mapped.map_offset(mapped.stem.len())..], "ами");
+}
+
+#[test]
+fn max_stem_bytes_test() {
+    let word = "високотехнологічного";
+    let stem = Stemmer::new().stem(word);
+    assert_eq!(fit_bytes(stem.clone(), 64), stem);
+    let budgeted = Stemmer::builder().max_stem_bytes(16).build();
+    let key = budgeted.stem(word);
+    assert!(key.len() <= 16, "{}", key);
+    assert!(key.starts_with("вис"));
+    assert_eq!(key, budgeted.stem("високотехнологічний"));
+    assert_ne!(key, budgeted.stem("високотехнократичного"));
+    // the prefix is cut back to a char boundary
+    assert_eq!(fit_bytes("а".repeat(10), 17).len(), 16);
+    assert_eq!(fit_bytes("а".repeat(10), 5).len(), 5);
+    assert_eq!(budgeted.stem("книгами"), "книг");
+    assert_eq!(
+        budgeted
+            .try_stem("Високотехнологічного".as_bytes())
+            .unwrap(),
+        key
+    );
+    assert_ne!(budgeted.fingerprint(), Stemmer::new().fingerprint());
+}
+
+#[test]
+fn restrip_test() {
+    let restrip = Stemmer::builder().restrip(2).build();
+    assert_eq!(Stemmer::new().stem("книгамиами"), "книгам");
+    assert_eq!(restrip.stem("книгамиами"), "книг");
+    assert_eq!(restrip.stem("книгаа"), "книг");
+    assert_eq!(restrip.stem("книгами"), "книг");
+    // another ending, and one that would eat the root
+    assert_eq!(restrip.stem("військових"), "військов");
+    assert_eq!(restrip.stem("оамам"), "оам");
+    let (_, trace) = restrip.stem_with_trace("книгамиамиами");
+    let passes: Vec<(&str, usize)> = trace.iter().map(|h| (&*h.suffix, h.pass)).collect();
+    assert_eq!(passes, [("ами", 0), ("ами", 1), ("ами", 2)]);
+    let explained = restrip.stem_explained("книгамиами").to_string();
+    assert!(
+        explained.ends_with("noun -ами (pass 2) → книг = книг"),
+        "{}",
+        explained
+    );
+    assert_ne!(restrip.fingerprint(), Stemmer::new().fingerprint());
+}
+
+#[test]
+fn preserve_case_test() {
+    assert_eq!(restore_case("Київського", "київ"), "Київ");
+    assert_eq!(restore_case("КИЇВСЬКОГО", "київ"), "КИЇВ");
+    assert_eq!(
+        restore_case("Віце-Прем’єра", "віце-прем'єр"),
+        "Віце-Прем'єр"
+    );
+    assert_eq!(restore_case("ГРН", "гривн"), "ГРИВН");
+    assert_eq!(restore_case("Грн", "гривн"), "Гривн");
+
+    let stemmer = Stemmer::builder().preserve_case(true).cache(10).build();
+    assert_eq!(stemmer.stem("Київського"), "Київськ");
+    assert_eq!(stemmer.stem("київського"), "київськ");
+    assert_eq!(
+        stemmer.try_stem("Київського".as_bytes()).unwrap(),
+        "Київськ"
+    );
+    assert_eq!(
+        stemmer.stem_cached("Київського", &StemCache::new(1 << 20)),
+        "Київськ"
+    );
+    assert_eq!(Stemmer::default().stem("Київського"), "київськ");
+}
+
+#[test]
+fn compounds_test() {
+    let whole = Stemmer::new();
+    let parts = Stemmer::builder().compounds(Compounds::Parts).build();
+    assert_eq!(whole.stem("жовто-блакитного"), "жовто-блакитн");
+    assert_eq!(parts.stem("жовто-блакитного"), "жовт-блакитн");
+    assert_eq!(parts.stem("Інтернет-магазину"), "інтернет-магазин");
+    assert_eq!(parts.stem("книгами"), "книг");
+    assert_eq!(
+        parts.try_stem("жовто-блакитного".as_bytes()).unwrap(),
+        "жовт-блакитн"
+    );
+    let cache = StemCache::new(1 << 20);
+    assert_eq!(
+        parts.stem_cached("жовто-блакитного", &cache),
+        "жовт-блакитн"
+    );
+    assert_eq!(cache.stats().entries, 2);
+    assert_ne!(whole.fingerprint(), parts.fingerprint());
+}
+
+#[test]
+fn mode_test() {
+    let light = Stemmer::builder().mode(Mode::Light).build();
+    let aggressive = Stemmer::builder().mode(Mode::Aggressive).build();
+    assert_eq!(aggressive.stem("читали"), "чит");
+    assert_eq!(light.stem("читали"), "читал");
+    for word in ["книгами", "зеленого", "університетами", "мова"] {
+        assert_eq!(light.stem(word), aggressive.stem(word));
+    }
+    // the verb and reflexive endings stay
+    assert_eq!(aggressive.stem("сміятися"), "смі");
+    assert_eq!(light.stem("сміятися"), "сміятис");
+    assert_ne!(light.fingerprint(), aggressive.fingerprint());
+    assert_eq!(aggressive.fingerprint(), Stemmer::new().fingerprint());
+}
+
+#[test]
+fn strip_prefixes_test() {
+    let stemmer = Stemmer::builder()
+        .strip_prefixes(SUPERLATIVE_PREFIXES.iter().copied())
+        .build();
+    let stem = stemmer.stem("кращий");
+    for word in ["найкращий", "Якнайкращого", "щонайкраще"] {
+        assert_eq!(stemmer.stem(word), stem);
+    }
+    assert_ne!(Stemmer::new().stem("найкращий"), stem);
+    // too short a rest to be a word of its own
+    assert_eq!(stemmer.stem("найти"), Stemmer::new().stem("найти"));
+    assert_eq!(stemmer.stem_mapped("найкращий").stem, stem);
+}
+
+#[test]
+fn feminitives_test() {
+    let stemmer = Stemmer::builder().feminitives(true).build();
+    for (feminitive, base) in [
+        ("авторка", "автор"),
+        ("директорками", "директор"),
+        ("членкиня", "член"),
+        ("вчительки", "вчитель"),
+    ] {
+        assert_eq!(stemmer.stem(feminitive), stemmer.stem(base));
+    }
+    assert_eq!(Stemmer::new().stem("авторка"), "авторк");
+}
+
+#[test]
+fn diminutives_test() {
+    let stemmer = Stemmer::builder().diminutives(true).build();
+    assert_eq!(stemmer.stem("котика"), stemmer.stem("кота"));
+    assert_eq!(stemmer.stem("листочками"), stemmer.stem("листи"));
+    assert_eq!(stemmer.stem("язика"), "язик");
+    assert_eq!(Stemmer::new().stem("котика"), "котик");
+
+    let frequencies = FrequencyList::load("котик\t10\nкот\t1\n".as_bytes()).unwrap();
+    let guarded = Stemmer::builder()
+        .diminutives(true)
+        .frequency_guard(frequencies, 5)
+        .build();
+    assert_eq!(guarded.stem("котика"), "котик");
+}
+
+#[test]
+fn lemmatize_test() {
+    let vesum = "людей людина noun:anim:p:v_rod\nпішов іти verb:imperf:past:m\n";
+    let lemmas = LemmaDictionary::from_vesum(vesum.as_bytes()).unwrap();
+    let stemmer = Stemmer::builder().lemmatize(lemmas).build();
+    assert_eq!(stemmer.stem("Людей"), "людина");
+    assert_eq!(
+        stemmer.stem_outcome("пішов"),
+        StemOutcome::Listed("іти".into())
+    );
+    // unknown words fall back to the rules
+    assert_eq!(stemmer.stem("книгами"), "книг");
+    assert_ne!(stemmer.fingerprint(), Stemmer::new().fingerprint());
+}
+
+#[test]
+fn disambiguate_test() {
+    use crate::corpus::ConflationReport;
+
+    let gold: Vec<(String, String)> = [
+        ("ліс", "ліс"),
+        ("ліси", "ліс"),
+        ("лісів", "ліс"),
+        ("лісом", "ліс"),
+        ("голос", "голос"),
+        ("голоси", "голос"),
+        ("голосом", "голос"),
+        ("книги", "книга"),
+        ("книгами", "книга"),
+    ]
+    .iter()
+    .map(|(form, lemma)| (form.to_string(), lemma.to_string()))
+    .collect();
+    // the stems of the gold forms with the pipeline order, as a corpus would count them
+    let stemmer = Stemmer::new();
+    let frequencies =
+        FrequencyList::from_counts(gold.iter().map(|(form, _)| (stemmer.stem(form), 1)));
+    let disambiguating = Stemmer::builder().disambiguate(frequencies).build();
+    assert_eq!(stemmer.stem("ліси"), "лі");
+    assert_eq!(disambiguating.stem("ліси"), "ліс");
+    assert_eq!(disambiguating.stem("голоси"), "голос");
+    assert_eq!(disambiguating.stem("книгами"), "книг");
+    assert_eq!(disambiguating.try_stem("ліси".as_bytes()).unwrap(), "ліс");
+    // uncounted stems keep the pipeline order
+    assert_eq!(disambiguating.stem("гуси"), "гу");
+
+    let before = ConflationReport::check(&stemmer, &gold);
+    let after = ConflationReport::check(&disambiguating, &gold);
+    assert_eq!((before.conflated, after.conflated), (1, 3));
+    assert_eq!(after.precision(), 1.0);
+    assert_ne!(disambiguating.fingerprint(), stemmer.fingerprint());
+}
+
+#[test]
+fn min_stem_chars_test() {
+    let stemmer = Stemmer::builder().min_stem_chars(3).build();
+    for (word, short, guarded) in [
+        ("уха", "ух", "уха"),
+        ("іде", "ід", "іде"),
+        ("осі", "ос", "осі"),
+        ("книгами", "книг", "книг"),
+    ] {
+        assert_eq!(Stemmer::new().stem(word), short);
+        assert_eq!(stemmer.stem(word), guarded);
+    }
+    assert_ne!(stemmer.fingerprint(), Stemmer::new().fingerprint());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_options_test() {
+    let options: StemmerOptions =
+        serde_json::from_str(r#"{"mode": "light", "apostrophes": "keep", "restrip": 1}"#).unwrap();
+    assert_eq!(options.mode, Mode::Light);
+    assert_eq!(options.apostrophes, Apostrophes::Keep);
+    assert!(!options.ocr_tolerant);
+    assert!(serde_json::from_str::<StemmerOptions>(r#"{"modes": "light"}"#).is_err());
+
+    let mut rules = RuleSet::builtin();
+    rules.set_suffixes("noun", ["ами"]).unwrap();
+    let stemmer = Stemmer::builder()
+        .pack(crate::packs::surzhyk())
+        .exceptions(Dictionary::from_pairs([("люди", "людин")]))
+        .protected(WordList::from_words(["київстар"]))
+        .frequency_guard(FrequencyList::from_counts([("народ", 3)]), 2)
+        .rules(rules)
+        .lemmatize(LemmaDictionary::from_vesum("людьми людина noun".as_bytes()).unwrap())
+        .homoglyph_repair(HomoglyphRepair::Letters)
+        .build();
+    let json = serde_json::to_string(stemmer.options()).unwrap();
+    let options: StemmerOptions = serde_json::from_str(&json).unwrap();
+    assert_eq!(&options, stemmer.options());
+    let restored = Stemmer::from_options(options);
+    assert_eq!(restored.fingerprint(), stemmer.fingerprint());
+    assert_eq!(restored.stem("люди"), "людин");
+    assert_eq!(restored.stem("людьми"), "людина");
+}
@@ -0,0 +1,161 @@
+use std::iter::FusedIterator;
+
+pub use stemmer_uk_analysis::stopwords::{default_list, is_stopword};
+
+use crate::analyzer::StemmedToken;
+use crate::tokenizer::{Token, TokenKind};
+use crate::wordlist::WordList;
+
+/// The default stop-word list as a `WordList`, borrowing the built-in table.
+pub fn default_words() -> WordList<'static> {
+    WordList::from_slice(default_list())
+}
+
+/// An item of a token iterator `StopWordFilter` can look at.
+pub trait TokenWord {
+    /// The word to look up, `None` for items that are never stop words.
+    fn word(&self) -> Option<&str>;
+}
+
+impl TokenWord for Token<'_> {
+    fn word(&self) -> Option<&str> {
+        match self.kind {
+            TokenKind::Word => Some(self.text),
+            _ => None,
+        }
+    }
+}
+
+impl TokenWord for StemmedToken<'_> {
+    fn word(&self) -> Option<&str> {
+        Some(self.original)
+    }
+}
+
+/// Drops stop words from token iterators, by default those of the default list.
+///
+/// The list can be extended or replaced between uses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StopWordFilter<'w> {
+    words: WordList<'w>,
+}
+
+impl Default for StopWordFilter<'static> {
+    fn default() -> Self {
+        StopWordFilter::new()
+    }
+}
+
+impl StopWordFilter<'static> {
+    pub fn new() -> Self {
+        StopWordFilter::with_words(default_words())
+    }
+}
+
+impl<'w> StopWordFilter<'w> {
+    pub fn with_words(words: WordList<'w>) -> Self {
+        StopWordFilter { words }
+    }
+
+    pub fn words(&self) -> &WordList<'w> {
+        &self.words
+    }
+
+    pub fn set_words(&mut self, words: WordList<'w>) {
+        self.words = words;
+    }
+
+    /// Adds lowercase words to the list.
+    pub fn extend<I, W>(&mut self, words: I)
+    where
+        I: IntoIterator<Item = W>,
+        W: Into<std::borrow::Cow<'w, str>>,
+    {
+        for word in words {
+            self.words.insert(word);
+        }
+    }
+
+    pub fn remove(&mut self, word: &str) {
+        self.words.remove(word);
+    }
+
+    /// Whether `word` is in the list; case insensitive.
+    pub fn is_stopword(&self, word: &str) -> bool {
+        self.words.contains_lowercase(word)
+    }
+
+    /// The items of `tokens` that aren't stop words.
+    pub fn filter<I>(&self, tokens: I) -> StopWordFiltered<'_, 'w, I::IntoIter>
+    where
+        I: IntoIterator,
+        I::Item: TokenWord,
+    {
+        StopWordFiltered {
+            filter: self,
+            tokens: tokens.into_iter(),
+        }
+    }
+}
+
+/// Iterator returned by `StopWordFilter::filter`.
+#[derive(Clone, Debug)]
+pub struct StopWordFiltered<'f, 'w, I> {
+    filter: &'f StopWordFilter<'w>,
+    tokens: I,
+}
+
+impl<I> Iterator for StopWordFiltered<'_, '_, I>
+where
+    I: Iterator,
+    I::Item: TokenWord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let filter = self.filter;
+        self.tokens
+            .find(|token| !token.word().is_some_and(|word| filter.is_stopword(word)))
+    }
+}
+
+impl<I> FusedIterator for StopWordFiltered<'_, '_, I>
+where
+    I: FusedIterator,
+    I::Item: TokenWord,
+{
+}
+
+#[test]
+fn stopwords_test() {
+    assert!(default_words().is_borrowed());
+    assert!(default_words().contains_lowercase("Що"));
+}
+
+#[test]
+fn stop_word_filter_test() {
+    let text = "Він пішов у ліс, а вона — до міста";
+    let mut filter = StopWordFilter::new();
+    let words = |filter: &StopWordFilter| -> Vec<String> {
+        let tokens = crate::tokenizer::tokenize(text);
+        filter
+            .filter(tokens)
+            .filter_map(|t| t.word().map(str::to_string))
+            .collect()
+    };
+    assert_eq!(words(&filter), ["пішов", "ліс", "міста"]);
+
+    filter.extend(vec!["ліс"]);
+    filter.remove("він");
+    assert_eq!(words(&filter), ["Він", "пішов", "міста"]);
+
+    filter.set_words(WordList::from_words(vec!["міста"]));
+    assert_eq!(words(&filter).len(), 7);
+
+    let analyzer = crate::analyzer::Analyzer::new();
+    let stems: Vec<_> = StopWordFilter::new()
+        .filter(analyzer.stem_tokens(text))
+        .map(|t| t.stem)
+        .collect();
+    assert_eq!(stems, ["піш", "ліс", "міст"]);
+}
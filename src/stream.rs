@@ -0,0 +1,225 @@
+//! Stemming files line by line, for corpora that don't fit in memory.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::analyzer::Analyzer;
+
+/// How `stem_lines` stems its input.
+#[derive(Clone)]
+pub struct StreamOptions {
+    pub analyzer: Analyzer,
+    /// Stems every line as one word, for word lists, instead of the words of running text.
+    pub words: bool,
+    /// Threads stemming batches of lines; 1 stems on the calling thread, as everything
+    /// does on wasm targets.
+    pub threads: usize,
+    /// Lines per batch handed to a thread.
+    pub batch_lines: usize,
+}
+
+impl Default for StreamOptions {
+    fn default() -> Self {
+        StreamOptions {
+            analyzer: Analyzer::new(),
+            words: false,
+            threads: 1,
+            batch_lines: 4096,
+        }
+    }
+}
+
+// lines read together, stemmed by one thread; the buffers are reused once written
+#[derive(Default)]
+struct Batch {
+    seq: u64,
+    lines: u64,
+    input: String,
+    output: String,
+}
+
+/// Writes every line of `reader` to `writer` with its words replaced by their stems, or
+/// with `options.words` as the stem of the line, keeping the line endings. Memory stays
+/// bounded by a few batches per thread whatever the size of the input. Returns the number
+/// of lines.
+pub fn stem_lines<R: BufRead, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    options: &StreamOptions,
+) -> io::Result<u64> {
+    let threads = if cfg!(target_family = "wasm") {
+        1
+    } else {
+        options.threads.max(1)
+    };
+    if threads == 1 {
+        let (mut line, mut output) = (String::new(), String::new());
+        let mut lines = 0;
+        while reader.read_line(&mut line)? > 0 {
+            stem_line(options, &line, &mut output);
+            writer.write_all(output.as_bytes())?;
+            line.clear();
+            output.clear();
+            lines += 1;
+        }
+        writer.flush()?;
+        return Ok(lines);
+    }
+
+    let (jobs, queue) = mpsc::channel::<Batch>();
+    let queue = Mutex::new(queue);
+    let (done, stemmed) = mpsc::channel::<Option<Batch>>();
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            let (queue, done) = (&queue, done.clone());
+            scope.spawn(move || loop {
+                let next = queue.lock().unwrap().recv();
+                let Ok(mut batch) = next else { break };
+                let res = panic::catch_unwind(AssertUnwindSafe(|| {
+                    for line in batch.input.split_inclusive('\n') {
+                        stem_line(options, line, &mut batch.output);
+                    }
+                }));
+                // a panicking thread reports a missing batch instead of leaving the
+                // caller waiting for it
+                if done.send(res.ok().map(|()| batch)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(done);
+        let res = feed(&mut reader, &mut writer, options, threads, &jobs, &stemmed);
+        drop(jobs);
+        res
+    })
+}
+
+// Reads batches for the threads and writes them back in order, waiting for stemmed
+// batches while `2 * threads` are out.
+fn feed<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    options: &StreamOptions,
+    threads: usize,
+    jobs: &Sender<Batch>,
+    stemmed: &Receiver<Option<Batch>>,
+) -> io::Result<u64> {
+    let mut out = Reorder::default();
+    let mut sent = 0;
+    loop {
+        let mut batch = out.free.pop().unwrap_or_default();
+        batch.seq = sent;
+        batch.lines = 0;
+        while batch.lines < options.batch_lines.max(1) as u64
+            && reader.read_line(&mut batch.input)? > 0
+        {
+            batch.lines += 1;
+        }
+        if batch.lines == 0 {
+            break;
+        }
+        jobs.send(batch)
+            .map_err(|_| io::Error::other("the stemming threads stopped"))?;
+        sent += 1;
+        while sent - out.written >= 2 * threads as u64 {
+            out.write_next(writer, stemmed)?;
+        }
+    }
+    while out.written < sent {
+        out.write_next(writer, stemmed)?;
+    }
+    writer.flush()?;
+    Ok(out.lines)
+}
+
+// the stemmed batches that arrived before the ones preceding them
+#[derive(Default)]
+struct Reorder {
+    pending: BTreeMap<u64, Batch>,
+    free: Vec<Batch>,
+    written: u64,
+    lines: u64,
+}
+
+impl Reorder {
+    // waits for a stemmed batch and writes those next in order
+    fn write_next<W: Write>(
+        &mut self,
+        writer: &mut W,
+        stemmed: &Receiver<Option<Batch>>,
+    ) -> io::Result<()> {
+        let batch = stemmed
+            .recv()
+            .ok()
+            .flatten()
+            .ok_or_else(|| io::Error::other("a stemming thread panicked"))?;
+        self.pending.insert(batch.seq, batch);
+        while let Some(mut batch) = self.pending.remove(&self.written) {
+            writer.write_all(batch.output.as_bytes())?;
+            self.lines += batch.lines;
+            self.written += 1;
+            batch.input.clear();
+            batch.output.clear();
+            self.free.push(batch);
+        }
+        Ok(())
+    }
+}
+
+// appends the stemmed `line` and its line ending to `output`
+fn stem_line(options: &StreamOptions, line: &str, output: &mut String) {
+    let text = line.trim_end_matches(['\n', '\r']);
+    if options.words {
+        let word = text.trim();
+        if !word.is_empty() {
+            output.push_str(&options.analyzer.get_stemmer().stem(word));
+        }
+    } else {
+        output.extend(options.analyzer.stem_text_iter(text));
+    }
+    output.push_str(&line[text.len()..]);
+}
+
+#[test]
+fn stem_lines_test() {
+    let input = "Слідство триває.\r\nНародами, книгами\n\nавтора";
+    let mut output = Vec::new();
+    let lines = stem_lines(input.as_bytes(), &mut output, &StreamOptions::default()).unwrap();
+    assert_eq!(lines, 4);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "слідств трив.\r\nнарод, книг\n\nавтор"
+    );
+
+    let words = StreamOptions {
+        words: true,
+        ..StreamOptions::default()
+    };
+    let mut output = Vec::new();
+    stem_lines(" Народами \nкнигами\n".as_bytes(), &mut output, &words).unwrap();
+    assert_eq!(output, "народ\nкниг\n".as_bytes());
+}
+
+#[test]
+fn parallel_stem_lines_test() {
+    let input: String = (0..1000)
+        .map(|i| format!("{} Народами і книгами {}\n", i, ["автора", "Києва"][i % 2]))
+        .collect();
+    let mut serial = Vec::new();
+    stem_lines(input.as_bytes(), &mut serial, &StreamOptions::default()).unwrap();
+    let parallel = StreamOptions {
+        threads: 4,
+        batch_lines: 7,
+        ..StreamOptions::default()
+    };
+    let mut output = Vec::new();
+    let lines = stem_lines(input.as_bytes(), &mut output, &parallel).unwrap();
+    assert_eq!(lines, 1000);
+    assert_eq!(output, serial);
+    let mut empty = Vec::new();
+    assert_eq!(stem_lines("".as_bytes(), &mut empty, &parallel).unwrap(), 0);
+}
@@ -0,0 +1,170 @@
+//! A token filter for tantivy analyzers, taking the place of tantivy's Snowball `Stemmer`:
+//!
+//! ```ignore
+//! let analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+//!     .filter(LowerCaser)
+//!     .filter(stemmer_uk::tantivy::StemmerFilter::new())
+//!     .build();
+//! index.tokenizers().register("uk_stem", analyzer);
+//! ```
+//!
+//! It is written against `tantivy-tokenizer-api`, the crate tantivy's own tokenizers and
+//! filters implement, so it works with every tantivy version using the same API version.
+
+use tantivy_tokenizer_api::{Token, TokenFilter, TokenStream, Tokenizer};
+
+use crate::tokenizer::{script, Script};
+use crate::Stemmer;
+
+/// Replaces the text of every Cyrillic token with its stem. Other tokens pass unchanged,
+/// so that a filter for another language can follow; offsets and positions are kept.
+#[derive(Clone, Debug, Default)]
+pub struct StemmerFilter {
+    stemmer: Stemmer,
+}
+
+impl StemmerFilter {
+    /// A filter stemming like `stem_word`.
+    pub fn new() -> Self {
+        StemmerFilter::default()
+    }
+
+    /// A filter stemming with `stemmer`; give it a cache with `StemmerBuilder::cache`, since
+    /// indexing sees the same words over and over.
+    pub fn with_stemmer(stemmer: Stemmer) -> Self {
+        StemmerFilter { stemmer }
+    }
+}
+
+impl TokenFilter for StemmerFilter {
+    type Tokenizer<T: Tokenizer> = StemmerFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> StemmerFilterWrapper<T> {
+        StemmerFilterWrapper {
+            stemmer: self.stemmer,
+            inner: tokenizer,
+        }
+    }
+}
+
+/// The tokenizer `StemmerFilter` turns a tokenizer into.
+#[derive(Clone, Debug)]
+pub struct StemmerFilterWrapper<T> {
+    stemmer: Stemmer,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for StemmerFilterWrapper<T> {
+    type TokenStream<'a> = StemmerTokenStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        StemmerTokenStream {
+            stemmer: &self.stemmer,
+            tail: self.inner.token_stream(text),
+        }
+    }
+}
+
+/// The token stream of `StemmerFilterWrapper`.
+pub struct StemmerTokenStream<'a, T> {
+    stemmer: &'a Stemmer,
+    tail: T,
+}
+
+impl<T: TokenStream> TokenStream for StemmerTokenStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        let token = self.tail.token_mut();
+        if script(&token.text) == Script::Cyrillic {
+            token.text = self.stemmer.stem(&token.text);
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+// splits on whitespace, like tantivy's `WhitespaceTokenizer`
+#[cfg(test)]
+#[derive(Clone)]
+struct Whitespace;
+
+#[cfg(test)]
+struct WhitespaceStream<'a> {
+    words: std::str::SplitWhitespace<'a>,
+    text: &'a str,
+    token: Token,
+}
+
+#[cfg(test)]
+impl Tokenizer for Whitespace {
+    type TokenStream<'a> = WhitespaceStream<'a>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> WhitespaceStream<'a> {
+        WhitespaceStream {
+            words: text.split_whitespace(),
+            text,
+            token: Token::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl TokenStream for WhitespaceStream<'_> {
+    fn advance(&mut self) -> bool {
+        let word = match self.words.next() {
+            Some(word) => word,
+            None => return false,
+        };
+        let start = word.as_ptr() as usize - self.text.as_ptr() as usize;
+        self.token = Token {
+            offset_from: start,
+            offset_to: start + word.len(),
+            position: self.token.position.wrapping_add(1),
+            text: word.to_string(),
+            position_length: 1,
+        };
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[test]
+fn stemmer_filter_test() {
+    let mut tokenizer = StemmerFilter::new().transform(Whitespace);
+    let text = "Слідство триває tantivy Народами";
+    let mut stream = tokenizer.token_stream(text);
+    let mut tokens = Vec::new();
+    stream.process(&mut |t| tokens.push((t.text.clone(), t.offset_from, t.position)));
+    assert_eq!(
+        tokens,
+        [
+            ("слідств".to_string(), 0, 0),
+            ("трив".to_string(), 17, 1),
+            ("tantivy".to_string(), 30, 2),
+            ("народ".to_string(), 38, 3)
+        ]
+    );
+
+    let cached = Stemmer::builder().cache(100).build();
+    let mut tokenizer = StemmerFilter::with_stemmer(cached).transform(Whitespace);
+    assert_eq!(
+        tokenizer.token_stream("книгами").next().unwrap().text,
+        "книг"
+    );
+}
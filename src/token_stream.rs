@@ -0,0 +1,499 @@
+//! Serialized analyzed token streams, so analysis can run once in a pipeline stage and be
+//! consumed later by indexers or ML jobs without re-stemming.
+//!
+//! Two formats carry the same records: a compact binary one (`TokenStreamWriter`,
+//! `TokenStreamReader`) and JSON Lines, one object per token (`write_json`, `JsonReader`).
+
+use std::convert::TryFrom;
+use std::io::{self, BufRead, Read, Write};
+
+use crate::analyzer::{Analyzer, Term};
+use crate::export::{json_string, push_varint, read_varint};
+use crate::tokenizer::TokenKind;
+
+const MAGIC: &[u8; 4] = b"UKTS";
+const VERSION: u8 = 1;
+
+/// The term's token also produced the previous term (a synonym or abbreviation
+/// expansion), so both share a position.
+pub const STACKED: u8 = 1;
+/// The term differs from the lowercased token text.
+pub const STEMMED: u8 = 2;
+
+const KINDS: [(TokenKind, &str); 9] = [
+    (TokenKind::Word, "word"),
+    (TokenKind::Number, "number"),
+    (TokenKind::Whitespace, "whitespace"),
+    (TokenKind::Punctuation, "punctuation"),
+    (TokenKind::Emoji, "emoji"),
+    (TokenKind::Emoticon, "emoticon"),
+    (TokenKind::Control, "control"),
+    (TokenKind::Tag, "tag"),
+    (TokenKind::Other, "other"),
+];
+
+fn kind_code(kind: TokenKind) -> u8 {
+    KINDS.iter().position(|(k, _)| *k == kind).unwrap_or(8) as u8
+}
+
+fn kind_name(kind: TokenKind) -> &'static str {
+    KINDS[kind_code(kind) as usize].1
+}
+
+/// One analyzed term with its token, owning its text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StreamToken {
+    pub kind: TokenKind,
+    /// The token text as it appears in the document.
+    pub text: String,
+    pub term: String,
+    /// Byte offsets of the token in the document.
+    pub start: usize,
+    pub end: usize,
+    /// Index of the token among the tokens that produced terms.
+    pub position: u32,
+    /// `STACKED` and `STEMMED`.
+    pub flags: u8,
+}
+
+/// Converts the terms of one document, numbering their positions.
+pub fn from_terms(terms: &[Term]) -> Vec<StreamToken> {
+    let mut res: Vec<StreamToken> = Vec::with_capacity(terms.len());
+    for term in terms {
+        let token = term.token;
+        let stacked = res
+            .last()
+            .is_some_and(|prev| prev.start == token.start && prev.end == token.end);
+        let position = match res.last() {
+            Some(prev) if stacked => prev.position,
+            Some(prev) => prev.position + 1,
+            None => 0,
+        };
+        let mut flags = 0;
+        if stacked {
+            flags |= STACKED;
+        }
+        if term.term != token.text.to_lowercase() {
+            flags |= STEMMED;
+        }
+        res.push(StreamToken {
+            kind: token.kind,
+            text: token.text.to_string(),
+            term: term.term.clone(),
+            start: token.start,
+            end: token.end,
+            position,
+            flags,
+        });
+    }
+    res
+}
+
+/// Analyzes `text` with `analyzer` into stream tokens.
+pub fn analyze(analyzer: &Analyzer, text: &str) -> Vec<StreamToken> {
+    from_terms(&analyzer.analyze(text))
+}
+
+fn push_str(out: &mut Vec<u8>, s: &str) {
+    push_varint(out, s.len() as u64);
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// Writes the binary format: `"UKTS"` and a version byte, then per document a varint
+/// token count and per token the kind and flag bytes, varint start, length and position
+/// delta, and the length-prefixed text and term.
+pub struct TokenStreamWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> TokenStreamWriter<W> {
+    pub fn new(mut out: W) -> io::Result<Self> {
+        out.write_all(MAGIC)?;
+        out.write_all(&[VERSION])?;
+        Ok(TokenStreamWriter { out })
+    }
+
+    /// Writes the tokens of one document.
+    pub fn write_document(&mut self, tokens: &[StreamToken]) -> io::Result<()> {
+        let mut buf = Vec::new();
+        push_varint(&mut buf, tokens.len() as u64);
+        let mut position = 0;
+        for token in tokens {
+            buf.push(kind_code(token.kind));
+            buf.push(token.flags);
+            push_varint(&mut buf, token.start as u64);
+            push_varint(&mut buf, token.end.saturating_sub(token.start) as u64);
+            push_varint(&mut buf, u64::from(token.position.saturating_sub(position)));
+            position = token.position;
+            push_str(&mut buf, &token.text);
+            push_str(&mut buf, &token.term);
+        }
+        self.out.write_all(&buf)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Reads documents written by `TokenStreamWriter`, one `Vec` per document. Reads a byte
+/// at a time, so `source` should be buffered.
+pub struct TokenStreamReader<R: Read> {
+    source: R,
+    failed: bool,
+}
+
+impl<R: Read> TokenStreamReader<R> {
+    pub fn new(mut source: R) -> io::Result<Self> {
+        let mut header = [0; 5];
+        source
+            .read_exact(&mut header)
+            .map_err(|_| invalid("not a token stream"))?;
+        if &header[..4] != MAGIC {
+            return Err(invalid("not a token stream"));
+        }
+        if header[4] != VERSION {
+            return Err(invalid("unsupported token stream version"));
+        }
+        Ok(TokenStreamReader {
+            source,
+            failed: false,
+        })
+    }
+
+    // `None` at the end of the input
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut b = [0];
+        loop {
+            match self.source.read(&mut b) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(b[0])),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn byte(&mut self) -> io::Result<u8> {
+        self.next_byte()?
+            .ok_or_else(|| invalid("truncated token stream"))
+    }
+
+    fn varint_from(&mut self, first: u8) -> io::Result<u64> {
+        let mut bytes = vec![first];
+        while bytes.last().is_some_and(|b| b & 0x80 != 0) && bytes.len() < 10 {
+            bytes.push(self.byte()?);
+        }
+        read_varint(&bytes, &mut 0).ok_or_else(|| invalid("bad varint"))
+    }
+
+    fn varint(&mut self) -> io::Result<u64> {
+        let first = self.byte()?;
+        self.varint_from(first)
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        let len = self.varint()?;
+        let mut bytes = Vec::new();
+        self.source.by_ref().take(len).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != len {
+            return Err(invalid("truncated token stream"));
+        }
+        String::from_utf8(bytes).map_err(|_| invalid("string is not UTF-8"))
+    }
+
+    fn document(&mut self, first: u8) -> io::Result<Vec<StreamToken>> {
+        let count = self.varint_from(first)?;
+        let mut tokens = Vec::new();
+        let mut position = 0u32;
+        for _ in 0..count {
+            let kind = KINDS
+                .get(self.byte()? as usize)
+                .ok_or_else(|| invalid("unknown token kind"))?
+                .0;
+            let flags = self.byte()?;
+            let start = self.varint()? as usize;
+            let end = (self.varint()? as usize)
+                .checked_add(start)
+                .ok_or_else(|| invalid("bad token offsets"))?;
+            position = u32::try_from(self.varint()?)
+                .ok()
+                .and_then(|delta| position.checked_add(delta))
+                .ok_or_else(|| invalid("bad token position"))?;
+            tokens.push(StreamToken {
+                kind,
+                text: self.string()?,
+                term: self.string()?,
+                start,
+                end,
+                position,
+                flags,
+            });
+        }
+        Ok(tokens)
+    }
+}
+
+impl<R: Read> Iterator for TokenStreamReader<R> {
+    type Item = io::Result<Vec<StreamToken>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let res = match self.next_byte() {
+            Ok(None) => return None,
+            Ok(Some(first)) => self.document(first),
+            Err(err) => Err(err),
+        };
+        // stop at the first corrupt document
+        self.failed = res.is_err();
+        Some(res)
+    }
+}
+
+/// Writes `tokens` as JSON Lines, one object per token with the fields `doc`, `kind`,
+/// `text`, `term`, `start`, `end`, `position` and `flags`.
+pub fn write_json<W: Write>(out: &mut W, doc: usize, tokens: &[StreamToken]) -> io::Result<()> {
+    for token in tokens {
+        writeln!(
+            out,
+            "{{\"doc\":{},\"kind\":\"{}\",\"text\":{},\"term\":{},\"start\":{},\"end\":{},\"position\":{},\"flags\":{}}}",
+            doc,
+            kind_name(token.kind),
+            json_string(&token.text),
+            json_string(&token.term),
+            token.start,
+            token.end,
+            token.position,
+            token.flags
+        )?;
+    }
+    Ok(())
+}
+
+enum JsonValue {
+    Str(String),
+    Num(u64),
+}
+
+// parses the flat objects `write_json` produces: string and unsigned integer values only
+fn parse_object(line: &str) -> Option<Vec<(String, JsonValue)>> {
+    let mut chars = line.trim().chars().peekable();
+    let mut fields = Vec::new();
+    let skip_ws = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+    };
+    if chars.next()? != '{' {
+        return None;
+    }
+    loop {
+        skip_ws(&mut chars);
+        if chars.peek() == Some(&'}') && fields.is_empty() {
+            chars.next();
+            break;
+        }
+        let key = parse_string(&mut chars)?;
+        skip_ws(&mut chars);
+        if chars.next()? != ':' {
+            return None;
+        }
+        skip_ws(&mut chars);
+        let value = if chars.peek() == Some(&'"') {
+            JsonValue::Str(parse_string(&mut chars)?)
+        } else {
+            let mut digits = String::new();
+            while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                digits.push(*c);
+                chars.next();
+            }
+            JsonValue::Num(digits.parse().ok()?)
+        };
+        fields.push((key, value));
+        skip_ws(&mut chars);
+        match chars.next()? {
+            ',' => continue,
+            '}' => break,
+            _ => return None,
+        }
+    }
+    skip_ws(&mut chars);
+    chars.next().is_none().then_some(fields)
+}
+
+fn hex_unit(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<u32> {
+    let hex: String = chars.by_ref().take(4).collect();
+    u32::from_str_radix(&hex, 16)
+        .ok()
+        .filter(|_| hex.len() == 4)
+}
+
+fn parse_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<String> {
+    if chars.next()? != '"' {
+        return None;
+    }
+    let mut res = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(res),
+            '\\' => match chars.next()? {
+                'u' => {
+                    let high = hex_unit(chars)?;
+                    let code = if (0xd800..0xdc00).contains(&high) {
+                        if chars.next()? != '\\' || chars.next()? != 'u' {
+                            return None;
+                        }
+                        let low = hex_unit(chars)?;
+                        0x10000 + ((high - 0xd800) << 10) + low.checked_sub(0xdc00)?
+                    } else {
+                        high
+                    };
+                    res.push(char::from_u32(code)?);
+                }
+                'n' => res.push('\n'),
+                't' => res.push('\t'),
+                'r' => res.push('\r'),
+                'b' => res.push('\u{8}'),
+                'f' => res.push('\u{c}'),
+                c @ ('"' | '\\' | '/') => res.push(c),
+                _ => return None,
+            },
+            c => res.push(c),
+        }
+    }
+}
+
+/// Reads JSON Lines written by `write_json`, one `(doc, token)` per line.
+pub struct JsonReader<R: BufRead> {
+    lines: io::Lines<R>,
+}
+
+impl<R: BufRead> JsonReader<R> {
+    pub fn new(source: R) -> Self {
+        JsonReader {
+            lines: source.lines(),
+        }
+    }
+}
+
+fn token_from_json(line: &str) -> Option<(usize, StreamToken)> {
+    let mut fields = parse_object(line)?;
+    let mut take = |name: &str| {
+        let i = fields.iter().position(|(key, _)| key == name)?;
+        Some(fields.swap_remove(i).1)
+    };
+    let mut num = |name: &str| match take(name)? {
+        JsonValue::Num(n) => Some(n),
+        JsonValue::Str(_) => None,
+    };
+    let (doc, start, end, position, flags) = (
+        num("doc")?,
+        num("start")?,
+        num("end")?,
+        num("position")?,
+        num("flags")?,
+    );
+    let mut string = |name: &str| match take(name)? {
+        JsonValue::Str(s) => Some(s),
+        JsonValue::Num(_) => None,
+    };
+    let kind = string("kind")?;
+    let kind = KINDS.iter().find(|(_, name)| *name == kind)?.0;
+    let token = StreamToken {
+        kind,
+        text: string("text")?,
+        term: string("term")?,
+        start: start as usize,
+        end: end as usize,
+        position: u32::try_from(position).ok()?,
+        flags: u8::try_from(flags).ok()?,
+    };
+    Some((doc as usize, token))
+}
+
+impl<R: BufRead> Iterator for JsonReader<R> {
+    type Item = io::Result<(usize, StreamToken)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err)),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(token_from_json(&line).ok_or_else(|| invalid("malformed token line")));
+        }
+    }
+}
+
+#[test]
+fn from_terms_test() {
+    let analyzer = Analyzer::new().synonym("авто", "машина");
+    let tokens = analyze(&analyzer, "Нове авто, 2024");
+    let terms: Vec<_> = tokens
+        .iter()
+        .map(|t| (t.term.as_str(), t.position, t.flags))
+        .collect();
+    assert_eq!(
+        terms,
+        vec![
+            ("нов", 0, STEMMED),
+            ("авт", 1, STEMMED),
+            ("машин", 1, STACKED | STEMMED),
+            ("2024", 2, 0)
+        ]
+    );
+    assert_eq!((tokens[1].text.as_str(), tokens[1].start), ("авто", 9));
+}
+
+#[test]
+fn binary_roundtrip_test() {
+    let analyzer = Analyzer::new();
+    let docs = [
+        analyze(&analyzer, "Слідство триває :)"),
+        Vec::new(),
+        analyze(&analyzer, "з'їзд \"партії\""),
+    ];
+    let mut writer = TokenStreamWriter::new(Vec::new()).unwrap();
+    for doc in docs.iter() {
+        writer.write_document(doc).unwrap();
+    }
+    let data = writer.into_inner();
+    let read: Vec<_> = TokenStreamReader::new(&data[..])
+        .unwrap()
+        .collect::<io::Result<_>>()
+        .unwrap();
+    assert_eq!(read, docs);
+
+    assert!(TokenStreamReader::new(&b"UKTR\x01"[..]).is_err());
+    let mut reader = TokenStreamReader::new(&data[..data.len() - 3]).unwrap();
+    assert!(reader.by_ref().any(|doc| doc.is_err()));
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn json_roundtrip_test() {
+    let analyzer = Analyzer::new();
+    let tokens = analyze(&analyzer, "Він сказав: \"привіт\\\" 🙂\tдобре");
+    let mut out = Vec::new();
+    write_json(&mut out, 7, &tokens).unwrap();
+    let read: Vec<_> = JsonReader::new(&out[..])
+        .collect::<io::Result<Vec<_>>>()
+        .unwrap();
+    assert!(read.iter().all(|(doc, _)| *doc == 7));
+    assert_eq!(read.into_iter().map(|(_, t)| t).collect::<Vec<_>>(), tokens);
+
+    let line = r#"{ "doc": 0, "kind": "word", "text": "і🙂", "term": "i", "start": 0, "end": 6, "position": 0, "flags": 0 }"#;
+    let (_, token) = token_from_json(line).unwrap();
+    assert_eq!(token.text, "і🙂");
+    assert!(token_from_json(r#"{"doc": "0"}"#).is_none());
+    assert!(token_from_json("{\"doc\":0").is_none());
+}
@@ -0,0 +1,305 @@
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+
+use crate::tokenizer::{tokenize, Script, TokenKind};
+use stemmer_uk_core::normalize::APOSTROPHES;
+
+// http://zakon.rada.gov.ua/laws/show/55-2010-п (KMU 2010 national romanization)
+fn kmu_latin(c: char, initial: bool) -> &'static str {
+    match c {
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        'г' => "h",
+        'ґ' => "g",
+        'д' => "d",
+        'е' => "e",
+        'є' if initial => "ye",
+        'є' => "ie",
+        'ж' => "zh",
+        'з' => "z",
+        'и' => "y",
+        'і' => "i",
+        'ї' if initial => "yi",
+        'ї' => "i",
+        'й' if initial => "y",
+        'й' => "i",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'н' => "n",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'у' => "u",
+        'ф' => "f",
+        'х' => "kh",
+        'ц' => "ts",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ю' if initial => "yu",
+        'ю' => "iu",
+        'я' if initial => "ya",
+        'я' => "ia",
+        _ => "",
+    }
+}
+
+/// Romanizes a lowercase Ukrainian word with the KMU 2010 table.
+pub fn to_latin(word: &str) -> String {
+    let mut res = String::with_capacity(word.len());
+    let mut initial = true;
+    let mut prev = None;
+    for c in word.chars() {
+        let latin = kmu_latin(c, initial);
+        // "зг" is romanized as "zgh" to keep it apart from "ж"
+        if c == 'г' && prev == Some('з') {
+            res.push_str("gh");
+        } else if !latin.is_empty() {
+            res.push_str(latin);
+        } else if c != 'ь' && !APOSTROPHES.contains(&c) {
+            res.push(c);
+        }
+        initial = !(c.is_alphabetic() || APOSTROPHES.contains(&c));
+        prev = Some(c);
+    }
+    res
+}
+
+const LATIN_VOWELS: &str = "aeiouy";
+
+// longest first; covers KMU and the common informal (Polish/Czech-like) spellings
+const CYRILLIC: [(&str, &str); 38] = [
+    ("shch", "щ"),
+    ("sch", "щ"),
+    ("zgh", "зг"),
+    ("zh", "ж"),
+    ("kh", "х"),
+    ("ts", "ц"),
+    ("ch", "ч"),
+    ("sh", "ш"),
+    ("ya", "я"),
+    ("ja", "я"),
+    ("yu", "ю"),
+    ("ju", "ю"),
+    ("ye", "є"),
+    ("je", "є"),
+    ("yi", "ї"),
+    ("ji", "ї"),
+    ("a", "а"),
+    ("b", "б"),
+    ("v", "в"),
+    ("w", "в"),
+    ("h", "г"),
+    ("g", "г"),
+    ("d", "д"),
+    ("e", "е"),
+    ("z", "з"),
+    ("y", "и"),
+    ("i", "і"),
+    ("j", "й"),
+    ("k", "к"),
+    ("q", "к"),
+    ("l", "л"),
+    ("m", "м"),
+    ("n", "н"),
+    ("o", "о"),
+    ("p", "п"),
+    ("r", "р"),
+    ("s", "с"),
+    ("t", "т"),
+];
+
+const CYRILLIC_REST: [(&str, &str); 5] =
+    [("u", "у"), ("f", "ф"), ("c", "ц"), ("x", "кс"), ("'", "ь")];
+
+/// Converts a transliterated Ukrainian word back to Cyrillic. The mapping is heuristic:
+/// non-initial "ia"/"iu"/"ie" and "i" after a vowel follow the KMU conventions.
+pub fn to_cyrillic(word: &str) -> String {
+    to_cyrillic_mapped(word).0
+}
+
+/// `to_cyrillic`, also returning for every output character the byte offset in
+/// `word.to_lowercase()` of the Latin character it was converted from.
+pub fn to_cyrillic_mapped(word: &str) -> (String, Vec<usize>) {
+    let lower = word.to_lowercase();
+    let mut res = String::with_capacity(lower.len() * 2);
+    let mut sources = Vec::with_capacity(lower.len());
+    let mut rest = lower.as_str();
+    let mut prev: Option<char> = None;
+
+    while let Some(c) = rest.chars().next() {
+        let after_vowel = prev.is_some_and(|p| LATIN_VOWELS.contains(p));
+        let after_consonant = prev.is_some_and(|p| p.is_ascii_alphabetic() && !after_vowel);
+
+        let (len, to) = if rest == "yi" && prev.is_some() {
+            (2, "ий")
+        } else if after_consonant && rest.starts_with("yi") {
+            (1, "и")
+        } else if prev.is_some() && rest.len() > 1 && rest.starts_with('i') && !after_vowel
+            || after_vowel
+                && (rest.starts_with("ia") || rest.starts_with("iu") || rest.starts_with("ie"))
+        {
+            match &rest[1..2] {
+                "a" => (2, "я"),
+                "u" => (2, "ю"),
+                "e" => (2, "є"),
+                _ => (1, "і"),
+            }
+        } else if after_vowel && (c == 'i' || c == 'y') && !rest[1..].starts_with(['a', 'u', 'e']) {
+            (1, if c == 'i' { "ї" } else { "й" })
+        } else if let Some((from, to)) = CYRILLIC
+            .iter()
+            .chain(CYRILLIC_REST.iter())
+            .find(|(from, _)| rest.starts_with(from))
+        {
+            (from.len(), *to)
+        } else {
+            (c.len_utf8(), &rest[..c.len_utf8()])
+        };
+
+        res.push_str(to);
+        let source = lower.len() - rest.len();
+        sources.extend(to.chars().map(|_| source));
+        prev = rest[..len].chars().last();
+        rest = &rest[len..];
+    }
+    (res, sources)
+}
+
+// Training material for the bigram models; the Ukrainian text is romanized at startup.
+const UK_SAMPLE: &str = "привіт як справи дякую добре ми живемо в україні київ харків львів одеса \
+    дніпро сьогодні вранці була гарна погода завтра піде дощ я хочу купити хліб і молоко \
+    де знаходиться вулиця шевченка скільки це коштує будь ласка допоможіть мені знайти \
+    дорогу до вокзалу наша родина любить подорожувати ввечері ми дивимося новини \
+    уряд ухвалив рішення про підтримку освіти та охорони здоров'я люди чекають змін \
+    щодня о восьмій годині я їду на роботу автобусом мій брат навчається в університеті \
+    добрий день вибачте дуже приємно звичайно чудово зрозуміло нічого страшного \
+    жовтий зелений синій червоний великий маленький новий старий хороший поганий";
+
+const EN_SAMPLE: &str =
+    "hello how are you thank you very much we live in the city and work every day \
+    today the weather was nice in the morning tomorrow it will rain i want to buy bread and milk \
+    where is the street how much does it cost please help me find the way to the station \
+    our family likes to travel in the evening we watch the news on television \
+    the government made a decision to support education and health care people expect change \
+    every day at eight o clock i go to work by bus my brother studies at the university \
+    good afternoon excuse me nice to meet you of course great understood never mind \
+    yellow green blue red big small new old good bad computer software server download";
+
+struct BigramModel {
+    counts: HashMap<(char, char), f64>,
+    total: f64,
+}
+
+impl BigramModel {
+    fn train(text: &str) -> Self {
+        let mut counts = HashMap::new();
+        let mut total = 0.0;
+        for word in text.split_whitespace() {
+            for pair in bigrams(word) {
+                *counts.entry(pair).or_insert(0.0) += 1.0;
+                total += 1.0;
+            }
+        }
+        BigramModel { counts, total }
+    }
+
+    // add-one smoothed over 28 * 28 possible bigrams
+    fn log_prob(&self, pair: (char, char)) -> f64 {
+        let count = self.counts.get(&pair).copied().unwrap_or(0.0);
+        ((count + 1.0) / (self.total + 784.0)).ln()
+    }
+}
+
+fn bigrams(word: &str) -> Vec<(char, char)> {
+    let chars: Vec<char> = std::iter::once('^')
+        .chain(word.chars())
+        .chain(std::iter::once('$'))
+        .collect();
+    chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+lazy_static! {
+    static ref UK_MODEL: BigramModel = crate::lazy(|| BigramModel::train(&to_latin(UK_SAMPLE)));
+    static ref EN_MODEL: BigramModel = crate::lazy(|| BigramModel::train(EN_SAMPLE));
+}
+
+pub(crate) fn warm_up() {
+    lazy_static::initialize(&UK_MODEL);
+    lazy_static::initialize(&EN_MODEL);
+}
+
+/// Average per-bigram log-likelihood ratio of "transliterated Ukrainian" over English.
+/// Positive values favour Ukrainian; non-Latin tokens score `f64::NEG_INFINITY`.
+pub fn translit_score(token: &str) -> f64 {
+    let word = token.to_lowercase();
+    if word.is_empty() || !word.chars().all(|c| c.is_ascii_lowercase() || c == '\'') {
+        return f64::NEG_INFINITY;
+    }
+    let pairs = bigrams(&word);
+    let sum: f64 = pairs
+        .iter()
+        .map(|p| UK_MODEL.log_prob(*p) - EN_MODEL.log_prob(*p))
+        .sum();
+    sum / pairs.len() as f64
+}
+
+/// Whether a Latin-script token looks like transliterated Ukrainian.
+pub fn is_translit(token: &str) -> bool {
+    token.chars().count() >= 3 && translit_score(token) > 0.0
+}
+
+/// Whether most Latin words of `text` look like transliterated Ukrainian.
+pub fn is_translit_document(text: &str) -> bool {
+    let (mut translit, mut latin) = (0, 0);
+    for token in tokenize(text).filter(|t| t.kind == TokenKind::Word) {
+        if token.script() == Script::Latin {
+            latin += 1;
+            if is_translit(token.text) {
+                translit += 1;
+            }
+        }
+    }
+    latin > 0 && translit * 2 > latin
+}
+
+#[test]
+fn to_latin_test() {
+    assert_eq!(to_latin("київ"), "kyiv");
+    assert_eq!(to_latin("згорани"), "zghorany");
+    assert_eq!(to_latin("щастя"), "shchastia");
+    assert_eq!(to_latin("юрій"), "yurii");
+    assert_eq!(to_latin("з'їзд 2"), "zizd 2");
+}
+
+#[test]
+fn to_cyrillic_test() {
+    assert_eq!(to_cyrillic("Kyiv"), "київ");
+    assert_eq!(to_cyrillic("pryvit"), "привіт");
+    assert_eq!(to_cyrillic("dyakuyu"), "дякую");
+    assert_eq!(to_cyrillic("shcho"), "що");
+    assert_eq!(to_cyrillic("Ukraina"), "україна");
+    assert_eq!(to_cyrillic("zhovtyi"), "жовтий");
+    assert_eq!(to_cyrillic("shchastia"), "щастя");
+    assert_eq!(to_cyrillic("Kyieva"), "києва");
+    assert_eq!(to_cyrillic("mriia"), "мрія");
+}
+
+#[test]
+fn detector_test() {
+    assert!(is_translit("pryvit"));
+    assert!(is_translit("dyakuyu"));
+    assert!(is_translit("shcho"));
+    assert!(!is_translit("computer"));
+    assert!(!is_translit("the"));
+    assert!(is_translit_document(
+        "pryvit, yak spravy? vse dobre, dyakuyu"
+    ));
+    assert!(!is_translit_document(
+        "hello, how are you? all good, thanks"
+    ));
+}
@@ -0,0 +1,193 @@
+//! A character trigram model of Ukrainian words, telling words from the random strings,
+//! keys and hashes of log files.
+//!
+//! The built-in model is trained offline and embedded from `rules/trigrams.txt`;
+//! `cargo run --example train_ngrams -- words.txt > rules/trigrams.txt` retrains it from a
+//! word list.
+
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use stemmer_uk_core::normalize::APOSTROPHES;
+
+pub const SOURCE_PATH: &str = "rules/trigrams.txt";
+
+const ALPHABET: &str = "абвгґдеєжзиіїйклмнопрстуфхцчшщьюя'";
+// before the first and after the last letter
+const BOUNDARY: char = '_';
+// stands for every char outside the alphabet
+const OTHER: char = '#';
+// the alphabet, the boundary and `OTHER`
+const SYMBOLS: f64 = 36.0;
+// weights of the trigram, bigram and unigram estimates of a char
+const WEIGHTS: [f64; 3] = [0.6, 0.3, 0.1];
+
+/// Trigram counts of Ukrainian words.
+#[derive(Clone, Debug, Default)]
+pub struct NgramModel {
+    trigrams: HashMap<[char; 3], u32>,
+    // the counts of the trigrams starting with each bigram
+    contexts: HashMap<[char; 2], u32>,
+    // the same for the bigrams and unigrams at the end of the trigrams
+    bigrams: HashMap<[char; 2], u32>,
+    bigram_contexts: HashMap<char, u32>,
+    unigrams: HashMap<char, u32>,
+    total: u32,
+}
+
+impl NgramModel {
+    /// Counts the trigrams of `words`, skipping words with chars outside the Ukrainian
+    /// alphabet.
+    pub fn train<'w, I>(words: I) -> Self
+    where
+        I: IntoIterator<Item = &'w str>,
+    {
+        let mut model = NgramModel::default();
+        for word in words {
+            let symbols = symbols(word);
+            if symbols.len() > 2 && !symbols.contains(&OTHER) {
+                for t in symbols.windows(3) {
+                    model.add([t[0], t[1], t[2]], 1);
+                }
+            }
+        }
+        model
+    }
+
+    /// Reads the `to_text` format: a trigram and its count per line, `_` marking word
+    /// boundaries, and `#` comments.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut model = NgramModel::default();
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = || format!("{}:{}: expected `<trigram>\\t<count>`", SOURCE_PATH, i + 1);
+            let (trigram, count) = line.split_once('\t').ok_or_else(err)?;
+            let chars: Vec<char> = trigram.chars().collect();
+            let count = count.trim().parse().map_err(|_| err())?;
+            match chars[..] {
+                [a, b, c] => model.add([a, b, c], count),
+                _ => return Err(err()),
+            }
+        }
+        Ok(model)
+    }
+
+    /// The trigrams and their counts in the `parse` format, sorted.
+    pub fn to_text(&self) -> String {
+        let mut trigrams: Vec<_> = self.trigrams.iter().collect();
+        trigrams.sort();
+        let mut out = String::new();
+        for (t, count) in trigrams {
+            writeln!(out, "{}{}{}\t{}", t[0], t[1], t[2], count).unwrap();
+        }
+        out
+    }
+
+    /// How Ukrainian `token` looks, between 0 and 1: 0.5 for strings the model finds as
+    /// likely as random chars, above 0.6 for most words. Digits, Latin letters and
+    /// unpronounceable clusters pull the score down, and random strings, keys and hashes
+    /// score below 0.2.
+    pub fn score(&self, token: &str) -> f32 {
+        let symbols = symbols(token);
+        if symbols.len() <= 2 {
+            return 0.0;
+        }
+        let bits: f64 = symbols
+            .windows(3)
+            .map(|t| -self.probability([t[0], t[1], t[2]]).log2())
+            .sum();
+        let entropy = bits / (symbols.len() - 2) as f64;
+        // bits per char the model saves over uniformly random chars
+        let gain = SYMBOLS.log2() - entropy;
+        (1.0 / (1.0 + (-2.0 * gain).exp2())) as f32
+    }
+
+    fn add(&mut self, [a, b, c]: [char; 3], count: u32) {
+        *self.trigrams.entry([a, b, c]).or_insert(0) += count;
+        *self.contexts.entry([a, b]).or_insert(0) += count;
+        *self.bigrams.entry([b, c]).or_insert(0) += count;
+        *self.bigram_contexts.entry(b).or_insert(0) += count;
+        *self.unigrams.entry(c).or_insert(0) += count;
+        self.total += count;
+    }
+
+    // of the last char of `trigram` given the first two, interpolating the trigram, bigram
+    // and add-one smoothed unigram estimates
+    fn probability(&self, [a, b, c]: [char; 3]) -> f64 {
+        let ratio = |count: Option<&u32>, context: Option<&u32>| match (count, context) {
+            (Some(&count), Some(&context)) => f64::from(count) / f64::from(context),
+            _ => 0.0,
+        };
+        let unigram = f64::from(self.unigrams.get(&c).copied().unwrap_or(0) + 1)
+            / (f64::from(self.total) + SYMBOLS);
+        WEIGHTS[0] * ratio(self.trigrams.get(&[a, b, c]), self.contexts.get(&[a, b]))
+            + WEIGHTS[1] * ratio(self.bigrams.get(&[b, c]), self.bigram_contexts.get(&b))
+            + WEIGHTS[2] * unigram
+    }
+}
+
+// the lowercased chars of `word` between boundaries, with `OTHER` for unknown chars
+fn symbols(word: &str) -> Vec<char> {
+    let mut symbols = vec![BOUNDARY];
+    for c in word.chars().flat_map(char::to_lowercase) {
+        symbols.push(match c {
+            'ё' => 'е',
+            c if APOSTROPHES.contains(&c) => '\'',
+            c if ALPHABET.contains(c) => c,
+            _ => OTHER,
+        });
+    }
+    symbols.push(BOUNDARY);
+    symbols
+}
+
+lazy_static! {
+    static ref BUILTIN: NgramModel =
+        crate::lazy(|| NgramModel::parse(include_str!("../rules/trigrams.txt")).unwrap());
+}
+
+pub(crate) fn warm_up() {
+    lazy_static::initialize(&BUILTIN);
+}
+
+/// `NgramModel::score` with the built-in model.
+pub fn looks_like_ukrainian(token: &str) -> f32 {
+    BUILTIN.score(token)
+}
+
+#[test]
+fn looks_like_ukrainian_test() {
+    for word in [
+        "слідство",
+        "Народами",
+        "подорожуємо",
+        "з'їзд",
+        "вода",
+        "ліс",
+    ]
+    .iter()
+    {
+        assert!(looks_like_ukrainian(word) > 0.6, "{}", word);
+    }
+    for token in ["жщхфкпр", "ьъыгкщ", "a3f9c2e1", "k8s", "ї7щ2", "пвпвпв"].iter()
+    {
+        assert!(looks_like_ukrainian(token) < 0.2, "{}", token);
+    }
+    assert_eq!(looks_like_ukrainian(""), 0.0);
+}
+
+#[test]
+fn ngram_model_test() {
+    let model = NgramModel::train(["мама", "мала", "kyiv"].iter().copied());
+    assert_eq!(model.trigrams[&['_', 'м', 'а']], 2);
+    assert_eq!(model.contexts[&['м', 'а']], 3);
+    assert!(!model.trigrams.contains_key(&['_', 'k', 'y']));
+    let parsed = NgramModel::parse(&model.to_text()).unwrap();
+    assert_eq!(parsed.to_text(), model.to_text());
+    assert!(model.score("мама") > model.score("амам"));
+    assert!(NgramModel::parse("мам").is_err());
+}
@@ -0,0 +1,86 @@
+//! JavaScript bindings for client-side search in the browser, built with
+//! `wasm-pack build --target web -- --features wasm`.
+//!
+//! The crate has no C dependencies, so nothing beyond the `wasm32-unknown-unknown` target
+//! is needed. Names follow JavaScript conventions: `stemWord`, `stemText` and the
+//! `UkStemmer` class.
+
+use wasm_bindgen::prelude::*;
+
+use crate::analyzer::Analyzer;
+use crate::tokenizer::TokenKind;
+
+/// `stem_word` with the default configuration.
+#[wasm_bindgen(js_name = stemWord)]
+pub fn stem_word(word: &str) -> String {
+    crate::stem_word(word.to_string())
+}
+
+/// `stem_text` with the default configuration.
+#[wasm_bindgen(js_name = stemText)]
+pub fn stem_text(text: &str) -> String {
+    crate::stem_text(text)
+}
+
+/// A stemmer and analyzer kept between calls, so their tables are built once.
+#[wasm_bindgen]
+pub struct UkStemmer {
+    analyzer: Analyzer,
+}
+
+impl Default for UkStemmer {
+    fn default() -> Self {
+        UkStemmer::new()
+    }
+}
+
+#[wasm_bindgen]
+impl UkStemmer {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> UkStemmer {
+        UkStemmer {
+            analyzer: Analyzer::new(),
+        }
+    }
+
+    pub fn stem(&self, word: &str) -> String {
+        self.analyzer.get_stemmer().stem(word)
+    }
+
+    /// The stems of `words`, in order.
+    #[wasm_bindgen(js_name = stemAll)]
+    pub fn stem_all(&self, words: Vec<String>) -> Vec<String> {
+        self.analyzer.get_stemmer().stem_all(&words).collect()
+    }
+
+    #[wasm_bindgen(js_name = stemText)]
+    pub fn stem_text(&self, text: &str) -> String {
+        self.analyzer.stem_text(text)
+    }
+
+    /// The search terms of the words of `text`, for indexing or querying.
+    pub fn terms(&self, text: &str) -> Vec<String> {
+        self.analyzer
+            .analyze(text)
+            .into_iter()
+            .filter(|t| t.token.kind == TokenKind::Word)
+            .map(|t| t.term)
+            .collect()
+    }
+}
+
+#[test]
+fn wasm_api_test() {
+    assert_eq!(stem_word("Народами"), "народ");
+    assert_eq!(
+        stem_text("Слідство триває"),
+        crate::stem_text("Слідство триває")
+    );
+    let stemmer = UkStemmer::new();
+    assert_eq!(stemmer.stem("книгами"), "книг");
+    assert_eq!(
+        stemmer.stem_all(vec!["книгами".into(), "автора".into()]),
+        ["книг", "автор"]
+    );
+    assert_eq!(stemmer.terms("Слідство, 5 грн"), ["слідств", "грн"]);
+}
@@ -0,0 +1,183 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use crate::analyzer::Analyzer;
+use crate::dictionary::Dictionary;
+use crate::shared::SharedAnalyzer;
+use crate::wordlist::WordList;
+
+/// Reloads exception, stop-word and protected-term files into a `SharedAnalyzer` when
+/// they change on disk.
+///
+/// Changes are detected by polling modification times and sizes. All files are read
+/// before anything is swapped in, so readers never see a mix of old and new lists, and
+/// a file that fails to load leaves the previous resources in place.
+pub struct ResourceWatcher {
+    target: Arc<SharedAnalyzer>,
+    base: Analyzer,
+    exceptions: Option<PathBuf>,
+    stopwords: Option<PathBuf>,
+    protected: Option<PathBuf>,
+    interval: Duration,
+    stamps: Vec<Option<(SystemTime, u64)>>,
+}
+
+impl ResourceWatcher {
+    /// Loaded resources are applied on top of `base`.
+    pub fn new(target: Arc<SharedAnalyzer>, base: Analyzer) -> Self {
+        ResourceWatcher {
+            target,
+            base,
+            exceptions: None,
+            stopwords: None,
+            protected: None,
+            interval: Duration::from_secs(2),
+            stamps: Vec::new(),
+        }
+    }
+
+    /// A `form<TAB>stem` file, see `Dictionary::load`.
+    pub fn exceptions<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.exceptions = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// One word per line, see `WordList::load`.
+    pub fn stopwords<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.stopwords = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn protected<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.protected = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// How often `spawn` checks the files, two seconds by default.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    fn files(&self) -> impl Iterator<Item = &Path> {
+        self.exceptions
+            .iter()
+            .chain(&self.stopwords)
+            .chain(&self.protected)
+            .map(PathBuf::as_path)
+    }
+
+    /// Loads every file and swaps the result in.
+    pub fn reload(&mut self) -> io::Result<()> {
+        let stamps = self
+            .files()
+            .map(|path| {
+                let meta = fs::metadata(path)?;
+                Ok(Some((meta.modified()?, meta.len())))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let mut stemmer = self.base.get_stemmer().to_builder();
+        if let Some(path) = &self.exceptions {
+            stemmer = stemmer.exceptions(Dictionary::load(BufReader::new(File::open(path)?))?);
+        }
+        if let Some(path) = &self.protected {
+            stemmer = stemmer.protected(WordList::load(BufReader::new(File::open(path)?))?);
+        }
+        let mut analyzer = self.base.clone().stemmer(stemmer.build());
+        if let Some(path) = &self.stopwords {
+            analyzer = analyzer.stopwords(WordList::load(BufReader::new(File::open(path)?))?);
+        }
+
+        self.target.store(analyzer);
+        self.stamps = stamps;
+        Ok(())
+    }
+
+    /// Reloads if any file changed since the last load; returns whether it did.
+    pub fn poll(&mut self) -> io::Result<bool> {
+        let changed = self.stamps.len() != self.files().count()
+            || self.files().zip(&self.stamps).any(|(path, stamp)| {
+                let current = fs::metadata(path)
+                    .and_then(|meta| Ok((meta.modified()?, meta.len())))
+                    .ok();
+                current != *stamp
+            });
+        if changed {
+            self.reload()?;
+        }
+        Ok(changed)
+    }
+
+    /// Polls on a background thread until the handle is dropped. Failed reloads are
+    /// passed to `on_error` and retried on the next change.
+    pub fn spawn<F>(mut self, mut on_error: F) -> WatchHandle
+    where
+        F: FnMut(io::Error) + Send + 'static,
+    {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = thread::spawn(move || loop {
+            if let Err(err) = self.poll() {
+                on_error(err);
+            }
+            match stopped.recv_timeout(self.interval) {
+                Err(RecvTimeoutError::Timeout) => {}
+                _ => break,
+            }
+        });
+        WatchHandle {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
+
+/// Stops the watcher thread when dropped.
+pub struct WatchHandle {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[test]
+fn resource_watcher_test() {
+    let dir = std::env::temp_dir().join(format!("ukstem-watch-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let exceptions = dir.join("exceptions.tsv");
+    let stopwords = dir.join("stopwords.txt");
+    fs::write(&exceptions, "люди\tлюдина\n").unwrap();
+    fs::write(&stopwords, "ось\n").unwrap();
+
+    let shared = Arc::new(SharedAnalyzer::default());
+    let mut watcher = ResourceWatcher::new(Arc::clone(&shared), Analyzer::new())
+        .exceptions(&exceptions)
+        .stopwords(&stopwords);
+    assert!(watcher.poll().unwrap());
+    assert!(!watcher.poll().unwrap());
+    assert_eq!(shared.load().terms("ось люди"), ["людина"]);
+
+    fs::write(&exceptions, "люди\tлюд-\nдіти\tдитина\n").unwrap();
+    assert!(watcher.poll().unwrap());
+    assert_eq!(shared.load().terms("люди діти"), ["люд-", "дитина"]);
+
+    fs::write(&exceptions, "broken line\n").unwrap();
+    assert!(watcher.poll().is_err());
+    assert_eq!(shared.load().terms("діти"), ["дитина"]);
+
+    let handle = watcher.interval(Duration::from_millis(10)).spawn(|_| {});
+    drop(handle);
+    fs::remove_dir_all(&dir).unwrap();
+}
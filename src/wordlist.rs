@@ -0,0 +1,223 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead};
+
+/// A sorted set of lowercase words, such as stop words or protected terms.
+///
+/// A list built from an already sorted slice borrows it, so `&'static` lists (for example
+/// from `include_str!` data split with `from_lines`) cost no copies of the words.
+#[derive(Clone)]
+pub struct WordList<'a> {
+    words: Words<'a>,
+}
+
+#[derive(Clone)]
+enum Words<'a> {
+    Borrowed(&'a [&'a str]),
+    Owned(Vec<Cow<'a, str>>),
+}
+
+impl Default for WordList<'_> {
+    fn default() -> Self {
+        WordList {
+            words: Words::Owned(Vec::new()),
+        }
+    }
+}
+
+impl<'a> WordList<'a> {
+    pub fn new() -> Self {
+        WordList::default()
+    }
+
+    /// Borrows `words` when they are sorted by code point without duplicates, otherwise
+    /// keeps a sorted copy of the references.
+    pub fn from_slice(words: &'a [&'a str]) -> Self {
+        if words.windows(2).all(|w| w[0] < w[1]) {
+            WordList {
+                words: Words::Borrowed(words),
+            }
+        } else {
+            WordList::from_words(words.iter().copied())
+        }
+    }
+
+    /// One word per line; blank lines and lines starting with `#` are skipped. The words
+    /// borrow from `text`.
+    pub fn from_lines(text: &'a str) -> Self {
+        WordList::from_words(text.lines().filter_map(list_line))
+    }
+
+    pub fn from_words<I, W>(words: I) -> Self
+    where
+        I: IntoIterator<Item = W>,
+        W: Into<Cow<'a, str>>,
+    {
+        let mut words: Vec<Cow<'a, str>> = words.into_iter().map(Into::into).collect();
+        words.sort_unstable();
+        words.dedup();
+        WordList {
+            words: Words::Owned(words),
+        }
+    }
+
+    /// Reads the `from_lines` format from `reader` into an owned list.
+    pub fn load<R: BufRead>(reader: R) -> io::Result<WordList<'static>> {
+        let mut words = Vec::new();
+        for line in reader.lines() {
+            if let Some(word) = list_line(&line?) {
+                words.push(word.to_string());
+            }
+        }
+        Ok(WordList::from_words(words))
+    }
+
+    fn to_mut(&mut self) -> &mut Vec<Cow<'a, str>> {
+        if let Words::Borrowed(words) = self.words {
+            self.words = Words::Owned(words.iter().map(|w| Cow::Borrowed(*w)).collect());
+        }
+        match &mut self.words {
+            Words::Owned(words) => words,
+            Words::Borrowed(_) => unreachable!(),
+        }
+    }
+
+    fn search(&self, word: &str) -> Result<usize, usize> {
+        match &self.words {
+            Words::Borrowed(words) => words.binary_search(&word),
+            Words::Owned(words) => words.binary_search_by(|w| w.as_ref().cmp(word)),
+        }
+    }
+
+    pub fn insert<W: Into<Cow<'a, str>>>(&mut self, word: W) {
+        let word = word.into();
+        if let Err(i) = self.search(&word) {
+            self.to_mut().insert(i, word);
+        }
+    }
+
+    pub fn remove(&mut self, word: &str) {
+        if let Ok(i) = self.search(word) {
+            self.to_mut().remove(i);
+        }
+    }
+
+    /// Exact lookup; the list holds lowercase words.
+    pub fn contains(&self, word: &str) -> bool {
+        self.search(word).is_ok()
+    }
+
+    /// Lowercases `word` first if needed.
+    pub fn contains_lowercase(&self, word: &str) -> bool {
+        if word.chars().any(char::is_uppercase) {
+            self.contains(&word.to_lowercase())
+        } else {
+            self.contains(word)
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        let (borrowed, owned) = match &self.words {
+            Words::Borrowed(words) => (&words[..], &[][..]),
+            Words::Owned(words) => (&[][..], &words[..]),
+        };
+        borrowed
+            .iter()
+            .copied()
+            .chain(owned.iter().map(AsRef::as_ref))
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.words {
+            Words::Borrowed(words) => words.len(),
+            Words::Owned(words) => words.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the list still borrows its source slice.
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.words, Words::Borrowed(_))
+    }
+
+    /// Bytes of words held in owned strings, not counting the list itself.
+    pub fn owned_bytes(&self) -> usize {
+        match &self.words {
+            Words::Borrowed(_) => 0,
+            Words::Owned(words) => words
+                .iter()
+                .map(|w| match w {
+                    Cow::Owned(w) => w.capacity(),
+                    Cow::Borrowed(_) => 0,
+                })
+                .sum(),
+        }
+    }
+}
+
+fn list_line(line: &str) -> Option<&str> {
+    let line = line.trim();
+    (!line.is_empty() && !line.starts_with('#')).then_some(line)
+}
+
+impl PartialEq for WordList<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for WordList<'_> {}
+
+impl Hash for WordList<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        self.iter().for_each(|w| w.hash(state));
+    }
+}
+
+// part of stemmer and analyzer fingerprints, so only the words matter
+impl fmt::Debug for WordList<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// A sorted sequence of words.
+#[cfg(feature = "serde")]
+impl serde::Serialize for WordList<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for WordList<'static> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let words: Vec<String> = serde::Deserialize::deserialize(deserializer)?;
+        Ok(WordList::from_words(words))
+    }
+}
+
+#[test]
+fn word_list_test() {
+    static SORTED: [&str; 3] = ["а", "та", "і"];
+    let unsorted = WordList::from_slice(&["та", "а"]);
+    assert!(!unsorted.is_borrowed());
+
+    let mut list = WordList::from_slice(&SORTED);
+    assert!(list.is_borrowed());
+    assert!(list.contains("та") && list.contains_lowercase("Та"));
+    list.insert("але");
+    assert!(!list.is_borrowed());
+    assert_eq!(list.iter().collect::<Vec<_>>(), ["а", "але", "та", "і"]);
+
+    let lines = WordList::from_lines("# stop words\nщо\n\nяк\nщо\n");
+    assert_eq!(lines.len(), 2);
+    let loaded = WordList::load("# stop words\nщо\n\nяк\nщо\n".as_bytes()).unwrap();
+    assert_eq!(loaded, lines);
+    assert!(loaded.owned_bytes() > 0 && lines.owned_bytes() == 0);
+}
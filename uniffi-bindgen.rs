@@ -0,0 +1,4 @@
+// Generates the Swift and Kotlin wrappers of the `uniffi` feature, see `stemmer_uk::mobile`.
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}